@@ -9,6 +9,7 @@ use cosmwasm_std::{
 use moneymarket::interest_model::{
     BorrowRateResponse, ConfigResponse, HandleMsg, InitMsg, QueryMsg,
 };
+use moneymarket_math::interest_model::compute_borrow_rate;
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -109,18 +110,14 @@ fn query_borrow_rate<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<BorrowRateResponse> {
     let config: Config = read_config(&deps.storage)?;
 
-    // ignore decimal parts
-    let total_value_in_market =
-        Decimal256::from_uint256(market_balance) + total_liabilities - total_reserves;
-
-    let utilization_ratio = if total_value_in_market.is_zero() {
-        Decimal256::zero()
-    } else {
-        total_liabilities / total_value_in_market
-    };
-
     Ok(BorrowRateResponse {
-        rate: utilization_ratio * config.interest_multiplier + config.base_rate,
+        rate: compute_borrow_rate(
+            config.base_rate,
+            config.interest_multiplier,
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        ),
     })
 }
 
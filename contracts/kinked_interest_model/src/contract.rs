@@ -0,0 +1,295 @@
+use crate::state::{read_config, store_config, Config};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    to_binary, Api, Binary, Env, Extern, HandleResponse, HandleResult, HumanAddr, InitResponse,
+    Querier, StdError, StdResult, Storage,
+};
+use moneymarket::kinked_interest_model::{
+    BorrowRateResponse, ConfigResponse, HandleMsg, InitMsg, QueryMsg,
+};
+use moneymarket_math::interest_model::compute_kinked_borrow_rate;
+
+pub fn init<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    _env: Env,
+    msg: InitMsg,
+) -> StdResult<InitResponse> {
+    assert_optimal_utilization(msg.optimal_utilization)?;
+
+    store_config(
+        &mut deps.storage,
+        &Config {
+            owner: deps.api.canonical_address(&msg.owner)?,
+            base_rate: msg.base_rate,
+            interest_multiplier: msg.interest_multiplier,
+            optimal_utilization: msg.optimal_utilization,
+            jump_multiplier: msg.jump_multiplier,
+        },
+    )?;
+
+    Ok(InitResponse::default())
+}
+
+pub fn handle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: HandleMsg,
+) -> HandleResult {
+    match msg {
+        HandleMsg::UpdateConfig {
+            owner,
+            base_rate,
+            interest_multiplier,
+            optimal_utilization,
+            jump_multiplier,
+        } => update_config(
+            deps,
+            env,
+            owner,
+            base_rate,
+            interest_multiplier,
+            optimal_utilization,
+            jump_multiplier,
+        ),
+    }
+}
+
+pub fn update_config<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    owner: Option<HumanAddr>,
+    base_rate: Option<Decimal256>,
+    interest_multiplier: Option<Decimal256>,
+    optimal_utilization: Option<Decimal256>,
+    jump_multiplier: Option<Decimal256>,
+) -> HandleResult {
+    let mut config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.canonical_address(&owner)?;
+    }
+
+    if let Some(base_rate) = base_rate {
+        config.base_rate = base_rate;
+    }
+
+    if let Some(interest_multiplier) = interest_multiplier {
+        config.interest_multiplier = interest_multiplier;
+    }
+
+    if let Some(optimal_utilization) = optimal_utilization {
+        assert_optimal_utilization(optimal_utilization)?;
+        config.optimal_utilization = optimal_utilization;
+    }
+
+    if let Some(jump_multiplier) = jump_multiplier {
+        config.jump_multiplier = jump_multiplier;
+    }
+
+    store_config(&mut deps.storage, &config)?;
+    Ok(HandleResponse::default())
+}
+
+fn assert_optimal_utilization(optimal_utilization: Decimal256) -> StdResult<()> {
+    if optimal_utilization > Decimal256::one() {
+        return Err(StdError::generic_err(
+            "optimal_utilization cannot be bigger than one",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    msg: QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::BorrowRate {
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        } => to_binary(&query_borrow_rate(
+            deps,
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        )?),
+    }
+}
+
+fn query_config<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ConfigResponse> {
+    let state = read_config(&deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.human_address(&state.owner)?,
+        base_rate: state.base_rate,
+        interest_multiplier: state.interest_multiplier,
+        optimal_utilization: state.optimal_utilization,
+        jump_multiplier: state.jump_multiplier,
+    };
+
+    Ok(resp)
+}
+
+fn query_borrow_rate<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> StdResult<BorrowRateResponse> {
+    let config: Config = read_config(&deps.storage)?;
+
+    Ok(BorrowRateResponse {
+        rate: compute_kinked_borrow_rate(
+            config.base_rate,
+            config.interest_multiplier,
+            config.optimal_utilization,
+            config.jump_multiplier,
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::StdError;
+
+    fn mock_init_msg() -> InitMsg {
+        InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            base_rate: Decimal256::percent(10),
+            interest_multiplier: Decimal256::percent(10),
+            optimal_utilization: Decimal256::percent(80),
+            jump_multiplier: Decimal256::from_uint256(2u64),
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let env = mock_env("addr0000", &[]);
+
+        // we can just call .unwrap() to assert this was a success
+        let res = init(&mut deps, env, mock_init_msg()).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let value = query_config(&deps).unwrap();
+        assert_eq!("owner0000", value.owner.as_str());
+        assert_eq!("0.1", &value.base_rate.to_string());
+        assert_eq!("0.1", &value.interest_multiplier.to_string());
+        assert_eq!("0.8", &value.optimal_utilization.to_string());
+        assert_eq!("2", &value.jump_multiplier.to_string());
+
+        let value = query_borrow_rate(
+            &deps,
+            Uint256::from(1000000u128),
+            Decimal256::from_uint256(500000u128),
+            Decimal256::from_uint256(100000u128),
+        )
+        .unwrap();
+        // utilization_ratio = 0.35714285714285714, below the kink
+        // borrow_rate = 0.035714285 + 0.1
+        assert_eq!("0.135714285714285714", &value.rate.to_string());
+
+        let value = query_borrow_rate(
+            &deps,
+            Uint256::from(100000u128),
+            Decimal256::from_uint256(900000u128),
+            Decimal256::zero(),
+        )
+        .unwrap();
+        // utilization_ratio = 0.9, above the kink
+        // borrow_rate = 0.8 * 0.1 + 0.1 * 2 + 0.1
+        assert_eq!("0.38", &value.rate.to_string());
+
+        // optimal utilization above one
+        let msg = InitMsg {
+            optimal_utilization: Decimal256::percent(101),
+            ..mock_init_msg()
+        };
+        let env = mock_env("addr0000", &[]);
+        match init(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "optimal_utilization cannot be bigger than one")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, mock_init_msg()).unwrap();
+
+        // update owner and kink
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: Some(HumanAddr("owner0001".to_string())),
+            base_rate: None,
+            interest_multiplier: None,
+            optimal_utilization: Some(Decimal256::percent(90)),
+            jump_multiplier: Some(Decimal256::from_uint256(3u64)),
+        };
+
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let value = query_config(&deps).unwrap();
+        assert_eq!("owner0001", value.owner.as_str());
+        assert_eq!("0.1", &value.base_rate.to_string());
+        assert_eq!("0.1", &value.interest_multiplier.to_string());
+        assert_eq!("0.9", &value.optimal_utilization.to_string());
+        assert_eq!("3", &value.jump_multiplier.to_string());
+
+        // invalid kink
+        let env = mock_env("owner0001", &[]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: None,
+            base_rate: None,
+            interest_multiplier: None,
+            optimal_utilization: Some(Decimal256::percent(120)),
+            jump_multiplier: None,
+        };
+
+        let res = handle(&mut deps, env, msg);
+        match res {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "optimal_utilization cannot be bigger than one")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // Unauthorized err
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: None,
+            base_rate: Some(Decimal256::percent(1)),
+            interest_multiplier: Some(Decimal256::percent(1)),
+            optimal_utilization: None,
+            jump_multiplier: None,
+        };
+
+        let res = handle(&mut deps, env, msg);
+        match res {
+            Err(StdError::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+    }
+}
@@ -10,8 +10,9 @@ use cosmwasm_std::{
 };
 use cw20::Cw20HandleMsg;
 use moneymarket::liquidation::{BidResponse, BidsResponse};
-use moneymarket::oracle::PriceResponse;
-use moneymarket::querier::{deduct_tax, query_price, TimeConstraints};
+use moneymarket::oracle::{PriceResponse, PriceStatus};
+use moneymarket::querier::{deduct_tax, query_twap_price, TimeConstraints};
+use moneymarket_math::oracle::compute_confidence_band;
 
 pub fn submit_bid<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -141,18 +142,29 @@ pub fn execute_bid<S: Storage, A: Api, Q: Querier>(
     let bid: Bid = read_bid(&deps.storage, &bidder_raw, &collateral_token_raw)?;
 
     let oracle_contract = deps.api.human_address(&config.oracle_contract)?;
-    let price: PriceResponse = query_price(
+    let price: PriceResponse = query_twap_price(
         &deps,
         &oracle_contract,
         collateral_token.to_string(),
         config.stable_denom.clone(),
+        config.twap_window,
         Some(TimeConstraints {
             block_time: env.block.time,
             valid_timeframe: config.price_timeframe,
         }),
     )?;
 
-    let collateral_value = amount * price.rate;
+    // halted or unknown markets cannot be liquidated into
+    if price.status != PriceStatus::Trading {
+        return Err(StdError::generic_err(format!(
+            "Collateral price is not trading: {}",
+            collateral_token
+        )));
+    }
+
+    // uncertain prices make the bidder pay more
+    let (_, price_upper) = compute_confidence_band(price.rate, price.confidence);
+    let collateral_value = amount * price_upper;
     let required_stable = collateral_value
         * (Decimal256::one() - std::cmp::min(bid.premium_rate, config.max_premium_rate));
     if required_stable > bid.amount {
@@ -14,6 +14,7 @@ use moneymarket::liquidation::{
 };
 use moneymarket::querier::query_tax_rate;
 use moneymarket::tokens::TokensHuman;
+use moneymarket_math::liquidation::{compute_fee_deductor, compute_liquidation_ratio};
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -31,6 +32,7 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
             max_premium_rate: msg.max_premium_rate,
             liquidation_threshold: msg.liquidation_threshold,
             price_timeframe: msg.price_timeframe,
+            twap_window: 0,
         },
     )?;
 
@@ -53,6 +55,7 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             max_premium_rate,
             liquidation_threshold,
             price_timeframe,
+            twap_window,
         } => update_config(
             deps,
             env,
@@ -64,6 +67,7 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             max_premium_rate,
             liquidation_threshold,
             price_timeframe,
+            twap_window,
         ),
         HandleMsg::SubmitBid {
             collateral_token,
@@ -123,6 +127,7 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
     max_premium_rate: Option<Decimal256>,
     liquidation_threshold: Option<Uint256>,
     price_timeframe: Option<u64>,
+    twap_window: Option<u64>,
 ) -> HandleResult {
     let mut config: Config = read_config(&deps.storage)?;
     if deps.api.canonical_address(&env.message.sender)? != config.owner {
@@ -161,6 +166,10 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
         config.price_timeframe = price_timeframe;
     }
 
+    if let Some(twap_window) = twap_window {
+        config.twap_window = twap_window;
+    }
+
     store_config(&mut deps.storage, &config)?;
     Ok(HandleResponse::default())
 }
@@ -218,6 +227,7 @@ fn query_config<S: Storage, A: Api, Q: Querier>(
         max_premium_rate: config.max_premium_rate,
         liquidation_threshold: config.liquidation_threshold,
         price_timeframe: config.price_timeframe,
+        twap_window: config.twap_window,
     };
 
     Ok(resp)
@@ -247,30 +257,21 @@ fn query_liquidation_amount<S: Storage, A: Api, Q: Querier>(
     }
 
     let tax_rate = query_tax_rate(&deps)?;
+    let fee_deductor = compute_fee_deductor(config.max_premium_rate, config.bid_fee, tax_rate);
+    let liquidation_ratio = compute_liquidation_ratio(
+        borrow_amount,
+        borrow_limit,
+        collaterals_value,
+        fee_deductor,
+        config.safe_ratio,
+        config.liquidation_threshold,
+    );
 
-    let fee_deductor = (Decimal256::one() - config.max_premium_rate)
-        * (Decimal256::one() - config.bid_fee)
-        * (Decimal256::one() - tax_rate);
-
-    // expected_repay_amount must be bigger than borrow_amount
-    // else force liquidate all collaterals
-    let expected_repay_amount = collaterals_value * fee_deductor;
-    if expected_repay_amount <= borrow_amount {
+    // Force liquidate all collaterals
+    if liquidation_ratio == Decimal256::one() {
         return Ok(LiquidationAmountResponse { collaterals });
     }
 
-    // When collaterals_value is smaller than liquidation_threshold,
-    // liquidate all collaterals
-    let safe_borrow_amount = borrow_limit * config.safe_ratio;
-    let liquidation_ratio = if collaterals_value < config.liquidation_threshold {
-        Decimal256::from_uint256(borrow_amount) / Decimal256::from_uint256(expected_repay_amount)
-    } else {
-        Decimal256::from_uint256(borrow_amount - safe_borrow_amount)
-            / Decimal256::from_uint256(expected_repay_amount - safe_borrow_amount)
-    };
-
-    // Cap the liquidation_ratio to 1
-    let liquidation_ratio = std::cmp::min(Decimal256::one(), liquidation_ratio);
     Ok(LiquidationAmountResponse {
         collaterals: collaterals
             .iter()
@@ -25,6 +25,10 @@ pub struct Config {
     pub max_premium_rate: Decimal256,
     pub liquidation_threshold: Uint256,
     pub price_timeframe: u64,
+    /// Bids are executed at the oracle TWAP over this many seconds;
+    /// zero uses the spot price
+    #[serde(default)]
+    pub twap_window: u64,
 }
 
 pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<()> {
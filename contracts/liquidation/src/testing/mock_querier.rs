@@ -9,7 +9,7 @@ use cosmwasm_std::{
 };
 use std::collections::HashMap;
 
-use moneymarket::oracle::PriceResponse;
+use moneymarket::oracle::{PriceResponse, PriceStatus};
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -72,12 +72,16 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
 pub struct OraclePriceQuerier {
     // this lets us iterate over all pairs that match the first string
     oracle_price: HashMap<(String, String), (Decimal256, u64, u64)>,
+    oracle_confidence: HashMap<(String, String), Decimal256>,
+    oracle_status: HashMap<(String, String), PriceStatus>,
 }
 
 impl OraclePriceQuerier {
     pub fn new(oracle_price: &[(&(String, String), &(Decimal256, u64, u64))]) -> Self {
         OraclePriceQuerier {
             oracle_price: oracle_price_to_map(oracle_price),
+            oracle_confidence: HashMap::new(),
+            oracle_status: HashMap::new(),
         }
     }
 }
@@ -142,11 +146,28 @@ impl WasmMockQuerier {
                 msg,
             }) => match from_binary(&msg).unwrap() {
                 QueryMsg::Price { base, quote } => {
-                    match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
+                    match self
+                        .oracle_price_querier
+                        .oracle_price
+                        .get(&(base.clone(), quote.clone()))
+                    {
                         Some(v) => Ok(to_binary(&PriceResponse {
                             rate: v.0,
                             last_updated_base: v.1,
                             last_updated_quote: v.2,
+                            feeders_base: 1,
+                            feeders_quote: 1,
+                            confidence: self
+                                .oracle_price_querier
+                                .oracle_confidence
+                                .get(&(base.clone(), quote.clone()))
+                                .copied(),
+                            status: self
+                                .oracle_price_querier
+                                .oracle_status
+                                .get(&(base, quote))
+                                .copied()
+                                .unwrap_or(PriceStatus::Trading),
                         })),
                         None => Err(SystemError::InvalidRequest {
                             error: "No oracle price exists".to_string(),
@@ -180,4 +201,21 @@ impl WasmMockQuerier {
     ) {
         self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
     }
+
+    pub fn with_oracle_confidence(
+        &mut self,
+        oracle_confidence: &[(&(String, String), &Decimal256)],
+    ) {
+        self.oracle_price_querier.oracle_confidence = oracle_confidence
+            .iter()
+            .map(|(base_quote, confidence)| ((*base_quote).clone(), **confidence))
+            .collect();
+    }
+
+    pub fn with_oracle_status(&mut self, oracle_status: &[(&(String, String), &PriceStatus)]) {
+        self.oracle_price_querier.oracle_status = oracle_status
+            .iter()
+            .map(|(base_quote, status)| ((*base_quote).clone(), **status))
+            .collect();
+    }
 }
@@ -12,6 +12,7 @@ use moneymarket::liquidation::{
     BidResponse, BidsResponse, ConfigResponse, Cw20HookMsg, HandleMsg, InitMsg,
     LiquidationAmountResponse, QueryMsg,
 };
+use moneymarket::oracle::PriceStatus;
 
 #[test]
 fn proper_initialization() {
@@ -47,6 +48,7 @@ fn proper_initialization() {
             max_premium_rate: Decimal256::percent(5),
             liquidation_threshold: Uint256::from(100000000u64),
             price_timeframe: 60u64,
+            twap_window: 0u64,
         }
     );
 }
@@ -80,6 +82,7 @@ fn update_config() {
         max_premium_rate: None,
         liquidation_threshold: None,
         price_timeframe: None,
+        twap_window: None,
     };
 
     let res = handle(&mut deps, env, msg).unwrap();
@@ -98,6 +101,7 @@ fn update_config() {
             max_premium_rate: Decimal256::percent(5),
             liquidation_threshold: Uint256::from(100000000u64),
             price_timeframe: 60u64,
+            twap_window: 0u64,
         }
     );
 
@@ -112,6 +116,7 @@ fn update_config() {
         max_premium_rate: Some(Decimal256::percent(7)),
         liquidation_threshold: Some(Uint256::from(150000000u64)),
         price_timeframe: Some(120u64),
+        twap_window: Some(600u64),
     };
 
     let res = handle(&mut deps, env, msg).unwrap();
@@ -130,6 +135,7 @@ fn update_config() {
             max_premium_rate: Decimal256::percent(7),
             liquidation_threshold: Uint256::from(150000000u64),
             price_timeframe: 120u64,
+            twap_window: 600u64,
         }
     );
 
@@ -144,6 +150,7 @@ fn update_config() {
         max_premium_rate: Some(Decimal256::percent(7)),
         liquidation_threshold: Some(Uint256::from(150000000u64)),
         price_timeframe: Some(100u64),
+        twap_window: None,
     };
 
     let res = handle(&mut deps, env, msg);
@@ -456,6 +463,87 @@ fn execute_bid() {
     );
 }
 
+#[test]
+fn execute_bid_with_confidence() {
+    let mut deps = mock_dependencies(20, &[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        oracle_contract: HumanAddr::from("oracle0000"),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        max_premium_rate: Decimal256::percent(5),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(Decimal256::percent(50), env.block.time, env.block.time),
+    )]);
+    deps.querier.with_oracle_confidence(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &Decimal256::percent(5),
+    )]);
+
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::SubmitBid {
+        collateral_token: HumanAddr::from("asset0000"),
+        premium_rate: Decimal256::percent(1),
+    };
+    let env = mock_env(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500000u128),
+        }],
+    );
+    handle(&mut deps, env, msg).unwrap();
+
+    // collateral is valued at the top of the band, 0.5 + 0.05
+    // required_stable = 1,000,000 * 0.55 * 0.99
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0001"),
+        amount: Uint128::from(1000000u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: HumanAddr::from("addr0000"),
+                fee_address: None,
+                repay_address: None,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env("asset0000", &[]);
+    let res = handle(&mut deps, env, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Insufficient bid balance; Required balance: 544500")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a halted market cannot be liquidated into
+    deps.querier.with_oracle_status(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &PriceStatus::Halted,
+    )]);
+    let env = mock_env("asset0000", &[]);
+    let res = handle(&mut deps, env, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Collateral price is not trading: asset0000")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 #[test]
 fn query_liquidation_amount() {
     let mut deps = mock_dependencies(20, &[]);
@@ -6,10 +6,10 @@ use cosmwasm_std::{
 };
 use moneymarket::interest_model::BorrowRateResponse;
 use moneymarket::market::{BorrowerInfoResponse, BorrowerInfosResponse};
-use moneymarket::overseer::BorrowLimitResponse;
+use moneymarket::overseer::{BorrowLimitResponse, HandleMsg as OverseerHandleMsg};
 use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket_math::market::{self as math, Liability, MarketState};
 
-use crate::deposit::compute_exchange_rate_raw;
 use crate::querier::{query_borrow_limit, query_borrow_rate, query_target_deposit_rate};
 use crate::state::{
     read_borrower_info, read_borrower_infos, read_config, read_state, store_borrower_info,
@@ -39,8 +39,13 @@ pub fn borrow_stable<S: Storage, A: Api, Q: Querier>(
     compute_borrower_reward(&state, &mut liability);
 
     let overseer = deps.api.human_address(&config.overseer_contract)?;
-    let borrow_limit_res: BorrowLimitResponse =
-        query_borrow_limit(deps, &overseer, &borrower, Some(env.block.time))?;
+    let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
+        deps,
+        &overseer,
+        &borrower,
+        Some(env.block.height),
+        Some(env.block.time),
+    )?;
 
     if borrow_limit_res.borrow_limit < borrow_amount + liability.loan_amount {
         return Err(StdError::generic_err(format!(
@@ -59,22 +64,36 @@ pub fn borrow_stable<S: Storage, A: Api, Q: Querier>(
     assert_max_borrow_factor(&config, &state, current_balance, borrow_amount)?;
 
     liability.loan_amount += borrow_amount;
+    liability.isolated |= borrow_limit_res.isolated;
     state.total_liabilities += Decimal256::from_uint256(borrow_amount);
     store_state(&mut deps.storage, &state)?;
     store_borrower_info(&mut deps.storage, &borrower_raw, &liability)?;
 
+    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Bank(BankMsg::Send {
+        from_address: env.contract.address,
+        to_address: to.unwrap_or_else(|| borrower.clone()),
+        amount: vec![deduct_tax(
+            &deps,
+            Coin {
+                denom: config.stable_denom,
+                amount: borrow_amount.into(),
+            },
+        )?],
+    })];
+
+    // the overseer tracks the debt backed by isolated collaterals
+    if borrow_limit_res.isolated {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: overseer,
+            send: vec![],
+            msg: to_binary(&OverseerHandleMsg::UpdateBorrowerLoan {
+                borrower: borrower.clone(),
+            })?,
+        }));
+    }
+
     Ok(HandleResponse {
-        messages: vec![CosmosMsg::Bank(BankMsg::Send {
-            from_address: env.contract.address,
-            to_address: to.unwrap_or_else(|| borrower.clone()),
-            amount: vec![deduct_tax(
-                &deps,
-                Coin {
-                    denom: config.stable_denom,
-                    amount: borrow_amount.into(),
-                },
-            )?],
-        })],
+        messages,
         log: vec![
             log("action", "borrow_stable"),
             log("borrower", borrower),
@@ -175,9 +194,25 @@ pub fn repay_stable<S: Storage, A: Api, Q: Querier>(
 
     state.total_liabilities = state.total_liabilities - Decimal256::from_uint256(repay_amount);
 
+    // only loans recorded as isolated are tracked by the overseer
+    let isolated = liability.isolated;
+    if liability.loan_amount.is_zero() {
+        liability.isolated = false;
+    }
+
     store_borrower_info(&mut deps.storage, &borrower_raw, &liability)?;
     store_state(&mut deps.storage, &state)?;
 
+    if isolated {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.overseer_contract)?,
+            send: vec![],
+            msg: to_binary(&OverseerHandleMsg::UpdateBorrowerLoan {
+                borrower: borrower.clone(),
+            })?,
+        }));
+    }
+
     Ok(HandleResponse {
         messages,
         log: vec![
@@ -294,71 +329,37 @@ pub fn compute_interest_raw(
     borrow_rate: Decimal256,
     target_deposit_rate: Decimal256,
 ) {
-    if state.last_interest_updated >= block_height {
-        return;
-    }
-
-    let passed_blocks = Decimal256::from_uint256(block_height - state.last_interest_updated);
-
-    let interest_factor = passed_blocks * borrow_rate;
-    let interest_accrued = state.total_liabilities * interest_factor;
-
-    state.global_interest_index =
-        state.global_interest_index * (Decimal256::one() + interest_factor);
-    state.total_liabilities += interest_accrued;
-
-    let mut exchange_rate = compute_exchange_rate_raw(&state, aterra_supply, balance);
-    let effective_deposit_rate = exchange_rate / state.prev_exchange_rate;
-    let deposit_rate = (effective_deposit_rate - Decimal256::one()) / passed_blocks;
-
-    if deposit_rate > target_deposit_rate {
-        // excess_deposit_rate(_per_block)
-        let excess_deposit_rate = deposit_rate - target_deposit_rate;
-        let prev_deposits =
-            Decimal256::from_uint256(state.prev_aterra_supply * state.prev_exchange_rate);
-
-        // excess_yield = prev_deposits * excess_deposit_rate(_per_block) * blocks
-        let excess_yield = prev_deposits * passed_blocks * excess_deposit_rate;
-        
-        state.total_reserves += excess_yield;
-        exchange_rate = compute_exchange_rate_raw(&state, aterra_supply, balance);
-    }
-
-    state.prev_aterra_supply = aterra_supply;
-    state.prev_exchange_rate = exchange_rate;
-    state.last_interest_updated = block_height;
+    let mut market_state = MarketState::from(&*state);
+    math::compute_interest(
+        &mut market_state,
+        block_height,
+        balance,
+        aterra_supply,
+        borrow_rate,
+        target_deposit_rate,
+    );
+    *state = State::from(market_state);
 }
 
 /// Compute new interest and apply to liability
-pub(crate) fn compute_borrower_interest(state: &State, liability: &mut BorrowerInfo) {
-    liability.loan_amount =
-        liability.loan_amount * state.global_interest_index / liability.interest_index;
-    liability.interest_index = state.global_interest_index;
+pub fn compute_borrower_interest(state: &State, liability: &mut BorrowerInfo) {
+    let mut market_liability = Liability::from(&*liability);
+    math::compute_borrower_interest(&MarketState::from(state), &mut market_liability);
+    liability.apply(market_liability);
 }
 
 /// Compute distributed reward and update global index
 pub fn compute_reward(state: &mut State, block_height: u64) {
-    if state.last_reward_updated >= block_height {
-        return;
-    }
-
-    let passed_blocks = Decimal256::from_uint256(block_height - state.last_reward_updated);
-    let reward_accrued = passed_blocks * state.anc_emission_rate;
-    let borrow_amount = state.total_liabilities / state.global_interest_index;
-
-    if !reward_accrued.is_zero() && !borrow_amount.is_zero() {
-        state.global_reward_index += reward_accrued / borrow_amount;
-    }
-
-    state.last_reward_updated = block_height;
+    let mut market_state = MarketState::from(&*state);
+    math::compute_reward(&mut market_state, block_height);
+    *state = State::from(market_state);
 }
 
 /// Compute reward amount a borrower received
-pub(crate) fn compute_borrower_reward(state: &State, liability: &mut BorrowerInfo) {
-    liability.pending_rewards += Decimal256::from_uint256(liability.loan_amount)
-        / state.global_interest_index
-        * (state.global_reward_index - liability.reward_index);
-    liability.reward_index = state.global_reward_index;
+pub fn compute_borrower_reward(state: &State, liability: &mut BorrowerInfo) {
+    let mut market_liability = Liability::from(&*liability);
+    math::compute_borrower_reward(&MarketState::from(state), &mut market_liability);
+    liability.apply(market_liability);
 }
 
 pub fn query_borrower_info<S: Storage, A: Api, Q: Querier>(
@@ -9,6 +9,7 @@ use crate::state::{read_config, read_state, store_state, Config, State};
 
 use cw20::Cw20HandleMsg;
 use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket_math::market::{self as math, MarketState};
 
 pub fn deposit_stable<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -166,12 +167,5 @@ pub fn compute_exchange_rate_raw(
     aterra_supply: Uint256,
     contract_balance: Uint256,
 ) -> Decimal256 {
-    if aterra_supply.is_zero() {
-        return Decimal256::one();
-    }
-
-    // (aterra / stable_denom)
-    // exchange_rate = (balance + total_liabilities - total_reserves) / aterra_supply
-    (Decimal256::from_uint256(contract_balance) + state.total_liabilities - state.total_reserves)
-        / Decimal256::from_uint256(aterra_supply)
+    math::compute_exchange_rate(&MarketState::from(state), aterra_supply, contract_balance)
 }
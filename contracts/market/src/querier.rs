@@ -31,6 +31,7 @@ pub fn query_borrow_limit<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     overseer_addr: &HumanAddr,
     borrower: &HumanAddr,
+    block_height: Option<u64>,
     block_time: Option<u64>,
 ) -> StdResult<BorrowLimitResponse> {
     let borrow_limit: BorrowLimitResponse =
@@ -38,6 +39,7 @@ pub fn query_borrow_limit<S: Storage, A: Api, Q: Querier>(
             contract_addr: HumanAddr::from(overseer_addr),
             msg: to_binary(&OverseerQueryMsg::BorrowLimit {
                 borrower: HumanAddr::from(borrower),
+                block_height,
                 block_time,
             })?,
         }))?;
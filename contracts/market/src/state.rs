@@ -6,6 +6,7 @@ use cosmwasm_std::{Api, CanonicalAddr, Extern, HumanAddr, Order, Querier, StdRes
 use cosmwasm_storage::{bucket, bucket_read, ReadonlyBucket, ReadonlySingleton, Singleton};
 
 use moneymarket::market::BorrowerInfoResponse;
+use moneymarket_math::market::{Liability, MarketState};
 
 pub const KEY_CONFIG: &[u8] = b"config";
 pub const KEY_STATE: &[u8] = b"state";
@@ -45,6 +46,62 @@ pub struct BorrowerInfo {
     pub reward_index: Decimal256,
     pub loan_amount: Uint256,
     pub pending_rewards: Decimal256,
+    /// Set once the loan is backed by an isolated collateral
+    #[serde(default)]
+    pub isolated: bool,
+}
+
+impl From<&State> for MarketState {
+    fn from(state: &State) -> Self {
+        MarketState {
+            total_liabilities: state.total_liabilities,
+            total_reserves: state.total_reserves,
+            last_interest_updated: state.last_interest_updated,
+            last_reward_updated: state.last_reward_updated,
+            global_interest_index: state.global_interest_index,
+            global_reward_index: state.global_reward_index,
+            anc_emission_rate: state.anc_emission_rate,
+            prev_aterra_supply: state.prev_aterra_supply,
+            prev_exchange_rate: state.prev_exchange_rate,
+        }
+    }
+}
+
+impl From<MarketState> for State {
+    fn from(state: MarketState) -> Self {
+        State {
+            total_liabilities: state.total_liabilities,
+            total_reserves: state.total_reserves,
+            last_interest_updated: state.last_interest_updated,
+            last_reward_updated: state.last_reward_updated,
+            global_interest_index: state.global_interest_index,
+            global_reward_index: state.global_reward_index,
+            anc_emission_rate: state.anc_emission_rate,
+            prev_aterra_supply: state.prev_aterra_supply,
+            prev_exchange_rate: state.prev_exchange_rate,
+        }
+    }
+}
+
+impl From<&BorrowerInfo> for Liability {
+    fn from(info: &BorrowerInfo) -> Self {
+        Liability {
+            interest_index: info.interest_index,
+            reward_index: info.reward_index,
+            loan_amount: info.loan_amount,
+            pending_rewards: info.pending_rewards,
+        }
+    }
+}
+
+impl BorrowerInfo {
+    /// Writes back the loan fields updated by the shared math
+    pub fn apply(&mut self, liability: Liability) {
+        self.interest_index = liability.interest_index;
+        self.reward_index = liability.reward_index;
+        self.loan_amount = liability.loan_amount;
+        self.pending_rewards = liability.pending_rewards;
+    }
 }
 
 pub fn store_config<S: Storage>(storage: &mut S, data: &Config) -> StdResult<()> {
@@ -79,6 +136,7 @@ pub fn read_borrower_info<S: Storage>(storage: &S, borrower: &CanonicalAddr) ->
             reward_index: Decimal256::zero(),
             loan_amount: Uint256::zero(),
             pending_rewards: Decimal256::zero(),
+            isolated: false,
         },
     }
 }
@@ -24,6 +24,7 @@ fn proper_compute_borrower_interest() {
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::zero(),
         pending_rewards: Decimal256::zero(),
+        isolated: false,
     };
     compute_borrower_interest(&mock_state, &mut liability1);
     let liability2 = BorrowerInfo {
@@ -31,6 +32,7 @@ fn proper_compute_borrower_interest() {
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::zero(),
         pending_rewards: Decimal256::zero(),
+        isolated: false,
     };
     assert_eq!(liability1, liability2);
 
@@ -50,6 +52,7 @@ fn proper_compute_borrower_interest() {
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::from(80u128),
         pending_rewards: Decimal256::zero(),
+        isolated: false,
     };
     compute_borrower_interest(&mock_state2, &mut liability3);
     let liability4 = BorrowerInfo {
@@ -57,6 +60,7 @@ fn proper_compute_borrower_interest() {
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::from(40u128),
         pending_rewards: Decimal256::zero(),
+        isolated: false,
     };
     assert_eq!(liability3, liability4);
 }
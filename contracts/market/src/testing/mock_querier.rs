@@ -28,6 +28,7 @@ pub enum QueryMsg {
     /// Query borrow limit to overseer contract
     BorrowLimit {
         borrower: HumanAddr,
+        block_height: Option<u64>,
         block_time: Option<u64>,
     },
     /// Query ANC emission rate to distribution model contract
@@ -67,6 +68,7 @@ pub struct WasmMockQuerier {
     tax_querier: TaxQuerier,
     borrow_rate_querier: BorrowRateQuerier,
     borrow_limit_querier: BorrowLimitQuerier,
+    isolated_querier: IsolatedQuerier,
     canonical_length: usize,
 }
 
@@ -171,6 +173,24 @@ pub(crate) fn borrow_limit_to_map(
     borrow_limit_map
 }
 
+#[derive(Clone, Default)]
+pub struct IsolatedQuerier {
+    // borrower to the isolated collateral it holds
+    collaterals: HashMap<HumanAddr, HumanAddr>,
+}
+
+impl IsolatedQuerier {
+    pub fn new(collaterals: &[(&HumanAddr, &HumanAddr)]) -> Self {
+        let mut collaterals_map: HashMap<HumanAddr, HumanAddr> = HashMap::new();
+        for (borrower, collateral_token) in collaterals.iter() {
+            collaterals_map.insert((*borrower).clone(), (*collateral_token).clone());
+        }
+        IsolatedQuerier {
+            collaterals: collaterals_map,
+        }
+    }
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -230,11 +250,15 @@ impl WasmMockQuerier {
                     },
                     QueryMsg::BorrowLimit {
                         borrower,
+                        block_height: _,
                         block_time: _,
                     } => match self.borrow_limit_querier.borrow_limit.get(&borrower) {
                         Some(v) => Ok(to_binary(&BorrowLimitResponse {
-                            borrower,
+                            borrower: borrower.clone(),
                             borrow_limit: *v,
+                            liquidation_limit: *v,
+                            category: None,
+                            isolated: self.isolated_querier.collaterals.contains_key(&borrower),
                         })),
                         None => Err(SystemError::InvalidRequest {
                             error: "No borrow limit exists".to_string(),
@@ -262,6 +286,9 @@ impl WasmMockQuerier {
                         stable_denom: "uusd".to_string(),
                         epoch_period: 100u64,
                         price_timeframe: 100u64,
+                        twap_window: 0u64,
+                        close_factor: Decimal256::zero(),
+                        pid_interest_model: None,
                     })),
                 }
             }
@@ -337,6 +364,7 @@ impl WasmMockQuerier {
             tax_querier: TaxQuerier::default(),
             borrow_rate_querier: BorrowRateQuerier::default(),
             borrow_limit_querier: BorrowLimitQuerier::default(),
+            isolated_querier: IsolatedQuerier::default(),
             canonical_length,
         }
     }
@@ -367,4 +395,8 @@ impl WasmMockQuerier {
     pub fn with_borrow_limit(&mut self, borrow_limit: &[(&HumanAddr, &Uint256)]) {
         self.borrow_limit_querier = BorrowLimitQuerier::new(borrow_limit);
     }
+
+    pub fn with_isolated_collateral(&mut self, collaterals: &[(&HumanAddr, &HumanAddr)]) {
+        self.isolated_querier = IsolatedQuerier::new(collaterals);
+    }
 }
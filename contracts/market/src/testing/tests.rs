@@ -13,6 +13,7 @@ use cw20::{Cw20CoinHuman, Cw20HandleMsg, Cw20ReceiveMsg, MinterResponse};
 use moneymarket::market::{
     BorrowerInfoResponse, ConfigResponse, Cw20HookMsg, HandleMsg, InitMsg, QueryMsg, StateResponse,
 };
+use moneymarket::overseer::HandleMsg as OverseerHandleMsg;
 use moneymarket::querier::deduct_tax;
 use std::str::FromStr;
 use terraswap::hook::InitHook;
@@ -1340,6 +1341,160 @@ fn repay_stable() {
     );
 }
 
+#[test]
+fn borrow_and_repay_stable_isolated() {
+    let mut deps = mock_dependencies(
+        20,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let env = mock_env(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+    // Register anchor token contract
+    let msg = HandleMsg::RegisterATerra {};
+    let env = mock_env("AT-uusd", &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // Register overseer contract
+    let msg = HandleMsg::RegisterContracts {
+        overseer_contract: HumanAddr::from("overseer"),
+        interest_model: HumanAddr::from("interest"),
+        distribution_model: HumanAddr::from("distribution"),
+        collector_contract: HumanAddr::from("collector"),
+        distributor_contract: HumanAddr::from("distributor"),
+    };
+    let mut env = mock_env("addr0000", &[]);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&HumanAddr::from("interest"), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&HumanAddr::from("addr0000"), &Uint256::from(1000000u64))]);
+
+    store_state(
+        &mut deps.storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // only borrowers holding isolated collateral are reported to the overseer
+    deps.querier
+        .with_isolated_collateral(&[(&HumanAddr::from("addr0000"), &HumanAddr::from("bisol"))]);
+    let update_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: HumanAddr::from("overseer"),
+        send: vec![],
+        msg: to_binary(&OverseerHandleMsg::UpdateBorrowerLoan {
+            borrower: HumanAddr::from("addr0000"),
+        })
+        .unwrap(),
+    });
+
+    let msg = HandleMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    env.block.height += 100;
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                to_address: HumanAddr::from("addr0000"),
+                amount: vec![deduct_tax(
+                    &deps,
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(500000u128),
+                    }
+                )
+                .unwrap()],
+            }),
+            update_msg.clone(),
+        ]
+    );
+
+    deps.querier.update_balance(
+        HumanAddr::from(MOCK_CONTRACT_ADDR),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+        }],
+    );
+
+    let msg = HandleMsg::RepayStable {};
+    env.message.sent_funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128(100000u128),
+    }];
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![update_msg.clone()]);
+
+    // a liquidation repays the rest and still reports the loan
+    deps.querier.update_balance(
+        HumanAddr::from(MOCK_CONTRACT_ADDR),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500000u128),
+        }],
+    );
+    let mut liquidation_env = mock_env("overseer", &[]);
+    liquidation_env.block.height = env.block.height;
+    let res = handle(
+        &mut deps,
+        liquidation_env,
+        HandleMsg::RepayStableFromLiquidation {
+            borrower: HumanAddr::from("addr0000"),
+            prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![update_msg]);
+
+    // a fully repaid loan is no longer reported
+    deps.querier.with_isolated_collateral(&[]);
+    let borrow_msg = HandleMsg::BorrowStable {
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+    };
+    let _res = handle(&mut deps, env.clone(), borrow_msg).unwrap();
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+}
+
 #[test]
 fn repay_stable_from_liquidation() {
     let mut deps = mock_dependencies(
@@ -4,7 +4,8 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::oracle::{
-    ConfigResponse, HandleMsg, InitMsg, PriceResponse, PricesResponse, QueryMsg,
+    CircuitBreakerResponse, ConfigResponse, FeedersResponse, HandleMsg, InitMsg, PriceResponse,
+    PricesResponse, QueryMsg, RouteResponse,
 };
 
 fn main() {
@@ -17,6 +18,9 @@ fn main() {
     export_schema(&schema_for!(HandleMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(FeedersResponse), &out_dir);
     export_schema(&schema_for!(PriceResponse), &out_dir);
     export_schema(&schema_for!(PricesResponse), &out_dir);
+    export_schema(&schema_for!(CircuitBreakerResponse), &out_dir);
+    export_schema(&schema_for!(RouteResponse), &out_dir);
 }
@@ -1,6 +1,11 @@
+use crate::migration::{migrate_config, migrate_feeders};
 use crate::state::{
-    read_config, read_feeder, read_price, read_prices, store_config, store_feeder, store_price,
-    Config, PriceInfo,
+    is_feeder, read_config, read_feeder_assets, read_feeders, read_observation_at, read_price,
+    read_price_bounds, read_price_opt, read_prices, read_quarantine, read_route, read_stale_prices,
+    read_submissions, remove_feeder, remove_oldest_observation, remove_quarantine, remove_route,
+    store_config, store_feeder, store_observation, store_price, store_price_bounds,
+    store_quarantine, store_route, store_submission, Config, Observation, PriceBounds, PriceInfo,
+    Quarantine, Submission, MAX_OBSERVATIONS,
 };
 
 use cosmwasm_bignumber::Decimal256;
@@ -10,8 +15,12 @@ use cosmwasm_std::{
 };
 
 use moneymarket::oracle::{
-    ConfigResponse, FeederResponse, HandleMsg, InitMsg, MigrateMsg, PriceResponse, PricesResponse,
-    PricesResponseElem, QueryMsg,
+    CircuitBreakerResponse, ConfigResponse, FeederResponse, FeedersResponse, HandleMsg, InitMsg,
+    MigrateMsg, PriceFeed, PriceResponse, PriceStatus, PricesResponse, PricesResponseElem,
+    QueryMsg, RouteResponse,
+};
+use moneymarket_math::oracle::{
+    compute_median_price, compute_price_deviation, compute_rate_confidence,
 };
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
@@ -19,11 +28,15 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
     _env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
+    assert_min_feeders(msg.min_feeders)?;
+
     store_config(
         &mut deps.storage,
         &Config {
             owner: deps.api.canonical_address(&msg.owner)?,
             base_asset: msg.base_asset,
+            min_feeders: msg.min_feeders,
+            feed_window: msg.feed_window,
         },
     )?;
 
@@ -36,9 +49,40 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     msg: HandleMsg,
 ) -> HandleResult {
     match msg {
-        HandleMsg::UpdateConfig { owner } => update_config(deps, env, owner),
+        HandleMsg::UpdateConfig {
+            owner,
+            min_feeders,
+            feed_window,
+        } => update_config(deps, env, owner, min_feeders, feed_window),
         HandleMsg::RegisterFeeder { asset, feeder } => register_feeder(deps, env, asset, feeder),
-        HandleMsg::FeedPrice { prices } => feed_prices(deps, env, prices),
+        HandleMsg::DeregisterFeeder { asset, feeder } => {
+            deregister_feeder(deps, env, asset, feeder)
+        }
+        HandleMsg::FeedPrice { prices } => feed_prices(
+            deps,
+            env,
+            prices
+                .into_iter()
+                .map(|(asset, price)| PriceFeed {
+                    asset,
+                    price,
+                    confidence: None,
+                    status: PriceStatus::Trading,
+                })
+                .collect(),
+        ),
+        HandleMsg::FeedPriceWithConfidence { prices } => feed_prices(deps, env, prices),
+        HandleMsg::UpdateCircuitBreaker {
+            asset,
+            max_deviation_per_update,
+            floor,
+            ceiling,
+        } => update_circuit_breaker(deps, env, asset, max_deviation_per_update, floor, ceiling),
+        HandleMsg::RegisterRoute { asset, quote } => register_route(deps, env, asset, quote),
+        HandleMsg::ResetCircuitBreaker {
+            asset,
+            accept_quarantined,
+        } => reset_circuit_breaker(deps, env, asset, accept_quarantined),
     }
 }
 
@@ -46,6 +90,8 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     owner: Option<HumanAddr>,
+    min_feeders: Option<u32>,
+    feed_window: Option<u64>,
 ) -> HandleResult {
     let mut config: Config = read_config(&deps.storage)?;
     if deps.api.canonical_address(&env.message.sender)? != config.owner {
@@ -56,6 +102,15 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
         config.owner = deps.api.canonical_address(&owner)?;
     }
 
+    if let Some(min_feeders) = min_feeders {
+        assert_min_feeders(min_feeders)?;
+        config.min_feeders = min_feeders;
+    }
+
+    if let Some(feed_window) = feed_window {
+        config.feed_window = feed_window;
+    }
+
     store_config(&mut deps.storage, &config)?;
     Ok(HandleResponse::default())
 }
@@ -88,34 +143,168 @@ pub fn register_feeder<S: Storage, A: Api, Q: Querier>(
     });
 }
 
+pub fn deregister_feeder<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset: String,
+    feeder: HumanAddr,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    let feeder_raw = deps.api.canonical_address(&feeder)?;
+    if !is_feeder(&deps.storage, &asset, &feeder_raw) {
+        return Err(StdError::generic_err(
+            "Feeder is not registered for the asset",
+        ));
+    }
+
+    remove_feeder(&mut deps.storage, &asset, &feeder_raw);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "deregister_feeder"),
+            log("asset", asset),
+            log("feeder", feeder),
+        ],
+        data: None,
+    })
+}
+
+pub fn register_route<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset: String,
+    quote: String,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    if asset == config.base_asset {
+        return Err(StdError::generic_err("The base asset cannot be routed"));
+    }
+
+    if quote == config.base_asset {
+        remove_route(&mut deps.storage, &asset);
+    } else {
+        if quote == asset || read_route_path(&deps.storage, &config, &quote)?.contains(&asset) {
+            return Err(StdError::generic_err("Route cannot loop back to the asset"));
+        }
+
+        store_route(&mut deps.storage, &asset, &quote)?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "register_route"),
+            log("asset", asset),
+            log("quote", quote),
+        ],
+        data: None,
+    })
+}
+
+fn assert_min_feeders(min_feeders: u32) -> StdResult<()> {
+    if min_feeders == 0 {
+        return Err(StdError::generic_err("min_feeders must be at least one"));
+    }
+
+    Ok(())
+}
+
 pub fn feed_prices<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    prices: Vec<(String, Decimal256)>,
+    prices: Vec<PriceFeed>,
 ) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
     let mut logs = vec![log("action", "feed_prices")];
     let sender_raw = deps.api.canonical_address(&env.message.sender)?;
-    for price in prices {
-        let asset: String = price.0;
-        let price: Decimal256 = price.1;
+    for feed in prices {
+        let asset: String = feed.asset;
+        let price: Decimal256 = feed.price;
 
         // Check feeder permission
-        let feeder = read_feeder(&deps.storage, &asset)?;
-        if feeder != sender_raw {
+        if !is_feeder(&deps.storage, &asset, &sender_raw) {
             return Err(StdError::unauthorized());
         }
 
         logs.push(log("asset", asset.to_string()));
         logs.push(log("price", price));
 
-        store_price(
+        store_submission(
             &mut deps.storage,
             &asset,
-            &PriceInfo {
-                last_updated_time: env.block.time,
+            &sender_raw,
+            &Submission {
                 price,
+                time: env.block.time,
+                confidence: feed.confidence,
+                status: feed.status,
             },
         )?;
+
+        // the price only moves once enough feeders agree within the window
+        let fresh_submissions: Vec<Submission> = read_submissions(&deps.storage, &asset)?
+            .into_iter()
+            .filter(|submission| submission.time + config.feed_window >= env.block.time)
+            .collect();
+        let feeders = fresh_submissions.len() as u32;
+        if feeders < config.min_feeders {
+            continue;
+        }
+
+        let mut fresh_prices: Vec<Decimal256> = fresh_submissions.iter().map(|s| s.price).collect();
+        // a submission without a band counts as a zero band, so a single
+        // feeder reporting one cannot set it alone
+        let confidence = if fresh_submissions.iter().any(|s| s.confidence.is_some()) {
+            let mut fresh_confidences: Vec<Decimal256> = fresh_submissions
+                .iter()
+                .map(|s| s.confidence.unwrap_or_default())
+                .collect();
+            compute_median_price(&mut fresh_confidences)
+        } else {
+            None
+        };
+        let mut fresh_statuses: Vec<PriceStatus> =
+            fresh_submissions.iter().map(|s| s.status).collect();
+        let status = compute_quorum_status(&mut fresh_statuses);
+
+        if let Some(median_price) = compute_median_price(&mut fresh_prices) {
+            if is_suspect_price(&deps.storage, &asset, median_price)? {
+                // hold the price back until the owner resets the breaker
+                store_quarantine(
+                    &mut deps.storage,
+                    &asset,
+                    &Quarantine {
+                        price: median_price,
+                        feeders,
+                        time: env.block.time,
+                        confidence,
+                        status,
+                    },
+                )?;
+                logs.push(log("circuit_breaker", asset.to_string()));
+                logs.push(log("quarantined_price", median_price));
+                continue;
+            }
+
+            update_price(
+                &mut deps.storage,
+                &asset,
+                median_price,
+                feeders,
+                env.block.time,
+                confidence,
+                status,
+            )?;
+        }
     }
 
     Ok(HandleResponse {
@@ -125,6 +314,175 @@ pub fn feed_prices<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Least reliable status reported by more than half of the submissions,
+/// so a single feeder cannot halt a price the others see trading
+fn compute_quorum_status(statuses: &mut [PriceStatus]) -> PriceStatus {
+    if statuses.is_empty() {
+        return PriceStatus::Unknown;
+    }
+
+    statuses.sort_unstable();
+    statuses[(statuses.len() - 1) / 2]
+}
+
+/// A price is suspect while the breaker is tripped, or when it leaves
+/// the asset's band or moves too far from the last applied price
+fn is_suspect_price<S: Storage>(storage: &S, asset: &str, price: Decimal256) -> StdResult<bool> {
+    if read_quarantine(storage, asset)?.is_some() {
+        return Ok(true);
+    }
+
+    let bounds: PriceBounds = read_price_bounds(storage, asset)?;
+    if matches!(bounds.floor, Some(floor) if price < floor)
+        || matches!(bounds.ceiling, Some(ceiling) if price > ceiling)
+    {
+        return Ok(true);
+    }
+
+    if let (Some(max_deviation), Some(prev)) = (
+        bounds.max_deviation_per_update,
+        read_price_opt(storage, asset)?,
+    ) {
+        return Ok(compute_price_deviation(prev.price, price) > max_deviation);
+    }
+
+    Ok(false)
+}
+
+pub fn update_circuit_breaker<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset: String,
+    max_deviation_per_update: Option<Decimal256>,
+    floor: Option<Decimal256>,
+    ceiling: Option<Decimal256>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    if let (Some(floor), Some(ceiling)) = (floor, ceiling) {
+        if floor > ceiling {
+            return Err(StdError::generic_err("floor cannot be bigger than ceiling"));
+        }
+    }
+
+    store_price_bounds(
+        &mut deps.storage,
+        &asset,
+        &PriceBounds {
+            max_deviation_per_update,
+            floor,
+            ceiling,
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "update_circuit_breaker"), log("asset", asset)],
+        data: None,
+    })
+}
+
+pub fn reset_circuit_breaker<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset: String,
+    accept_quarantined: bool,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    let quarantine = match read_quarantine(&deps.storage, &asset)? {
+        Some(quarantine) => quarantine,
+        None => {
+            return Err(StdError::generic_err(
+                "Circuit breaker is not tripped for the asset",
+            ))
+        }
+    };
+
+    remove_quarantine(&mut deps.storage, &asset);
+    if accept_quarantined {
+        update_price(
+            &mut deps.storage,
+            &asset,
+            quarantine.price,
+            quarantine.feeders,
+            quarantine.time,
+            quarantine.confidence,
+            quarantine.status,
+        )?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "reset_circuit_breaker"),
+            log("asset", asset),
+            log("accept_quarantined", accept_quarantined),
+        ],
+        data: None,
+    })
+}
+
+fn update_price<S: Storage>(
+    storage: &mut S,
+    asset: &str,
+    price: Decimal256,
+    feeders: u32,
+    time: u64,
+    confidence: Option<Decimal256>,
+    status: PriceStatus,
+) -> StdResult<()> {
+    // accumulate the previous price over the time it was live
+    let (cumulative_price, mut observation_count) = match read_price_opt(storage, asset)? {
+        Some(prev) if prev.last_updated_time == time => {
+            (prev.cumulative_price, prev.observation_count)
+        }
+        Some(prev) => (
+            prev.cumulative_price
+                + prev.price * Decimal256::from_uint256(time - prev.last_updated_time),
+            prev.observation_count + 1,
+        ),
+        None => (Decimal256::zero(), 1),
+    };
+
+    store_observation(
+        storage,
+        asset,
+        &Observation {
+            time,
+            price,
+            cumulative_price,
+            feeders,
+            confidence,
+            status,
+        },
+    )?;
+    if observation_count > MAX_OBSERVATIONS {
+        remove_oldest_observation(storage, asset)?;
+        observation_count = MAX_OBSERVATIONS;
+    }
+
+    store_price(
+        storage,
+        asset,
+        &PriceInfo {
+            last_updated_time: time,
+            price,
+            cumulative_price,
+            observation_count,
+            feeders,
+            confidence,
+            status,
+        },
+    )
+}
+
 pub fn query<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     msg: QueryMsg,
@@ -132,10 +490,40 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::Feeder { asset } => to_binary(&query_feeder(deps, asset)?),
-        QueryMsg::Price { base, quote } => to_binary(&query_price(deps, base, quote)?),
+        QueryMsg::Feeders { start_after, limit } => {
+            to_binary(&query_feeders(deps, start_after, limit)?)
+        }
+        QueryMsg::Price {
+            base,
+            quote,
+            block_time,
+        } => to_binary(&query_price(deps, base, quote, block_time)?),
+        QueryMsg::TwapPrice {
+            base,
+            quote,
+            window,
+            block_time,
+        } => to_binary(&query_twap_price(deps, base, quote, window, block_time)?),
+        QueryMsg::PriceAt { base, quote, time } => {
+            to_binary(&query_price_at(deps, base, quote, time)?)
+        }
         QueryMsg::Prices { start_after, limit } => {
             to_binary(&query_prices(deps, start_after, limit)?)
         }
+        QueryMsg::StalePrices {
+            max_age,
+            block_time,
+            start_after,
+            limit,
+        } => to_binary(&query_stale_prices(
+            deps,
+            max_age,
+            block_time,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::CircuitBreaker { asset } => to_binary(&query_circuit_breaker(deps, asset)?),
+        QueryMsg::Route { asset } => to_binary(&query_route(deps, asset)?),
     }
 }
 
@@ -146,6 +534,8 @@ fn query_config<S: Storage, A: Api, Q: Querier>(
     let resp = ConfigResponse {
         owner: deps.api.human_address(&state.owner)?,
         base_asset: state.base_asset,
+        min_feeders: state.min_feeders,
+        feed_window: state.feed_window,
     };
 
     Ok(resp)
@@ -155,44 +545,284 @@ fn query_feeder<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     asset: String,
 ) -> StdResult<FeederResponse> {
-    let feeder = read_feeder(&deps.storage, &asset)?;
+    let feeders = read_feeders(&deps.storage, &asset)?
+        .iter()
+        .map(|feeder| deps.api.human_address(feeder))
+        .collect::<StdResult<Vec<HumanAddr>>>()?;
     let resp = FeederResponse {
         asset,
-        feeder: deps.api.human_address(&feeder)?,
+        feeder: feeders[0].clone(),
+        feeders,
     };
 
     Ok(resp)
 }
 
+fn query_feeders<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<FeedersResponse> {
+    let feeders = read_feeder_assets(&deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|asset| query_feeder(deps, asset))
+        .collect::<StdResult<Vec<FeederResponse>>>()?;
+
+    Ok(FeedersResponse { feeders })
+}
+
 fn query_price<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     base: String,
     quote: String,
+    block_time: Option<u64>,
 ) -> StdResult<PriceResponse> {
     let config: Config = read_config(&deps.storage)?;
-    let quote_price = if config.base_asset == quote {
-        PriceInfo {
-            price: Decimal256::one(),
-            last_updated_time: 9999999999,
+    let leg_price = |asset: &str| read_live_price(&deps.storage, &config, asset, block_time);
+    let quote_price = resolve_route(&deps.storage, &quote, leg_price)?;
+    let base_price = resolve_route(&deps.storage, &base, leg_price)?;
+
+    Ok(compute_price_response(base_price, quote_price))
+}
+
+fn compute_price_response(base_price: PriceInfo, quote_price: PriceInfo) -> PriceResponse {
+    let rate = base_price.price / quote_price.price;
+    PriceResponse {
+        rate,
+        last_updated_base: base_price.last_updated_time,
+        last_updated_quote: quote_price.last_updated_time,
+        feeders_base: base_price.feeders,
+        feeders_quote: quote_price.feeders,
+        confidence: compute_rate_confidence(
+            rate,
+            &[
+                (base_price.price, base_price.confidence),
+                (quote_price.price, quote_price.confidence),
+            ],
+        ),
+        status: base_price.status.max(quote_price.status),
+    }
+}
+
+/// Time weighted average price over the `window` seconds before
+/// `block_time`, the same interval for every leg; the latest price counts
+/// up to `block_time`. A zero window returns the spot price
+fn query_twap_price<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    base: String,
+    quote: String,
+    window: u64,
+    block_time: u64,
+) -> StdResult<PriceResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    let leg_price =
+        |asset: &str| compute_twap_price(&deps.storage, &config, asset, window, block_time);
+    let quote_price = resolve_route(&deps.storage, &quote, leg_price)?;
+    let base_price = resolve_route(&deps.storage, &base, leg_price)?;
+
+    Ok(compute_price_response(base_price, quote_price))
+}
+
+fn compute_twap_price<S: Storage>(
+    storage: &S,
+    config: &Config,
+    asset: &str,
+    window: u64,
+    block_time: u64,
+) -> StdResult<PriceInfo> {
+    let price: PriceInfo = read_live_price(storage, config, asset, Some(block_time))?;
+    if config.base_asset == asset || window == 0 {
+        return Ok(price);
+    }
+
+    let start_time = block_time.checked_sub(window);
+    let cumulative_prices = match start_time {
+        Some(start_time) => (
+            read_cumulative_price_at(storage, asset, &price, start_time)?,
+            read_cumulative_price_at(storage, asset, &price, block_time)?,
+        ),
+        None => (None, None),
+    };
+    let (start_cumulative_price, end_cumulative_price) = match cumulative_prices {
+        (Some(start), Some(end)) => (start, end),
+        _ => {
+            return Err(StdError::generic_err(
+                "Not enough price history for the requested window",
+            ))
         }
-    } else {
-        read_price(&deps.storage, &quote)?
     };
 
-    let base_price = if config.base_asset == base {
-        PriceInfo {
+    Ok(PriceInfo {
+        price: (end_cumulative_price - start_cumulative_price) / Decimal256::from_uint256(window),
+        ..price
+    })
+}
+
+/// Latest price of the asset; with a `block_time`, `feeders` is the number
+/// of submissions still within `feed_window`, which must reach `min_feeders`
+fn read_live_price<S: Storage>(
+    storage: &S,
+    config: &Config,
+    asset: &str,
+    block_time: Option<u64>,
+) -> StdResult<PriceInfo> {
+    if config.base_asset == asset {
+        return Ok(PriceInfo {
             price: Decimal256::one(),
             last_updated_time: 9999999999,
+            cumulative_price: Decimal256::zero(),
+            observation_count: 0,
+            feeders: 0,
+            confidence: None,
+            status: PriceStatus::Trading,
+        });
+    }
+
+    if read_quarantine(storage, asset)?.is_some() {
+        return Err(StdError::generic_err(format!(
+            "Price circuit breaker is tripped for the asset: {}",
+            asset
+        )));
+    }
+
+    let mut price: PriceInfo = read_price(storage, asset)?;
+    if let Some(block_time) = block_time {
+        price.feeders = read_submissions(storage, asset)?
+            .iter()
+            .filter(|submission| submission.time + config.feed_window >= block_time)
+            .count() as u32;
+        if price.feeders < config.min_feeders {
+            return Err(StdError::generic_err(format!(
+                "Not enough fresh price submissions for the asset: {}",
+                asset
+            )));
         }
-    } else {
-        read_price(&deps.storage, &base)?
-    };
+    }
 
-    Ok(PriceResponse {
-        rate: base_price.price / quote_price.price,
-        last_updated_base: base_price.last_updated_time,
-        last_updated_quote: quote_price.last_updated_time,
-    })
+    Ok(price)
+}
+
+/// Sum of price * seconds since the first feed, up to `time`;
+/// every price stays live until the next observation
+fn read_cumulative_price_at<S: Storage>(
+    storage: &S,
+    asset: &str,
+    price: &PriceInfo,
+    time: u64,
+) -> StdResult<Option<Decimal256>> {
+    if time >= price.last_updated_time {
+        return Ok(Some(
+            price.cumulative_price
+                + price.price * Decimal256::from_uint256(time - price.last_updated_time),
+        ));
+    }
+
+    Ok(
+        read_observation_at(storage, asset, time)?.map(|observation| {
+            observation.cumulative_price
+                + observation.price * Decimal256::from_uint256(time - observation.time)
+        }),
+    )
+}
+
+/// Price each asset had at `time`, from the latest observation at or
+/// before it; `last_updated_*` are the times of those observations
+fn query_price_at<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    base: String,
+    quote: String,
+    time: u64,
+) -> StdResult<PriceResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    let leg_price = |asset: &str| read_price_at(&deps.storage, &config, asset, time);
+    let quote_price = resolve_route(&deps.storage, &quote, leg_price)?;
+    let base_price = resolve_route(&deps.storage, &base, leg_price)?;
+
+    Ok(compute_price_response(base_price, quote_price))
+}
+
+fn read_price_at<S: Storage>(
+    storage: &S,
+    config: &Config,
+    asset: &str,
+    time: u64,
+) -> StdResult<PriceInfo> {
+    if config.base_asset == asset {
+        return Ok(PriceInfo {
+            price: Decimal256::one(),
+            last_updated_time: 9999999999,
+            cumulative_price: Decimal256::zero(),
+            observation_count: 0,
+            feeders: 0,
+            confidence: None,
+            status: PriceStatus::Trading,
+        });
+    }
+
+    match read_observation_at(storage, asset, time)? {
+        Some(observation) => Ok(PriceInfo {
+            price: observation.price,
+            last_updated_time: observation.time,
+            cumulative_price: observation.cumulative_price,
+            observation_count: 0,
+            feeders: observation.feeders,
+            confidence: observation.confidence,
+            status: observation.status,
+        }),
+        None => Err(StdError::generic_err(format!(
+            "No price history for the asset at the requested time: {}",
+            asset
+        ))),
+    }
+}
+
+/// Multiplies the legs of the asset's route down to the base asset;
+/// the result is as old as its oldest leg
+fn resolve_route<S: Storage, F: Fn(&str) -> StdResult<PriceInfo>>(
+    storage: &S,
+    asset: &str,
+    leg_price: F,
+) -> StdResult<PriceInfo> {
+    let mut price: PriceInfo = leg_price(asset)?;
+    let mut leg_asset: String = asset.to_string();
+    while let Some(quote) = read_route(storage, &leg_asset)? {
+        let leg: PriceInfo = leg_price(&quote)?;
+        let rate = price.price * leg.price;
+        price = PriceInfo {
+            price: rate,
+            last_updated_time: price.last_updated_time.min(leg.last_updated_time),
+            feeders: price.feeders.min(leg.feeders),
+            confidence: compute_rate_confidence(
+                rate,
+                &[(price.price, price.confidence), (leg.price, leg.confidence)],
+            ),
+            status: price.status.max(leg.status),
+            ..price
+        };
+        leg_asset = quote;
+    }
+
+    Ok(price)
+}
+
+/// Assets the price of `asset` goes through, ending with the base asset
+fn read_route_path<S: Storage>(
+    storage: &S,
+    config: &Config,
+    asset: &str,
+) -> StdResult<Vec<String>> {
+    let mut route: Vec<String> = vec![];
+    let mut leg_asset: String = asset.to_string();
+    while let Some(quote) = read_route(storage, &leg_asset)? {
+        route.push(quote.clone());
+        leg_asset = quote;
+    }
+
+    if leg_asset != config.base_asset {
+        route.push(config.base_asset.clone());
+    }
+
+    Ok(route)
 }
 
 fn query_prices<S: Storage, A: Api, Q: Querier>(
@@ -204,6 +834,45 @@ fn query_prices<S: Storage, A: Api, Q: Querier>(
     Ok(PricesResponse { prices })
 }
 
+fn query_circuit_breaker<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset: String,
+) -> StdResult<CircuitBreakerResponse> {
+    let bounds: PriceBounds = read_price_bounds(&deps.storage, &asset)?;
+    let quarantine: Option<Quarantine> = read_quarantine(&deps.storage, &asset)?;
+
+    Ok(CircuitBreakerResponse {
+        asset,
+        max_deviation_per_update: bounds.max_deviation_per_update,
+        floor: bounds.floor,
+        ceiling: bounds.ceiling,
+        tripped: quarantine.is_some(),
+        quarantined_price: quarantine.as_ref().map(|q| q.price),
+        quarantined_time: quarantine.map(|q| q.time),
+    })
+}
+
+fn query_route<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset: String,
+) -> StdResult<RouteResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    let route = read_route_path(&deps.storage, &config, &asset)?;
+    Ok(RouteResponse { asset, route })
+}
+
+fn query_stale_prices<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    max_age: u64,
+    block_time: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PricesResponse> {
+    let prices: Vec<PricesResponseElem> =
+        read_stale_prices(&deps.storage, max_age, block_time, start_after, limit)?;
+    Ok(PricesResponse { prices })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,6 +887,8 @@ mod tests {
         let msg = InitMsg {
             owner: HumanAddr("owner0000".to_string()),
             base_asset: "base0000".to_string(),
+            min_feeders: 1,
+            feed_window: 60,
         };
 
         let env = mock_env("addr0000", &[]);
@@ -239,6 +910,8 @@ mod tests {
         let msg = InitMsg {
             owner: HumanAddr("owner0000".to_string()),
             base_asset: "base0000".to_string(),
+            min_feeders: 1,
+            feed_window: 60,
         };
 
         let env = mock_env("addr0000", &[]);
@@ -248,6 +921,8 @@ mod tests {
         let env = mock_env("owner0000", &[]);
         let msg = HandleMsg::UpdateConfig {
             owner: Some(HumanAddr("owner0001".to_string())),
+            min_feeders: None,
+            feed_window: None,
         };
 
         let res = handle(&mut deps, env, msg).unwrap();
@@ -258,9 +933,41 @@ mod tests {
         assert_eq!("owner0001", value.owner.as_str());
         assert_eq!("base0000", &value.base_asset.to_string());
 
+        // update quorum
+        let env = mock_env("owner0001", &[]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: None,
+            min_feeders: Some(3),
+            feed_window: Some(30),
+        };
+
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let value = query_config(&deps).unwrap();
+        assert_eq!(3, value.min_feeders);
+        assert_eq!(30, value.feed_window);
+
+        // empty quorum
+        let env = mock_env("owner0001", &[]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: None,
+            min_feeders: Some(0),
+            feed_window: None,
+        };
+
+        match handle(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "min_feeders must be at least one")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
         // Unauthorized err
         let env = mock_env("owner0000", &[]);
-        let msg = HandleMsg::UpdateConfig { owner: None };
+        let msg = HandleMsg::UpdateConfig {
+            owner: None,
+            min_feeders: None,
+            feed_window: None,
+        };
 
         let res = handle(&mut deps, env, msg);
         match res {
@@ -276,6 +983,8 @@ mod tests {
         let msg = InitMsg {
             owner: HumanAddr("owner0000".to_string()),
             base_asset: "base0000".to_string(),
+            min_feeders: 1,
+            feed_window: 60,
         };
 
         let env = mock_env("addr0000", &[]);
@@ -310,25 +1019,225 @@ mod tests {
             FeederResponse {
                 asset: "mAAPL".to_string(),
                 feeder: HumanAddr::from("feeder0000"),
+                feeders: vec![HumanAddr::from("feeder0000")],
             }
         );
     }
 
     #[test]
-    fn feed_price() {
+    fn deregister_feeder() {
         let mut deps = mock_dependencies(20, &[]);
 
         let msg = InitMsg {
             owner: HumanAddr("owner0000".to_string()),
             base_asset: "base0000".to_string(),
+            min_feeders: 2,
+            feed_window: 60,
         };
 
         let env = mock_env("addr0000", &[]);
         let _res = init(&mut deps, env, msg).unwrap();
 
-        // Register feeder for mAAPL
-        let msg = HandleMsg::RegisterFeeder {
-            asset: "mAAPL".to_string(),
+        let env = mock_env("owner0000", &[]);
+        for (asset, feeder) in &[
+            ("mAAPL", "feeder0000"),
+            ("mAAPL", "feeder0001"),
+            ("mGOGL", "feeder0000"),
+        ] {
+            let msg = HandleMsg::RegisterFeeder {
+                asset: asset.to_string(),
+                feeder: HumanAddr::from(*feeder),
+            };
+            let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        }
+
+        let value = query_feeders(&deps, None, None).unwrap();
+        assert_eq!(
+            value.feeders,
+            vec![
+                FeederResponse {
+                    asset: "mAAPL".to_string(),
+                    feeder: HumanAddr::from("feeder0000"),
+                    feeders: vec![HumanAddr::from("feeder0000"), HumanAddr::from("feeder0001")],
+                },
+                FeederResponse {
+                    asset: "mGOGL".to_string(),
+                    feeder: HumanAddr::from("feeder0000"),
+                    feeders: vec![HumanAddr::from("feeder0000")],
+                },
+            ]
+        );
+
+        let value = query_feeders(&deps, Some("mAAPL".to_string()), Some(1)).unwrap();
+        assert_eq!(value.feeders.len(), 1);
+        assert_eq!(value.feeders[0].asset, "mGOGL");
+
+        // Unauthorized err
+        let env = mock_env("addr0000", &[]);
+        let msg = HandleMsg::DeregisterFeeder {
+            asset: "mAAPL".to_string(),
+            feeder: HumanAddr::from("feeder0001"),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(StdError::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::DeregisterFeeder {
+            asset: "mGOGL".to_string(),
+            feeder: HumanAddr::from("feeder0001"),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "Feeder is not registered for the asset")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // the submission of a removed feeder no longer counts
+        let env = mock_env("feeder0001", &[]);
+        let msg = HandleMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::one())],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::DeregisterFeeder {
+            asset: "mAAPL".to_string(),
+            feeder: HumanAddr::from("feeder0001"),
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(
+            res.log,
+            vec![
+                log("action", "deregister_feeder"),
+                log("asset", "mAAPL"),
+                log("feeder", "feeder0001"),
+            ]
+        );
+
+        let env = mock_env("feeder0000", &[]);
+        let msg = HandleMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::one())],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        assert!(read_price_opt(&deps.storage, "mAAPL").unwrap().is_none());
+
+        let env = mock_env("feeder0001", &[]);
+        let msg = HandleMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::one())],
+        };
+        match handle(&mut deps, env, msg) {
+            Err(StdError::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // an asset without feeders is no longer listed
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::DeregisterFeeder {
+            asset: "mGOGL".to_string(),
+            feeder: HumanAddr::from("feeder0000"),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let value = query_feeders(&deps, None, None).unwrap();
+        assert_eq!(
+            value.feeders,
+            vec![FeederResponse {
+                asset: "mAAPL".to_string(),
+                feeder: HumanAddr::from("feeder0000"),
+                feeders: vec![HumanAddr::from("feeder0000")],
+            }]
+        );
+    }
+
+    #[test]
+    fn stale_prices() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            base_asset: "base0000".to_string(),
+            min_feeders: 1,
+            feed_window: 60,
+        };
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("owner0000", &[]);
+        for asset in &["mAAPL", "mGOGL"] {
+            let msg = HandleMsg::RegisterFeeder {
+                asset: asset.to_string(),
+                feeder: HumanAddr::from("feeder0000"),
+            };
+            let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        }
+
+        let start_time = mock_env("feeder0000", &[]).block.time;
+        for (asset, time) in &[("mAAPL", start_time), ("mGOGL", start_time + 100)] {
+            let mut env = mock_env("feeder0000", &[]);
+            env.block.time = *time;
+            let msg = HandleMsg::FeedPrice {
+                prices: vec![(asset.to_string(), Decimal256::one())],
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        let stale_assets =
+            |max_age: u64, block_time: u64, start_after: Option<String>, limit: Option<u32>| {
+                let msg = QueryMsg::StalePrices {
+                    max_age,
+                    block_time,
+                    start_after,
+                    limit,
+                };
+                let res: PricesResponse = from_binary(&query(&deps, msg).unwrap()).unwrap();
+                res.prices
+                    .into_iter()
+                    .map(|elem| elem.asset)
+                    .collect::<Vec<String>>()
+            };
+
+        assert_eq!(
+            stale_assets(60, start_time + 100, None, None),
+            vec!["mAAPL".to_string()]
+        );
+        assert_eq!(
+            stale_assets(60, start_time + 200, None, None),
+            vec!["mAAPL".to_string(), "mGOGL".to_string()]
+        );
+
+        // exactly max_age old is not stale yet
+        assert!(stale_assets(100, start_time + 100, None, None).is_empty());
+
+        assert_eq!(
+            stale_assets(60, start_time + 200, None, Some(1)),
+            vec!["mAAPL".to_string()]
+        );
+        assert_eq!(
+            stale_assets(60, start_time + 200, Some("mAAPL".to_string()), Some(1)),
+            vec!["mGOGL".to_string()]
+        );
+    }
+
+    #[test]
+    fn feed_price() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            base_asset: "base0000".to_string(),
+            min_feeders: 1,
+            feed_window: 60,
+        };
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        // Register feeder for mAAPL
+        let msg = HandleMsg::RegisterFeeder {
+            asset: "mAAPL".to_string(),
             feeder: HumanAddr::from("feeder0000"),
         };
         let env = mock_env("owner0000", &[]);
@@ -351,24 +1260,32 @@ mod tests {
         };
         let _res = handle(&mut deps, env.clone(), msg).unwrap();
         let value: PriceResponse =
-            query_price(&deps, "mAAPL".to_string(), "base0000".to_string()).unwrap();
+            query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap();
         assert_eq!(
             value,
             PriceResponse {
                 rate: Decimal256::from_str("1.2").unwrap(),
                 last_updated_base: env.block.time,
                 last_updated_quote: 9999999999,
+                feeders_base: 1,
+                feeders_quote: 0,
+                confidence: None,
+                status: PriceStatus::Trading,
             }
         );
 
         let value: PriceResponse =
-            query_price(&deps, "mGOGL".to_string(), "mAAPL".to_string()).unwrap();
+            query_price(&deps, "mGOGL".to_string(), "mAAPL".to_string(), None).unwrap();
         assert_eq!(
             value,
             PriceResponse {
                 rate: Decimal256::from_str("1.833333333333333333").unwrap(),
                 last_updated_base: env.block.time,
                 last_updated_quote: env.block.time,
+                feeders_base: 1,
+                feeders_quote: 1,
+                confidence: None,
+                status: PriceStatus::Trading,
             }
         );
 
@@ -381,11 +1298,15 @@ mod tests {
                         asset: "mAAPL".to_string(),
                         price: Decimal256::from_str("1.2").unwrap(),
                         last_updated_time: env.block.time,
+                        confidence: None,
+                        status: PriceStatus::Trading,
                     },
                     PricesResponseElem {
                         asset: "mGOGL".to_string(),
                         price: Decimal256::from_str("2.2").unwrap(),
                         last_updated_time: env.block.time,
+                        confidence: None,
+                        status: PriceStatus::Trading,
                     }
                 ],
             }
@@ -403,12 +1324,882 @@ mod tests {
             _ => panic!("Must return unauthorized error"),
         }
     }
+
+    #[test]
+    fn twap_price() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            base_asset: "base0000".to_string(),
+            min_feeders: 1,
+            feed_window: 60,
+        };
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("owner0000", &[]);
+        for asset in &["mAAPL", "mGOGL"] {
+            let msg = HandleMsg::RegisterFeeder {
+                asset: asset.to_string(),
+                feeder: HumanAddr::from("feeder0000"),
+            };
+            let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        }
+
+        // mAAPL 1.0 -> 2.0 -> 4.0 every 100 seconds, mGOGL stays at 2.0
+        let start_time = mock_env("feeder0000", &[]).block.time;
+        for (i, price) in vec!["1.0", "2.0", "4.0"].into_iter().enumerate() {
+            let mut env = mock_env("feeder0000", &[]);
+            env.block.time = start_time + 100 * i as u64;
+            let msg = HandleMsg::FeedPrice {
+                prices: vec![
+                    ("mAAPL".to_string(), Decimal256::from_str(price).unwrap()),
+                    ("mGOGL".to_string(), Decimal256::from_str("2.0").unwrap()),
+                ],
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        // zero window is the spot price
+        let block_time = start_time + 200;
+        let value = query_twap_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            0,
+            block_time,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap()
+        );
+        assert_eq!(value.rate, Decimal256::from_str("4.0").unwrap());
+
+        // (1.0 * 100 + 2.0 * 100) / 200
+        let value = query_twap_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            200,
+            block_time,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            PriceResponse {
+                rate: Decimal256::from_str("1.5").unwrap(),
+                last_updated_base: start_time + 200,
+                last_updated_quote: 9999999999,
+                feeders_base: 1,
+                feeders_quote: 0,
+                confidence: None,
+                status: PriceStatus::Trading,
+            }
+        );
+
+        // the window starts between two observations
+        // (1.0 * 50 + 2.0 * 100) / 150
+        let value = query_twap_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            150,
+            block_time,
+        )
+        .unwrap();
+        assert_eq!(
+            value.rate,
+            Decimal256::from_str("1.666666666666666666").unwrap()
+        );
+
+        // the latest price counts up to the block time
+        // (1.0 * 50 + 2.0 * 100 + 4.0 * 50) / 200
+        let value = query_twap_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            200,
+            block_time + 50,
+        )
+        .unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("2.25").unwrap());
+        assert_eq!(value.last_updated_base, start_time + 200);
+
+        // both legs are averaged over the same interval
+        // (2.0 * 50 + 4.0 * 50) / 100 over 2.0
+        let value = query_twap_price(
+            &deps,
+            "mAAPL".to_string(),
+            "mGOGL".to_string(),
+            100,
+            block_time + 50,
+        )
+        .unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("1.5").unwrap());
+
+        // the window goes beyond the first observation
+        match query_twap_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            201,
+            block_time,
+        ) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "Not enough price history for the requested window")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // a second feed in the same block replaces the observation
+        let mut env = mock_env("feeder0000", &[]);
+        env.block.time = start_time + 200;
+        let msg = HandleMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::from_str("3.0").unwrap())],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let value = query_twap_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            200,
+            block_time,
+        )
+        .unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("1.5").unwrap());
+        let price = read_price(&deps.storage, "mAAPL").unwrap();
+        assert_eq!(price.observation_count, 3);
+
+        // only the latest observations are kept
+        for i in 3..MAX_OBSERVATIONS + 1 {
+            let mut env = mock_env("feeder0000", &[]);
+            env.block.time = start_time + 100 * i;
+            let msg = HandleMsg::FeedPrice {
+                prices: vec![("mAAPL".to_string(), Decimal256::from_str("3.0").unwrap())],
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+        let price = read_price(&deps.storage, "mAAPL").unwrap();
+        assert_eq!(price.observation_count, MAX_OBSERVATIONS);
+        assert_eq!(
+            read_observation_at(&deps.storage, "mAAPL", start_time + 99).unwrap(),
+            None
+        );
+        let value = query_twap_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            100 * (MAX_OBSERVATIONS - 1),
+            start_time + 100 * MAX_OBSERVATIONS,
+        )
+        .unwrap();
+        // (2.0 * 100 + 3.0 * 100 * (MAX_OBSERVATIONS - 2)) / (100 * (MAX_OBSERVATIONS - 1))
+        assert_eq!(
+            value.rate,
+            Decimal256::from_uint256(2 + 3 * (MAX_OBSERVATIONS - 2))
+                / Decimal256::from_uint256(MAX_OBSERVATIONS - 1)
+        );
+    }
+
+    #[test]
+    fn price_at() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            base_asset: "base0000".to_string(),
+            min_feeders: 1,
+            feed_window: 60,
+        };
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("owner0000", &[]);
+        for asset in &["mAAPL", "mGOGL"] {
+            let msg = HandleMsg::RegisterFeeder {
+                asset: asset.to_string(),
+                feeder: HumanAddr::from("feeder0000"),
+            };
+            let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        }
+
+        let start_time = mock_env("feeder0000", &[]).block.time;
+        for (asset, price, time) in &[
+            ("mAAPL", "1.0", start_time),
+            ("mGOGL", "4.0", start_time + 50),
+            ("mAAPL", "2.0", start_time + 100),
+        ] {
+            let mut env = mock_env("feeder0000", &[]);
+            env.block.time = *time;
+            let msg = HandleMsg::FeedPrice {
+                prices: vec![(asset.to_string(), Decimal256::from_str(price).unwrap())],
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        // the price that was live just before the second feed
+        let msg = QueryMsg::PriceAt {
+            base: "mAAPL".to_string(),
+            quote: "base0000".to_string(),
+            time: start_time + 99,
+        };
+        let value: PriceResponse = from_binary(&query(&deps, msg).unwrap()).unwrap();
+        assert_eq!(
+            value,
+            PriceResponse {
+                rate: Decimal256::one(),
+                last_updated_base: start_time,
+                last_updated_quote: 9999999999,
+                feeders_base: 1,
+                feeders_quote: 0,
+                confidence: None,
+                status: PriceStatus::Trading,
+            }
+        );
+
+        let value = query_price_at(
+            &deps,
+            "mGOGL".to_string(),
+            "mAAPL".to_string(),
+            start_time + 120,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            PriceResponse {
+                rate: Decimal256::from_str("2.0").unwrap(),
+                last_updated_base: start_time + 50,
+                last_updated_quote: start_time + 100,
+                feeders_base: 1,
+                feeders_quote: 1,
+                confidence: None,
+                status: PriceStatus::Trading,
+            }
+        );
+
+        // mGOGL has no history yet
+        match query_price_at(
+            &deps,
+            "mGOGL".to_string(),
+            "mAAPL".to_string(),
+            start_time + 20,
+        ) {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+                msg,
+                "No price history for the asset at the requested time: mGOGL"
+            ),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    #[test]
+    fn route_price() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            base_asset: "base0000".to_string(),
+            min_feeders: 1,
+            feed_window: 60,
+        };
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("owner0000", &[]);
+        for asset in &["bLUNA", "LUNA"] {
+            let msg = HandleMsg::RegisterFeeder {
+                asset: asset.to_string(),
+                feeder: HumanAddr::from("feeder0000"),
+            };
+            let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        }
+
+        // Unauthorized err
+        let env = mock_env("addr0000", &[]);
+        let msg = HandleMsg::RegisterRoute {
+            asset: "bLUNA".to_string(),
+            quote: "LUNA".to_string(),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(StdError::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::RegisterRoute {
+            asset: "bLUNA".to_string(),
+            quote: "LUNA".to_string(),
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(
+            res.log,
+            vec![
+                log("action", "register_route"),
+                log("asset", "bLUNA"),
+                log("quote", "LUNA"),
+            ]
+        );
+
+        let value = query_route(&deps, "bLUNA".to_string()).unwrap();
+        assert_eq!(
+            value.route,
+            vec!["LUNA".to_string(), "base0000".to_string()]
+        );
+
+        for (asset, quote) in &[("LUNA", "bLUNA"), ("LUNA", "LUNA")] {
+            let env = mock_env("owner0000", &[]);
+            let msg = HandleMsg::RegisterRoute {
+                asset: asset.to_string(),
+                quote: quote.to_string(),
+            };
+            match handle(&mut deps, env, msg) {
+                Err(StdError::GenericErr { msg, .. }) => {
+                    assert_eq!(msg, "Route cannot loop back to the asset")
+                }
+                _ => panic!("DO NOT ENTER HERE"),
+            }
+        }
+
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::RegisterRoute {
+            asset: "base0000".to_string(),
+            quote: "LUNA".to_string(),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "The base asset cannot be routed")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // bLUNA is fed in LUNA, LUNA in the base asset
+        let start_time = mock_env("feeder0000", &[]).block.time;
+        for (asset, price, time) in &[
+            ("LUNA", "50.0", start_time),
+            ("bLUNA", "0.98", start_time + 10),
+        ] {
+            let mut env = mock_env("feeder0000", &[]);
+            env.block.time = *time;
+            let msg = HandleMsg::FeedPrice {
+                prices: vec![(asset.to_string(), Decimal256::from_str(price).unwrap())],
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        // the routed price is as old as the LUNA leg
+        let value = query_price(&deps, "bLUNA".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(
+            value,
+            PriceResponse {
+                rate: Decimal256::from_uint256(49u64),
+                last_updated_base: start_time,
+                last_updated_quote: 9999999999,
+                feeders_base: 1,
+                feeders_quote: 0,
+                confidence: None,
+                status: PriceStatus::Trading,
+            }
+        );
+
+        let value = query_price(&deps, "bLUNA".to_string(), "LUNA".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("0.98").unwrap());
+
+        let value = query_price_at(
+            &deps,
+            "bLUNA".to_string(),
+            "base0000".to_string(),
+            start_time + 10,
+        )
+        .unwrap();
+        assert_eq!(value.rate, Decimal256::from_uint256(49u64));
+
+        // quoting in the base asset removes the route
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::RegisterRoute {
+            asset: "bLUNA".to_string(),
+            quote: "base0000".to_string(),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let value = query_route(&deps, "bLUNA".to_string()).unwrap();
+        assert_eq!(value.route, vec!["base0000".to_string()]);
+
+        let value = query_price(&deps, "bLUNA".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("0.98").unwrap());
+        assert_eq!(value.last_updated_base, start_time + 10);
+    }
+
+    #[test]
+    fn feed_price_with_confidence() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            base_asset: "base0000".to_string(),
+            min_feeders: 2,
+            feed_window: 60,
+        };
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("owner0000", &[]);
+        for (asset, feeder) in &[
+            ("mAAPL", "feeder0000"),
+            ("mAAPL", "feeder0001"),
+            ("mAAPL", "feeder0002"),
+            ("mGOGL", "feeder0000"),
+            ("mGOGL", "feeder0001"),
+        ] {
+            let msg = HandleMsg::RegisterFeeder {
+                asset: asset.to_string(),
+                feeder: HumanAddr::from(*feeder),
+            };
+            let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        }
+
+        let feed = |asset: &str, price: &str, confidence: &str, status: PriceStatus| PriceFeed {
+            asset: asset.to_string(),
+            price: Decimal256::from_str(price).unwrap(),
+            confidence: Some(Decimal256::from_str(confidence).unwrap()),
+            status,
+        };
+
+        let env = mock_env("feeder0000", &[]);
+        let msg = HandleMsg::FeedPriceWithConfidence {
+            prices: vec![
+                feed("mAAPL", "10.0", "0.2", PriceStatus::Trading),
+                feed("mGOGL", "5.0", "0.05", PriceStatus::Trading),
+            ],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("feeder0001", &[]);
+        let msg = HandleMsg::FeedPriceWithConfidence {
+            prices: vec![
+                feed("mAAPL", "10.2", "0.4", PriceStatus::Halted),
+                feed("mGOGL", "5.0", "0.05", PriceStatus::Trading),
+            ],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        // a plain feed has no band and reports trading
+        let env = mock_env("feeder0002", &[]);
+        let msg = HandleMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::from_str("9.9").unwrap())],
+        };
+        let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+        // median confidence with a missing band counted as zero,
+        // status reported by the majority
+        let value = query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(
+            value,
+            PriceResponse {
+                rate: Decimal256::from_str("10.0").unwrap(),
+                last_updated_base: env.block.time,
+                last_updated_quote: 9999999999,
+                feeders_base: 3,
+                feeders_quote: 0,
+                confidence: Some(Decimal256::from_str("0.2").unwrap()),
+                status: PriceStatus::Trading,
+            }
+        );
+
+        // relative confidences add up; 2.0 * (0.2 / 10.0 + 0.05 / 5.0)
+        let value = query_price(&deps, "mAAPL".to_string(), "mGOGL".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("2.0").unwrap());
+        assert_eq!(
+            value.confidence,
+            Some(Decimal256::from_str("0.06").unwrap())
+        );
+        assert_eq!(value.status, PriceStatus::Trading);
+
+        let value = query_prices(&deps, None, None).unwrap();
+        assert_eq!(
+            value.prices[1],
+            PricesResponseElem {
+                asset: "mGOGL".to_string(),
+                price: Decimal256::from_str("5.0").unwrap(),
+                last_updated_time: env.block.time,
+                confidence: Some(Decimal256::from_str("0.05").unwrap()),
+                status: PriceStatus::Trading,
+            }
+        );
+
+        // a majority halts the price, the least reliable leg sets the rate status
+        let env = mock_env("feeder0002", &[]);
+        let msg = HandleMsg::FeedPriceWithConfidence {
+            prices: vec![feed("mAAPL", "9.9", "0.1", PriceStatus::Halted)],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let value = query_price(&deps, "mAAPL".to_string(), "mGOGL".to_string(), None).unwrap();
+        assert_eq!(value.status, PriceStatus::Halted);
+    }
+
+    #[test]
+    fn feed_price_median() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            base_asset: "base0000".to_string(),
+            min_feeders: 2,
+            feed_window: 60,
+        };
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("owner0000", &[]);
+        for feeder in &["feeder0000", "feeder0001", "feeder0002"] {
+            let msg = HandleMsg::RegisterFeeder {
+                asset: "mAAPL".to_string(),
+                feeder: HumanAddr::from(*feeder),
+            };
+            let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        }
+
+        let value = query_feeder(&deps, "mAAPL".to_string()).unwrap();
+        assert_eq!(3, value.feeders.len());
+
+        let start_time = mock_env("feeder0000", &[]).block.time;
+        let feed = |deps: &mut Extern<_, _, _>, feeder: &str, price: &str, time: u64| {
+            let mut env = mock_env(feeder, &[]);
+            env.block.time = time;
+            let msg = HandleMsg::FeedPrice {
+                prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+            };
+            handle(deps, env, msg)
+        };
+
+        // a single feeder does not reach the quorum
+        let _res = feed(&mut deps, "feeder0000", "1.0", start_time).unwrap();
+        match query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "No price data for the specified asset exist")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // two fresh submissions, the median is their mean
+        let _res = feed(&mut deps, "feeder0001", "2.0", start_time + 10).unwrap();
+        let value = query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("1.5").unwrap());
+        assert_eq!(value.last_updated_base, start_time + 10);
+        assert_eq!(value.feeders_base, 2);
+
+        // three fresh submissions, an outlier does not move the median
+        let _res = feed(&mut deps, "feeder0002", "100.0", start_time + 20).unwrap();
+        let value = query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("2.0").unwrap());
+        assert_eq!(value.feeders_base, 3);
+
+        // feeder0000 falls out of the window
+        let _res = feed(&mut deps, "feeder0001", "3.0", start_time + 70).unwrap();
+        let value = query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("51.5").unwrap());
+        assert_eq!(value.last_updated_base, start_time + 70);
+        assert_eq!(value.feeders_base, 2);
+
+        // only feeder0001 is fresh, the price is left to go stale
+        let _res = feed(&mut deps, "feeder0001", "4.0", start_time + 100).unwrap();
+        let value = query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("51.5").unwrap());
+        assert_eq!(value.last_updated_base, start_time + 70);
+
+        // the quorum is rechecked at the block time
+        let value = query_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            Some(start_time + 80),
+        )
+        .unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("51.5").unwrap());
+        assert_eq!(value.feeders_base, 2);
+        match query_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            Some(start_time + 100),
+        ) {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+                msg,
+                "Not enough fresh price submissions for the asset: mAAPL"
+            ),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // unregistered feeder
+        match feed(&mut deps, "addr0000", "1.0", start_time + 100) {
+            Err(StdError::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+    }
+
+    #[test]
+    fn circuit_breaker() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            base_asset: "base0000".to_string(),
+            min_feeders: 1,
+            feed_window: 60,
+        };
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::RegisterFeeder {
+            asset: "mAAPL".to_string(),
+            feeder: HumanAddr::from("feeder0000"),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let feed = |deps: &mut Extern<_, _, _>, price: &str| {
+            let env = mock_env("feeder0000", &[]);
+            let msg = HandleMsg::FeedPrice {
+                prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+            };
+            handle(deps, env, msg)
+        };
+        let _res = feed(&mut deps, "1.0").unwrap();
+
+        // Unauthorized err
+        let env = mock_env("addr0000", &[]);
+        let msg = HandleMsg::UpdateCircuitBreaker {
+            asset: "mAAPL".to_string(),
+            max_deviation_per_update: Some(Decimal256::percent(10)),
+            floor: None,
+            ceiling: None,
+        };
+        match handle(&mut deps, env, msg) {
+            Err(StdError::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // inverted band
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::UpdateCircuitBreaker {
+            asset: "mAAPL".to_string(),
+            max_deviation_per_update: Some(Decimal256::percent(10)),
+            floor: Some(Decimal256::from_str("2.0").unwrap()),
+            ceiling: Some(Decimal256::from_str("0.5").unwrap()),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "floor cannot be bigger than ceiling")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::UpdateCircuitBreaker {
+            asset: "mAAPL".to_string(),
+            max_deviation_per_update: Some(Decimal256::percent(10)),
+            floor: Some(Decimal256::from_str("0.5").unwrap()),
+            ceiling: Some(Decimal256::from_str("2.0").unwrap()),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        // within the limits
+        let _res = feed(&mut deps, "1.05").unwrap();
+        let value = query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("1.05").unwrap());
+
+        // a 42% move is quarantined
+        let res = feed(&mut deps, "1.5").unwrap();
+        assert_eq!(
+            res.log,
+            vec![
+                log("action", "feed_prices"),
+                log("asset", "mAAPL"),
+                log("price", "1.5"),
+                log("circuit_breaker", "mAAPL"),
+                log("quarantined_price", "1.5"),
+            ]
+        );
+
+        for (base, quote) in &[("mAAPL", "base0000"), ("base0000", "mAAPL")] {
+            match query_price(&deps, base.to_string(), quote.to_string(), None) {
+                Err(StdError::GenericErr { msg, .. }) => {
+                    assert_eq!(msg, "Price circuit breaker is tripped for the asset: mAAPL")
+                }
+                _ => panic!("DO NOT ENTER HERE"),
+            }
+        }
+
+        // every price is held back while tripped
+        let _res = feed(&mut deps, "1.06").unwrap();
+        let value = query_circuit_breaker(&deps, "mAAPL".to_string()).unwrap();
+        assert_eq!(
+            value,
+            CircuitBreakerResponse {
+                asset: "mAAPL".to_string(),
+                max_deviation_per_update: Some(Decimal256::percent(10)),
+                floor: Some(Decimal256::from_str("0.5").unwrap()),
+                ceiling: Some(Decimal256::from_str("2.0").unwrap()),
+                tripped: true,
+                quarantined_price: Some(Decimal256::from_str("1.06").unwrap()),
+                quarantined_time: Some(mock_env("feeder0000", &[]).block.time),
+            }
+        );
+
+        // discard the quarantined price
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::ResetCircuitBreaker {
+            asset: "mAAPL".to_string(),
+            accept_quarantined: false,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let value = query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("1.05").unwrap());
+
+        // above the ceiling, accepted by the owner
+        let _res = feed(&mut deps, "2.1").unwrap();
+        let value = query_circuit_breaker(&deps, "mAAPL".to_string()).unwrap();
+        assert!(value.tripped);
+
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::ResetCircuitBreaker {
+            asset: "mAAPL".to_string(),
+            accept_quarantined: true,
+        };
+        let _res = handle(&mut deps, env, msg.clone()).unwrap();
+        let value = query_price(&deps, "mAAPL".to_string(), "base0000".to_string(), None).unwrap();
+        assert_eq!(value.rate, Decimal256::from_str("2.1").unwrap());
+
+        let env = mock_env("owner0000", &[]);
+        match handle(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "Circuit breaker is not tripped for the asset")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    #[test]
+    fn migrate_legacy_state() {
+        use crate::state::{KEY_CONFIG, PREFIX_FEEDER, PREFIX_PRICE};
+        use cosmwasm_std::CanonicalAddr;
+        use cosmwasm_storage::{singleton, Bucket};
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize)]
+        struct LegacyConfig {
+            owner: CanonicalAddr,
+            base_asset: String,
+        }
+
+        #[derive(Serialize, Deserialize)]
+        struct LegacyPriceInfo {
+            price: Decimal256,
+            last_updated_time: u64,
+        }
+
+        let mut deps = mock_dependencies(20, &[]);
+        let owner = deps
+            .api
+            .canonical_address(&HumanAddr::from("owner0000"))
+            .unwrap();
+        let feeder = deps
+            .api
+            .canonical_address(&HumanAddr::from("feeder0000"))
+            .unwrap();
+        let last_updated_time = mock_env("addr0000", &[]).block.time;
+        singleton(&mut deps.storage, KEY_CONFIG)
+            .save(&LegacyConfig {
+                owner,
+                base_asset: "base0000".to_string(),
+            })
+            .unwrap();
+        for asset in &["mAAPL", "mGOGL"] {
+            Bucket::new(PREFIX_FEEDER, &mut deps.storage)
+                .save(asset.as_bytes(), &feeder)
+                .unwrap();
+        }
+        Bucket::new(PREFIX_PRICE, &mut deps.storage)
+            .save(
+                b"mAAPL",
+                &LegacyPriceInfo {
+                    price: Decimal256::percent(150),
+                    last_updated_time,
+                },
+            )
+            .unwrap();
+
+        let env = mock_env("addr0000", &[]);
+        let _res = migrate(&mut deps, env, MigrateMsg {}).unwrap();
+
+        let value = query_config(&deps).unwrap();
+        assert_eq!(value.min_feeders, 1);
+        assert_eq!(value.feed_window, 60);
+
+        let value = query_feeders(&deps, None, None).unwrap();
+        assert_eq!(
+            value.feeders,
+            vec![
+                FeederResponse {
+                    asset: "mAAPL".to_string(),
+                    feeder: HumanAddr::from("feeder0000"),
+                    feeders: vec![HumanAddr::from("feeder0000")],
+                },
+                FeederResponse {
+                    asset: "mGOGL".to_string(),
+                    feeder: HumanAddr::from("feeder0000"),
+                    feeders: vec![HumanAddr::from("feeder0000")],
+                },
+            ]
+        );
+
+        // the legacy price counts as the feeder's submission
+        let value = query_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            Some(last_updated_time + 60),
+        )
+        .unwrap();
+        assert_eq!(value.rate, Decimal256::percent(150));
+        assert_eq!(value.last_updated_base, last_updated_time);
+        assert_eq!(value.feeders_base, 1);
+
+        // the price history starts with the next feed
+        let mut env = mock_env("feeder0000", &[]);
+        env.block.time = last_updated_time + 100;
+        let msg = HandleMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::percent(250))],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let value = query_twap_price(
+            &deps,
+            "mAAPL".to_string(),
+            "base0000".to_string(),
+            50,
+            last_updated_time + 150,
+        )
+        .unwrap();
+        assert_eq!(value.rate, Decimal256::percent(250));
+    }
 }
 
 pub fn migrate<S: Storage, A: Api, Q: Querier>(
-    _deps: &mut Extern<S, A, Q>,
+    deps: &mut Extern<S, A, Q>,
     _env: Env,
     _msg: MigrateMsg,
 ) -> MigrateResult {
+    // migrate config to use new Config
+    migrate_config(&mut deps.storage)?;
+
+    // move the single feeder of each asset into the feeder set
+    migrate_feeders(&mut deps.storage)?;
+
     Ok(MigrateResponse::default())
 }
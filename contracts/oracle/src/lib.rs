@@ -1,4 +1,5 @@
 pub mod contract;
+pub mod migration;
 pub mod state;
 
 #[cfg(all(target_arch = "wasm32", not(feature = "library")))]
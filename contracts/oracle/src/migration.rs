@@ -0,0 +1,106 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{CanonicalAddr, Order, StdResult, Storage};
+use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton};
+
+use moneymarket::oracle::PriceStatus;
+
+use crate::state::{
+    store_config, store_feeder, store_price, store_submission, Config, PriceInfo, Submission,
+    KEY_CONFIG, PREFIX_FEEDER, PREFIX_PRICE,
+};
+
+/// The single feeder of the legacy oracle always made the quorum
+const DEFAULT_MIN_FEEDERS: u32 = 1;
+/// Same as the overseer price timeframe, so a price is not built
+/// from submissions the overseer would consider too old
+const DEFAULT_FEED_WINDOW: u64 = 60;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub base_asset: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+struct LegacyPriceInfo {
+    pub price: Decimal256,
+    pub last_updated_time: u64,
+}
+
+fn read_legacy_config<S: Storage>(storage: &S) -> StdResult<LegacyConfig> {
+    ReadonlySingleton::new(storage, KEY_CONFIG).load()
+}
+
+fn read_legacy_price<S: Storage>(storage: &S, asset: &str) -> StdResult<Option<LegacyPriceInfo>> {
+    let price_bucket: ReadonlyBucket<S, LegacyPriceInfo> =
+        ReadonlyBucket::new(PREFIX_PRICE, storage);
+    price_bucket.may_load(asset.as_bytes())
+}
+
+pub fn migrate_config<S: Storage>(storage: &mut S) -> StdResult<()> {
+    let legacy_config: LegacyConfig = read_legacy_config(storage)?;
+    store_config(
+        storage,
+        &Config {
+            owner: legacy_config.owner,
+            base_asset: legacy_config.base_asset,
+            min_feeders: DEFAULT_MIN_FEEDERS,
+            feed_window: DEFAULT_FEED_WINDOW,
+        },
+    )
+}
+
+/// Moves the single feeder of each asset into the per asset feeder set;
+/// the current price becomes that feeder's submission, so the price stays
+/// valid until the next feed
+pub fn migrate_feeders<S: Storage>(storage: &mut S) -> StdResult<()> {
+    let legacy_feeders: Vec<(String, CanonicalAddr)> = {
+        let feeder_bucket: ReadonlyBucket<S, CanonicalAddr> =
+            ReadonlyBucket::new(PREFIX_FEEDER, storage);
+        feeder_bucket
+            .range(None, None, Order::Ascending)
+            .map(|item| {
+                let (k, v) = item?;
+                Ok((std::str::from_utf8(&k).unwrap().to_string(), v))
+            })
+            .collect::<StdResult<Vec<(String, CanonicalAddr)>>>()?
+    };
+
+    for (asset, feeder) in legacy_feeders.iter() {
+        let mut feeder_bucket: Bucket<S, CanonicalAddr> = Bucket::new(PREFIX_FEEDER, storage);
+        feeder_bucket.remove(asset.as_bytes());
+        store_feeder(storage, asset, feeder)?;
+
+        if let Some(legacy_price) = read_legacy_price(storage, asset)? {
+            store_submission(
+                storage,
+                asset,
+                feeder,
+                &Submission {
+                    price: legacy_price.price,
+                    time: legacy_price.last_updated_time,
+                    confidence: None,
+                    status: PriceStatus::Trading,
+                },
+            )?;
+            store_price(
+                storage,
+                asset,
+                &PriceInfo {
+                    price: legacy_price.price,
+                    last_updated_time: legacy_price.last_updated_time,
+                    cumulative_price: Decimal256::zero(),
+                    observation_count: 0,
+                    feeders: 1,
+                    confidence: None,
+                    status: PriceStatus::Trading,
+                },
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -5,17 +5,25 @@ use cosmwasm_bignumber::Decimal256;
 use cosmwasm_std::{CanonicalAddr, Order, StdError, StdResult, Storage};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
 
-use moneymarket::oracle::PricesResponseElem;
+use moneymarket::oracle::{PriceStatus, PricesResponseElem};
 
-static PREFIX_PRICE: &[u8] = b"price";
-static PREFIX_FEEDER: &[u8] = b"feeder";
+pub static PREFIX_PRICE: &[u8] = b"price";
+pub static PREFIX_FEEDER: &[u8] = b"feeder";
+static PREFIX_FEEDER_ASSET: &[u8] = b"feeder_asset";
+static PREFIX_OBSERVATION: &[u8] = b"observation";
+static PREFIX_SUBMISSION: &[u8] = b"submission";
+static PREFIX_PRICE_BOUNDS: &[u8] = b"price_bounds";
+static PREFIX_QUARANTINE: &[u8] = b"quarantine";
+static PREFIX_ROUTE: &[u8] = b"route";
 
-static KEY_CONFIG: &[u8] = b"config";
+pub static KEY_CONFIG: &[u8] = b"config";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: CanonicalAddr,
     pub base_asset: String,
+    pub min_feeders: u32,
+    pub feed_window: u64,
 }
 
 pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<()> {
@@ -30,6 +38,40 @@ pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
 pub struct PriceInfo {
     pub price: Decimal256,
     pub last_updated_time: u64,
+    /// Sum of price * seconds since the first feed, up to `last_updated_time`
+    #[serde(default)]
+    pub cumulative_price: Decimal256,
+    #[serde(default)]
+    pub observation_count: u64,
+    /// Number of submissions the price is the median of
+    #[serde(default)]
+    pub feeders: u32,
+    /// Median confidence of the submissions that reported one
+    pub confidence: Option<Decimal256>,
+    /// Least reliable status across the submissions
+    pub status: PriceStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Submission {
+    pub price: Decimal256,
+    pub time: u64,
+    pub confidence: Option<Decimal256>,
+    pub status: PriceStatus,
+}
+
+/// Number of observations kept per asset for TWAP and point-in-time
+/// lookups; the oldest one is dropped once the history is full
+pub const MAX_OBSERVATIONS: u64 = 64;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Observation {
+    pub time: u64,
+    pub price: Decimal256,
+    pub cumulative_price: Decimal256,
+    pub feeders: u32,
+    pub confidence: Option<Decimal256>,
+    pub status: PriceStatus,
 }
 
 pub fn store_price<S: Storage>(storage: &mut S, asset: &str, price: &PriceInfo) -> StdResult<()> {
@@ -38,19 +80,174 @@ pub fn store_price<S: Storage>(storage: &mut S, asset: &str, price: &PriceInfo)
 }
 
 pub fn read_price<S: Storage>(storage: &S, asset: &str) -> StdResult<PriceInfo> {
-    let price_bucket: ReadonlyBucket<S, PriceInfo> = ReadonlyBucket::new(PREFIX_PRICE, storage);
-    let res = price_bucket.load(asset.as_bytes());
-    match res {
-        Ok(data) => Ok(data),
-        Err(_err) => Err(StdError::generic_err(
+    match read_price_opt(storage, asset)? {
+        Some(data) => Ok(data),
+        None => Err(StdError::generic_err(
             "No price data for the specified asset exist",
         )),
     }
 }
 
+pub fn read_price_opt<S: Storage>(storage: &S, asset: &str) -> StdResult<Option<PriceInfo>> {
+    let price_bucket: ReadonlyBucket<S, PriceInfo> = ReadonlyBucket::new(PREFIX_PRICE, storage);
+    price_bucket.may_load(asset.as_bytes())
+}
+
+/// Observations are keyed by time, so a second feed in the same block
+/// replaces the first one
+pub fn store_observation<S: Storage>(
+    storage: &mut S,
+    asset: &str,
+    observation: &Observation,
+) -> StdResult<()> {
+    let mut observation_bucket: Bucket<S, Observation> =
+        Bucket::multilevel(&[PREFIX_OBSERVATION, asset.as_bytes()], storage);
+    observation_bucket.save(&observation.time.to_be_bytes(), observation)
+}
+
+pub fn remove_oldest_observation<S: Storage>(storage: &mut S, asset: &str) -> StdResult<()> {
+    let oldest: Option<Vec<u8>> = {
+        let observation_bucket: ReadonlyBucket<S, Observation> =
+            ReadonlyBucket::multilevel(&[PREFIX_OBSERVATION, asset.as_bytes()], storage);
+        let mut iter = observation_bucket.range(None, None, Order::Ascending);
+        match iter.next() {
+            Some(item) => Some(item?.0),
+            None => None,
+        }
+    };
+
+    if let Some(key) = oldest {
+        let mut observation_bucket: Bucket<S, Observation> =
+            Bucket::multilevel(&[PREFIX_OBSERVATION, asset.as_bytes()], storage);
+        observation_bucket.remove(&key);
+    }
+
+    Ok(())
+}
+
+/// Latest observation at or before the given time
+pub fn read_observation_at<S: Storage>(
+    storage: &S,
+    asset: &str,
+    time: u64,
+) -> StdResult<Option<Observation>> {
+    let observation_bucket: ReadonlyBucket<S, Observation> =
+        ReadonlyBucket::multilevel(&[PREFIX_OBSERVATION, asset.as_bytes()], storage);
+    let end = time.checked_add(1).map(|end| end.to_be_bytes());
+    let mut iter = observation_bucket.range(None, end.as_ref().map(|e| &e[..]), Order::Descending);
+    match iter.next() {
+        Some(item) => Ok(Some(item?.1)),
+        None => Ok(None),
+    }
+}
+
+/// Circuit breaker limits of an asset; `None` disables the check
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PriceBounds {
+    pub max_deviation_per_update: Option<Decimal256>,
+    pub floor: Option<Decimal256>,
+    pub ceiling: Option<Decimal256>,
+}
+
+/// Latest aggregated price held back by a tripped circuit breaker
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Quarantine {
+    pub price: Decimal256,
+    pub feeders: u32,
+    pub time: u64,
+    pub confidence: Option<Decimal256>,
+    pub status: PriceStatus,
+}
+
+pub fn store_price_bounds<S: Storage>(
+    storage: &mut S,
+    asset: &str,
+    bounds: &PriceBounds,
+) -> StdResult<()> {
+    let mut bounds_bucket: Bucket<S, PriceBounds> = Bucket::new(PREFIX_PRICE_BOUNDS, storage);
+    bounds_bucket.save(asset.as_bytes(), bounds)
+}
+
+pub fn read_price_bounds<S: Storage>(storage: &S, asset: &str) -> StdResult<PriceBounds> {
+    let bounds_bucket: ReadonlyBucket<S, PriceBounds> =
+        ReadonlyBucket::new(PREFIX_PRICE_BOUNDS, storage);
+    Ok(bounds_bucket
+        .may_load(asset.as_bytes())?
+        .unwrap_or_default())
+}
+
+pub fn store_quarantine<S: Storage>(
+    storage: &mut S,
+    asset: &str,
+    quarantine: &Quarantine,
+) -> StdResult<()> {
+    let mut quarantine_bucket: Bucket<S, Quarantine> = Bucket::new(PREFIX_QUARANTINE, storage);
+    quarantine_bucket.save(asset.as_bytes(), quarantine)
+}
+
+pub fn read_quarantine<S: Storage>(storage: &S, asset: &str) -> StdResult<Option<Quarantine>> {
+    let quarantine_bucket: ReadonlyBucket<S, Quarantine> =
+        ReadonlyBucket::new(PREFIX_QUARANTINE, storage);
+    quarantine_bucket.may_load(asset.as_bytes())
+}
+
+pub fn remove_quarantine<S: Storage>(storage: &mut S, asset: &str) {
+    let mut quarantine_bucket: Bucket<S, Quarantine> = Bucket::new(PREFIX_QUARANTINE, storage);
+    quarantine_bucket.remove(asset.as_bytes())
+}
+
+/// Asset the fed prices of `asset` are quoted in, when it is not
+/// the base asset
+pub fn store_route<S: Storage>(storage: &mut S, asset: &str, quote: &str) -> StdResult<()> {
+    let mut route_bucket: Bucket<S, String> = Bucket::new(PREFIX_ROUTE, storage);
+    route_bucket.save(asset.as_bytes(), &quote.to_string())
+}
+
+pub fn read_route<S: Storage>(storage: &S, asset: &str) -> StdResult<Option<String>> {
+    let route_bucket: ReadonlyBucket<S, String> = ReadonlyBucket::new(PREFIX_ROUTE, storage);
+    route_bucket.may_load(asset.as_bytes())
+}
+
+pub fn remove_route<S: Storage>(storage: &mut S, asset: &str) {
+    let mut route_bucket: Bucket<S, String> = Bucket::new(PREFIX_ROUTE, storage);
+    route_bucket.remove(asset.as_bytes())
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
+/// Prices last updated more than `max_age` seconds before `block_time`
+pub fn read_stale_prices<S: Storage>(
+    storage: &S,
+    max_age: u64,
+    block_time: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PricesResponseElem>> {
+    let price_bucket: ReadonlyBucket<S, PriceInfo> = ReadonlyBucket::new(PREFIX_PRICE, storage);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    price_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((k, v)) if block_time.saturating_sub(v.last_updated_time) > max_age => {
+                Some(Ok(PricesResponseElem {
+                    asset: std::str::from_utf8(&k).unwrap().to_string(),
+                    price: v.price,
+                    last_updated_time: v.last_updated_time,
+                    confidence: v.confidence,
+                    status: v.status,
+                }))
+            }
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .take(limit)
+        .collect()
+}
+
 pub fn read_prices<S: Storage>(
     storage: &S,
     start_after: Option<String>,
@@ -72,6 +269,8 @@ pub fn read_prices<S: Storage>(
                 asset,
                 price: v.price,
                 last_updated_time: v.last_updated_time,
+                confidence: v.confidence,
+                status: v.status,
             })
         })
         .collect()
@@ -82,19 +281,96 @@ pub fn store_feeder<S: Storage>(
     asset: &str,
     feeder: &CanonicalAddr,
 ) -> StdResult<()> {
-    let mut price_bucket: Bucket<S, CanonicalAddr> = Bucket::new(PREFIX_FEEDER, storage);
-    price_bucket.save(asset.as_bytes(), &feeder)
+    let mut feeder_bucket: Bucket<S, bool> =
+        Bucket::multilevel(&[PREFIX_FEEDER, asset.as_bytes()], storage);
+    feeder_bucket.save(feeder.as_slice(), &true)?;
+
+    let mut asset_bucket: Bucket<S, bool> = Bucket::new(PREFIX_FEEDER_ASSET, storage);
+    asset_bucket.save(asset.as_bytes(), &true)
+}
+
+/// Removes the feeder and its last submission; the asset leaves the
+/// feeder asset index with its last feeder
+pub fn remove_feeder<S: Storage>(storage: &mut S, asset: &str, feeder: &CanonicalAddr) {
+    let mut feeder_bucket: Bucket<S, bool> =
+        Bucket::multilevel(&[PREFIX_FEEDER, asset.as_bytes()], storage);
+    feeder_bucket.remove(feeder.as_slice());
+
+    let mut submission_bucket: Bucket<S, Submission> =
+        Bucket::multilevel(&[PREFIX_SUBMISSION, asset.as_bytes()], storage);
+    submission_bucket.remove(feeder.as_slice());
+
+    if read_feeders(storage, asset).is_err() {
+        let mut asset_bucket: Bucket<S, bool> = Bucket::new(PREFIX_FEEDER_ASSET, storage);
+        asset_bucket.remove(asset.as_bytes());
+    }
+}
+
+/// Assets with at least one feeder
+pub fn read_feeder_assets<S: Storage>(
+    storage: &S,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<String>> {
+    let asset_bucket: ReadonlyBucket<S, bool> = ReadonlyBucket::new(PREFIX_FEEDER_ASSET, storage);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    asset_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, _) = item?;
+            Ok(std::str::from_utf8(&k).unwrap().to_string())
+        })
+        .collect()
 }
 
-pub fn read_feeder<S: Storage>(storage: &S, asset: &str) -> StdResult<CanonicalAddr> {
-    let price_bucket: ReadonlyBucket<S, CanonicalAddr> = ReadonlyBucket::new(PREFIX_FEEDER, storage);
-    let res = price_bucket.load(asset.as_bytes());
-    match res {
-        Ok(data) => Ok(data),
-        Err(_err) => Err(StdError::generic_err(
+pub fn is_feeder<S: Storage>(storage: &S, asset: &str, feeder: &CanonicalAddr) -> bool {
+    let feeder_bucket: ReadonlyBucket<S, bool> =
+        ReadonlyBucket::multilevel(&[PREFIX_FEEDER, asset.as_bytes()], storage);
+    feeder_bucket.load(feeder.as_slice()).is_ok()
+}
+
+pub fn read_feeders<S: Storage>(storage: &S, asset: &str) -> StdResult<Vec<CanonicalAddr>> {
+    let feeder_bucket: ReadonlyBucket<S, bool> =
+        ReadonlyBucket::multilevel(&[PREFIX_FEEDER, asset.as_bytes()], storage);
+    let feeders: Vec<CanonicalAddr> = feeder_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (k, _) = item?;
+            Ok(CanonicalAddr::from(k))
+        })
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+
+    if feeders.is_empty() {
+        return Err(StdError::generic_err(
             "No feeder data for the specified asset exist",
-        )),
+        ));
     }
+
+    Ok(feeders)
+}
+
+pub fn store_submission<S: Storage>(
+    storage: &mut S,
+    asset: &str,
+    feeder: &CanonicalAddr,
+    submission: &Submission,
+) -> StdResult<()> {
+    let mut submission_bucket: Bucket<S, Submission> =
+        Bucket::multilevel(&[PREFIX_SUBMISSION, asset.as_bytes()], storage);
+    submission_bucket.save(feeder.as_slice(), submission)
+}
+
+pub fn read_submissions<S: Storage>(storage: &S, asset: &str) -> StdResult<Vec<Submission>> {
+    let submission_bucket: ReadonlyBucket<S, Submission> =
+        ReadonlyBucket::multilevel(&[PREFIX_SUBMISSION, asset.as_bytes()], storage);
+    submission_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect()
 }
 
 // this will set the first key after the provided key, by appending a 1 byte
@@ -4,8 +4,9 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, HandleMsg,
-    InitMsg, QueryMsg, WhitelistResponse,
+    AllCollateralsResponse, BorrowLimitResponse, BorrowerHealthResponse, CategoryResponse,
+    CollateralsResponse, ConfigResponse, HandleMsg, InitMsg, IsolatedLoanResponse,
+    LiquidatableBorrowersResponse, QueryMsg, WhitelistResponse,
 };
 use moneymarket_overseer::state::EpochState;
 
@@ -20,8 +21,12 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(AllCollateralsResponse), &out_dir);
     export_schema(&schema_for!(BorrowLimitResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerHealthResponse), &out_dir);
+    export_schema(&schema_for!(CategoryResponse), &out_dir);
     export_schema(&schema_for!(CollateralsResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(EpochState), &out_dir);
+    export_schema(&schema_for!(IsolatedLoanResponse), &out_dir);
+    export_schema(&schema_for!(LiquidatableBorrowersResponse), &out_dir);
     export_schema(&schema_for!(WhitelistResponse), &out_dir);
 }
@@ -0,0 +1,253 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    log, Api, CanonicalAddr, Env, Extern, HandleResponse, HandleResult, HumanAddr, Querier,
+    StdError, StdResult, Storage,
+};
+
+use crate::collateral::compute_borrow_limit;
+use crate::querier::query_borrower_info;
+use crate::state::{
+    read_borrower_category, read_category, read_category_locked, read_collaterals, read_config,
+    read_whitelist_elem, remove_borrower_category, store_borrower_category, store_category,
+    store_category_locked, store_whitelist_elem, Category, Config, WhitelistElem,
+};
+
+use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::overseer::CategoryResponse;
+use moneymarket::tokens::Tokens;
+
+pub fn create_category<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    category: String,
+    max_ltv: Decimal256,
+    liquidation_threshold: Decimal256,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    if read_category(&deps.storage, &category).is_ok() {
+        return Err(StdError::generic_err("Category already exists"));
+    }
+
+    if liquidation_threshold < max_ltv {
+        return Err(StdError::generic_err(
+            "Liquidation threshold cannot be smaller than max_ltv",
+        ));
+    }
+
+    store_category(
+        &mut deps.storage,
+        &category,
+        &Category {
+            max_ltv,
+            liquidation_threshold,
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "create_category"),
+            log("category", category),
+            log("max_ltv", max_ltv),
+            log("liquidation_threshold", liquidation_threshold),
+        ],
+        data: None,
+    })
+}
+
+pub fn assign_category<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    collateral_token: HumanAddr,
+    category: Option<String>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    if let Some(category) = &category {
+        read_category(&deps.storage, category)?;
+    }
+
+    let collateral_token_raw = deps.api.canonical_address(&collateral_token)?;
+    let mut whitelist_elem: WhitelistElem =
+        read_whitelist_elem(&deps.storage, &collateral_token_raw)?;
+
+    // borrowers in the category would silently lose its ratios
+    if whitelist_elem.category != category
+        && !read_category_locked(&deps.storage, &collateral_token_raw).is_zero()
+    {
+        return Err(StdError::generic_err(format!(
+            "Collateral is locked by borrowers in its category: {}",
+            collateral_token
+        )));
+    }
+
+    whitelist_elem.category = category.clone();
+    store_whitelist_elem(&mut deps.storage, &collateral_token_raw, &whitelist_elem)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "assign_category"),
+            log("collateral_token", collateral_token),
+            log("category", category.unwrap_or_default()),
+        ],
+        data: None,
+    })
+}
+
+pub fn enter_category<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    category: String,
+) -> HandleResult {
+    let borrower_raw = deps.api.canonical_address(&env.message.sender)?;
+    read_category(&deps.storage, &category)?;
+
+    let collaterals: Tokens = read_collaterals(&deps.storage, &borrower_raw);
+    for collateral in collaterals.iter() {
+        let whitelist_elem: WhitelistElem = read_whitelist_elem(&deps.storage, &collateral.0)?;
+        if whitelist_elem.category.as_ref() != Some(&category) {
+            return Err(StdError::generic_err(format!(
+                "Collateral is not in the category: {}",
+                deps.api.human_address(&collateral.0)?
+            )));
+        }
+    }
+
+    // switching categories keeps the collaterals counted
+    if read_borrower_category(&deps.storage, &borrower_raw).is_none() {
+        add_category_locked(&mut deps.storage, &collaterals)?;
+    }
+    store_borrower_category(&mut deps.storage, &borrower_raw, &category)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "enter_category"),
+            log("borrower", env.message.sender),
+            log("category", category),
+        ],
+        data: None,
+    })
+}
+
+pub fn leave_category<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let market = deps.api.human_address(&config.market_contract)?;
+
+    let borrower = env.message.sender;
+    let borrower_raw = deps.api.canonical_address(&borrower)?;
+    let category = match read_borrower_category(&deps.storage, &borrower_raw) {
+        Some(v) => v,
+        None => return Err(StdError::generic_err("Borrower is not in a category")),
+    };
+
+    // Leaving the category falls back to the whitelisted LTVs,
+    // so the loan must still be covered by them
+    let collaterals: Tokens = read_collaterals(&deps.storage, &borrower_raw);
+    if !collaterals.is_empty() {
+        let (borrow_limit, _) = compute_borrow_limit(
+            deps,
+            &collaterals,
+            None,
+            Some(env.block.height),
+            Some(env.block.time),
+        )?;
+        let borrow_amount_res: BorrowerInfoResponse =
+            query_borrower_info(deps, &market, &borrower, env.block.height)?;
+        if borrow_limit < borrow_amount_res.loan_amount {
+            return Err(StdError::generic_err(format!(
+                "Cannot leave category; Loan liability becomes greater than borrow limit: {}",
+                borrow_limit
+            )));
+        }
+    }
+
+    sub_category_locked(&mut deps.storage, &collaterals)?;
+    remove_borrower_category(&mut deps.storage, &borrower_raw);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "leave_category"),
+            log("borrower", borrower),
+            log("category", category),
+        ],
+        data: None,
+    })
+}
+
+#[allow(clippy::ptr_arg)]
+pub(crate) fn add_category_locked<S: Storage>(
+    storage: &mut S,
+    collaterals: &Tokens,
+) -> StdResult<()> {
+    for collateral in collaterals.iter() {
+        let category_locked = read_category_locked(storage, &collateral.0) + collateral.1;
+        store_category_locked(storage, &collateral.0, &category_locked)?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::ptr_arg)]
+pub(crate) fn sub_category_locked<S: Storage>(
+    storage: &mut S,
+    collaterals: &Tokens,
+) -> StdResult<()> {
+    for collateral in collaterals.iter() {
+        let category_locked: Uint256 = read_category_locked(storage, &collateral.0);
+        if category_locked < collateral.1 {
+            return Err(StdError::generic_err("Category locked amount underflow"));
+        }
+
+        store_category_locked(storage, &collateral.0, &(category_locked - collateral.1))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the category entered by the borrower,
+/// only when all of the given collaterals belong to it
+pub fn read_applied_category<S: Storage>(
+    storage: &S,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+) -> StdResult<Option<(String, Category)>> {
+    let category = match read_borrower_category(storage, borrower) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    for collateral in collaterals.iter() {
+        let whitelist_elem: WhitelistElem = read_whitelist_elem(storage, &collateral.0)?;
+        if whitelist_elem.category.as_ref() != Some(&category) {
+            return Ok(None);
+        }
+    }
+
+    let data = read_category(storage, &category)?;
+    Ok(Some((category, data)))
+}
+
+pub fn query_category<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    category: String,
+) -> StdResult<CategoryResponse> {
+    let data = read_category(&deps.storage, &category)?;
+    Ok(CategoryResponse {
+        category,
+        max_ltv: data.max_ltv,
+        liquidation_threshold: data.liquidation_threshold,
+    })
+}
@@ -1,22 +1,35 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    log, to_binary, Api, CosmosMsg, Env, Extern, HandleResponse, HandleResult, HumanAddr, Querier,
-    StdError, StdResult, Storage, WasmMsg,
+    log, to_binary, Api, CanonicalAddr, CosmosMsg, Env, Extern, HandleResponse, HandleResult,
+    HumanAddr, Querier, StdError, StdResult, Storage, WasmMsg,
 };
+use std::cmp::min;
 
+use crate::category::{add_category_locked, read_applied_category, sub_category_locked};
 use crate::querier::{query_borrower_info, query_liquidation_amount};
 use crate::state::{
-    read_all_collaterals, read_collaterals, read_config, read_whitelist_elem, store_collaterals,
-    Config, WhitelistElem,
+    read_all_collaterals, read_borrower_category, read_collaterals, read_config,
+    read_isolated_debt, read_isolated_loan, read_total_locked, read_whitelist_elem,
+    remove_isolated_loan, store_collaterals, store_isolated_debt, store_isolated_loan,
+    store_total_locked, Category, Config, IsolatedLoan, WhitelistElem,
 };
 
 use moneymarket::custody::HandleMsg as CustodyHandleMsg;
 use moneymarket::liquidation::LiquidationAmountResponse;
 use moneymarket::market::{BorrowerInfoResponse, HandleMsg as MarketHandleMsg};
-use moneymarket::oracle::PriceResponse;
-use moneymarket::overseer::{AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse};
-use moneymarket::querier::{query_balance, query_price, TimeConstraints};
+use moneymarket::oracle::{PriceResponse, PriceStatus};
+use moneymarket::overseer::{
+    AllCollateralsResponse, BorrowLimitResponse, BorrowerHealthResponse,
+    BorrowerHealthResponseElem, CollateralDeprecation, CollateralsResponse, HandleMsg,
+    IsolatedLoanResponse, LiquidatableBorrowersResponse, LiquidatableBorrowersResponseElem,
+};
+use moneymarket::querier::{query_balance, query_twap_price, TimeConstraints};
 use moneymarket::tokens::{Tokens, TokensHuman, TokensMath, TokensToHuman, TokensToRaw};
+use moneymarket_math::oracle::compute_confidence_band;
+use moneymarket_math::overseer::{
+    compute_close_factor_ratio, compute_deprecated_ltv, compute_health_factor,
+    compute_liquidation_price,
+};
 
 pub fn lock_collateral<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -29,11 +42,46 @@ pub fn lock_collateral<S: Storage, A: Api, Q: Querier>(
     let collaterals: Tokens = collaterals_human.to_raw(&deps)?;
 
     cur_collaterals.add(collaterals.clone());
-    store_collaterals(&mut deps.storage, &borrower_raw, &cur_collaterals)?;
+    if cur_collaterals.len() > 1 {
+        for collateral in cur_collaterals.iter() {
+            let whitelist_elem: WhitelistElem = read_whitelist_elem(&deps.storage, &collateral.0)?;
+            if whitelist_elem.isolated {
+                return Err(StdError::generic_err(
+                    "Isolated collateral cannot be mixed with other collaterals",
+                ));
+            }
+        }
+    }
 
+    let borrower_category = read_borrower_category(&deps.storage, &borrower_raw);
     let mut messages: Vec<CosmosMsg> = vec![];
-    for collateral in collaterals {
+    for collateral in collaterals.iter() {
         let whitelist_elem: WhitelistElem = read_whitelist_elem(&deps.storage, &collateral.0)?;
+        if whitelist_elem.deprecation.is_some() {
+            return Err(StdError::generic_err(format!(
+                "Cannot lock deprecated collateral: {}",
+                deps.api.human_address(&collateral.0)?
+            )));
+        }
+
+        if borrower_category.is_some() && whitelist_elem.category != borrower_category {
+            return Err(StdError::generic_err(format!(
+                "Collateral is not in the borrower's category: {}",
+                deps.api.human_address(&collateral.0)?
+            )));
+        }
+
+        let total_locked = read_total_locked(&deps.storage, &collateral.0) + collateral.1;
+        if let Some(max_locked_amount) = whitelist_elem.max_locked_amount {
+            if total_locked > max_locked_amount {
+                return Err(StdError::generic_err(format!(
+                    "Lock amount too high; Total locked amount becomes greater than max locked amount: {}",
+                    max_locked_amount
+                )));
+            }
+        }
+        store_total_locked(&mut deps.storage, &collateral.0, &total_locked)?;
+
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps.api.human_address(&whitelist_elem.custody_contract)?,
             send: vec![],
@@ -44,6 +92,11 @@ pub fn lock_collateral<S: Storage, A: Api, Q: Querier>(
         }));
     }
 
+    if borrower_category.is_some() {
+        add_category_locked(&mut deps.storage, &collaterals)?;
+    }
+    store_collaterals(&mut deps.storage, &borrower_raw, &cur_collaterals)?;
+
     // Logging stuff, so can be removed
     let collateral_logs: Vec<String> = collaterals_human
         .iter()
@@ -82,7 +135,14 @@ pub fn unlock_collateral<S: Storage, A: Api, Q: Querier>(
     }
 
     // Compute borrow limit with collaterals except unlock target collaterals
-    let (borrow_limit, _) = compute_borrow_limit(deps, &cur_collaterals, Some(env.block.time))?;
+    let category = read_applied_category(&deps.storage, &borrower_raw, &cur_collaterals)?;
+    let (borrow_limit, _) = compute_borrow_limit(
+        deps,
+        &cur_collaterals,
+        category.as_ref().map(|(_, category)| category),
+        Some(env.block.height),
+        Some(env.block.time),
+    )?;
     let borrow_amount_res: BorrowerInfoResponse =
         query_borrower_info(deps, &market, &borrower, env.block.height)?;
     if borrow_limit < borrow_amount_res.loan_amount {
@@ -92,7 +152,14 @@ pub fn unlock_collateral<S: Storage, A: Api, Q: Querier>(
         )));
     }
 
+    sub_total_locked(&mut deps.storage, &collaterals)?;
+    if read_borrower_category(&deps.storage, &borrower_raw).is_some() {
+        sub_category_locked(&mut deps.storage, &collaterals)?;
+    }
     store_collaterals(&mut deps.storage, &borrower_raw, &cur_collaterals)?;
+    if cur_collaterals.is_empty() {
+        release_isolated_loan(&mut deps.storage, &borrower_raw)?;
+    }
 
     let mut messages: Vec<CosmosMsg> = vec![];
     for collateral in collaterals.clone() {
@@ -129,22 +196,130 @@ pub fn liquidate_collateral<S: Storage, A: Api, Q: Querier>(
     env: Env,
     borrower: HumanAddr,
 ) -> HandleResult {
+    let liquidator = env.message.sender.clone();
+    liquidate_borrower(deps, env, borrower, liquidator)
+}
+
+/// Queue a liquidation of every unhealthy borrower. Each one is liquidated
+/// by a separate LiquidateBorrower message, so the market balance before
+/// its liquidation is read after the previous borrower was repaid.
+/// Only the health check happens here; a queued liquidation that fails
+/// reverts the whole batch
+pub fn liquidate_collaterals<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    borrowers: Vec<HumanAddr>,
+) -> HandleResult {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut logs = vec![log("action", "liquidate_collaterals")];
+    let mut queued: Vec<HumanAddr> = vec![];
+    for borrower in borrowers {
+        // a second liquidation in the same batch would find the borrower healthy
+        if queued.contains(&borrower) {
+            continue;
+        }
+
+        // a borrower whose state cannot be computed must not block the others
+        let (liquidation_limit, _, borrow_amount) = match compute_liquidation_state(
+            deps,
+            &borrower,
+            env.block.height,
+            Some(env.block.time),
+        ) {
+            Ok(v) => v,
+            Err(err) => {
+                logs.push(log("failed", &borrower));
+                logs.push(log("error", err));
+                continue;
+            }
+        };
+        if liquidation_limit >= borrow_amount {
+            logs.push(log("skipped", &borrower));
+            continue;
+        }
+
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.clone(),
+            send: vec![],
+            msg: to_binary(&HandleMsg::LiquidateBorrower {
+                borrower: borrower.clone(),
+                liquidator: env.message.sender.clone(),
+            })?,
+        }));
+        logs.push(log("queued", &borrower));
+        queued.push(borrower);
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: None,
+    })
+}
+
+/// Liquidation of a single borrower queued by LiquidateCollaterals;
+/// only the overseer itself can send it
+pub fn liquidate_queued_borrower<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    borrower: HumanAddr,
+    liquidator: HumanAddr,
+) -> HandleResult {
+    if env.message.sender != env.contract.address {
+        return Err(StdError::unauthorized());
+    }
+
+    liquidate_borrower(deps, env, borrower, liquidator)
+}
+
+/// Liquidation limit, collateral prices and loan amount of the borrower
+fn compute_liquidation_state<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    borrower: &HumanAddr,
+    block_height: u64,
+    block_time: Option<u64>,
+) -> StdResult<(Uint256, Vec<Decimal256>, Uint256)> {
     let config: Config = read_config(&deps.storage)?;
     let market = deps.api.human_address(&config.market_contract)?;
 
+    let borrower_raw = deps.api.canonical_address(borrower)?;
+    let collaterals: Tokens = read_collaterals(&deps.storage, &borrower_raw);
+
+    // Compute liquidation limit with the locked collaterals
+    let category = read_applied_category(&deps.storage, &borrower_raw, &collaterals)?;
+    let (liquidation_limit, collateral_prices) = compute_liquidation_limit(
+        deps,
+        &collaterals,
+        category.as_ref().map(|(_, category)| category),
+        Some(block_height),
+        block_time,
+    )?;
+    let borrow_amount_res: BorrowerInfoResponse =
+        query_borrower_info(deps, &market, borrower, block_height)?;
+
+    Ok((
+        liquidation_limit,
+        collateral_prices,
+        borrow_amount_res.loan_amount,
+    ))
+}
+
+fn liquidate_borrower<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    borrower: HumanAddr,
+    liquidator: HumanAddr,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
     let borrower_raw = deps.api.canonical_address(&borrower)?;
     let mut cur_collaterals: Tokens = read_collaterals(&deps.storage, &borrower_raw);
+    let (liquidation_limit, collateral_prices, borrow_amount) =
+        compute_liquidation_state(deps, &borrower, env.block.height, Some(env.block.time))?;
 
-    // Compute borrow limit with collaterals except unlock target collaterals
-    let (borrow_limit, collateral_prices) =
-        compute_borrow_limit(deps, &cur_collaterals, Some(env.block.time))?;
-    let borrow_amount_res: BorrowerInfoResponse =
-        query_borrower_info(deps, &market, &borrower, env.block.height)?;
-    let borrow_amount = borrow_amount_res.loan_amount;
-
-    // borrow limit is equal or bigger than loan amount
+    // liquidation limit is equal or bigger than loan amount
     // cannot liquidation collaterals
-    if borrow_limit >= borrow_amount {
+    if liquidation_limit >= borrow_amount {
         return Err(StdError::generic_err(
             "Cannot liquidate safely collateralized loan",
         ));
@@ -154,16 +329,30 @@ pub fn liquidate_collateral<S: Storage, A: Api, Q: Querier>(
         &deps,
         &deps.api.human_address(&config.liquidation_contract)?,
         borrow_amount,
-        borrow_limit,
+        liquidation_limit,
         &cur_collaterals.to_human(&deps)?,
-        collateral_prices,
+        collateral_prices.clone(),
     )?;
 
     let liquidation_amount = liquidation_amount_res.collaterals.to_raw(&deps)?;
+    let liquidation_amount = apply_close_factor(
+        &config,
+        borrow_amount,
+        &cur_collaterals,
+        &collateral_prices,
+        liquidation_amount,
+    );
 
     // Store left collaterals
     cur_collaterals.sub(liquidation_amount.clone())?;
     store_collaterals(&mut deps.storage, &borrower_raw, &cur_collaterals)?;
+    sub_total_locked(&mut deps.storage, &liquidation_amount)?;
+    if read_borrower_category(&deps.storage, &borrower_raw).is_some() {
+        sub_category_locked(&mut deps.storage, &liquidation_amount)?;
+    }
+    if cur_collaterals.is_empty() {
+        release_isolated_loan(&mut deps.storage, &borrower_raw)?;
+    }
 
     let market_contract = deps.api.human_address(&config.market_contract)?;
     let prev_balance: Uint256 = query_balance(&deps, &market_contract, config.stable_denom)?;
@@ -177,7 +366,7 @@ pub fn liquidate_collateral<S: Storage, A: Api, Q: Querier>(
                 contract_addr: deps.api.human_address(&whitelist_elem.custody_contract)?,
                 send: vec![],
                 msg: to_binary(&CustodyHandleMsg::LiquidateCollateral {
-                    liquidator: env.message.sender.clone(),
+                    liquidator: liquidator.clone(),
                     borrower: borrower.clone(),
                     amount: collateral.1,
                 })?,
@@ -204,6 +393,202 @@ pub fn liquidate_collateral<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Scale down the liquidation amount so a single liquidation repays at most
+/// `close_factor` of the loan; the next call rechecks the remaining position
+#[allow(clippy::ptr_arg)]
+fn apply_close_factor(
+    config: &Config,
+    borrow_amount: Uint256,
+    collaterals: &Tokens,
+    collateral_prices: &[Decimal256],
+    liquidation_amount: Tokens,
+) -> Tokens {
+    let mut liquidation_value = Uint256::zero();
+    for collateral in liquidation_amount.iter() {
+        if let Some((_, price)) = collaterals
+            .iter()
+            .zip(collateral_prices.iter())
+            .find(|(c, _)| c.0 == collateral.0)
+        {
+            liquidation_value += collateral.1 * *price;
+        }
+    }
+
+    let ratio = compute_close_factor_ratio(borrow_amount, config.close_factor, liquidation_value);
+    if ratio == Decimal256::one() {
+        return liquidation_amount;
+    }
+
+    liquidation_amount
+        .into_iter()
+        .map(|c| (c.0, c.1 * ratio))
+        .filter(|c| c.1 > Uint256::zero())
+        .collect()
+}
+
+#[allow(clippy::ptr_arg)]
+fn sub_total_locked<S: Storage>(storage: &mut S, collaterals: &Tokens) -> StdResult<()> {
+    for collateral in collaterals.iter() {
+        let total_locked = read_total_locked(storage, &collateral.0);
+        if total_locked < collateral.1 {
+            return Err(StdError::generic_err("Total locked amount underflow"));
+        }
+
+        store_total_locked(storage, &collateral.0, &(total_locked - collateral.1))?;
+    }
+
+    Ok(())
+}
+
+pub fn update_borrower_loan<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    borrower: HumanAddr,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let market = deps.api.human_address(&config.market_contract)?;
+    if env.message.sender != market {
+        return Err(StdError::unauthorized());
+    }
+
+    let borrower_raw = deps.api.canonical_address(&borrower)?;
+    let prev_loan_amount = read_isolated_loan(&deps.storage, &borrower_raw)
+        .map(|isolated_loan| isolated_loan.loan_amount)
+        .unwrap_or_else(Uint256::zero);
+    release_isolated_loan(&mut deps.storage, &borrower_raw)?;
+
+    // the market has already stored the loan, including the accrued interest
+    let borrower_info: BorrowerInfoResponse =
+        query_borrower_info(deps, &market, &borrower, env.block.height)?;
+    let loan_amount = borrower_info.loan_amount;
+
+    // only borrowers holding isolated collateral are tracked
+    let collaterals: Tokens = read_collaterals(&deps.storage, &borrower_raw);
+    if let Some(whitelist_elem) = read_isolated_collateral(&deps.storage, &collaterals)? {
+        let collateral_token = collaterals[0].0.clone();
+        let isolated_debt = read_isolated_debt(&deps.storage, &collateral_token) + loan_amount;
+        if let Some(debt_ceiling) = whitelist_elem.debt_ceiling {
+            // repaying is always allowed, even above the ceiling
+            if loan_amount > prev_loan_amount && isolated_debt > debt_ceiling {
+                return Err(StdError::generic_err(format!(
+                    "Isolated debt exceeds the debt ceiling: {}",
+                    debt_ceiling
+                )));
+            }
+        }
+
+        store_isolated_debt(&mut deps.storage, &collateral_token, &isolated_debt)?;
+        store_isolated_loan(
+            &mut deps.storage,
+            &borrower_raw,
+            &IsolatedLoan {
+                collateral_token,
+                loan_amount,
+            },
+        )?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "update_borrower_loan"),
+            log("borrower", borrower),
+            log("loan_amount", loan_amount),
+        ],
+        data: None,
+    })
+}
+
+/// Whitelist entry of the collateral when it is the borrower's only,
+/// isolated, collateral
+#[allow(clippy::ptr_arg)]
+fn read_isolated_collateral<S: Storage>(
+    storage: &S,
+    collaterals: &Tokens,
+) -> StdResult<Option<WhitelistElem>> {
+    if collaterals.len() != 1 {
+        return Ok(None);
+    }
+
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(storage, &collaterals[0].0)?;
+    Ok(if whitelist_elem.isolated {
+        Some(whitelist_elem)
+    } else {
+        None
+    })
+}
+
+pub fn query_isolated_loan<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    borrower: HumanAddr,
+) -> StdResult<IsolatedLoanResponse> {
+    let borrower_raw = deps.api.canonical_address(&borrower)?;
+    match read_isolated_loan(&deps.storage, &borrower_raw) {
+        Some(isolated_loan) => Ok(IsolatedLoanResponse {
+            borrower,
+            collateral_token: Some(deps.api.human_address(&isolated_loan.collateral_token)?),
+            loan_amount: isolated_loan.loan_amount,
+        }),
+        None => Ok(IsolatedLoanResponse {
+            borrower,
+            collateral_token: None,
+            loan_amount: Uint256::zero(),
+        }),
+    }
+}
+
+/// Removes the borrower's last reported loan from the isolated debt
+fn release_isolated_loan<S: Storage>(storage: &mut S, borrower: &CanonicalAddr) -> StdResult<()> {
+    if let Some(isolated_loan) = read_isolated_loan(storage, borrower) {
+        let isolated_debt = read_isolated_debt(storage, &isolated_loan.collateral_token);
+        let isolated_debt = if isolated_debt > isolated_loan.loan_amount {
+            isolated_debt - isolated_loan.loan_amount
+        } else {
+            Uint256::zero()
+        };
+        store_isolated_debt(storage, &isolated_loan.collateral_token, &isolated_debt)?;
+        remove_isolated_loan(storage, borrower);
+    }
+
+    Ok(())
+}
+
+/// Caps the borrow limit of a borrower holding isolated collateral, so the
+/// debt backed by the collateral stays below its debt ceiling
+#[allow(clippy::ptr_arg)]
+fn apply_debt_ceiling<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    borrow_limit: Uint256,
+) -> StdResult<Uint256> {
+    let debt_ceiling = match read_isolated_collateral(&deps.storage, collaterals)? {
+        Some(WhitelistElem {
+            debt_ceiling: Some(debt_ceiling),
+            ..
+        }) => debt_ceiling,
+        _ => return Ok(borrow_limit),
+    };
+
+    let collateral_token = &collaterals[0].0;
+    // debt of the other borrowers holding the collateral
+    let isolated_debt = read_isolated_debt(&deps.storage, collateral_token);
+    let own_loan = read_isolated_loan(&deps.storage, borrower)
+        .map(|isolated_loan| isolated_loan.loan_amount)
+        .unwrap_or_else(Uint256::zero);
+    let other_debt = if isolated_debt > own_loan {
+        isolated_debt - own_loan
+    } else {
+        Uint256::zero()
+    };
+
+    if other_debt >= debt_ceiling {
+        return Ok(Uint256::zero());
+    }
+
+    Ok(min(borrow_limit, debt_ceiling - other_debt))
+}
+
 pub fn query_collaterals<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     borrower: HumanAddr,
@@ -237,54 +622,277 @@ pub fn query_all_collaterals<S: Storage, A: Api, Q: Querier>(
     Ok(AllCollateralsResponse { all_collaterals })
 }
 
+/// Scan a page of borrowers with locked collaterals and return the ones
+/// whose loan exceeds the liquidation limit
+pub fn query_liquidatable_borrowers<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+    block_height: u64,
+    block_time: Option<u64>,
+) -> StdResult<LiquidatableBorrowersResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.canonical_address(&start_after)?)
+    } else {
+        None
+    };
+
+    let all_collaterals: Vec<CollateralsResponse> = read_all_collaterals(deps, start_after, limit)?;
+
+    let mut borrowers: Vec<LiquidatableBorrowersResponseElem> = vec![];
+    let mut failed_borrowers: Vec<HumanAddr> = vec![];
+    for collaterals in all_collaterals.iter() {
+        // report the failing borrower, so the rest of the page is still scanned
+        let (liquidation_limit, _, loan_amount) = match compute_liquidation_state(
+            deps,
+            &collaterals.borrower,
+            block_height,
+            block_time,
+        ) {
+            Ok(v) => v,
+            Err(_) => {
+                failed_borrowers.push(collaterals.borrower.clone());
+                continue;
+            }
+        };
+        if liquidation_limit >= loan_amount {
+            continue;
+        }
+
+        borrowers.push(LiquidatableBorrowersResponseElem {
+            borrower: collaterals.borrower.clone(),
+            loan_amount,
+            liquidation_limit,
+            shortfall: loan_amount - liquidation_limit,
+        });
+    }
+
+    Ok(LiquidatableBorrowersResponse {
+        borrowers,
+        failed_borrowers,
+        last_scanned_borrower: all_collaterals.last().map(|c| c.borrower.clone()),
+    })
+}
+
 #[allow(clippy::ptr_arg)]
 pub(crate) fn compute_borrow_limit<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    collaterals: &Tokens,
+    category: Option<&Category>,
+    block_height: Option<u64>,
+    block_time: Option<u64>,
+) -> StdResult<(Uint256, Vec<Decimal256>)> {
+    compute_collateral_limit(deps, collaterals, block_time, |elem| {
+        let max_ltv = category.map_or(elem.max_ltv, |category| category.max_ltv);
+        match &elem.deprecation {
+            None => max_ltv,
+            Some(deprecation) => ramp_deprecated_ltv(deprecation, max_ltv, block_height),
+        }
+    })
+}
+
+/// Loans above this limit can be liquidated; it is never lower than
+/// the borrow limit, so a maxed out loan survives a small price move
+#[allow(clippy::ptr_arg)]
+pub(crate) fn compute_liquidation_limit<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    collaterals: &Tokens,
+    category: Option<&Category>,
+    block_height: Option<u64>,
+    block_time: Option<u64>,
+) -> StdResult<(Uint256, Vec<Decimal256>)> {
+    compute_collateral_limit(deps, collaterals, block_time, |elem| {
+        compute_liquidation_threshold(elem, category, block_height)
+    })
+}
+
+/// Deprecated collaterals ramp their threshold down along with max_ltv,
+/// so the liquidation limit stays above the borrow limit until both reach zero
+fn compute_liquidation_threshold(
+    elem: &WhitelistElem,
+    category: Option<&Category>,
+    block_height: Option<u64>,
+) -> Decimal256 {
+    let liquidation_threshold = category.map_or(elem.liquidation_threshold(), |category| {
+        category.liquidation_threshold
+    });
+    match &elem.deprecation {
+        None => liquidation_threshold,
+        Some(deprecation) => ramp_deprecated_ltv(deprecation, liquidation_threshold, block_height),
+    }
+}
+
+fn ramp_deprecated_ltv(
+    deprecation: &CollateralDeprecation,
+    ltv: Decimal256,
+    block_height: Option<u64>,
+) -> Decimal256 {
+    match block_height {
+        Some(block_height) => compute_deprecated_ltv(
+            ltv,
+            deprecation.start_height,
+            deprecation.ramp_blocks,
+            block_height,
+        ),
+        // without a block height the ramp is assumed to be over
+        None => Decimal256::zero(),
+    }
+}
+
+#[allow(clippy::ptr_arg)]
+fn compute_collateral_limit<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     collaterals: &Tokens,
     block_time: Option<u64>,
+    ltv: impl Fn(&WhitelistElem) -> Decimal256,
 ) -> StdResult<(Uint256, Vec<Decimal256>)> {
     let config: Config = read_config(&deps.storage)?;
     let oracle_contract = deps.api.human_address(&config.oracle_contract)?;
 
-    let mut borrow_limit: Uint256 = Uint256::zero();
+    let mut limit: Uint256 = Uint256::zero();
     let mut collateral_prices: Vec<Decimal256> = vec![];
     for collateral in collaterals.iter() {
-        let collateral_token = collateral.0.clone();
+        let collateral_token = deps.api.human_address(&collateral.0)?;
         let collateral_amount = collateral.1;
 
-        let price: PriceResponse = query_price(
+        let price: PriceResponse = query_twap_price(
             &deps,
             &oracle_contract,
-            (deps.api.human_address(&collateral_token)?).to_string(),
+            collateral_token.to_string(),
             config.stable_denom.to_string(),
+            config.twap_window,
             block_time.map(|block_time| TimeConstraints {
                 block_time,
                 valid_timeframe: config.price_timeframe,
             }),
         )?;
 
+        // halted or unknown markets cannot back loans nor be liquidated
+        if price.status != PriceStatus::Trading {
+            return Err(StdError::generic_err(format!(
+                "Collateral price is not trading: {}",
+                collateral_token
+            )));
+        }
+
+        // uncertain prices lower the collateral value
+        let (price_lower, _) = compute_confidence_band(price.rate, price.confidence);
         let elem: WhitelistElem = read_whitelist_elem(&deps.storage, &collateral.0)?;
-        let collateral_value = collateral_amount * price.rate;
-        borrow_limit += collateral_value * elem.max_ltv;
-        collateral_prices.push(price.rate);
+        let collateral_value = collateral_amount * price_lower;
+        limit += collateral_value * ltv(&elem);
+        collateral_prices.push(price_lower);
     }
 
-    // returns borrow_limit with collaterals value in stable denom
-    Ok((borrow_limit, collateral_prices))
+    // returns the limit with collaterals value in stable denom
+    Ok((limit, collateral_prices))
 }
 
 pub fn query_borrow_limit<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     borrower: HumanAddr,
+    block_height: Option<u64>,
     block_time: Option<u64>,
 ) -> StdResult<BorrowLimitResponse> {
-    let collaterals = read_collaterals(&deps.storage, &deps.api.canonical_address(&borrower)?);
+    let borrower_raw = deps.api.canonical_address(&borrower)?;
+    let collaterals = read_collaterals(&deps.storage, &borrower_raw);
+
+    let category = read_applied_category(&deps.storage, &borrower_raw, &collaterals)?;
 
     // Compute borrow limit with collaterals
-    let (borrow_limit, _) = compute_borrow_limit(deps, &collaterals, block_time)?;
+    let (borrow_limit, _) = compute_borrow_limit(
+        deps,
+        &collaterals,
+        category.as_ref().map(|(_, category)| category),
+        block_height,
+        block_time,
+    )?;
+    let borrow_limit = apply_debt_ceiling(deps, &borrower_raw, &collaterals, borrow_limit)?;
+    let (liquidation_limit, _) = compute_liquidation_limit(
+        deps,
+        &collaterals,
+        category.as_ref().map(|(_, category)| category),
+        block_height,
+        block_time,
+    )?;
 
     Ok(BorrowLimitResponse {
         borrower,
         borrow_limit,
+        liquidation_limit,
+        category: category.map(|(name, _)| name),
+        isolated: read_isolated_collateral(&deps.storage, &collaterals)?.is_some(),
+    })
+}
+
+pub fn query_borrower_health<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    borrower: HumanAddr,
+    block_height: u64,
+    block_time: Option<u64>,
+) -> StdResult<BorrowerHealthResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    let market = deps.api.human_address(&config.market_contract)?;
+
+    let borrower_raw = deps.api.canonical_address(&borrower)?;
+    let collaterals = read_collaterals(&deps.storage, &borrower_raw);
+    let category = read_applied_category(&deps.storage, &borrower_raw, &collaterals)?;
+    let (borrow_limit, collateral_prices) = compute_borrow_limit(
+        deps,
+        &collaterals,
+        category.as_ref().map(|(_, category)| category),
+        Some(block_height),
+        block_time,
+    )?;
+    let borrow_limit = apply_debt_ceiling(deps, &borrower_raw, &collaterals, borrow_limit)?;
+    let borrow_amount_res: BorrowerInfoResponse =
+        query_borrower_info(deps, &market, &borrower, block_height)?;
+    let loan_amount = borrow_amount_res.loan_amount;
+
+    // reuse the borrow limit prices, so both limits see the same oracle state
+    let mut liquidation_limit = Uint256::zero();
+    let mut collateral_limits: Vec<(Uint256, Decimal256)> = vec![];
+    for (collateral, price) in collaterals.iter().zip(collateral_prices.iter()) {
+        let liquidation_threshold = compute_liquidation_threshold(
+            &read_whitelist_elem(&deps.storage, &collateral.0)?,
+            category.as_ref().map(|(_, category)| category),
+            Some(block_height),
+        );
+        let collateral_limit = collateral.1 * *price * liquidation_threshold;
+        liquidation_limit += collateral_limit;
+        collateral_limits.push((collateral_limit, liquidation_threshold));
+    }
+
+    let mut health_collaterals: Vec<BorrowerHealthResponseElem> = vec![];
+    for ((collateral, price), (collateral_limit, liquidation_threshold)) in collaterals
+        .iter()
+        .zip(collateral_prices.iter())
+        .zip(collateral_limits.iter())
+    {
+        health_collaterals.push(BorrowerHealthResponseElem {
+            collateral_token: deps.api.human_address(&collateral.0)?,
+            amount: collateral.1,
+            price: *price,
+            liquidation_price: compute_liquidation_price(
+                loan_amount,
+                liquidation_limit,
+                *collateral_limit,
+                collateral.1,
+                *liquidation_threshold,
+            ),
+        });
+    }
+
+    Ok(BorrowerHealthResponse {
+        borrower,
+        loan_amount,
+        borrow_limit,
+        liquidation_limit,
+        health_factor: compute_health_factor(loan_amount, liquidation_limit),
+        borrowable_amount: if borrow_limit > loan_amount {
+            borrow_limit - loan_amount
+        } else {
+            Uint256::zero()
+        },
+        collaterals: health_collaterals,
     })
 }
@@ -5,24 +5,32 @@ use cosmwasm_std::{
     StdError, StdResult, Storage, WasmMsg,
 };
 
+use crate::category::{
+    assign_category, create_category, enter_category, leave_category, query_category,
+};
 use crate::collateral::{
-    liquidate_collateral, lock_collateral, query_all_collaterals, query_borrow_limit,
-    query_collaterals, unlock_collateral,
+    liquidate_collateral, liquidate_collaterals, liquidate_queued_borrower, lock_collateral,
+    query_all_collaterals, query_borrow_limit, query_borrower_health, query_collaterals,
+    query_isolated_loan, query_liquidatable_borrowers, unlock_collateral, update_borrower_loan,
 };
-use crate::querier::query_epoch_state;
+use crate::querier::{query_epoch_state, query_pid_epoch_passed};
 use crate::state::{
-    read_config, read_epoch_state, read_whitelist, read_whitelist_elem, store_config,
-    store_epoch_state, store_whitelist_elem, Config, EpochState, WhitelistElem,
+    read_config, read_epoch_state, read_isolated_debt, read_total_locked, read_whitelist,
+    read_whitelist_elem, remove_isolated_debt, remove_total_locked, remove_whitelist_elem,
+    reset_total_locked, store_config, store_epoch_state, store_whitelist_elem, Config, EpochState,
+    WhitelistElem,
 };
 
 use moneymarket::custody::HandleMsg as CustodyHandleMsg;
 use moneymarket::market::EpochStateResponse;
 use moneymarket::market::HandleMsg as MarketHandleMsg;
 use moneymarket::overseer::{
-    ConfigResponse, HandleMsg, InitMsg, MigrateMsg, QueryMsg, WhitelistResponse,
-    WhitelistResponseElem,
+    CollateralDeprecation, ConfigResponse, HandleMsg, InitMsg, MigrateMsg, QueryMsg,
+    WhitelistResponse, WhitelistResponseElem,
 };
+use moneymarket::pid_interest_model::HandleMsg as PidHandleMsg;
 use moneymarket::querier::{deduct_tax, query_balance};
+use moneymarket_math::overseer::{compute_deposit_rate, compute_distributed_interest};
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -44,6 +52,9 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
             buffer_distribution_factor: msg.buffer_distribution_factor,
             anc_purchase_factor: msg.anc_purchase_factor,
             price_timeframe: msg.price_timeframe,
+            twap_window: 0,
+            close_factor: Decimal256::zero(),
+            pid_interest_model: None,
         },
     )?;
 
@@ -77,6 +88,9 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             anc_purchase_factor,
             epoch_period,
             price_timeframe,
+            twap_window,
+            close_factor,
+            pid_interest_model,
         } => update_config(
             deps,
             env,
@@ -89,6 +103,9 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             anc_purchase_factor,
             epoch_period,
             price_timeframe,
+            twap_window,
+            close_factor,
+            pid_interest_model,
         ),
         HandleMsg::Whitelist {
             name,
@@ -96,6 +113,10 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             collateral_token,
             custody_contract,
             max_ltv,
+            liquidation_threshold,
+            max_locked_amount,
+            isolated,
+            debt_ceiling,
         } => register_whitelist(
             deps,
             env,
@@ -104,12 +125,47 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             collateral_token,
             custody_contract,
             max_ltv,
+            liquidation_threshold,
+            max_locked_amount,
+            isolated,
+            debt_ceiling,
         ),
         HandleMsg::UpdateWhitelist {
             collateral_token,
             custody_contract,
             max_ltv,
-        } => update_whitelist(deps, env, collateral_token, custody_contract, max_ltv),
+            liquidation_threshold,
+            max_locked_amount,
+            remove_max_locked_amount,
+            debt_ceiling,
+        } => update_whitelist(
+            deps,
+            env,
+            collateral_token,
+            custody_contract,
+            max_ltv,
+            liquidation_threshold,
+            max_locked_amount,
+            remove_max_locked_amount,
+            debt_ceiling,
+        ),
+        HandleMsg::DeprecateCollateral {
+            collateral_token,
+            ramp_blocks,
+        } => deprecate_collateral(deps, env, collateral_token, ramp_blocks),
+        HandleMsg::RemoveWhitelist { collateral_token } => {
+            remove_whitelist(deps, env, collateral_token)
+        }
+        HandleMsg::CreateCategory {
+            category,
+            max_ltv,
+            liquidation_threshold,
+        } => create_category(deps, env, category, max_ltv, liquidation_threshold),
+        HandleMsg::AssignCategory {
+            collateral_token,
+            category,
+        } => assign_category(deps, env, collateral_token, category),
+        HandleMsg::UpdateBorrowerLoan { borrower } => update_borrower_loan(deps, env, borrower),
         HandleMsg::ExecuteEpochOperations {} => execute_epoch_operations(deps, env),
         HandleMsg::UpdateEpochState {
             interest_buffer,
@@ -117,7 +173,16 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         } => update_epoch_state(deps, env, interest_buffer, distributed_interest),
         HandleMsg::LockCollateral { collaterals } => lock_collateral(deps, env, collaterals),
         HandleMsg::UnlockCollateral { collaterals } => unlock_collateral(deps, env, collaterals),
+        HandleMsg::EnterCategory { category } => enter_category(deps, env, category),
+        HandleMsg::LeaveCategory {} => leave_category(deps, env),
         HandleMsg::LiquidateCollateral { borrower } => liquidate_collateral(deps, env, borrower),
+        HandleMsg::LiquidateCollaterals { borrowers } => {
+            liquidate_collaterals(deps, env, borrowers)
+        }
+        HandleMsg::LiquidateBorrower {
+            borrower,
+            liquidator,
+        } => liquidate_queued_borrower(deps, env, borrower, liquidator),
     }
 }
 
@@ -134,6 +199,9 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
     anc_purchase_factor: Option<Decimal256>,
     epoch_period: Option<u64>,
     price_timeframe: Option<u64>,
+    twap_window: Option<u64>,
+    close_factor: Option<Decimal256>,
+    pid_interest_model: Option<HumanAddr>,
 ) -> HandleResult {
     let mut config: Config = read_config(&deps.storage)?;
 
@@ -177,6 +245,24 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
         config.price_timeframe = price_timeframe;
     }
 
+    if let Some(twap_window) = twap_window {
+        config.twap_window = twap_window;
+    }
+
+    if let Some(close_factor) = close_factor {
+        if close_factor > Decimal256::one() {
+            return Err(StdError::generic_err(
+                "Close factor cannot be greater than one",
+            ));
+        }
+
+        config.close_factor = close_factor;
+    }
+
+    if let Some(pid_interest_model) = pid_interest_model {
+        config.pid_interest_model = Some(deps.api.canonical_address(&pid_interest_model)?);
+    }
+
     store_config(&mut deps.storage, &config)?;
 
     Ok(HandleResponse {
@@ -186,6 +272,7 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn register_whitelist<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -194,12 +281,28 @@ pub fn register_whitelist<S: Storage, A: Api, Q: Querier>(
     collateral_token: HumanAddr,
     custody_contract: HumanAddr,
     max_ltv: Decimal256,
+    liquidation_threshold: Decimal256,
+    max_locked_amount: Option<Uint256>,
+    isolated: bool,
+    debt_ceiling: Option<Uint256>,
 ) -> HandleResult {
     let config: Config = read_config(&deps.storage)?;
     if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
         return Err(StdError::unauthorized());
     }
 
+    if liquidation_threshold < max_ltv {
+        return Err(StdError::generic_err(
+            "Liquidation threshold cannot be smaller than max_ltv",
+        ));
+    }
+
+    if !isolated && debt_ceiling.is_some() {
+        return Err(StdError::generic_err(
+            "Debt ceiling can only be set for isolated collateral",
+        ));
+    }
+
     let collateral_token_raw = deps.api.canonical_address(&collateral_token)?;
     if read_whitelist_elem(&deps.storage, &collateral_token_raw).is_ok() {
         return Err(StdError::generic_err(
@@ -215,6 +318,12 @@ pub fn register_whitelist<S: Storage, A: Api, Q: Querier>(
             symbol: symbol.to_string(),
             custody_contract: deps.api.canonical_address(&custody_contract)?,
             max_ltv,
+            liquidation_threshold: Some(liquidation_threshold),
+            deprecation: None,
+            max_locked_amount,
+            isolated,
+            debt_ceiling,
+            category: None,
         },
     )?;
 
@@ -227,23 +336,36 @@ pub fn register_whitelist<S: Storage, A: Api, Q: Querier>(
             log("collateral_token", collateral_token),
             log("custody_contract", custody_contract),
             log("LTV", max_ltv),
+            log("liquidation_threshold", liquidation_threshold),
         ],
         data: None,
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_whitelist<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     collateral_token: HumanAddr,
     custody_contract: Option<HumanAddr>,
     max_ltv: Option<Decimal256>,
+    liquidation_threshold: Option<Decimal256>,
+    max_locked_amount: Option<Uint256>,
+    remove_max_locked_amount: Option<bool>,
+    debt_ceiling: Option<Uint256>,
 ) -> HandleResult {
     let config: Config = read_config(&deps.storage)?;
     if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
         return Err(StdError::unauthorized());
     }
 
+    let remove_max_locked_amount = remove_max_locked_amount.unwrap_or(false);
+    if remove_max_locked_amount && max_locked_amount.is_some() {
+        return Err(StdError::generic_err(
+            "Cannot set and remove max_locked_amount at the same time",
+        ));
+    }
+
     let collateral_token_raw = deps.api.canonical_address(&collateral_token)?;
     let mut whitelist_elem: WhitelistElem =
         read_whitelist_elem(&deps.storage, &collateral_token_raw)?;
@@ -256,6 +378,34 @@ pub fn update_whitelist<S: Storage, A: Api, Q: Querier>(
         whitelist_elem.max_ltv = max_ltv;
     }
 
+    if let Some(liquidation_threshold) = liquidation_threshold {
+        whitelist_elem.liquidation_threshold = Some(liquidation_threshold);
+    }
+
+    if let Some(max_locked_amount) = max_locked_amount {
+        whitelist_elem.max_locked_amount = Some(max_locked_amount);
+    }
+
+    if remove_max_locked_amount {
+        whitelist_elem.max_locked_amount = None;
+    }
+
+    if let Some(debt_ceiling) = debt_ceiling {
+        if !whitelist_elem.isolated {
+            return Err(StdError::generic_err(
+                "Debt ceiling can only be set for isolated collateral",
+            ));
+        }
+
+        whitelist_elem.debt_ceiling = Some(debt_ceiling);
+    }
+
+    if whitelist_elem.liquidation_threshold() < whitelist_elem.max_ltv {
+        return Err(StdError::generic_err(
+            "Liquidation threshold cannot be smaller than max_ltv",
+        ));
+    }
+
     store_whitelist_elem(&mut deps.storage, &collateral_token_raw, &whitelist_elem)?;
 
     Ok(HandleResponse {
@@ -268,6 +418,77 @@ pub fn update_whitelist<S: Storage, A: Api, Q: Querier>(
                 deps.api.human_address(&whitelist_elem.custody_contract)?,
             ),
             log("LTV", whitelist_elem.max_ltv),
+            log(
+                "liquidation_threshold",
+                whitelist_elem.liquidation_threshold(),
+            ),
+        ],
+        data: None,
+    })
+}
+
+pub fn deprecate_collateral<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    collateral_token: HumanAddr,
+    ramp_blocks: u64,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    let collateral_token_raw = deps.api.canonical_address(&collateral_token)?;
+    let mut whitelist_elem: WhitelistElem =
+        read_whitelist_elem(&deps.storage, &collateral_token_raw)?;
+    if whitelist_elem.deprecation.is_some() {
+        return Err(StdError::generic_err("Collateral is already deprecated"));
+    }
+
+    whitelist_elem.deprecation = Some(CollateralDeprecation {
+        start_height: env.block.height,
+        ramp_blocks,
+    });
+    store_whitelist_elem(&mut deps.storage, &collateral_token_raw, &whitelist_elem)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "deprecate_collateral"),
+            log("collateral_token", collateral_token),
+            log("ramp_blocks", ramp_blocks),
+        ],
+        data: None,
+    })
+}
+
+pub fn remove_whitelist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    collateral_token: HumanAddr,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    let collateral_token_raw = deps.api.canonical_address(&collateral_token)?;
+    read_whitelist_elem(&deps.storage, &collateral_token_raw)?;
+    if !read_total_locked(&deps.storage, &collateral_token_raw).is_zero() {
+        return Err(StdError::generic_err(
+            "Collateral is still locked by borrowers",
+        ));
+    }
+
+    remove_whitelist_elem(&mut deps.storage, &collateral_token_raw);
+    remove_total_locked(&mut deps.storage, &collateral_token_raw);
+    remove_isolated_debt(&mut deps.storage, &collateral_token_raw);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "remove_whitelist"),
+            log("collateral_token", collateral_token),
         ],
         data: None,
     })
@@ -296,9 +517,8 @@ pub fn execute_epoch_operations<S: Storage, A: Api, Q: Querier>(
 
     // effective_deposit_rate = cur_exchange_rate / prev_exchange_rate
     // deposit_rate = (effective_deposit_rate - 1) / blocks
-    let effective_deposit_rate = epoch_state.exchange_rate / state.prev_exchange_rate;
     let deposit_rate =
-        (effective_deposit_rate - Decimal256::one()) / Decimal256::from_uint256(blocks);
+        compute_deposit_rate(state.prev_exchange_rate, epoch_state.exchange_rate, blocks);
 
     let mut messages: Vec<CosmosMsg> = vec![];
     let mut interest_buffer = query_balance(
@@ -331,17 +551,15 @@ pub fn execute_epoch_operations<S: Storage, A: Api, Q: Querier>(
     // Only executed when deposit rate < threshold_deposit_rate
     let mut distributed_interest: Uint256 = Uint256::zero();
     if deposit_rate < config.threshold_deposit_rate {
-        // missing_deposit_rate(_per_block)
-        let missing_deposit_rate = config.threshold_deposit_rate - deposit_rate;
-        let prev_deposits = state.prev_aterra_supply * state.prev_exchange_rate;
-
-        // missing_deposits = prev_deposits * missing_deposit_rate(_per_block) * blocks
-        let missing_deposits = prev_deposits * blocks * missing_deposit_rate;
         let distribution_buffer = interest_buffer * config.buffer_distribution_factor;
-
-        // When there was not enough deposits happens,
-        // distribute interest to market contract
-        distributed_interest = std::cmp::min(missing_deposits, distribution_buffer);
+        distributed_interest = compute_distributed_interest(
+            state.prev_aterra_supply,
+            state.prev_exchange_rate,
+            blocks,
+            deposit_rate,
+            config.threshold_deposit_rate,
+            distribution_buffer,
+        );
         interest_buffer = interest_buffer - distributed_interest;
 
         if !distributed_interest.is_zero() {
@@ -379,6 +597,19 @@ pub fn execute_epoch_operations<S: Storage, A: Api, Q: Querier>(
         }));
     }
 
+    // Let the adaptive interest model observe the market,
+    // once its own epoch has passed
+    if let Some(pid_interest_model) = config.pid_interest_model {
+        let pid_interest_model = deps.api.human_address(&pid_interest_model)?;
+        if query_pid_epoch_passed(deps, &pid_interest_model, env.block.height)? {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pid_interest_model,
+                send: vec![],
+                msg: to_binary(&PidHandleMsg::Observe {})?,
+            }));
+        }
+    }
+
     // Execute store epoch state operation
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: env.contract.address,
@@ -493,8 +724,38 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
         }
         QueryMsg::BorrowLimit {
             borrower,
+            block_height,
+            block_time,
+        } => to_binary(&query_borrow_limit(
+            deps,
+            borrower,
+            block_height,
+            block_time,
+        )?),
+        QueryMsg::BorrowerHealth {
+            borrower,
+            block_height,
+            block_time,
+        } => to_binary(&query_borrower_health(
+            deps,
+            borrower,
+            block_height,
+            block_time,
+        )?),
+        QueryMsg::Category { category } => to_binary(&query_category(deps, category)?),
+        QueryMsg::IsolatedLoan { borrower } => to_binary(&query_isolated_loan(deps, borrower)?),
+        QueryMsg::LiquidatableBorrowers {
+            start_after,
+            limit,
+            block_height,
+            block_time,
+        } => to_binary(&query_liquidatable_borrowers(
+            deps,
+            start_after,
+            limit,
+            block_height,
             block_time,
-        } => to_binary(&query_borrow_limit(deps, borrower, block_time)?),
+        )?),
     }
 }
 
@@ -515,6 +776,12 @@ pub fn query_config<S: Storage, A: Api, Q: Querier>(
         buffer_distribution_factor: config.buffer_distribution_factor,
         anc_purchase_factor: config.anc_purchase_factor,
         price_timeframe: config.price_timeframe,
+        twap_window: config.twap_window,
+        close_factor: config.close_factor,
+        pid_interest_model: match config.pid_interest_model {
+            Some(v) => Some(deps.api.human_address(&v)?),
+            None => None,
+        },
     })
 }
 
@@ -531,17 +798,25 @@ pub fn query_whitelist<S: Storage, A: Api, Q: Querier>(
     limit: Option<u32>,
 ) -> StdResult<WhitelistResponse> {
     if let Some(collateral_token) = collateral_token {
-        let whitelist_elem: WhitelistElem = read_whitelist_elem(
-            &deps.storage,
-            &deps.api.canonical_address(&collateral_token)?,
-        )?;
+        let collateral_token_raw = deps.api.canonical_address(&collateral_token)?;
+        let whitelist_elem: WhitelistElem =
+            read_whitelist_elem(&deps.storage, &collateral_token_raw)?;
+        let liquidation_threshold = whitelist_elem.liquidation_threshold();
         Ok(WhitelistResponse {
             elems: vec![WhitelistResponseElem {
                 name: whitelist_elem.name,
                 symbol: whitelist_elem.symbol,
                 max_ltv: whitelist_elem.max_ltv,
+                liquidation_threshold,
                 custody_contract: deps.api.human_address(&whitelist_elem.custody_contract)?,
                 collateral_token,
+                deprecation: whitelist_elem.deprecation,
+                max_locked_amount: whitelist_elem.max_locked_amount,
+                total_locked_amount: read_total_locked(&deps.storage, &collateral_token_raw),
+                isolated: whitelist_elem.isolated,
+                debt_ceiling: whitelist_elem.debt_ceiling,
+                isolated_debt: read_isolated_debt(&deps.storage, &collateral_token_raw),
+                category: whitelist_elem.category,
             }],
         })
     } else {
@@ -571,5 +846,8 @@ pub fn migrate<S: Storage, A: Api, Q: Querier>(
         },
     )?;
 
+    // collaterals locked before the totals were tracked are counted in
+    reset_total_locked(&mut deps.storage)?;
+
     Ok(MigrateResponse::default())
 }
@@ -1,3 +1,4 @@
+pub mod category;
 pub mod collateral;
 pub mod contract;
 pub mod querier;
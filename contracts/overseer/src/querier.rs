@@ -5,6 +5,9 @@ use cosmwasm_std::{
 
 use moneymarket::liquidation::{LiquidationAmountResponse, QueryMsg as LiquidationQueryMsg};
 use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse, QueryMsg as MarketQueryMsg};
+use moneymarket::pid_interest_model::{
+    ConfigResponse as PidConfigResponse, QueryMsg as PidQueryMsg, StateResponse as PidStateResponse,
+};
 use moneymarket::tokens::TokensHuman;
 
 pub fn query_epoch_state<S: Storage, A: Api, Q: Querier>(
@@ -66,3 +69,21 @@ pub fn query_liquidation_amount<S: Storage, A: Api, Q: Querier>(
 
     Ok(liquidation_amount_res)
 }
+
+/// Whether the adaptive interest model accepts a new observation
+pub fn query_pid_epoch_passed<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    pid_addr: &HumanAddr,
+    block_height: u64,
+) -> StdResult<bool> {
+    let config: PidConfigResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: HumanAddr::from(pid_addr),
+        msg: to_binary(&PidQueryMsg::Config {})?,
+    }))?;
+    let state: PidStateResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: HumanAddr::from(pid_addr),
+        msg: to_binary(&PidQueryMsg::State {})?,
+    }))?;
+
+    Ok(block_height >= state.last_observed_height + config.epoch_period)
+}
@@ -7,14 +7,20 @@ use cosmwasm_std::{
 };
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::overseer::{CollateralsResponse, WhitelistResponseElem};
-use moneymarket::tokens::Tokens;
+use moneymarket::overseer::{CollateralDeprecation, CollateralsResponse, WhitelistResponseElem};
+use moneymarket::tokens::{Tokens, TokensMath};
 
 const KEY_CONFIG: &[u8] = b"config";
 const KEY_EPOCH_STATE: &[u8] = b"epoch_state";
 
 const PREFIX_WHITELIST: &[u8] = b"whitelist";
 const PREFIX_COLLATERALS: &[u8] = b"collateral";
+const PREFIX_TOTAL_LOCKED: &[u8] = b"total_locked";
+const PREFIX_ISOLATED_DEBT: &[u8] = b"isolated_debt";
+const PREFIX_ISOLATED_LOAN: &[u8] = b"isolated_loan";
+const PREFIX_CATEGORY: &[u8] = b"category";
+const PREFIX_BORROWER_CATEGORY: &[u8] = b"borrower_category";
+const PREFIX_CATEGORY_LOCKED: &[u8] = b"category_locked";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -30,6 +36,18 @@ pub struct Config {
     pub buffer_distribution_factor: Decimal256,
     pub anc_purchase_factor: Decimal256,
     pub price_timeframe: u64,
+    /// Collaterals are valued at the oracle TWAP over this many seconds;
+    /// zero uses the spot price
+    #[serde(default)]
+    pub twap_window: u64,
+    /// Share of the loan a single liquidation may repay;
+    /// zero liquidates as much as the liquidation contract returns
+    #[serde(default)]
+    pub close_factor: Decimal256,
+    /// Adaptive interest model asked to observe the market
+    /// at every epoch operation
+    #[serde(default)]
+    pub pid_interest_model: Option<CanonicalAddr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -46,7 +64,47 @@ pub struct WhitelistElem {
     pub name: String,
     pub symbol: String,
     pub max_ltv: Decimal256,
+    /// Missing on collaterals whitelisted before liquidation thresholds,
+    /// which keep being liquidated at their max_ltv
+    #[serde(default)]
+    pub liquidation_threshold: Option<Decimal256>,
     pub custody_contract: CanonicalAddr,
+    /// Set once the collateral is deprecated
+    #[serde(default)]
+    pub deprecation: Option<CollateralDeprecation>,
+    /// Cap on the amount locked by all borrowers
+    #[serde(default)]
+    pub max_locked_amount: Option<Uint256>,
+    /// Isolated collaterals cannot be mixed with other collaterals
+    #[serde(default)]
+    pub isolated: bool,
+    /// Cap on the debt backed by an isolated collateral
+    #[serde(default)]
+    pub debt_ceiling: Option<Uint256>,
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+impl WhitelistElem {
+    pub fn liquidation_threshold(&self) -> Decimal256 {
+        self.liquidation_threshold.unwrap_or(self.max_ltv)
+    }
+}
+
+/// LTV ratios used instead of the whitelisted ones,
+/// when all collaterals of a borrower belong to the category
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Category {
+    pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
+}
+
+/// Loan of a borrower holding isolated collateral,
+/// as last reported by the market contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsolatedLoan {
+    pub collateral_token: CanonicalAddr,
+    pub loan_amount: Uint256,
 }
 
 pub fn store_config<S: Storage>(storage: &mut S, data: &Config) -> StdResult<()> {
@@ -90,6 +148,11 @@ pub fn read_whitelist_elem<S: Storage>(
     }
 }
 
+pub fn remove_whitelist_elem<S: Storage>(storage: &mut S, collateral_token: &CanonicalAddr) {
+    let mut whitelist_bucket: Bucket<S, WhitelistElem> = Bucket::new(PREFIX_WHITELIST, storage);
+    whitelist_bucket.remove(collateral_token.as_slice());
+}
+
 pub fn read_whitelist<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     start_after: Option<CanonicalAddr>,
@@ -106,14 +169,24 @@ pub fn read_whitelist<S: Storage, A: Api, Q: Querier>(
         .take(limit)
         .map(|elem| {
             let (k, v) = elem?;
-            let collateral_token: HumanAddr = deps.api.human_address(&CanonicalAddr::from(k))?;
+            let collateral_token_raw = CanonicalAddr::from(k);
+            let collateral_token: HumanAddr = deps.api.human_address(&collateral_token_raw)?;
             let custody_contract: HumanAddr = deps.api.human_address(&v.custody_contract)?;
+            let liquidation_threshold = v.liquidation_threshold();
             Ok(WhitelistResponseElem {
                 name: v.name,
                 symbol: v.symbol,
                 collateral_token,
                 custody_contract,
                 max_ltv: v.max_ltv,
+                liquidation_threshold,
+                deprecation: v.deprecation,
+                max_locked_amount: v.max_locked_amount,
+                total_locked_amount: read_total_locked(&deps.storage, &collateral_token_raw),
+                isolated: v.isolated,
+                debt_ceiling: v.debt_ceiling,
+                isolated_debt: read_isolated_debt(&deps.storage, &collateral_token_raw),
+                category: v.category,
             })
         })
         .collect()
@@ -144,6 +217,157 @@ pub fn read_collaterals<S: Storage>(storage: &S, borrower: &CanonicalAddr) -> To
     }
 }
 
+pub fn store_total_locked<S: Storage>(
+    storage: &mut S,
+    collateral_token: &CanonicalAddr,
+    total_locked: &Uint256,
+) -> StdResult<()> {
+    let mut total_locked_bucket: Bucket<S, Uint256> = Bucket::new(PREFIX_TOTAL_LOCKED, storage);
+    total_locked_bucket.save(collateral_token.as_slice(), total_locked)
+}
+
+pub fn read_total_locked<S: Storage>(storage: &S, collateral_token: &CanonicalAddr) -> Uint256 {
+    let total_locked_bucket: ReadonlyBucket<S, Uint256> =
+        ReadonlyBucket::new(PREFIX_TOTAL_LOCKED, storage);
+    match total_locked_bucket.load(collateral_token.as_slice()) {
+        Ok(v) => v,
+        _ => Uint256::zero(),
+    }
+}
+
+/// Recomputes the total locked amount of every collateral from the
+/// collaterals of all borrowers
+pub fn reset_total_locked<S: Storage>(storage: &mut S) -> StdResult<()> {
+    let mut totals: Tokens = vec![];
+    {
+        let collaterals_bucket: ReadonlyBucket<S, Tokens> =
+            ReadonlyBucket::new(PREFIX_COLLATERALS, storage);
+        for elem in collaterals_bucket.range(None, None, Order::Ascending) {
+            let (_, collaterals) = elem?;
+            totals.add(collaterals);
+        }
+    }
+
+    for (collateral_token, total_locked) in totals.iter() {
+        store_total_locked(storage, collateral_token, total_locked)?;
+    }
+
+    Ok(())
+}
+
+pub fn remove_total_locked<S: Storage>(storage: &mut S, collateral_token: &CanonicalAddr) {
+    let mut total_locked_bucket: Bucket<S, Uint256> = Bucket::new(PREFIX_TOTAL_LOCKED, storage);
+    total_locked_bucket.remove(collateral_token.as_slice());
+}
+
+pub fn store_isolated_debt<S: Storage>(
+    storage: &mut S,
+    collateral_token: &CanonicalAddr,
+    isolated_debt: &Uint256,
+) -> StdResult<()> {
+    let mut isolated_debt_bucket: Bucket<S, Uint256> = Bucket::new(PREFIX_ISOLATED_DEBT, storage);
+    isolated_debt_bucket.save(collateral_token.as_slice(), isolated_debt)
+}
+
+pub fn read_isolated_debt<S: Storage>(storage: &S, collateral_token: &CanonicalAddr) -> Uint256 {
+    let isolated_debt_bucket: ReadonlyBucket<S, Uint256> =
+        ReadonlyBucket::new(PREFIX_ISOLATED_DEBT, storage);
+    match isolated_debt_bucket.load(collateral_token.as_slice()) {
+        Ok(v) => v,
+        _ => Uint256::zero(),
+    }
+}
+
+pub fn remove_isolated_debt<S: Storage>(storage: &mut S, collateral_token: &CanonicalAddr) {
+    let mut isolated_debt_bucket: Bucket<S, Uint256> = Bucket::new(PREFIX_ISOLATED_DEBT, storage);
+    isolated_debt_bucket.remove(collateral_token.as_slice());
+}
+
+pub fn store_isolated_loan<S: Storage>(
+    storage: &mut S,
+    borrower: &CanonicalAddr,
+    isolated_loan: &IsolatedLoan,
+) -> StdResult<()> {
+    let mut isolated_loan_bucket: Bucket<S, IsolatedLoan> =
+        Bucket::new(PREFIX_ISOLATED_LOAN, storage);
+    isolated_loan_bucket.save(borrower.as_slice(), isolated_loan)
+}
+
+pub fn read_isolated_loan<S: Storage>(
+    storage: &S,
+    borrower: &CanonicalAddr,
+) -> Option<IsolatedLoan> {
+    let isolated_loan_bucket: ReadonlyBucket<S, IsolatedLoan> =
+        ReadonlyBucket::new(PREFIX_ISOLATED_LOAN, storage);
+    isolated_loan_bucket.load(borrower.as_slice()).ok()
+}
+
+pub fn remove_isolated_loan<S: Storage>(storage: &mut S, borrower: &CanonicalAddr) {
+    let mut isolated_loan_bucket: Bucket<S, IsolatedLoan> =
+        Bucket::new(PREFIX_ISOLATED_LOAN, storage);
+    isolated_loan_bucket.remove(borrower.as_slice());
+}
+
+pub fn store_category<S: Storage>(
+    storage: &mut S,
+    category: &str,
+    data: &Category,
+) -> StdResult<()> {
+    let mut category_bucket: Bucket<S, Category> = Bucket::new(PREFIX_CATEGORY, storage);
+    category_bucket.save(category.as_bytes(), data)
+}
+
+pub fn read_category<S: Storage>(storage: &S, category: &str) -> StdResult<Category> {
+    let category_bucket: ReadonlyBucket<S, Category> =
+        ReadonlyBucket::new(PREFIX_CATEGORY, storage);
+    match category_bucket.load(category.as_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err("Category does not exist")),
+    }
+}
+
+pub fn store_borrower_category<S: Storage>(
+    storage: &mut S,
+    borrower: &CanonicalAddr,
+    category: &str,
+) -> StdResult<()> {
+    let mut borrower_category_bucket: Bucket<S, String> =
+        Bucket::new(PREFIX_BORROWER_CATEGORY, storage);
+    borrower_category_bucket.save(borrower.as_slice(), &category.to_string())
+}
+
+pub fn read_borrower_category<S: Storage>(storage: &S, borrower: &CanonicalAddr) -> Option<String> {
+    let borrower_category_bucket: ReadonlyBucket<S, String> =
+        ReadonlyBucket::new(PREFIX_BORROWER_CATEGORY, storage);
+    borrower_category_bucket.load(borrower.as_slice()).ok()
+}
+
+pub fn remove_borrower_category<S: Storage>(storage: &mut S, borrower: &CanonicalAddr) {
+    let mut borrower_category_bucket: Bucket<S, String> =
+        Bucket::new(PREFIX_BORROWER_CATEGORY, storage);
+    borrower_category_bucket.remove(borrower.as_slice());
+}
+
+/// Amount of the collateral locked by borrowers who entered a category
+pub fn store_category_locked<S: Storage>(
+    storage: &mut S,
+    collateral_token: &CanonicalAddr,
+    category_locked: &Uint256,
+) -> StdResult<()> {
+    let mut category_locked_bucket: Bucket<S, Uint256> =
+        Bucket::new(PREFIX_CATEGORY_LOCKED, storage);
+    category_locked_bucket.save(collateral_token.as_slice(), category_locked)
+}
+
+pub fn read_category_locked<S: Storage>(storage: &S, collateral_token: &CanonicalAddr) -> Uint256 {
+    let category_locked_bucket: ReadonlyBucket<S, Uint256> =
+        ReadonlyBucket::new(PREFIX_CATEGORY_LOCKED, storage);
+    match category_locked_bucket.load(collateral_token.as_slice()) {
+        Ok(v) => v,
+        _ => Uint256::zero(),
+    }
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
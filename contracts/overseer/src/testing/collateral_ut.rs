@@ -39,6 +39,10 @@ fn proper_compute_borrow_limit() {
         collateral_token: HumanAddr::from("bluna"),
         custody_contract: HumanAddr::from("custody_bluna"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
 
     let _res = handle(&mut deps, env.clone(), msg);
@@ -49,6 +53,10 @@ fn proper_compute_borrow_limit() {
         collateral_token: HumanAddr::from("batom"),
         custody_contract: HumanAddr::from("custody_batom"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
 
     let _res = handle(&mut deps, env.clone(), msg);
@@ -88,7 +96,7 @@ fn proper_compute_borrow_limit() {
     );
     collaterals.push(token2);
 
-    let res = compute_borrow_limit(&deps, &collaterals, None).unwrap();
+    let res = compute_borrow_limit(&deps, &collaterals, None, None, None).unwrap();
     let mut vec: Vec<Decimal256> = vec![];
     vec.push(Decimal256::from_uint256(1000u128));
     vec.push(Decimal256::from_uint256(2000u128));
@@ -11,7 +11,7 @@ use std::collections::HashMap;
 
 use moneymarket::liquidation::LiquidationAmountResponse;
 use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse};
-use moneymarket::oracle::PriceResponse;
+use moneymarket::oracle::{PriceResponse, PriceStatus};
 use moneymarket::tokens::TokensHuman;
 
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
@@ -96,12 +96,14 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
 pub struct OraclePriceQuerier {
     // this lets us iterate over all pairs that match the first string
     oracle_price: HashMap<(String, String), (Decimal256, u64, u64)>,
+    oracle_status: HashMap<(String, String), PriceStatus>,
 }
 
 impl OraclePriceQuerier {
     pub fn new(oracle_price: &[(&(String, String), &(Decimal256, u64, u64))]) -> Self {
         OraclePriceQuerier {
             oracle_price: oracle_price_to_map(oracle_price),
+            oracle_status: HashMap::new(),
         }
     }
 }
@@ -265,11 +267,21 @@ impl WasmMockQuerier {
                         }),
                     },
                     QueryMsg::Price { base, quote } => {
-                        match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
+                        let base_quote = (base, quote);
+                        match self.oracle_price_querier.oracle_price.get(&base_quote) {
                             Some(v) => Ok(to_binary(&PriceResponse {
                                 rate: v.0,
                                 last_updated_base: v.1,
                                 last_updated_quote: v.2,
+                                feeders_base: 1,
+                                feeders_quote: 1,
+                                confidence: None,
+                                status: self
+                                    .oracle_price_querier
+                                    .oracle_status
+                                    .get(&base_quote)
+                                    .copied()
+                                    .unwrap_or(PriceStatus::Trading),
                             })),
                             None => Err(SystemError::InvalidRequest {
                                 error: "No oracle price exists".to_string(),
@@ -344,6 +356,13 @@ impl WasmMockQuerier {
         self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
     }
 
+    pub fn with_oracle_status(&mut self, oracle_status: &[(&(String, String), &PriceStatus)]) {
+        self.oracle_price_querier.oracle_status = oracle_status
+            .iter()
+            .map(|(base_quote, status)| ((*base_quote).clone(), **status))
+            .collect();
+    }
+
     pub fn with_loan_amount(&mut self, loan_amount: &[(&HumanAddr, &Uint256)]) {
         self.loan_amount_querier = LoanAmountQuerier::new(loan_amount);
     }
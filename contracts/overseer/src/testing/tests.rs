@@ -1,20 +1,27 @@
-use crate::contract::{handle, init, query};
+use crate::contract::{handle, init, migrate, query};
 use crate::querier::query_epoch_state;
-use crate::state::{read_epoch_state, store_epoch_state, EpochState};
+use crate::state::{
+    read_epoch_state, read_isolated_debt, read_total_locked, store_epoch_state,
+    store_isolated_debt, store_total_locked, store_whitelist_elem, EpochState, WhitelistElem,
+};
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, log, to_binary, BankMsg, Coin, CosmosMsg, Decimal, HumanAddr, StdError, Uint128,
-    WasmMsg,
+    from_binary, from_slice, log, to_binary, to_vec, Api, BankMsg, Coin, CosmosMsg, Decimal,
+    HandleResponse, HumanAddr, StdError, Uint128, WasmMsg,
 };
 
 use moneymarket::custody::HandleMsg as CustodyHandleMsg;
 use moneymarket::market::HandleMsg as MarketHandleMsg;
+use moneymarket::oracle::PriceStatus;
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, HandleMsg,
-    InitMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    AllCollateralsResponse, BorrowLimitResponse, BorrowerHealthResponse,
+    BorrowerHealthResponseElem, CategoryResponse, CollateralDeprecation, CollateralsResponse,
+    ConfigResponse, HandleMsg, InitMsg, IsolatedLoanResponse, LiquidatableBorrowersResponse,
+    LiquidatableBorrowersResponseElem, MigrateMsg, QueryMsg, WhitelistResponse,
+    WhitelistResponseElem,
 };
 use moneymarket::querier::deduct_tax;
 
@@ -61,6 +68,9 @@ fn proper_initialization() {
             buffer_distribution_factor: Decimal256::percent(20),
             anc_purchase_factor: Decimal256::percent(20),
             price_timeframe: 60u64,
+            twap_window: 0u64,
+            close_factor: Decimal256::zero(),
+            pid_interest_model: None,
         }
     );
 
@@ -113,6 +123,9 @@ fn update_config() {
         anc_purchase_factor: None,
         epoch_period: None,
         price_timeframe: None,
+        twap_window: None,
+        close_factor: None,
+        pid_interest_model: None,
     };
 
     let res = handle(&mut deps, env, msg).unwrap();
@@ -135,6 +148,9 @@ fn update_config() {
         anc_purchase_factor: Some(Decimal256::percent(10)),
         epoch_period: Some(100000u64),
         price_timeframe: Some(120u64),
+        twap_window: Some(600u64),
+        close_factor: Some(Decimal256::percent(50)),
+        pid_interest_model: Some(HumanAddr::from("pid1")),
     };
 
     let res = handle(&mut deps, env, msg).unwrap();
@@ -158,6 +174,33 @@ fn update_config() {
     assert_eq!(Decimal256::percent(10), config_res.anc_purchase_factor);
     assert_eq!(100000u64, config_res.epoch_period);
     assert_eq!(120u64, config_res.price_timeframe);
+    assert_eq!(600u64, config_res.twap_window);
+    assert_eq!(Decimal256::percent(50), config_res.close_factor);
+    assert_eq!(Some(HumanAddr::from("pid1")), config_res.pid_interest_model);
+
+    let env = mock_env("owner1", &[]);
+    let msg = HandleMsg::UpdateConfig {
+        owner_addr: None,
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        twap_window: None,
+        close_factor: Some(Decimal256::percent(101)),
+        pid_interest_model: None,
+    };
+
+    let res = handle(&mut deps, env, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Close factor cannot be greater than one")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
     // Unauthorized err
     let env = mock_env("owner", &[]);
@@ -171,6 +214,9 @@ fn update_config() {
         anc_purchase_factor: None,
         epoch_period: None,
         price_timeframe: None,
+        twap_window: None,
+        close_factor: None,
+        pid_interest_model: None,
     };
 
     let res = handle(&mut deps, env, msg);
@@ -209,6 +255,10 @@ fn whitelist() {
         collateral_token: HumanAddr::from("bluna"),
         custody_contract: HumanAddr::from("custody"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -229,6 +279,7 @@ fn whitelist() {
             log("collateral_token", "bluna"),
             log("custody_contract", "custody"),
             log("LTV", "0.6"),
+            log("liquidation_threshold", "0.6"),
         ]
     );
 
@@ -251,6 +302,14 @@ fn whitelist() {
                 collateral_token: HumanAddr::from("bluna"),
                 custody_contract: HumanAddr::from("custody"),
                 max_ltv: Decimal256::percent(60),
+                liquidation_threshold: Decimal256::percent(60),
+                deprecation: None,
+                max_locked_amount: None,
+                total_locked_amount: Uint256::zero(),
+                isolated: false,
+                debt_ceiling: None,
+                isolated_debt: Uint256::zero(),
+                category: None,
             }]
         }
     );
@@ -262,6 +321,10 @@ fn whitelist() {
         collateral_token: HumanAddr::from("bluna"),
         custody_contract: HumanAddr::from("custody"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
 
     let env = mock_env("owner", &[]);
@@ -273,10 +336,55 @@ fn whitelist() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
+    // liquidation threshold cannot be below max_ltv
+    let msg = HandleMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: HumanAddr::from("batom"),
+        custody_contract: HumanAddr::from("custody"),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(50),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+
+    let env = mock_env("owner", &[]);
+    let res = handle(&mut deps, env, msg).unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => {
+            assert_eq!(msg, "Liquidation threshold cannot be smaller than max_ltv")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::UpdateWhitelist {
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: None,
+        max_ltv: Some(Decimal256::percent(70)),
+        liquidation_threshold: None,
+        max_locked_amount: None,
+        remove_max_locked_amount: None,
+        debt_ceiling: None,
+    };
+
+    let env = mock_env("owner", &[]);
+    let res = handle(&mut deps, env, msg).unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => {
+            assert_eq!(msg, "Liquidation threshold cannot be smaller than max_ltv")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
     let msg = HandleMsg::UpdateWhitelist {
         collateral_token: HumanAddr::from("bluna"),
         custody_contract: Some(HumanAddr::from("custody2")),
         max_ltv: Some(Decimal256::percent(30)),
+        liquidation_threshold: Some(Decimal256::percent(40)),
+        max_locked_amount: None,
+        remove_max_locked_amount: None,
+        debt_ceiling: None,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -295,6 +403,7 @@ fn whitelist() {
             log("collateral_token", "bluna"),
             log("custody_contract", "custody2"),
             log("LTV", "0.3"),
+            log("liquidation_threshold", "0.4"),
         ]
     );
 
@@ -317,22 +426,61 @@ fn whitelist() {
                 collateral_token: HumanAddr::from("bluna"),
                 custody_contract: HumanAddr::from("custody2"),
                 max_ltv: Decimal256::percent(30),
+                liquidation_threshold: Decimal256::percent(40),
+                deprecation: None,
+                max_locked_amount: None,
+                total_locked_amount: Uint256::zero(),
+                isolated: false,
+                debt_ceiling: None,
+                isolated_debt: Uint256::zero(),
+                category: None,
             }]
         }
     );
+
+    // entries whitelisted before liquidation thresholds fall back to max_ltv
+    let custody_raw = deps
+        .api
+        .canonical_address(&HumanAddr::from("custody2"))
+        .unwrap();
+    let legacy_elem: WhitelistElem = from_slice(
+        format!(
+            r#"{{"name":"bluna","symbol":"bluna","max_ltv":"0.5","custody_contract":{}}}"#,
+            String::from_utf8(to_vec(&custody_raw).unwrap()).unwrap()
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(legacy_elem.liquidation_threshold, None);
+    assert_eq!(legacy_elem.liquidation_threshold(), Decimal256::percent(50));
+
+    let collateral_token_raw = deps
+        .api
+        .canonical_address(&HumanAddr::from("bluna"))
+        .unwrap();
+    store_whitelist_elem(&mut deps.storage, &collateral_token_raw, &legacy_elem).unwrap();
+    let res = query(
+        &deps,
+        QueryMsg::Whitelist {
+            collateral_token: None,
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(whitelist_res.elems[0].max_ltv, Decimal256::percent(50));
+    assert_eq!(
+        whitelist_res.elems[0].liquidation_threshold,
+        Decimal256::percent(50)
+    );
 }
 
 #[test]
-fn execute_epoch_operations() {
-    let mut deps = mock_dependencies(
-        20,
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(10000000000u128),
-        }],
-    );
+fn deprecate_collateral() {
+    let mut deps = mock_dependencies(20, &[]);
 
-    let mut env = mock_env("owner", &[]);
+    let env = mock_env("owner", &[]);
     let msg = InitMsg {
         owner_addr: HumanAddr::from("owner"),
         oracle_contract: HumanAddr::from("oracle"),
@@ -341,7 +489,7 @@ fn execute_epoch_operations() {
         collector_contract: HumanAddr::from("collector"),
         stable_denom: "uusd".to_string(),
         epoch_period: 86400u64,
-        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        threshold_deposit_rate: Decimal256::permille(3),
         target_deposit_rate: Decimal256::permille(5),
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
@@ -351,153 +499,1154 @@ fn execute_epoch_operations() {
     // we can just call .unwrap() to assert this was a success
     let _res = init(&mut deps, env.clone(), msg).unwrap();
 
-    // store whitelist elems
     let msg = HandleMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
         collateral_token: HumanAddr::from("bluna"),
         custody_contract: HumanAddr::from("custody_bluna"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(80),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
 
-    let _res = handle(&mut deps, env.clone(), msg);
+    let msg = HandleMsg::LockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(1000u64))],
+    };
+    let mut env = mock_env("addr0000", &[]);
+    let _res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(1000u64),
+            env.block.time,
+            env.block.time,
+        ),
+    )]);
 
-    let msg = HandleMsg::Whitelist {
-        name: "batom".to_string(),
-        symbol: "batom".to_string(),
-        collateral_token: HumanAddr::from("batom"),
-        custody_contract: HumanAddr::from("custody_batom"),
-        max_ltv: Decimal256::percent(60),
+    let deprecate_msg = HandleMsg::DeprecateCollateral {
+        collateral_token: HumanAddr::from("bluna"),
+        ramp_blocks: 100u64,
     };
+    let res = handle(&mut deps, env.clone(), deprecate_msg.clone());
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let _res = handle(&mut deps, env.clone(), msg);
+    let owner_env = mock_env("owner", &[]);
+    let res = handle(&mut deps, owner_env.clone(), deprecate_msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "deprecate_collateral"),
+            log("collateral_token", "bluna"),
+            log("ramp_blocks", 100),
+        ]
+    );
 
-    let msg = HandleMsg::ExecuteEpochOperations {};
-    let res = handle(&mut deps, env.clone(), msg.clone());
+    let res = handle(&mut deps, owner_env.clone(), deprecate_msg).unwrap_err();
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
-            msg,
-            "An epoch has not passed yet; last executed height: 12345"
-        ),
+        StdError::GenericErr { msg, .. } => assert_eq!(msg, "Collateral is already deprecated"),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    env.block.height += 86400u64;
+    let res = query(
+        &deps,
+        QueryMsg::Whitelist {
+            collateral_token: Some(HumanAddr::from("bluna")),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        whitelist_res.elems[0].deprecation,
+        Some(CollateralDeprecation {
+            start_height: owner_env.block.height,
+            ramp_blocks: 100u64,
+        })
+    );
 
-    // If deposit_rate is bigger than threshold_deposit_rate
-    deps.querier.with_epoch_state(&[(
-        &HumanAddr::from("market"),
-        &(Uint256::from(1000000u64), Decimal256::percent(120)),
-    )]);
+    // new locks are blocked
+    let res = handle(&mut deps, env.clone(), msg).unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => {
+            assert_eq!(msg, "Cannot lock deprecated collateral: bluna")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // (120 / 100 - 1) / 86400
-    // deposit rate = 0.000002314814814814
-    // accrued_buffer = 10,000,000,000
-    // anc_purchase_amount = accrued_buffer * 0.2 = 2,000,000,000
-    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    // max_ltv ramps down from 0.6 to zero over 100 blocks
+    let borrow_limit = |deps: &_, block_height: Option<u64>| -> Uint256 {
+        let res = query(
+            deps,
+            QueryMsg::BorrowLimit {
+                borrower: HumanAddr::from("addr0000"),
+                block_height,
+                block_time: None,
+            },
+        )
+        .unwrap();
+        let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+        borrow_limit_res.borrow_limit
+    };
     assert_eq!(
-        res.messages,
-        vec![
-            CosmosMsg::Bank(BankMsg::Send {
-                from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
-                to_address: HumanAddr::from("collector"),
-                amount: vec![deduct_tax(
-                    &deps,
-                    Coin {
-                        denom: "uusd".to_string(),
-                        amount: Uint128::from(2_000_000_000u128),
-                    }
-                )
-                .unwrap()],
-            }),
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from("custody_batom"),
-                send: vec![],
-                msg: to_binary(&CustodyHandleMsg::DistributeRewards {}).unwrap(),
-            }),
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from("custody_bluna"),
-                send: vec![],
-                msg: to_binary(&CustodyHandleMsg::DistributeRewards {}).unwrap(),
-            }),
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
-                send: vec![],
-                msg: to_binary(&HandleMsg::UpdateEpochState {
-                    interest_buffer: Uint256::from(8_000_000_000u128),
-                    distributed_interest: Uint256::zero(),
-                })
-                .unwrap(),
-            })
-        ]
+        borrow_limit(&deps, Some(env.block.height)),
+        Uint256::from(600000u64)
+    );
+    assert_eq!(
+        borrow_limit(&deps, Some(env.block.height + 25)),
+        Uint256::from(450000u64)
+    );
+    assert_eq!(
+        borrow_limit(&deps, Some(env.block.height + 100)),
+        Uint256::zero()
     );
+    assert_eq!(borrow_limit(&deps, None), Uint256::zero());
 
+    // liquidation_threshold ramps down from 0.8 along with max_ltv
+    let liquidation_limit = |deps: &_, block_height: Option<u64>| -> Uint256 {
+        let res = query(
+            deps,
+            QueryMsg::BorrowLimit {
+                borrower: HumanAddr::from("addr0000"),
+                block_height,
+                block_time: None,
+            },
+        )
+        .unwrap();
+        let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+        borrow_limit_res.liquidation_limit
+    };
     assert_eq!(
-        res.log,
-        vec![
-            log("action", "epoch_operations"),
-            log("deposit_rate", "0.000002314814814814"),
-            log("exchange_rate", "1.2"),
-            log("aterra_supply", "1000000"),
-            log("distributed_interest", "0"),
-            log("anc_purchase_amount", "2000000000"),
-        ]
+        liquidation_limit(&deps, Some(env.block.height)),
+        Uint256::from(800000u64)
+    );
+    assert_eq!(
+        liquidation_limit(&deps, Some(env.block.height + 25)),
+        Uint256::from(600000u64)
     );
+    assert_eq!(
+        liquidation_limit(&deps, Some(env.block.height + 100)),
+        Uint256::zero()
+    );
+    assert_eq!(liquidation_limit(&deps, None), Uint256::zero());
 
-    // store epoch state for test purpose
-    store_epoch_state(
-        &mut deps.storage,
-        &EpochState {
-            last_executed_height: env.block.height,
-            prev_exchange_rate: Decimal256::from_str("1.2").unwrap(),
-            prev_aterra_supply: Uint256::from_str("1000000").unwrap(),
-            prev_interest_buffer: Uint256::from_str("9999000000").unwrap(),
-            deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
+    // the ramped threshold also applies to the borrower health
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(300000u64))]);
+    let res = query(
+        &deps,
+        QueryMsg::BorrowerHealth {
+            borrower: HumanAddr::from("addr0000"),
+            block_height: env.block.height + 50,
+            block_time: None,
         },
     )
     .unwrap();
+    let health_res: BorrowerHealthResponse = from_binary(&res).unwrap();
+    assert_eq!(health_res.borrow_limit, Uint256::from(300000u64));
+    assert_eq!(health_res.liquidation_limit, Uint256::from(400000u64));
 
-    // If deposit rate is bigger than threshold
-    deps.querier.with_epoch_state(&[(
-        &HumanAddr::from("market"),
-        &(Uint256::from(1000000u64), Decimal256::percent(125)),
-    )]);
+    // cannot remove while the collateral is locked
+    let remove_msg = HandleMsg::RemoveWhitelist {
+        collateral_token: HumanAddr::from("bluna"),
+    };
+    let res = handle(&mut deps, env.clone(), remove_msg.clone());
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
+    let res = handle(&mut deps, owner_env.clone(), remove_msg.clone()).unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => {
+            assert_eq!(msg, "Collateral is still locked by borrowers")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    env.block.height += 86400u64;
+    env.block.height += 100;
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::zero())]);
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(1000u64))],
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    // accrued_buffer = 1,000,000
-    // interest_buffer = 9,999,000,000
-    // (125 / 120 - 1) / 86400
-    // deposit rate = 0.000000482253086419
-    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    // leftover per collateral totals are dropped with the whitelist entry
+    let bluna_raw = deps
+        .api
+        .canonical_address(&HumanAddr::from("bluna"))
+        .unwrap();
+    store_isolated_debt(&mut deps.storage, &bluna_raw, &Uint256::from(1u64)).unwrap();
+
+    let res = handle(&mut deps, owner_env, remove_msg).unwrap();
     assert_eq!(
-        res.messages,
+        res.log,
         vec![
-            CosmosMsg::Bank(BankMsg::Send {
-                from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
-                to_address: HumanAddr::from("collector"),
-                amount: vec![deduct_tax(
-                    &deps,
-                    Coin {
-                        denom: "uusd".to_string(),
-                        amount: Uint128::from(200_000u128),
-                    }
-                )
-                .unwrap()]
-            }),
-            CosmosMsg::Bank(BankMsg::Send {
-                from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
-                to_address: HumanAddr::from("market"),
-                amount: vec![deduct_tax(
-                    &deps,
-                    Coin {
-                        denom: "uusd".to_string(),
-                        amount: Uint128::from(53680u128),
+            log("action", "remove_whitelist"),
+            log("collateral_token", "bluna"),
+        ]
+    );
+    assert_eq!(
+        read_isolated_debt(&deps.storage, &bluna_raw),
+        Uint256::zero()
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::Whitelist {
+            collateral_token: Some(HumanAddr::from("bluna")),
+            start_after: None,
+            limit: None,
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Token is not registered as collateral")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn collateral_cap() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let env = mock_env("owner", &[]);
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        oracle_contract: HumanAddr::from("oracle"),
+        market_contract: HumanAddr::from("market"),
+        liquidation_contract: HumanAddr::from("liquidation"),
+        collector_contract: HumanAddr::from("collector"),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+
+    let msg = HandleMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: HumanAddr::from("custody_bluna"),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: Some(Uint256::from(1000u64)),
+        isolated: false,
+        debt_ceiling: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let lock_msg = |amount: u64| HandleMsg::LockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(amount))],
+    };
+    let total_locked_amount = |deps: &_| -> Uint256 {
+        let res = query(
+            deps,
+            QueryMsg::Whitelist {
+                collateral_token: Some(HumanAddr::from("bluna")),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            whitelist_res.elems[0].max_locked_amount,
+            Some(Uint256::from(1000u64))
+        );
+        whitelist_res.elems[0].total_locked_amount
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = handle(&mut deps, env.clone(), lock_msg(600)).unwrap();
+    assert_eq!(total_locked_amount(&deps), Uint256::from(600u64));
+
+    // the cap counts collaterals of all borrowers
+    let res = handle(&mut deps, mock_env("addr0001", &[]), lock_msg(500)).unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => assert_eq!(
+            msg,
+            "Lock amount too high; Total locked amount becomes greater than max locked amount: 1000"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = handle(&mut deps, mock_env("addr0001", &[]), lock_msg(400)).unwrap();
+    assert_eq!(total_locked_amount(&deps), Uint256::from(1000u64));
+
+    // unlocking frees up the cap
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(1000u64),
+            env.block.time,
+            env.block.time,
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::zero())]);
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(100u64))],
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(total_locked_amount(&deps), Uint256::from(900u64));
+
+    let _res = handle(&mut deps, env.clone(), lock_msg(100)).unwrap();
+    assert_eq!(total_locked_amount(&deps), Uint256::from(1000u64));
+
+    // the owner can lift the cap
+    let update_msg = |max_locked_amount: Option<Uint256>| HandleMsg::UpdateWhitelist {
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: None,
+        max_ltv: None,
+        liquidation_threshold: None,
+        max_locked_amount,
+        remove_max_locked_amount: Some(true),
+        debt_ceiling: None,
+    };
+    let res = handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        update_msg(Some(Uint256::from(2000u64))),
+    )
+    .unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => assert_eq!(
+            msg,
+            "Cannot set and remove max_locked_amount at the same time"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = handle(&mut deps, mock_env("owner", &[]), update_msg(None)).unwrap();
+    let _res = handle(&mut deps, mock_env("addr0001", &[]), lock_msg(500)).unwrap();
+    let bluna_raw = deps
+        .api
+        .canonical_address(&HumanAddr::from("bluna"))
+        .unwrap();
+    assert_eq!(
+        read_total_locked(&deps.storage, &bluna_raw),
+        Uint256::from(1500u64)
+    );
+
+    // locks made before the totals were tracked are not counted until the
+    // migration seeds them
+    store_total_locked(&mut deps.storage, &bluna_raw, &Uint256::zero()).unwrap();
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(100u64))],
+    };
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => assert_eq!(msg, "Total locked amount underflow"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = migrate(
+        &mut deps,
+        mock_env("owner", &[]),
+        MigrateMsg {
+            target_deposit_rate: Decimal256::permille(5),
+            threshold_deposit_rate: Decimal256::permille(3),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        read_total_locked(&deps.storage, &bluna_raw),
+        Uint256::from(1500u64)
+    );
+
+    let _res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        read_total_locked(&deps.storage, &bluna_raw),
+        Uint256::from(1400u64)
+    );
+}
+
+#[test]
+fn isolated_collateral() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let env = mock_env("owner", &[]);
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        oracle_contract: HumanAddr::from("oracle"),
+        market_contract: HumanAddr::from("market"),
+        liquidation_contract: HumanAddr::from("liquidation"),
+        collector_contract: HumanAddr::from("collector"),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+
+    let msg = HandleMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: HumanAddr::from("custody_bluna"),
+        max_ltv: Decimal256::percent(50),
+        liquidation_threshold: Decimal256::percent(50),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: Some(Uint256::from(1000u64)),
+    };
+    let res = handle(&mut deps, env.clone(), msg).unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => {
+            assert_eq!(msg, "Debt ceiling can only be set for isolated collateral")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: HumanAddr::from("custody_bluna"),
+        max_ltv: Decimal256::percent(50),
+        liquidation_threshold: Decimal256::percent(50),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    let msg = HandleMsg::Whitelist {
+        name: "bisol".to_string(),
+        symbol: "bisol".to_string(),
+        collateral_token: HumanAddr::from("bisol"),
+        custody_contract: HumanAddr::from("custody_bisol"),
+        max_ltv: Decimal256::percent(50),
+        liquidation_threshold: Decimal256::percent(50),
+        max_locked_amount: None,
+        isolated: true,
+        debt_ceiling: Some(Uint256::from(1000u64)),
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(Decimal256::one(), env.block.time, env.block.time),
+        ),
+        (
+            &("bisol".to_string(), "uusd".to_string()),
+            &(Decimal256::one(), env.block.time, env.block.time),
+        ),
+    ]);
+
+    let lock_msg = |collateral_token: &str| HandleMsg::LockCollateral {
+        collaterals: vec![(HumanAddr::from(collateral_token), Uint256::from(10000u64))],
+    };
+    let borrow_limit = |deps: &_, borrower: &str| -> Uint256 {
+        let res = query(
+            deps,
+            QueryMsg::BorrowLimit {
+                borrower: HumanAddr::from(borrower),
+                block_height: None,
+                block_time: None,
+            },
+        )
+        .unwrap();
+        let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+        borrow_limit_res.borrow_limit
+    };
+    let isolated_debt = |deps: &_| -> Uint256 {
+        let res = query(
+            deps,
+            QueryMsg::Whitelist {
+                collateral_token: Some(HumanAddr::from("bisol")),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+        whitelist_res.elems[0].isolated_debt
+    };
+
+    // isolated collateral cannot be mixed with other collaterals
+    let _res = handle(&mut deps, mock_env("addr0000", &[]), lock_msg("bisol")).unwrap();
+    let _res = handle(&mut deps, mock_env("addr0001", &[]), lock_msg("bluna")).unwrap();
+    for (borrower, collateral_token) in &[("addr0000", "bluna"), ("addr0001", "bisol")] {
+        let res = handle(
+            &mut deps,
+            mock_env(*borrower, &[]),
+            lock_msg(collateral_token),
+        );
+        match res {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+                msg,
+                "Isolated collateral cannot be mixed with other collaterals"
+            ),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    // borrow limit is capped by the debt ceiling
+    assert_eq!(borrow_limit(&deps, "addr0000"), Uint256::from(1000u64));
+    assert_eq!(borrow_limit(&deps, "addr0001"), Uint256::from(5000u64));
+
+    // the market reports the loans of isolated borrowers only
+    let is_isolated = |deps: &_, borrower: &str| -> bool {
+        let res = query(
+            deps,
+            QueryMsg::BorrowLimit {
+                borrower: HumanAddr::from(borrower),
+                block_height: None,
+                block_time: None,
+            },
+        )
+        .unwrap();
+        let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+        borrow_limit_res.isolated
+    };
+    assert!(is_isolated(&deps, "addr0000"));
+    assert!(!is_isolated(&deps, "addr0001"));
+
+    let update_msg = |borrower: &str| HandleMsg::UpdateBorrowerLoan {
+        borrower: HumanAddr::from(borrower),
+    };
+    let res = handle(&mut deps, mock_env("addr0000", &[]), update_msg("addr0000"));
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the loan is read back from the market
+    deps.querier.with_loan_amount(&[
+        (&HumanAddr::from("addr0000"), &Uint256::from(600u64)),
+        (&HumanAddr::from("addr0001"), &Uint256::from(3000u64)),
+    ]);
+    let res = handle(&mut deps, mock_env("market", &[]), update_msg("addr0000")).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "update_borrower_loan"),
+            log("borrower", "addr0000"),
+            log("loan_amount", "600"),
+        ]
+    );
+    assert_eq!(isolated_debt(&deps), Uint256::from(600u64));
+
+    let res = query(
+        &deps,
+        QueryMsg::IsolatedLoan {
+            borrower: HumanAddr::from("addr0000"),
+        },
+    )
+    .unwrap();
+    let isolated_loan_res: IsolatedLoanResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        isolated_loan_res,
+        IsolatedLoanResponse {
+            borrower: HumanAddr::from("addr0000"),
+            collateral_token: Some(HumanAddr::from("bisol")),
+            loan_amount: Uint256::from(600u64),
+        }
+    );
+
+    // loans backed by other collaterals are not tracked
+    let _res = handle(&mut deps, mock_env("market", &[]), update_msg("addr0001")).unwrap();
+    assert_eq!(isolated_debt(&deps), Uint256::from(600u64));
+
+    let res = query(
+        &deps,
+        QueryMsg::IsolatedLoan {
+            borrower: HumanAddr::from("addr0001"),
+        },
+    )
+    .unwrap();
+    let isolated_loan_res: IsolatedLoanResponse = from_binary(&res).unwrap();
+    assert_eq!(isolated_loan_res.collateral_token, None);
+
+    // the borrower's own loan counts towards its limit,
+    // the others' loans shrink what is left under the ceiling
+    let _res = handle(&mut deps, mock_env("addr0002", &[]), lock_msg("bisol")).unwrap();
+    assert_eq!(borrow_limit(&deps, "addr0000"), Uint256::from(1000u64));
+    assert_eq!(borrow_limit(&deps, "addr0002"), Uint256::from(400u64));
+
+    // a loan grown above the ceiling, accrued interest included, is rejected
+    deps.querier.with_loan_amount(&[
+        (&HumanAddr::from("addr0000"), &Uint256::from(600u64)),
+        (&HumanAddr::from("addr0002"), &Uint256::from(401u64)),
+    ]);
+    let res = handle(&mut deps, mock_env("market", &[]), update_msg("addr0002"));
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Isolated debt exceeds the debt ceiling: 1000")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_loan_amount(&[
+        (&HumanAddr::from("addr0000"), &Uint256::from(200u64)),
+        (&HumanAddr::from("addr0002"), &Uint256::zero()),
+    ]);
+    let _res = handle(&mut deps, mock_env("market", &[]), update_msg("addr0000")).unwrap();
+    assert_eq!(isolated_debt(&deps), Uint256::from(200u64));
+    assert_eq!(borrow_limit(&deps, "addr0002"), Uint256::from(800u64));
+
+    // unlocking all isolated collateral releases the loan
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::zero())]);
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![(HumanAddr::from("bisol"), Uint256::from(10000u64))],
+    };
+    let _res = handle(&mut deps, mock_env("addr0000", &[]), msg).unwrap();
+    assert_eq!(isolated_debt(&deps), Uint256::zero());
+    assert_eq!(borrow_limit(&deps, "addr0002"), Uint256::from(1000u64));
+}
+
+#[test]
+fn collateral_category() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let env = mock_env("owner", &[]);
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        oracle_contract: HumanAddr::from("oracle"),
+        market_contract: HumanAddr::from("market"),
+        liquidation_contract: HumanAddr::from("liquidation"),
+        collector_contract: HumanAddr::from("collector"),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+
+    for collateral_token in &["bluna", "stluna", "bisol"] {
+        let msg = HandleMsg::Whitelist {
+            name: collateral_token.to_string(),
+            symbol: collateral_token.to_string(),
+            collateral_token: HumanAddr::from(*collateral_token),
+            custody_contract: HumanAddr::from(format!("custody_{}", collateral_token)),
+            max_ltv: Decimal256::percent(50),
+            liquidation_threshold: Decimal256::percent(60),
+            max_locked_amount: None,
+            isolated: false,
+            debt_ceiling: None,
+        };
+        let _res = handle(&mut deps, env.clone(), msg).unwrap();
+    }
+
+    let create_msg = |max_ltv: u64, liquidation_threshold: u64| HandleMsg::CreateCategory {
+        category: "luna".to_string(),
+        max_ltv: Decimal256::percent(max_ltv),
+        liquidation_threshold: Decimal256::percent(liquidation_threshold),
+    };
+    let res = handle(&mut deps, mock_env("addr0000", &[]), create_msg(80, 90));
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = handle(&mut deps, env.clone(), create_msg(80, 70));
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Liquidation threshold cannot be smaller than max_ltv")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = handle(&mut deps, env.clone(), create_msg(80, 90)).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "create_category"),
+            log("category", "luna"),
+            log("max_ltv", "0.8"),
+            log("liquidation_threshold", "0.9"),
+        ]
+    );
+
+    let res = handle(&mut deps, env.clone(), create_msg(80, 90));
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Category already exists"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        &deps,
+        QueryMsg::Category {
+            category: "luna".to_string(),
+        },
+    )
+    .unwrap();
+    let category_res: CategoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        category_res,
+        CategoryResponse {
+            category: "luna".to_string(),
+            max_ltv: Decimal256::percent(80),
+            liquidation_threshold: Decimal256::percent(90),
+        }
+    );
+
+    let assign_msg = |collateral_token: &str, category: Option<&str>| HandleMsg::AssignCategory {
+        collateral_token: HumanAddr::from(collateral_token),
+        category: category.map(|v| v.to_string()),
+    };
+    let res = handle(&mut deps, env.clone(), assign_msg("bluna", Some("eth")));
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Category does not exist"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = handle(&mut deps, env.clone(), assign_msg("bluna", Some("luna"))).unwrap();
+    let _res = handle(&mut deps, env.clone(), assign_msg("stluna", Some("luna"))).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(Decimal256::one(), env.block.time, env.block.time),
+        ),
+        (
+            &("stluna".to_string(), "uusd".to_string()),
+            &(Decimal256::one(), env.block.time, env.block.time),
+        ),
+        (
+            &("bisol".to_string(), "uusd".to_string()),
+            &(Decimal256::one(), env.block.time, env.block.time),
+        ),
+    ]);
+
+    let lock_msg = |collateral_token: &str| HandleMsg::LockCollateral {
+        collaterals: vec![(HumanAddr::from(collateral_token), Uint256::from(10000u64))],
+    };
+    let enter_msg = HandleMsg::EnterCategory {
+        category: "luna".to_string(),
+    };
+    let borrow_limit = |deps: &_| -> BorrowLimitResponse {
+        let res = query(
+            deps,
+            QueryMsg::BorrowLimit {
+                borrower: HumanAddr::from("addr0000"),
+                block_height: None,
+                block_time: None,
+            },
+        )
+        .unwrap();
+        from_binary(&res).unwrap()
+    };
+
+    // borrower cannot enter with collateral outside the category
+    let _res = handle(&mut deps, mock_env("addr0001", &[]), lock_msg("bluna")).unwrap();
+    let _res = handle(&mut deps, mock_env("addr0001", &[]), lock_msg("bisol")).unwrap();
+    let res = handle(&mut deps, mock_env("addr0001", &[]), enter_msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Collateral is not in the category: bisol")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = handle(&mut deps, mock_env("addr0000", &[]), lock_msg("bluna")).unwrap();
+    let _res = handle(&mut deps, mock_env("addr0000", &[]), lock_msg("stluna")).unwrap();
+    assert_eq!(
+        borrow_limit(&deps),
+        BorrowLimitResponse {
+            borrower: HumanAddr::from("addr0000"),
+            borrow_limit: Uint256::from(10000u64),
+            liquidation_limit: Uint256::from(12000u64),
+            category: None,
+            isolated: false,
+        }
+    );
+
+    let res = handle(&mut deps, mock_env("addr0000", &[]), enter_msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "enter_category"),
+            log("borrower", "addr0000"),
+            log("category", "luna"),
+        ]
+    );
+    assert_eq!(
+        borrow_limit(&deps),
+        BorrowLimitResponse {
+            borrower: HumanAddr::from("addr0000"),
+            borrow_limit: Uint256::from(16000u64),
+            liquidation_limit: Uint256::from(18000u64),
+            category: Some("luna".to_string()),
+            isolated: false,
+        }
+    );
+
+    // collateral outside the category cannot be locked after entering
+    let res = handle(&mut deps, mock_env("addr0000", &[]), lock_msg("bisol"));
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Collateral is not in the borrower's category: bisol")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the loan must stay covered by the whitelisted ratios
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(12000u64))]);
+    let res = handle(
+        &mut deps,
+        mock_env("addr0000", &[]),
+        HandleMsg::LeaveCategory {},
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Cannot leave category; Loan liability becomes greater than borrow limit: 10000"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // collaterals held by borrowers in the category cannot leave it
+    let res = handle(&mut deps, env.clone(), assign_msg("stluna", None));
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Collateral is locked by borrowers in its category: stluna"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    assert_eq!(borrow_limit(&deps).borrow_limit, Uint256::from(16000u64));
+
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(8000u64))]);
+    let res = handle(
+        &mut deps,
+        mock_env("addr0000", &[]),
+        HandleMsg::LeaveCategory {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "leave_category"),
+            log("borrower", "addr0000"),
+            log("category", "luna"),
+        ]
+    );
+    assert_eq!(borrow_limit(&deps).borrow_limit, Uint256::from(10000u64));
+
+    // the collateral is free to move once no borrower in the category holds it
+    let _res = handle(&mut deps, env, assign_msg("stluna", None)).unwrap();
+
+    let res = handle(
+        &mut deps,
+        mock_env("addr0000", &[]),
+        HandleMsg::LeaveCategory {},
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Borrower is not in a category")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn deprecate_category_collateral() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let env = mock_env("owner", &[]);
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        oracle_contract: HumanAddr::from("oracle"),
+        market_contract: HumanAddr::from("market"),
+        liquidation_contract: HumanAddr::from("liquidation"),
+        collector_contract: HumanAddr::from("collector"),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+
+    let msg = HandleMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: HumanAddr::from("custody_bluna"),
+        max_ltv: Decimal256::percent(50),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    let msg = HandleMsg::CreateCategory {
+        category: "luna".to_string(),
+        max_ltv: Decimal256::percent(80),
+        liquidation_threshold: Decimal256::percent(90),
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    let msg = HandleMsg::AssignCategory {
+        collateral_token: HumanAddr::from("bluna"),
+        category: Some("luna".to_string()),
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(Decimal256::one(), env.block.time, env.block.time),
+    )]);
+
+    let msg = HandleMsg::LockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(10000u64))],
+    };
+    let _res = handle(&mut deps, mock_env("addr0000", &[]), msg).unwrap();
+    let msg = HandleMsg::EnterCategory {
+        category: "luna".to_string(),
+    };
+    let _res = handle(&mut deps, mock_env("addr0000", &[]), msg).unwrap();
+
+    let msg = HandleMsg::DeprecateCollateral {
+        collateral_token: HumanAddr::from("bluna"),
+        ramp_blocks: 100u64,
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    let borrow_limit = |deps: &_, block_height: u64| -> BorrowLimitResponse {
+        let res = query(
+            deps,
+            QueryMsg::BorrowLimit {
+                borrower: HumanAddr::from("addr0000"),
+                block_height: Some(block_height),
+                block_time: None,
+            },
+        )
+        .unwrap();
+        from_binary(&res).unwrap()
+    };
+
+    // the ramp starts from the category ratios, so nothing drops at once
+    let res = borrow_limit(&deps, env.block.height);
+    assert_eq!(res.borrow_limit, Uint256::from(8000u64));
+    assert_eq!(res.liquidation_limit, Uint256::from(9000u64));
+
+    let res = borrow_limit(&deps, env.block.height + 50);
+    assert_eq!(res.borrow_limit, Uint256::from(4000u64));
+    assert_eq!(res.liquidation_limit, Uint256::from(4500u64));
+}
+
+#[test]
+fn execute_epoch_operations() {
+    let mut deps = mock_dependencies(
+        20,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10000000000u128),
+        }],
+    );
+
+    let mut env = mock_env("owner", &[]);
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        oracle_contract: HumanAddr::from("oracle"),
+        market_contract: HumanAddr::from("market"),
+        liquidation_contract: HumanAddr::from("liquidation"),
+        collector_contract: HumanAddr::from("collector"),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = HandleMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: HumanAddr::from("custody_bluna"),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+
+    let _res = handle(&mut deps, env.clone(), msg);
+
+    let msg = HandleMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: HumanAddr::from("batom"),
+        custody_contract: HumanAddr::from("custody_batom"),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+
+    let _res = handle(&mut deps, env.clone(), msg);
+
+    let msg = HandleMsg::ExecuteEpochOperations {};
+    let res = handle(&mut deps, env.clone(), msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "An epoch has not passed yet; last executed height: 12345"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.height += 86400u64;
+
+    // If deposit_rate is bigger than threshold_deposit_rate
+    deps.querier.with_epoch_state(&[(
+        &HumanAddr::from("market"),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    // (120 / 100 - 1) / 86400
+    // deposit rate = 0.000002314814814814
+    // accrued_buffer = 10,000,000,000
+    // anc_purchase_amount = accrued_buffer * 0.2 = 2,000,000,000
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                to_address: HumanAddr::from("collector"),
+                amount: vec![deduct_tax(
+                    &deps,
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(2_000_000_000u128),
+                    }
+                )
+                .unwrap()],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("custody_batom"),
+                send: vec![],
+                msg: to_binary(&CustodyHandleMsg::DistributeRewards {}).unwrap(),
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("custody_bluna"),
+                send: vec![],
+                msg: to_binary(&CustodyHandleMsg::DistributeRewards {}).unwrap(),
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                send: vec![],
+                msg: to_binary(&HandleMsg::UpdateEpochState {
+                    interest_buffer: Uint256::from(8_000_000_000u128),
+                    distributed_interest: Uint256::zero(),
+                })
+                .unwrap(),
+            })
+        ]
+    );
+
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "epoch_operations"),
+            log("deposit_rate", "0.000002314814814814"),
+            log("exchange_rate", "1.2"),
+            log("aterra_supply", "1000000"),
+            log("distributed_interest", "0"),
+            log("anc_purchase_amount", "2000000000"),
+        ]
+    );
+
+    // store epoch state for test purpose
+    store_epoch_state(
+        &mut deps.storage,
+        &EpochState {
+            last_executed_height: env.block.height,
+            prev_exchange_rate: Decimal256::from_str("1.2").unwrap(),
+            prev_aterra_supply: Uint256::from_str("1000000").unwrap(),
+            prev_interest_buffer: Uint256::from_str("9999000000").unwrap(),
+            deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
+        },
+    )
+    .unwrap();
+
+    // If deposit rate is bigger than threshold
+    deps.querier.with_epoch_state(&[(
+        &HumanAddr::from("market"),
+        &(Uint256::from(1000000u64), Decimal256::percent(125)),
+    )]);
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    env.block.height += 86400u64;
+
+    // accrued_buffer = 1,000,000
+    // interest_buffer = 9,999,000,000
+    // (125 / 120 - 1) / 86400
+    // deposit rate = 0.000000482253086419
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                to_address: HumanAddr::from("collector"),
+                amount: vec![deduct_tax(
+                    &deps,
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(200_000u128),
+                    }
+                )
+                .unwrap()]
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                to_address: HumanAddr::from("market"),
+                amount: vec![deduct_tax(
+                    &deps,
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(53680u128),
                     }
                 )
                 .unwrap()]
@@ -510,14 +1659,265 @@ fn execute_epoch_operations() {
             CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: HumanAddr::from("custody_bluna"),
                 send: vec![],
-                msg: to_binary(&CustodyHandleMsg::DistributeRewards {}).unwrap(),
+                msg: to_binary(&CustodyHandleMsg::DistributeRewards {}).unwrap(),
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                send: vec![],
+                msg: to_binary(&HandleMsg::UpdateEpochState {
+                    interest_buffer: Uint256::from(9999746320u128),
+                    distributed_interest: Uint256::from(53148u128),
+                })
+                .unwrap(),
+            })
+        ]
+    );
+
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "epoch_operations"),
+            log("deposit_rate", "0.000000482253086419"),
+            log("exchange_rate", "1.25"),
+            log("aterra_supply", "1000000"),
+            log("distributed_interest", "53148"),
+            log("anc_purchase_amount", "200000")
+        ]
+    );
+}
+
+#[test]
+fn update_epoch_state() {
+    let mut deps = mock_dependencies(
+        20,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10000000000u128),
+        }],
+    );
+
+    let env = mock_env("owner", &[]);
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        oracle_contract: HumanAddr::from("oracle"),
+        market_contract: HumanAddr::from("market"),
+        liquidation_contract: HumanAddr::from("liquidation"),
+        collector_contract: HumanAddr::from("collector"),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = HandleMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: HumanAddr::from("custody_bluna"),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+
+    let _res = handle(&mut deps, env.clone(), msg);
+
+    let msg = HandleMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: HumanAddr::from("batom"),
+        custody_contract: HumanAddr::from("custody_batom"),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+
+    let _res = handle(&mut deps, env.clone(), msg);
+
+    // only contract itself can execute update_epoch_state
+    let msg = HandleMsg::UpdateEpochState {
+        interest_buffer: Uint256::from(10000000000u128),
+        distributed_interest: Uint256::from(1000000u128),
+    };
+    let res = handle(&mut deps, env.clone(), msg.clone());
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Assume execute epoch operation is executed
+    let mut env = mock_env(MOCK_CONTRACT_ADDR, &[]);
+    env.block.height += 86400u64;
+
+    deps.querier.with_epoch_state(&[(
+        &HumanAddr::from("market"),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("market"),
+            send: vec![],
+            msg: to_binary(&MarketHandleMsg::ExecuteEpochOperations {
+                deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
+                target_deposit_rate: Decimal256::permille(5),
+                threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+                distributed_interest: Uint256::from(1000000u128),
+            })
+            .unwrap(),
+        })]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "update_epoch_state"),
+            log("deposit_rate", "0.000002314814814814"),
+            log("aterra_supply", "1000000"),
+            log("exchange_rate", "1.2"),
+            log("interest_buffer", "10000000000"),
+        ]
+    );
+
+    // Deposit rate increased
+    deps.querier.with_epoch_state(&[(
+        &HumanAddr::from("market"),
+        &(Uint256::from(1000000u64), Decimal256::percent(125)),
+    )]);
+
+    env.block.height += 86400u64;
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("market"),
+            send: vec![],
+            msg: to_binary(&MarketHandleMsg::ExecuteEpochOperations {
+                deposit_rate: Decimal256::from_str("0.000000482253086419").unwrap(),
+                target_deposit_rate: Decimal256::permille(5),
+                threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+                distributed_interest: Uint256::from(1000000u128),
+            })
+            .unwrap(),
+        })]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "update_epoch_state"),
+            log("deposit_rate", "0.000000482253086419"),
+            log("aterra_supply", "1000000"),
+            log("exchange_rate", "1.25"),
+            log("interest_buffer", "10000000000"),
+        ]
+    );
+
+    let epoch_state_response =
+        query_epoch_state(&deps, &HumanAddr::from("market"), env.block.height, None).unwrap();
+    let epoch_state = read_epoch_state(&deps.storage).unwrap();
+
+    // deposit rate = 0.000000482253078703
+    assert_eq!(
+        epoch_state,
+        EpochState {
+            deposit_rate: Decimal256::from_ratio(482253086419u64, 1000000000000000000u64),
+            prev_aterra_supply: epoch_state_response.aterra_supply,
+            prev_exchange_rate: epoch_state_response.exchange_rate,
+            prev_interest_buffer: Uint256::from(10000000000u128),
+            last_executed_height: env.block.height,
+        }
+    )
+}
+
+#[test]
+fn lock_collateral() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let env = mock_env("owner", &[]);
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        oracle_contract: HumanAddr::from("oracle"),
+        market_contract: HumanAddr::from("market"),
+        liquidation_contract: HumanAddr::from("liquidation"),
+        collector_contract: HumanAddr::from("collector"),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = HandleMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: HumanAddr::from("custody_bluna"),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+
+    let _res = handle(&mut deps, env.clone(), msg);
+
+    let msg = HandleMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: HumanAddr::from("batom"),
+        custody_contract: HumanAddr::from("custody_batom"),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+
+    let _res = handle(&mut deps, env.clone(), msg);
+
+    let msg = HandleMsg::LockCollateral {
+        collaterals: vec![
+            (HumanAddr::from("bluna"), Uint256::from(1000000u64)),
+            (HumanAddr::from("batom"), Uint256::from(10000000u64)),
+        ],
+    };
+    let env = mock_env("addr0000", &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("custody_bluna"),
+                send: vec![],
+                msg: to_binary(&CustodyHandleMsg::LockCollateral {
+                    borrower: HumanAddr::from("addr0000"),
+                    amount: Uint256::from(1000000u64),
+                })
+                .unwrap(),
             }),
             CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                contract_addr: HumanAddr::from("custody_batom"),
                 send: vec![],
-                msg: to_binary(&HandleMsg::UpdateEpochState {
-                    interest_buffer: Uint256::from(9999746320u128),
-                    distributed_interest: Uint256::from(53148u128),
+                msg: to_binary(&CustodyHandleMsg::LockCollateral {
+                    borrower: HumanAddr::from("addr0000"),
+                    amount: Uint256::from(10000000u64),
                 })
                 .unwrap(),
             })
@@ -527,25 +1927,57 @@ fn execute_epoch_operations() {
     assert_eq!(
         res.log,
         vec![
-            log("action", "epoch_operations"),
-            log("deposit_rate", "0.000000482253086419"),
-            log("exchange_rate", "1.25"),
-            log("aterra_supply", "1000000"),
-            log("distributed_interest", "53148"),
-            log("anc_purchase_amount", "200000")
+            log("action", "lock_collateral"),
+            log("borrower", "addr0000"),
+            log("collaterals", "1000000bluna,10000000batom"),
         ]
     );
+
+    let res = query(
+        &deps,
+        QueryMsg::Collaterals {
+            borrower: HumanAddr::from("addr0000"),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: HumanAddr::from("addr0000"),
+            collaterals: vec![
+                (HumanAddr::from("batom"), Uint256::from(10000000u64)),
+                (HumanAddr::from("bluna"), Uint256::from(1000000u64)),
+            ]
+        }
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::AllCollaterals {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let all_collaterals_res: AllCollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        all_collaterals_res,
+        AllCollateralsResponse {
+            all_collaterals: vec![CollateralsResponse {
+                borrower: HumanAddr::from("addr0000"),
+                collaterals: vec![
+                    (HumanAddr::from("batom"), Uint256::from(10000000u64)),
+                    (HumanAddr::from("bluna"), Uint256::from(1000000u64)),
+                ]
+            }]
+        }
+    );
 }
 
 #[test]
-fn update_epoch_state() {
-    let mut deps = mock_dependencies(
-        20,
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(10000000000u128),
-        }],
-    );
+fn unlock_collateral() {
+    let mut deps = mock_dependencies(20, &[]);
 
     let env = mock_env("owner", &[]);
     let msg = InitMsg {
@@ -556,7 +1988,7 @@ fn update_epoch_state() {
         collector_contract: HumanAddr::from("collector"),
         stable_denom: "uusd".to_string(),
         epoch_period: 86400u64,
-        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        threshold_deposit_rate: Decimal256::permille(3),
         target_deposit_rate: Decimal256::permille(5),
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
@@ -573,6 +2005,10 @@ fn update_epoch_state() {
         collateral_token: HumanAddr::from("bluna"),
         custody_contract: HumanAddr::from("custody_bluna"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
 
     let _res = handle(&mut deps, env.clone(), msg);
@@ -583,109 +2019,212 @@ fn update_epoch_state() {
         collateral_token: HumanAddr::from("batom"),
         custody_contract: HumanAddr::from("custody_batom"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
 
     let _res = handle(&mut deps, env.clone(), msg);
 
-    // only contract itself can execute update_epoch_state
-    let msg = HandleMsg::UpdateEpochState {
-        interest_buffer: Uint256::from(10000000000u128),
-        distributed_interest: Uint256::from(1000000u128),
+    let msg = HandleMsg::LockCollateral {
+        collaterals: vec![
+            (HumanAddr::from("bluna"), Uint256::from(1000000u64)),
+            (HumanAddr::from("batom"), Uint256::from(10000000u64)),
+        ],
     };
-    let res = handle(&mut deps, env.clone(), msg.clone());
+    let env = mock_env("addr0000", &[]);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    // Failed to unlock more than locked amount
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![
+            (HumanAddr::from("bluna"), Uint256::from(1000001u64)),
+            (HumanAddr::from("batom"), Uint256::from(10000001u64)),
+        ],
+    };
+    let res = handle(&mut deps, env.clone(), msg);
     match res {
-        Err(StdError::Unauthorized { .. }) => {}
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Unlock amount cannot exceed locked amount")
+        }
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // Assume execute epoch operation is executed
-    let mut env = mock_env(MOCK_CONTRACT_ADDR, &[]);
-    env.block.height += 86400u64;
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time,
+                env.block.time,
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time,
+                env.block.time,
+            ),
+        ),
+    ]);
 
-    deps.querier.with_epoch_state(&[(
-        &HumanAddr::from("market"),
-        &(Uint256::from(1000000u64), Decimal256::percent(120)),
-    )]);
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(12600000000u64))]);
 
-    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    // cannot unlock any tokens
+    // Failed to unlock more than locked amount
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::one())],
+    };
+    let res = handle(&mut deps, env.clone(), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(
+                msg,
+                "Unlock amount too high; Loan liability becomes greater than borrow limit: 12599999400"
+            )
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![(HumanAddr::from("batom"), Uint256::one())],
+    };
+    let res = handle(&mut deps, env.clone(), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(
+                msg,
+                "Unlock amount too high; Loan liability becomes greater than borrow limit: 12599998800"
+            )
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(12599999400u64))]);
+    let res = query(
+        &deps,
+        QueryMsg::BorrowLimit {
+            borrower: HumanAddr::from("addr0000"),
+            block_height: None,
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(12600000000u64),);
+
+    // Cannot unlock 2bluna
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(2u64))],
+    };
+    let res = handle(&mut deps, env.clone(), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Unlock amount too high; Loan liability becomes greater than borrow limit: 12599998800")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Can unlock 1bluna
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::one())],
+    };
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
     assert_eq!(
         res.messages,
         vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: HumanAddr::from("market"),
+            contract_addr: HumanAddr::from("custody_bluna"),
             send: vec![],
-            msg: to_binary(&MarketHandleMsg::ExecuteEpochOperations {
-                deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
-                target_deposit_rate: Decimal256::permille(5),
-                threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
-                distributed_interest: Uint256::from(1000000u128),
+            msg: to_binary(&CustodyHandleMsg::UnlockCollateral {
+                borrower: HumanAddr::from("addr0000"),
+                amount: Uint256::one(),
             })
             .unwrap(),
-        })]
+        }),]
     );
+
     assert_eq!(
         res.log,
         vec![
-            log("action", "update_epoch_state"),
-            log("deposit_rate", "0.000002314814814814"),
-            log("aterra_supply", "1000000"),
-            log("exchange_rate", "1.2"),
-            log("interest_buffer", "10000000000"),
+            log("action", "unlock_collateral"),
+            log("borrower", "addr0000"),
+            log("collaterals", "1bluna"),
         ]
     );
 
-    // Deposit rate increased
-    deps.querier.with_epoch_state(&[(
-        &HumanAddr::from("market"),
-        &(Uint256::from(1000000u64), Decimal256::percent(125)),
-    )]);
+    //testing for unlocking more collaterals
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(125999900u128))]);
 
-    env.block.height += 86400u64;
-    let res = handle(&mut deps, env.clone(), msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: HumanAddr::from("market"),
-            send: vec![],
-            msg: to_binary(&MarketHandleMsg::ExecuteEpochOperations {
-                deposit_rate: Decimal256::from_str("0.000000482253086419").unwrap(),
-                target_deposit_rate: Decimal256::permille(5),
-                threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
-                distributed_interest: Uint256::from(1000000u128),
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![
+            (HumanAddr::from("bluna"), Uint256::from(1u128)),
+            (HumanAddr::from("batom"), Uint256::from(1u128)),
+        ],
+    };
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("custody_bluna"),
+                send: vec![],
+                msg: to_binary(&CustodyHandleMsg::UnlockCollateral {
+                    borrower: HumanAddr::from("addr0000"),
+                    amount: Uint256::from(1u128),
+                })
+                .unwrap(),
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("custody_batom"),
+                send: vec![],
+                msg: to_binary(&CustodyHandleMsg::UnlockCollateral {
+                    borrower: HumanAddr::from("addr0000"),
+                    amount: Uint256::from(1u128),
+                })
+                .unwrap(),
             })
-            .unwrap(),
-        })]
+        ]
     );
     assert_eq!(
         res.log,
         vec![
-            log("action", "update_epoch_state"),
-            log("deposit_rate", "0.000000482253086419"),
-            log("aterra_supply", "1000000"),
-            log("exchange_rate", "1.25"),
-            log("interest_buffer", "10000000000"),
+            log("action", "unlock_collateral"),
+            log("borrower", "addr0000"),
+            log("collaterals", "1bluna,1batom"),
         ]
     );
 
-    let epoch_state_response =
-        query_epoch_state(&deps, &HumanAddr::from("market"), env.block.height, None).unwrap();
-    let epoch_state = read_epoch_state(&deps.storage).unwrap();
-
-    // deposit rate = 0.000000482253078703
-    assert_eq!(
-        epoch_state,
-        EpochState {
-            deposit_rate: Decimal256::from_ratio(482253086419u64, 1000000000000000000u64),
-            prev_aterra_supply: epoch_state_response.aterra_supply,
-            prev_exchange_rate: epoch_state_response.exchange_rate,
-            prev_interest_buffer: Uint256::from(10000000000u128),
-            last_executed_height: env.block.height,
+    // halted collateral prices cannot back the loan
+    deps.querier.with_oracle_status(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &PriceStatus::Halted,
+    )]);
+    let msg = HandleMsg::UnlockCollateral {
+        collaterals: vec![(HumanAddr::from("batom"), Uint256::from(1u128))],
+    };
+    let res = handle(&mut deps, env, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Collateral price is not trading: bluna")
         }
-    )
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 }
 
 #[test]
-fn lock_collateral() {
+fn liquidate_collateral() {
     let mut deps = mock_dependencies(20, &[]);
+    deps.querier
+        .with_liquidation_percent(&[(&HumanAddr::from("liquidation"), &Decimal256::percent(1))]);
 
     let env = mock_env("owner", &[]);
     let msg = InitMsg {
@@ -713,6 +2252,10 @@ fn lock_collateral() {
         collateral_token: HumanAddr::from("bluna"),
         custody_contract: HumanAddr::from("custody_bluna"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
 
     let _res = handle(&mut deps, env.clone(), msg);
@@ -723,6 +2266,10 @@ fn lock_collateral() {
         collateral_token: HumanAddr::from("batom"),
         custody_contract: HumanAddr::from("custody_batom"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
 
     let _res = handle(&mut deps, env.clone(), msg);
@@ -734,40 +2281,108 @@ fn lock_collateral() {
         ],
     };
     let env = mock_env("addr0000", &[]);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time,
+                env.block.time,
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time,
+                env.block.time,
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    // liquidation_limit = 1000 * 1000000 * 0.7 + 2000 * 10000000 * 0.7
+    // = 14,700,000,000 uusd
+    let res = query(
+        &deps,
+        QueryMsg::BorrowLimit {
+            borrower: HumanAddr::from("addr0000"),
+            block_height: None,
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrow_limit_res,
+        BorrowLimitResponse {
+            borrower: HumanAddr::from("addr0000"),
+            borrow_limit: Uint256::from(12600000000u64),
+            liquidation_limit: Uint256::from(14700000000u64),
+            category: None,
+            isolated: false,
+        }
+    );
+
+    let msg = HandleMsg::LiquidateCollateral {
+        borrower: HumanAddr::from("addr0000"),
+    };
+    let env = mock_env("addr0001", &[]);
+
+    // loans above the borrow limit are safe until the liquidation limit
+    for loan_amount in &[12600000001u64, 14700000000u64] {
+        deps.querier
+            .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(*loan_amount))]);
+        let res = handle(&mut deps, env.clone(), msg.clone());
+        match res {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "Cannot liquidate safely collateralized loan")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(14700000001u64))]);
     let res = handle(&mut deps, env, msg).unwrap();
     assert_eq!(
         res.messages,
         vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("custody_batom"),
+                send: vec![],
+                msg: to_binary(&CustodyHandleMsg::LiquidateCollateral {
+                    liquidator: HumanAddr::from("addr0001"),
+                    borrower: HumanAddr::from("addr0000"),
+                    amount: Uint256::from(100000u64),
+                })
+                .unwrap(),
+            }),
             CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: HumanAddr::from("custody_bluna"),
                 send: vec![],
-                msg: to_binary(&CustodyHandleMsg::LockCollateral {
+                msg: to_binary(&CustodyHandleMsg::LiquidateCollateral {
+                    liquidator: HumanAddr::from("addr0001"),
                     borrower: HumanAddr::from("addr0000"),
-                    amount: Uint256::from(1000000u64),
+                    amount: Uint256::from(10000u64),
                 })
                 .unwrap(),
             }),
             CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from("custody_batom"),
+                contract_addr: HumanAddr::from("market"),
                 send: vec![],
-                msg: to_binary(&CustodyHandleMsg::LockCollateral {
+                msg: to_binary(&MarketHandleMsg::RepayStableFromLiquidation {
                     borrower: HumanAddr::from("addr0000"),
-                    amount: Uint256::from(10000000u64),
+                    prev_balance: Uint256::zero(),
                 })
                 .unwrap(),
             })
         ]
     );
 
-    assert_eq!(
-        res.log,
-        vec![
-            log("action", "lock_collateral"),
-            log("borrower", "addr0000"),
-            log("collaterals", "1000000bluna,10000000batom"),
-        ]
-    );
-
     let res = query(
         &deps,
         QueryMsg::Collaterals {
@@ -781,38 +2396,38 @@ fn lock_collateral() {
         CollateralsResponse {
             borrower: HumanAddr::from("addr0000"),
             collaterals: vec![
-                (HumanAddr::from("batom"), Uint256::from(10000000u64)),
-                (HumanAddr::from("bluna"), Uint256::from(1000000u64)),
+                (HumanAddr::from("batom"), Uint256::from(9900000u64)),
+                (HumanAddr::from("bluna"), Uint256::from(990000u64)),
             ]
         }
     );
 
+    // liquidated collaterals no longer count as locked
     let res = query(
         &deps,
-        QueryMsg::AllCollaterals {
+        QueryMsg::Whitelist {
+            collateral_token: None,
             start_after: None,
             limit: None,
         },
     )
     .unwrap();
-    let all_collaterals_res: AllCollateralsResponse = from_binary(&res).unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
     assert_eq!(
-        all_collaterals_res,
-        AllCollateralsResponse {
-            all_collaterals: vec![CollateralsResponse {
-                borrower: HumanAddr::from("addr0000"),
-                collaterals: vec![
-                    (HumanAddr::from("batom"), Uint256::from(10000000u64)),
-                    (HumanAddr::from("bluna"), Uint256::from(1000000u64)),
-                ]
-            }]
-        }
+        whitelist_res
+            .elems
+            .iter()
+            .map(|elem| elem.total_locked_amount)
+            .collect::<Vec<Uint256>>(),
+        vec![Uint256::from(9900000u64), Uint256::from(990000u64)]
     );
 }
 
 #[test]
-fn unlock_collateral() {
+fn liquidate_collateral_with_close_factor() {
     let mut deps = mock_dependencies(20, &[]);
+    deps.querier
+        .with_liquidation_percent(&[(&HumanAddr::from("liquidation"), &Decimal256::one())]);
 
     let env = mock_env("owner", &[]);
     let msg = InitMsg {
@@ -833,208 +2448,253 @@ fn unlock_collateral() {
     // we can just call .unwrap() to assert this was a success
     let _res = init(&mut deps, env.clone(), msg).unwrap();
 
-    // store whitelist elems
+    let msg = HandleMsg::UpdateConfig {
+        owner_addr: None,
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        twap_window: None,
+        close_factor: Some(Decimal256::percent(50)),
+        pid_interest_model: None,
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
     let msg = HandleMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
         collateral_token: HumanAddr::from("bluna"),
         custody_contract: HumanAddr::from("custody_bluna"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
-
-    let _res = handle(&mut deps, env.clone(), msg);
-
-    let msg = HandleMsg::Whitelist {
-        name: "batom".to_string(),
-        symbol: "batom".to_string(),
-        collateral_token: HumanAddr::from("batom"),
-        custody_contract: HumanAddr::from("custody_batom"),
-        max_ltv: Decimal256::percent(60),
-    };
-
-    let _res = handle(&mut deps, env.clone(), msg);
-
-    let msg = HandleMsg::LockCollateral {
-        collaterals: vec![
-            (HumanAddr::from("bluna"), Uint256::from(1000000u64)),
-            (HumanAddr::from("batom"), Uint256::from(10000000u64)),
-        ],
-    };
-    let env = mock_env("addr0000", &[]);
     let _res = handle(&mut deps, env.clone(), msg).unwrap();
 
-    // Failed to unlock more than locked amount
-    let msg = HandleMsg::UnlockCollateral {
-        collaterals: vec![
-            (HumanAddr::from("bluna"), Uint256::from(1000001u64)),
-            (HumanAddr::from("batom"), Uint256::from(10000001u64)),
-        ],
+    let msg = HandleMsg::LockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(1000000u64))],
     };
-    let res = handle(&mut deps, env.clone(), msg);
-    match res {
-        Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(msg, "Unlock amount cannot exceed locked amount")
-        }
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    deps.querier.with_oracle_price(&[
-        (
-            &("bluna".to_string(), "uusd".to_string()),
-            &(
-                Decimal256::from_ratio(1000u64, 1u64),
-                env.block.time,
-                env.block.time,
-            ),
-        ),
-        (
-            &("batom".to_string(), "uusd".to_string()),
-            &(
-                Decimal256::from_ratio(2000u64, 1u64),
-                env.block.time,
-                env.block.time,
-            ),
+    let _res = handle(&mut deps, mock_env("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time,
+            env.block.time,
         ),
-    ]);
+    )]);
 
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
+    // liquidation_limit = 1000 * 1000000 * 0.7 = 700,000,000 uusd
     deps.querier
-        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(12600000000u64))]);
-
-    // cannot unlock any tokens
-    // Failed to unlock more than locked amount
-    let msg = HandleMsg::UnlockCollateral {
-        collaterals: vec![(HumanAddr::from("bluna"), Uint256::one())],
-    };
-    let res = handle(&mut deps, env.clone(), msg);
-    match res {
-        Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(
-                msg,
-                "Unlock amount too high; Loan liability becomes greater than borrow limit: 12599999400"
-            )
-        }
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(700000001u64))]);
 
-    let msg = HandleMsg::UnlockCollateral {
-        collaterals: vec![(HumanAddr::from("batom"), Uint256::one())],
+    let msg = HandleMsg::LiquidateCollateral {
+        borrower: HumanAddr::from("addr0000"),
     };
-    let res = handle(&mut deps, env.clone(), msg);
-    match res {
-        Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(
-                msg,
-                "Unlock amount too high; Loan liability becomes greater than borrow limit: 12599998800"
-            )
-        }
+    let liquidated_amount = |res: &HandleResponse| match &res.messages[0] {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_binary(msg).unwrap() {
+            CustodyHandleMsg::LiquidateCollateral { amount, .. } => amount,
+            _ => panic!("DO NOT ENTER HERE"),
+        },
         _ => panic!("DO NOT ENTER HERE"),
-    }
+    };
+
+    // the liquidation contract returns all collaterals, worth 1,000,000,000 uusd;
+    // a single call repays at most 700,000,001 * 0.5 = 350,000,000 uusd
+    let res = handle(&mut deps, mock_env("addr0001", &[]), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(liquidated_amount(&res), Uint256::from(350000u64));
+
+    // the remaining position is still unhealthy, so the next call liquidates again;
+    // the capped amount rounds down
+    let res = handle(&mut deps, mock_env("addr0001", &[]), msg.clone()).unwrap();
+    assert_eq!(liquidated_amount(&res), Uint256::from(349999u64));
 
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
-    deps.querier
-        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(12599999400u64))]);
     let res = query(
         &deps,
-        QueryMsg::BorrowLimit {
+        QueryMsg::Collaterals {
             borrower: HumanAddr::from("addr0000"),
-            block_time: None,
         },
     )
     .unwrap();
-    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
-    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(12600000000u64),);
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res.collaterals,
+        vec![(HumanAddr::from("bluna"), Uint256::from(300001u64))]
+    );
 
-    // Cannot unlock 2bluna
-    let msg = HandleMsg::UnlockCollateral {
-        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(2u64))],
-    };
-    let res = handle(&mut deps, env.clone(), msg);
+    // once the repaid loan is covered again, liquidation stops
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(200000000u64))]);
+    let res = handle(&mut deps, mock_env("addr0001", &[]), msg);
     match res {
         Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(msg, "Unlock amount too high; Loan liability becomes greater than borrow limit: 12599998800")
+            assert_eq!(msg, "Cannot liquidate safely collateralized loan")
         }
         _ => panic!("DO NOT ENTER HERE"),
     }
+}
 
-    // Can unlock 1bluna
-    let msg = HandleMsg::UnlockCollateral {
-        collaterals: vec![(HumanAddr::from("bluna"), Uint256::one())],
+#[test]
+fn liquidate_collaterals() {
+    let mut deps = mock_dependencies(20, &[]);
+    deps.querier
+        .with_liquidation_percent(&[(&HumanAddr::from("liquidation"), &Decimal256::percent(1))]);
+
+    let env = mock_env("owner", &[]);
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        oracle_contract: HumanAddr::from("oracle"),
+        market_contract: HumanAddr::from("market"),
+        liquidation_contract: HumanAddr::from("liquidation"),
+        collector_contract: HumanAddr::from("collector"),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
     };
-    let res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+
+    let msg = HandleMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: HumanAddr::from("custody_bluna"),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    for borrower in &["addr0000", "addr0001"] {
+        let msg = HandleMsg::LockCollateral {
+            collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(1000000u64))],
+        };
+        let _res = handle(&mut deps, mock_env(*borrower, &[]), msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(Decimal256::one(), env.block.time, env.block.time),
+    )]);
+
+    // liquidation_limit = 1000000 * 1 * 0.7 = 700,000 uusd
+    deps.querier.with_loan_amount(&[
+        (&HumanAddr::from("addr0000"), &Uint256::from(700001u64)),
+        (&HumanAddr::from("addr0001"), &Uint256::from(700000u64)),
+    ]);
+
+    let msg = HandleMsg::LiquidateCollaterals {
+        borrowers: vec![
+            HumanAddr::from("addr0000"),
+            HumanAddr::from("addr0001"),
+            HumanAddr::from("addr0000"),
+        ],
+    };
+    let res = handle(&mut deps, mock_env("keeper", &[]), msg).unwrap();
     assert_eq!(
         res.messages,
         vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: HumanAddr::from("custody_bluna"),
+            contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
             send: vec![],
-            msg: to_binary(&CustodyHandleMsg::UnlockCollateral {
+            msg: to_binary(&HandleMsg::LiquidateBorrower {
                 borrower: HumanAddr::from("addr0000"),
-                amount: Uint256::one(),
+                liquidator: HumanAddr::from("keeper"),
             })
             .unwrap(),
-        }),]
+        })]
     );
-
     assert_eq!(
         res.log,
         vec![
-            log("action", "unlock_collateral"),
-            log("borrower", "addr0000"),
-            log("collaterals", "1bluna"),
+            log("action", "liquidate_collaterals"),
+            log("queued", "addr0000"),
+            log("skipped", "addr0001"),
         ]
     );
 
-    //testing for unlocking more collaterals
-    deps.querier
-        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(125999900u128))]);
-
-    let msg = HandleMsg::UnlockCollateral {
-        collaterals: vec![
-            (HumanAddr::from("bluna"), Uint256::from(1u128)),
-            (HumanAddr::from("batom"), Uint256::from(1u128)),
-        ],
+    let msg = HandleMsg::LiquidateBorrower {
+        borrower: HumanAddr::from("addr0000"),
+        liquidator: HumanAddr::from("keeper"),
     };
-    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    let res = handle(&mut deps, mock_env("keeper", &[]), msg.clone());
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the liquidation reward goes to the keeper, not to the overseer
+    let res = handle(&mut deps, mock_env(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
     assert_eq!(
         res.messages,
         vec![
             CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: HumanAddr::from("custody_bluna"),
                 send: vec![],
-                msg: to_binary(&CustodyHandleMsg::UnlockCollateral {
+                msg: to_binary(&CustodyHandleMsg::LiquidateCollateral {
+                    liquidator: HumanAddr::from("keeper"),
                     borrower: HumanAddr::from("addr0000"),
-                    amount: Uint256::from(1u128),
+                    amount: Uint256::from(10000u64),
                 })
                 .unwrap(),
             }),
             CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from("custody_batom"),
+                contract_addr: HumanAddr::from("market"),
                 send: vec![],
-                msg: to_binary(&CustodyHandleMsg::UnlockCollateral {
+                msg: to_binary(&MarketHandleMsg::RepayStableFromLiquidation {
                     borrower: HumanAddr::from("addr0000"),
-                    amount: Uint256::from(1u128),
+                    prev_balance: Uint256::zero(),
                 })
                 .unwrap(),
             })
         ]
     );
+
+    // a borrower whose loan cannot be queried does not abort the batch
+    let msg = HandleMsg::LockCollateral {
+        collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(1000000u64))],
+    };
+    let _res = handle(&mut deps, mock_env("addr0002", &[]), msg).unwrap();
+
+    let msg = HandleMsg::LiquidateCollaterals {
+        borrowers: vec![HumanAddr::from("addr0002"), HumanAddr::from("addr0000")],
+    };
+    let res = handle(&mut deps, mock_env("keeper", &[]), msg).unwrap();
     assert_eq!(
-        res.log,
-        vec![
-            log("action", "unlock_collateral"),
-            log("borrower", "addr0000"),
-            log("collaterals", "1bluna,1batom"),
-        ]
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
+            send: vec![],
+            msg: to_binary(&HandleMsg::LiquidateBorrower {
+                borrower: HumanAddr::from("addr0000"),
+                liquidator: HumanAddr::from("keeper"),
+            })
+            .unwrap(),
+        })]
     );
+    assert_eq!(res.log.len(), 4);
+    assert_eq!(res.log[1], log("failed", "addr0002"));
+    assert_eq!(res.log[2].key, "error");
+    assert_eq!(res.log[3], log("queued", "addr0000"));
 }
 
 #[test]
-fn liquidate_collateral() {
+fn liquidatable_borrowers() {
     let mut deps = mock_dependencies(20, &[]);
-    deps.querier
-        .with_liquidation_percent(&[(&HumanAddr::from("liquidation"), &Decimal256::percent(1))]);
 
     let env = mock_env("owner", &[]);
     let msg = InitMsg {
@@ -1055,16 +2715,148 @@ fn liquidate_collateral() {
     // we can just call .unwrap() to assert this was a success
     let _res = init(&mut deps, env.clone(), msg).unwrap();
 
-    // store whitelist elems
     let msg = HandleMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
         collateral_token: HumanAddr::from("bluna"),
         custody_contract: HumanAddr::from("custody_bluna"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
 
-    let _res = handle(&mut deps, env.clone(), msg);
+    for borrower in &["addr0000", "addr0001", "addr0002"] {
+        let msg = HandleMsg::LockCollateral {
+            collaterals: vec![(HumanAddr::from("bluna"), Uint256::from(1000000u64))],
+        };
+        let _res = handle(&mut deps, mock_env(*borrower, &[]), msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(Decimal256::one(), env.block.time, env.block.time),
+    )]);
+
+    // liquidation_limit = 1000000 * 1 * 0.7 = 700,000 uusd
+    deps.querier.with_loan_amount(&[
+        (&HumanAddr::from("addr0000"), &Uint256::from(750000u64)),
+        (&HumanAddr::from("addr0001"), &Uint256::from(700000u64)),
+        (&HumanAddr::from("addr0002"), &Uint256::from(800000u64)),
+    ]);
+
+    let liquidatable_borrowers = |deps: &_, start_after: Option<&str>, limit: Option<u32>| {
+        let res = query(
+            deps,
+            QueryMsg::LiquidatableBorrowers {
+                start_after: start_after.map(HumanAddr::from),
+                limit,
+                block_height: env.block.height,
+                block_time: None,
+            },
+        )
+        .unwrap();
+        let res: LiquidatableBorrowersResponse = from_binary(&res).unwrap();
+        res
+    };
+
+    assert_eq!(
+        liquidatable_borrowers(&deps, None, None),
+        LiquidatableBorrowersResponse {
+            borrowers: vec![
+                LiquidatableBorrowersResponseElem {
+                    borrower: HumanAddr::from("addr0000"),
+                    loan_amount: Uint256::from(750000u64),
+                    liquidation_limit: Uint256::from(700000u64),
+                    shortfall: Uint256::from(50000u64),
+                },
+                LiquidatableBorrowersResponseElem {
+                    borrower: HumanAddr::from("addr0002"),
+                    loan_amount: Uint256::from(800000u64),
+                    liquidation_limit: Uint256::from(700000u64),
+                    shortfall: Uint256::from(100000u64),
+                },
+            ],
+            failed_borrowers: vec![],
+            last_scanned_borrower: Some(HumanAddr::from("addr0002")),
+        }
+    );
+
+    // a page may hold no liquidatable borrower, but still moves the cursor
+    let res = liquidatable_borrowers(&deps, Some("addr0000"), Some(1));
+    assert_eq!(res.borrowers, vec![]);
+    assert_eq!(res.last_scanned_borrower, Some(HumanAddr::from("addr0001")));
+
+    let res = liquidatable_borrowers(&deps, Some("addr0001"), Some(1));
+    assert_eq!(res.borrowers.len(), 1);
+    assert_eq!(res.borrowers[0].borrower, HumanAddr::from("addr0002"));
+
+    assert_eq!(
+        liquidatable_borrowers(&deps, Some("addr0002"), None),
+        LiquidatableBorrowersResponse {
+            borrowers: vec![],
+            failed_borrowers: vec![],
+            last_scanned_borrower: None,
+        }
+    );
+
+    // a borrower whose loan cannot be queried is reported,
+    // and the cursor still moves past it
+    deps.querier.with_loan_amount(&[
+        (&HumanAddr::from("addr0000"), &Uint256::from(750000u64)),
+        (&HumanAddr::from("addr0002"), &Uint256::from(800000u64)),
+    ]);
+    let res = liquidatable_borrowers(&deps, Some("addr0000"), Some(2));
+    assert_eq!(res.borrowers.len(), 1);
+    assert_eq!(res.borrowers[0].borrower, HumanAddr::from("addr0002"));
+    assert_eq!(res.failed_borrowers, vec![HumanAddr::from("addr0001")]);
+    assert_eq!(res.last_scanned_borrower, Some(HumanAddr::from("addr0002")));
+
+    let res = liquidatable_borrowers(&deps, Some("addr0000"), Some(1));
+    assert_eq!(res.borrowers, vec![]);
+    assert_eq!(res.failed_borrowers, vec![HumanAddr::from("addr0001")]);
+    assert_eq!(res.last_scanned_borrower, Some(HumanAddr::from("addr0001")));
+}
+
+#[test]
+fn borrower_health() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let env = mock_env("owner", &[]);
+    let msg = InitMsg {
+        owner_addr: HumanAddr::from("owner"),
+        oracle_contract: HumanAddr::from("oracle"),
+        market_contract: HumanAddr::from("market"),
+        liquidation_contract: HumanAddr::from("liquidation"),
+        collector_contract: HumanAddr::from("collector"),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = HandleMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: HumanAddr::from("bluna"),
+        custody_contract: HumanAddr::from("custody_bluna"),
+        max_ltv: Decimal256::percent(40),
+        liquidation_threshold: Decimal256::percent(50),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
+    };
+
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
 
     let msg = HandleMsg::Whitelist {
         name: "batom".to_string(),
@@ -1072,14 +2864,18 @@ fn liquidate_collateral() {
         collateral_token: HumanAddr::from("batom"),
         custody_contract: HumanAddr::from("custody_batom"),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(80),
+        max_locked_amount: None,
+        isolated: false,
+        debt_ceiling: None,
     };
 
-    let _res = handle(&mut deps, env.clone(), msg);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
 
     let msg = HandleMsg::LockCollateral {
         collaterals: vec![
-            (HumanAddr::from("bluna"), Uint256::from(1000000u64)),
-            (HumanAddr::from("batom"), Uint256::from(10000000u64)),
+            (HumanAddr::from("bluna"), Uint256::from(1000u64)),
+            (HumanAddr::from("batom"), Uint256::from(1000u64)),
         ],
     };
     let env = mock_env("addr0000", &[]);
@@ -1089,7 +2885,7 @@ fn liquidate_collateral() {
         (
             &("bluna".to_string(), "uusd".to_string()),
             &(
-                Decimal256::from_ratio(1000u64, 1u64),
+                Decimal256::from_uint256(1000u64),
                 env.block.time,
                 env.block.time,
             ),
@@ -1097,84 +2893,77 @@ fn liquidate_collateral() {
         (
             &("batom".to_string(), "uusd".to_string()),
             &(
-                Decimal256::from_ratio(2000u64, 1u64),
+                Decimal256::from_uint256(2000u64),
                 env.block.time,
                 env.block.time,
             ),
         ),
     ]);
 
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
-    deps.querier
-        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(12600000000u64))]);
-
-    let msg = HandleMsg::LiquidateCollateral {
-        borrower: HumanAddr::from("addr0000"),
+    let query_health = |deps: &_| -> BorrowerHealthResponse {
+        let res = query(
+            deps,
+            QueryMsg::BorrowerHealth {
+                borrower: HumanAddr::from("addr0000"),
+                block_height: env.block.height,
+                block_time: None,
+            },
+        )
+        .unwrap();
+        from_binary(&res).unwrap()
     };
-    let env = mock_env("addr0001", &[]);
-    let res = handle(&mut deps, env.clone(), msg.clone());
-    match res {
-        Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(msg, "Cannot liquidate safely collateralized loan")
-        }
-        _ => panic!("DO NOT ENTER HERE"),
-    }
 
+    // borrow_limit = 1000 * 1000 * 0.4 + 1000 * 2000 * 0.6 = 1,600,000
+    // liquidation_limit = 1000 * 1000 * 0.5 + 1000 * 2000 * 0.8 = 2,100,000
     deps.querier
-        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(12600000001u64))]);
-    let res = handle(&mut deps, env, msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from("custody_batom"),
-                send: vec![],
-                msg: to_binary(&CustodyHandleMsg::LiquidateCollateral {
-                    liquidator: HumanAddr::from("addr0001"),
-                    borrower: HumanAddr::from("addr0000"),
-                    amount: Uint256::from(100000u64),
-                })
-                .unwrap(),
-            }),
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from("custody_bluna"),
-                send: vec![],
-                msg: to_binary(&CustodyHandleMsg::LiquidateCollateral {
-                    liquidator: HumanAddr::from("addr0001"),
-                    borrower: HumanAddr::from("addr0000"),
-                    amount: Uint256::from(10000u64),
-                })
-                .unwrap(),
-            }),
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from("market"),
-                send: vec![],
-                msg: to_binary(&MarketHandleMsg::RepayStableFromLiquidation {
-                    borrower: HumanAddr::from("addr0000"),
-                    prev_balance: Uint256::zero(),
-                })
-                .unwrap(),
-            })
-        ]
-    );
-
-    let res = query(
-        &deps,
-        QueryMsg::Collaterals {
-            borrower: HumanAddr::from("addr0000"),
-        },
-    )
-    .unwrap();
-    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::zero())]);
+    let health_res = query_health(&deps);
+    assert_eq!(health_res.borrow_limit, Uint256::from(1600000u64));
+    assert_eq!(health_res.liquidation_limit, Uint256::from(2100000u64));
+    assert_eq!(health_res.health_factor, None);
+    assert_eq!(health_res.borrowable_amount, Uint256::from(1600000u64));
+    assert!(health_res
+        .collaterals
+        .iter()
+        .all(|c| c.liquidation_price.is_none()));
+
+    // batom alone covers the loan, so bluna has no liquidation price;
+    // batom: 500,000 + 1000 * price * 0.8 = 1,500,000
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(1500000u64))]);
     assert_eq!(
-        collaterals_res,
-        CollateralsResponse {
+        query_health(&deps),
+        BorrowerHealthResponse {
             borrower: HumanAddr::from("addr0000"),
+            loan_amount: Uint256::from(1500000u64),
+            borrow_limit: Uint256::from(1600000u64),
+            liquidation_limit: Uint256::from(2100000u64),
+            health_factor: Some(Decimal256::from_str("1.4").unwrap()),
+            borrowable_amount: Uint256::from(100000u64),
             collaterals: vec![
-                (HumanAddr::from("batom"), Uint256::from(9900000u64)),
-                (HumanAddr::from("bluna"), Uint256::from(990000u64)),
-            ]
+                BorrowerHealthResponseElem {
+                    collateral_token: HumanAddr::from("batom"),
+                    amount: Uint256::from(1000u64),
+                    price: Decimal256::from_uint256(2000u64),
+                    liquidation_price: Some(Decimal256::from_uint256(1250u64)),
+                },
+                BorrowerHealthResponseElem {
+                    collateral_token: HumanAddr::from("bluna"),
+                    amount: Uint256::from(1000u64),
+                    price: Decimal256::from_uint256(1000u64),
+                    liquidation_price: None,
+                },
+            ],
         }
     );
+
+    // at the liquidation limit every collateral sits at its liquidation price
+    deps.querier
+        .with_loan_amount(&[(&HumanAddr::from("addr0000"), &Uint256::from(2100000u64))]);
+    let health_res = query_health(&deps);
+    assert_eq!(health_res.health_factor, Some(Decimal256::one()));
+    assert_eq!(health_res.borrowable_amount, Uint256::zero());
+    for collateral in health_res.collaterals {
+        assert_eq!(collateral.liquidation_price, Some(collateral.price));
+    }
 }
@@ -0,0 +1,545 @@
+use crate::querier::query_market_situation;
+use crate::state::{read_config, read_state, store_config, store_state, Config, State};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    log, to_binary, Api, Binary, Env, Extern, HandleResponse, HandleResult, HumanAddr,
+    InitResponse, Querier, StdError, StdResult, Storage,
+};
+use moneymarket::pid_interest_model::{
+    BorrowRateResponse, ConfigResponse, HandleMsg, InitMsg, QueryMsg, StateResponse,
+};
+use moneymarket_math::interest_model::{
+    clamp_rate, compute_pid_borrow_rate, compute_utilization_ratio,
+};
+
+pub fn init<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: InitMsg,
+) -> StdResult<InitResponse> {
+    assert_target_utilization(msg.target_utilization)?;
+    assert_rate_bounds(msg.min_rate, msg.max_rate)?;
+    if msg.initial_rate < msg.min_rate || msg.initial_rate > msg.max_rate {
+        return Err(StdError::generic_err(
+            "initial_rate must be between min_rate and max_rate",
+        ));
+    }
+
+    store_config(
+        &mut deps.storage,
+        &Config {
+            owner: deps.api.canonical_address(&msg.owner)?,
+            observer: deps.api.canonical_address(&msg.observer)?,
+            market: deps.api.canonical_address(&msg.market)?,
+            target_utilization: msg.target_utilization,
+            proportional_gain: msg.proportional_gain,
+            integral_gain: msg.integral_gain,
+            min_rate: msg.min_rate,
+            max_rate: msg.max_rate,
+            epoch_period: msg.epoch_period,
+        },
+    )?;
+
+    store_state(
+        &mut deps.storage,
+        &State {
+            borrow_rate: msg.initial_rate,
+            integral: msg.initial_rate,
+            last_observed_height: env.block.height,
+        },
+    )?;
+
+    Ok(InitResponse::default())
+}
+
+pub fn handle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: HandleMsg,
+) -> HandleResult {
+    match msg {
+        HandleMsg::UpdateConfig {
+            owner,
+            observer,
+            market,
+            target_utilization,
+            proportional_gain,
+            integral_gain,
+            min_rate,
+            max_rate,
+            epoch_period,
+        } => update_config(
+            deps,
+            env,
+            owner,
+            observer,
+            market,
+            target_utilization,
+            proportional_gain,
+            integral_gain,
+            min_rate,
+            max_rate,
+            epoch_period,
+        ),
+        HandleMsg::Observe {} => observe(deps, env),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_config<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    owner: Option<HumanAddr>,
+    observer: Option<HumanAddr>,
+    market: Option<HumanAddr>,
+    target_utilization: Option<Decimal256>,
+    proportional_gain: Option<Decimal256>,
+    integral_gain: Option<Decimal256>,
+    min_rate: Option<Decimal256>,
+    max_rate: Option<Decimal256>,
+    epoch_period: Option<u64>,
+) -> HandleResult {
+    let mut config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.canonical_address(&owner)?;
+    }
+
+    if let Some(observer) = observer {
+        config.observer = deps.api.canonical_address(&observer)?;
+    }
+
+    if let Some(market) = market {
+        config.market = deps.api.canonical_address(&market)?;
+    }
+
+    if let Some(target_utilization) = target_utilization {
+        assert_target_utilization(target_utilization)?;
+        config.target_utilization = target_utilization;
+    }
+
+    if let Some(proportional_gain) = proportional_gain {
+        config.proportional_gain = proportional_gain;
+    }
+
+    if let Some(integral_gain) = integral_gain {
+        config.integral_gain = integral_gain;
+    }
+
+    if let Some(min_rate) = min_rate {
+        config.min_rate = min_rate;
+    }
+
+    if let Some(max_rate) = max_rate {
+        config.max_rate = max_rate;
+    }
+
+    if let Some(epoch_period) = epoch_period {
+        config.epoch_period = epoch_period;
+    }
+
+    assert_rate_bounds(config.min_rate, config.max_rate)?;
+
+    // keep the controller within the new bounds
+    let mut state: State = read_state(&deps.storage)?;
+    state.borrow_rate = clamp_rate(state.borrow_rate, config.min_rate, config.max_rate);
+    state.integral = clamp_rate(state.integral, config.min_rate, config.max_rate);
+
+    store_config(&mut deps.storage, &config)?;
+    store_state(&mut deps.storage, &state)?;
+    Ok(HandleResponse::default())
+}
+
+pub fn observe<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.observer {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut state: State = read_state(&deps.storage)?;
+    if env.block.height < state.last_observed_height + config.epoch_period {
+        return Err(StdError::generic_err(format!(
+            "An epoch has not passed yet; last observed height: {}",
+            state.last_observed_height
+        )));
+    }
+
+    let (market_balance, total_liabilities, total_reserves) =
+        query_market_situation(deps, &deps.api.human_address(&config.market)?)?;
+    let (borrow_rate, integral) = compute_pid_borrow_rate(
+        config.target_utilization,
+        config.proportional_gain,
+        config.integral_gain,
+        config.min_rate,
+        config.max_rate,
+        state.integral,
+        market_balance,
+        total_liabilities,
+        total_reserves,
+    );
+
+    state.borrow_rate = borrow_rate;
+    state.integral = integral;
+    state.last_observed_height = env.block.height;
+    store_state(&mut deps.storage, &state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "observe"),
+            log(
+                "utilization_ratio",
+                compute_utilization_ratio(market_balance, total_liabilities, total_reserves),
+            ),
+            log("borrow_rate", borrow_rate),
+        ],
+        data: None,
+    })
+}
+
+fn assert_target_utilization(target_utilization: Decimal256) -> StdResult<()> {
+    if target_utilization > Decimal256::one() {
+        return Err(StdError::generic_err(
+            "target_utilization cannot be bigger than one",
+        ));
+    }
+
+    Ok(())
+}
+
+fn assert_rate_bounds(min_rate: Decimal256, max_rate: Decimal256) -> StdResult<()> {
+    if min_rate > max_rate {
+        return Err(StdError::generic_err(
+            "min_rate cannot be bigger than max_rate",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    msg: QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::BorrowRate { .. } => to_binary(&query_borrow_rate(deps)?),
+    }
+}
+
+fn query_config<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ConfigResponse> {
+    let config = read_config(&deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.human_address(&config.owner)?,
+        observer: deps.api.human_address(&config.observer)?,
+        market: deps.api.human_address(&config.market)?,
+        target_utilization: config.target_utilization,
+        proportional_gain: config.proportional_gain,
+        integral_gain: config.integral_gain,
+        min_rate: config.min_rate,
+        max_rate: config.max_rate,
+        epoch_period: config.epoch_period,
+    };
+
+    Ok(resp)
+}
+
+fn query_state<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<StateResponse> {
+    let state = read_state(&deps.storage)?;
+    let resp = StateResponse {
+        borrow_rate: state.borrow_rate,
+        integral: state.integral,
+        last_observed_height: state.last_observed_height,
+    };
+
+    Ok(resp)
+}
+
+fn query_borrow_rate<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<BorrowRateResponse> {
+    let state: State = read_state(&deps.storage)?;
+
+    Ok(BorrowRateResponse {
+        rate: state.borrow_rate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_querier::mock_dependencies;
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::StdError;
+
+    fn mock_init_msg() -> InitMsg {
+        InitMsg {
+            owner: HumanAddr("owner0000".to_string()),
+            observer: HumanAddr("overseer0000".to_string()),
+            market: HumanAddr("market0000".to_string()),
+            target_utilization: Decimal256::percent(80),
+            proportional_gain: Decimal256::percent(50),
+            integral_gain: Decimal256::percent(10),
+            min_rate: Decimal256::percent(1),
+            max_rate: Decimal256::percent(20),
+            initial_rate: Decimal256::percent(10),
+            epoch_period: 100,
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let env = mock_env("addr0000", &[]);
+
+        // we can just call .unwrap() to assert this was a success
+        let res = init(&mut deps, env.clone(), mock_init_msg()).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let value = query_config(&deps).unwrap();
+        assert_eq!("owner0000", value.owner.as_str());
+        assert_eq!("overseer0000", value.observer.as_str());
+        assert_eq!("market0000", value.market.as_str());
+        assert_eq!("0.8", &value.target_utilization.to_string());
+        assert_eq!("0.5", &value.proportional_gain.to_string());
+        assert_eq!("0.1", &value.integral_gain.to_string());
+        assert_eq!("0.01", &value.min_rate.to_string());
+        assert_eq!("0.2", &value.max_rate.to_string());
+        assert_eq!(100, value.epoch_period);
+
+        let value = query_state(&deps).unwrap();
+        assert_eq!(
+            value,
+            StateResponse {
+                borrow_rate: Decimal256::percent(10),
+                integral: Decimal256::percent(10),
+                last_observed_height: env.block.height,
+            }
+        );
+
+        let value = query_borrow_rate(&deps).unwrap();
+        assert_eq!("0.1", &value.rate.to_string());
+
+        // initial rate out of bounds
+        let msg = InitMsg {
+            initial_rate: Decimal256::percent(30),
+            ..mock_init_msg()
+        };
+        let env = mock_env("addr0000", &[]);
+        match init(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "initial_rate must be between min_rate and max_rate")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // inverted bounds
+        let msg = InitMsg {
+            min_rate: Decimal256::percent(30),
+            ..mock_init_msg()
+        };
+        let env = mock_env("addr0000", &[]);
+        match init(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "min_rate cannot be bigger than max_rate")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    #[test]
+    fn observe() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, mock_init_msg()).unwrap();
+
+        deps.querier.with_market(
+            &HumanAddr::from("market0000"),
+            1000000u128,
+            Decimal256::from_uint256(9000000u64),
+            Decimal256::zero(),
+        );
+
+        // only the observer can trigger observations
+        let mut env = mock_env("addr0000", &[]);
+        env.block.height += 100;
+        let msg = HandleMsg::Observe {};
+        match handle(&mut deps, env, msg.clone()) {
+            Err(StdError::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // epoch not passed
+        let mut env = mock_env("overseer0000", &[]);
+        env.block.height += 99;
+        match handle(&mut deps, env, msg.clone()) {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+                msg,
+                "An epoch has not passed yet; last observed height: 12345"
+            ),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // utilization_ratio = 0.9, above the target
+        // integral = 0.1 + 0.1 * 0.1
+        // borrow_rate = 0.11 + 0.1 * 0.5
+        let mut env = mock_env("overseer0000", &[]);
+        env.block.height += 100;
+        let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.log,
+            vec![
+                log("action", "observe"),
+                log("utilization_ratio", "0.9"),
+                log("borrow_rate", "0.16"),
+            ]
+        );
+        assert_eq!(
+            query_state(&deps).unwrap(),
+            StateResponse {
+                borrow_rate: Decimal256::percent(16),
+                integral: Decimal256::percent(11),
+                last_observed_height: env.block.height,
+            }
+        );
+
+        // the market situation given to the query is ignored
+        let value = query_borrow_rate(&deps).unwrap();
+        assert_eq!("0.16", &value.rate.to_string());
+
+        // utilization_ratio = 0.2, below the target
+        // integral = 0.11 - 0.6 * 0.1
+        // borrow_rate = 0.05 - 0.6 * 0.5, floored at min_rate
+        env.block.height += 100;
+        deps.querier.with_market(
+            &HumanAddr::from("market0000"),
+            4000000u128,
+            Decimal256::from_uint256(1000000u64),
+            Decimal256::zero(),
+        );
+        let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        assert_eq!(
+            query_state(&deps).unwrap(),
+            StateResponse {
+                borrow_rate: Decimal256::percent(1),
+                integral: Decimal256::percent(5),
+                last_observed_height: env.block.height,
+            }
+        );
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let env = mock_env("addr0000", &[]);
+        let _res = init(&mut deps, env, mock_init_msg()).unwrap();
+
+        // update owner, observer, market and bounds
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: Some(HumanAddr("owner0001".to_string())),
+            observer: Some(HumanAddr("overseer0001".to_string())),
+            market: Some(HumanAddr("market0001".to_string())),
+            target_utilization: Some(Decimal256::percent(70)),
+            proportional_gain: None,
+            integral_gain: None,
+            min_rate: Some(Decimal256::percent(12)),
+            max_rate: None,
+            epoch_period: Some(50),
+        };
+
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let value = query_config(&deps).unwrap();
+        assert_eq!("owner0001", value.owner.as_str());
+        assert_eq!("overseer0001", value.observer.as_str());
+        assert_eq!("market0001", value.market.as_str());
+        assert_eq!("0.7", &value.target_utilization.to_string());
+        assert_eq!("0.5", &value.proportional_gain.to_string());
+        assert_eq!("0.1", &value.integral_gain.to_string());
+        assert_eq!("0.12", &value.min_rate.to_string());
+        assert_eq!("0.2", &value.max_rate.to_string());
+        assert_eq!(50, value.epoch_period);
+
+        // the controller is moved within the new bounds
+        let value = query_state(&deps).unwrap();
+        assert_eq!("0.12", &value.borrow_rate.to_string());
+        assert_eq!("0.12", &value.integral.to_string());
+
+        // inverted bounds
+        let env = mock_env("owner0001", &[]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: None,
+            observer: None,
+            market: None,
+            target_utilization: None,
+            proportional_gain: None,
+            integral_gain: None,
+            min_rate: None,
+            max_rate: Some(Decimal256::percent(10)),
+            epoch_period: None,
+        };
+        match handle(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "min_rate cannot be bigger than max_rate")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // invalid target
+        let env = mock_env("owner0001", &[]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: None,
+            observer: None,
+            market: None,
+            target_utilization: Some(Decimal256::percent(101)),
+            proportional_gain: None,
+            integral_gain: None,
+            min_rate: None,
+            max_rate: None,
+            epoch_period: None,
+        };
+        match handle(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "target_utilization cannot be bigger than one")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        // Unauthorized err
+        let env = mock_env("owner0000", &[]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: None,
+            observer: None,
+            market: None,
+            target_utilization: None,
+            proportional_gain: Some(Decimal256::percent(1)),
+            integral_gain: None,
+            min_rate: None,
+            max_rate: None,
+            epoch_period: None,
+        };
+
+        let res = handle(&mut deps, env, msg);
+        match res {
+            Err(StdError::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+pub mod contract;
+pub mod querier;
+pub mod state;
+
+#[cfg(test)]
+mod testing;
+
+#[cfg(all(target_arch = "wasm32", not(feature = "library")))]
+cosmwasm_std::create_entry_points!(contract);
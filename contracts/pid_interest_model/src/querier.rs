@@ -0,0 +1,32 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    to_binary, Api, Extern, HumanAddr, Querier, QueryRequest, StdResult, Storage, WasmQuery,
+};
+
+use moneymarket::market::{ConfigResponse, QueryMsg as MarketQueryMsg, StateResponse};
+use moneymarket::querier::query_balance;
+
+/// Stable balance, total liabilities and total reserves of the market
+pub fn query_market_situation<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    market_addr: &HumanAddr,
+) -> StdResult<(Uint256, Decimal256, Decimal256)> {
+    let config: ConfigResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: HumanAddr::from(market_addr),
+        msg: to_binary(&MarketQueryMsg::Config {})?,
+    }))?;
+
+    // the stored state; accruing interest here would query this
+    // contract's borrow rate while it is being updated
+    let state: StateResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: HumanAddr::from(market_addr),
+        msg: to_binary(&MarketQueryMsg::State { block_height: None })?,
+    }))?;
+
+    let market_balance = query_balance(deps, market_addr, config.stable_denom)?;
+    Ok((
+        market_balance,
+        state.total_liabilities,
+        state.total_reserves,
+    ))
+}
@@ -0,0 +1,45 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read};
+
+static KEY_CONFIG: &[u8] = b"config";
+static KEY_STATE: &[u8] = b"state";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub observer: CanonicalAddr,
+    pub market: CanonicalAddr,
+    pub target_utilization: Decimal256,
+    pub proportional_gain: Decimal256,
+    pub integral_gain: Decimal256,
+    pub min_rate: Decimal256,
+    pub max_rate: Decimal256,
+    pub epoch_period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub borrow_rate: Decimal256,
+    pub integral: Decimal256,
+    pub last_observed_height: u64,
+}
+
+pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+pub fn store_state<S: Storage>(storage: &mut S, state: &State) -> StdResult<()> {
+    singleton(storage, KEY_STATE).save(state)
+}
+
+pub fn read_state<S: Storage>(storage: &S) -> StdResult<State> {
+    singleton_read(storage, KEY_STATE).load()
+}
@@ -0,0 +1,113 @@
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, Empty, Extern, HumanAddr, Querier, QuerierResult,
+    QueryRequest, SystemError, Uint128, WasmQuery,
+};
+
+use moneymarket::market::{ConfigResponse, QueryMsg as MarketQueryMsg, StateResponse};
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    canonical_length: usize,
+    contract_balance: &[Coin],
+) -> Extern<MockStorage, MockApi, WasmMockQuerier> {
+    let contract_addr = HumanAddr::from(MOCK_CONTRACT_ADDR);
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(&contract_addr, contract_balance)]));
+
+    Extern {
+        storage: MockStorage::default(),
+        api: MockApi::new(canonical_length),
+        querier: custom_querier,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    MarketQueryMsg::Config {} => Ok(to_binary(&ConfigResponse {
+                        owner_addr: HumanAddr::from("owner0000"),
+                        aterra_contract: HumanAddr::from("aterra0000"),
+                        interest_model: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                        distribution_model: HumanAddr::from("distribution0000"),
+                        overseer_contract: HumanAddr::from("overseer0000"),
+                        collector_contract: HumanAddr::from("collector0000"),
+                        distributor_contract: HumanAddr::from("distributor0000"),
+                        stable_denom: "uusd".to_string(),
+                        max_borrow_factor: Decimal256::one(),
+                    })),
+                    MarketQueryMsg::State { block_height: None } => Ok(to_binary(&StateResponse {
+                        total_liabilities: self.total_liabilities,
+                        total_reserves: self.total_reserves,
+                        last_interest_updated: 0,
+                        last_reward_updated: 0,
+                        global_interest_index: Decimal256::one(),
+                        global_reward_index: Decimal256::zero(),
+                        anc_emission_rate: Decimal256::zero(),
+                        prev_aterra_supply: Default::default(),
+                        prev_exchange_rate: Decimal256::one(),
+                    })),
+                    _ => Err(SystemError::InvalidRequest {
+                        error: format!("Unexpected query to {}", contract_addr),
+                        request: msg.as_slice().into(),
+                    }),
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+        }
+    }
+
+    // configure the market situation
+    pub fn with_market(
+        &mut self,
+        market_addr: &HumanAddr,
+        market_balance: u128,
+        total_liabilities: Decimal256,
+        total_reserves: Decimal256,
+    ) {
+        self.base.update_balance(
+            market_addr,
+            vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(market_balance),
+            }],
+        );
+        self.total_liabilities = total_liabilities;
+        self.total_reserves = total_reserves;
+    }
+}
@@ -0,0 +1,118 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+
+/// Linear borrow rate;
+/// borrow_rate = utilization_ratio * interest_multiplier + base_rate
+pub fn compute_borrow_rate(
+    base_rate: Decimal256,
+    interest_multiplier: Decimal256,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> Decimal256 {
+    compute_utilization_ratio(market_balance, total_liabilities, total_reserves)
+        * interest_multiplier
+        + base_rate
+}
+
+/// Jump rate borrow rate; linear up to `optimal_utilization`, then grows
+/// with the steeper `jump_multiplier` on the utilization above the kink
+pub fn compute_kinked_borrow_rate(
+    base_rate: Decimal256,
+    interest_multiplier: Decimal256,
+    optimal_utilization: Decimal256,
+    jump_multiplier: Decimal256,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> Decimal256 {
+    let utilization_ratio =
+        compute_utilization_ratio(market_balance, total_liabilities, total_reserves);
+    if utilization_ratio <= optimal_utilization {
+        return utilization_ratio * interest_multiplier + base_rate;
+    }
+
+    optimal_utilization * interest_multiplier
+        + (utilization_ratio - optimal_utilization) * jump_multiplier
+        + base_rate
+}
+
+/// One step of a PI controller steering utilization toward
+/// `target_utilization`; returns the new `(borrow_rate, integral)`
+///
+/// error = utilization_ratio - target_utilization
+/// integral = clamp(integral + error * integral_gain)
+/// borrow_rate = clamp(integral + error * proportional_gain)
+///
+/// The integral is kept within the rate bounds, so it does not wind up
+/// while the borrow rate is saturated.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_pid_borrow_rate(
+    target_utilization: Decimal256,
+    proportional_gain: Decimal256,
+    integral_gain: Decimal256,
+    min_rate: Decimal256,
+    max_rate: Decimal256,
+    integral: Decimal256,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> (Decimal256, Decimal256) {
+    let utilization_ratio =
+        compute_utilization_ratio(market_balance, total_liabilities, total_reserves);
+    let (error, over_target) = if utilization_ratio >= target_utilization {
+        (utilization_ratio - target_utilization, true)
+    } else {
+        (target_utilization - utilization_ratio, false)
+    };
+
+    let integral = clamp_rate(
+        apply_error(integral, error * integral_gain, over_target),
+        min_rate,
+        max_rate,
+    );
+    let borrow_rate = clamp_rate(
+        apply_error(integral, error * proportional_gain, over_target),
+        min_rate,
+        max_rate,
+    );
+
+    (borrow_rate, integral)
+}
+
+/// Keeps `rate` within `[min_rate, max_rate]`
+pub fn clamp_rate(rate: Decimal256, min_rate: Decimal256, max_rate: Decimal256) -> Decimal256 {
+    if rate < min_rate {
+        min_rate
+    } else if rate > max_rate {
+        max_rate
+    } else {
+        rate
+    }
+}
+
+fn apply_error(value: Decimal256, delta: Decimal256, increase: bool) -> Decimal256 {
+    if increase {
+        value + delta
+    } else if value > delta {
+        value - delta
+    } else {
+        Decimal256::zero()
+    }
+}
+
+/// utilization_ratio = total_liabilities / (balance + total_liabilities - total_reserves)
+pub fn compute_utilization_ratio(
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> Decimal256 {
+    // ignore decimal parts
+    let total_value_in_market =
+        Decimal256::from_uint256(market_balance) + total_liabilities - total_reserves;
+
+    if total_value_in_market.is_zero() {
+        Decimal256::zero()
+    } else {
+        total_liabilities / total_value_in_market
+    }
+}
@@ -0,0 +1,14 @@
+//! Pure money market math shared by the CosmWasm contracts and the Solana
+//! program, so both chains compute interest, exchange rates, liquidation
+//! amounts and aggregated oracle prices the same way. Nothing here touches
+//! storage, queriers or accounts.
+#![no_std]
+
+pub mod interest_model;
+pub mod liquidation;
+pub mod market;
+pub mod oracle;
+pub mod overseer;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,50 @@
+use core::cmp::min;
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+
+/// Portion of a liquidated collateral value a bidder ends up repaying
+pub fn compute_fee_deductor(
+    max_premium_rate: Decimal256,
+    bid_fee: Decimal256,
+    tax_rate: Decimal256,
+) -> Decimal256 {
+    (Decimal256::one() - max_premium_rate)
+        * (Decimal256::one() - bid_fee)
+        * (Decimal256::one() - tax_rate)
+}
+
+/// Ratio of every collateral to liquidate; zero when the loan is safely
+/// collateralized and one when all collaterals have to go
+pub fn compute_liquidation_ratio(
+    borrow_amount: Uint256,
+    borrow_limit: Uint256,
+    collaterals_value: Uint256,
+    fee_deductor: Decimal256,
+    safe_ratio: Decimal256,
+    liquidation_threshold: Uint256,
+) -> Decimal256 {
+    // Safely collateralized check
+    if borrow_amount <= borrow_limit {
+        return Decimal256::zero();
+    }
+
+    // expected_repay_amount must be bigger than borrow_amount
+    // else force liquidate all collaterals
+    let expected_repay_amount = collaterals_value * fee_deductor;
+    if expected_repay_amount <= borrow_amount {
+        return Decimal256::one();
+    }
+
+    // When collaterals_value is smaller than liquidation_threshold,
+    // liquidate all collaterals
+    let safe_borrow_amount = borrow_limit * safe_ratio;
+    let liquidation_ratio = if collaterals_value < liquidation_threshold {
+        Decimal256::from_uint256(borrow_amount) / Decimal256::from_uint256(expected_repay_amount)
+    } else {
+        Decimal256::from_uint256(borrow_amount - safe_borrow_amount)
+            / Decimal256::from_uint256(expected_repay_amount - safe_borrow_amount)
+    };
+
+    // Cap the liquidation_ratio to 1
+    min(Decimal256::one(), liquidation_ratio)
+}
@@ -0,0 +1,119 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+
+/// Interest and reward related part of the market state
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketState {
+    pub total_liabilities: Decimal256,
+    pub total_reserves: Decimal256,
+    pub last_interest_updated: u64,
+    pub last_reward_updated: u64,
+    pub global_interest_index: Decimal256,
+    pub global_reward_index: Decimal256,
+    pub anc_emission_rate: Decimal256,
+    pub prev_aterra_supply: Uint256,
+    pub prev_exchange_rate: Decimal256,
+}
+
+/// Interest and reward related part of a borrower liability
+#[derive(Clone, Debug, PartialEq)]
+pub struct Liability {
+    pub interest_index: Decimal256,
+    pub reward_index: Decimal256,
+    pub loan_amount: Uint256,
+    pub pending_rewards: Decimal256,
+}
+
+pub fn compute_exchange_rate(
+    state: &MarketState,
+    aterra_supply: Uint256,
+    contract_balance: Uint256,
+) -> Decimal256 {
+    if aterra_supply.is_zero() {
+        return Decimal256::one();
+    }
+
+    // (aterra / stable_denom)
+    // exchange_rate = (balance + total_liabilities - total_reserves) / aterra_supply
+    (Decimal256::from_uint256(contract_balance) + state.total_liabilities - state.total_reserves)
+        / Decimal256::from_uint256(aterra_supply)
+}
+
+// CONTRACT: to use this function as state update purpose,
+// executor must update following three state after execution
+// * state.prev_aterra_supply
+// * state.prev_exchange_rate
+// * state.last_interest_updated
+pub fn compute_interest(
+    state: &mut MarketState,
+    block_height: u64,
+    balance: Uint256,
+    aterra_supply: Uint256,
+    borrow_rate: Decimal256,
+    target_deposit_rate: Decimal256,
+) {
+    if state.last_interest_updated >= block_height {
+        return;
+    }
+
+    let passed_blocks = Decimal256::from_uint256(block_height - state.last_interest_updated);
+
+    let interest_factor = passed_blocks * borrow_rate;
+    let interest_accrued = state.total_liabilities * interest_factor;
+
+    state.global_interest_index =
+        state.global_interest_index * (Decimal256::one() + interest_factor);
+    state.total_liabilities += interest_accrued;
+
+    let mut exchange_rate = compute_exchange_rate(state, aterra_supply, balance);
+    let effective_deposit_rate = exchange_rate / state.prev_exchange_rate;
+    let deposit_rate = (effective_deposit_rate - Decimal256::one()) / passed_blocks;
+
+    if deposit_rate > target_deposit_rate {
+        // excess_deposit_rate(_per_block)
+        let excess_deposit_rate = deposit_rate - target_deposit_rate;
+        let prev_deposits =
+            Decimal256::from_uint256(state.prev_aterra_supply * state.prev_exchange_rate);
+
+        // excess_yield = prev_deposits * excess_deposit_rate(_per_block) * blocks
+        let excess_yield = prev_deposits * passed_blocks * excess_deposit_rate;
+
+        state.total_reserves += excess_yield;
+        exchange_rate = compute_exchange_rate(state, aterra_supply, balance);
+    }
+
+    state.prev_aterra_supply = aterra_supply;
+    state.prev_exchange_rate = exchange_rate;
+    state.last_interest_updated = block_height;
+}
+
+/// Compute new interest and apply to liability
+pub fn compute_borrower_interest(state: &MarketState, liability: &mut Liability) {
+    liability.loan_amount =
+        liability.loan_amount * state.global_interest_index / liability.interest_index;
+    liability.interest_index = state.global_interest_index;
+}
+
+/// Compute distributed reward and update global index
+pub fn compute_reward(state: &mut MarketState, block_height: u64) {
+    if state.last_reward_updated >= block_height {
+        return;
+    }
+
+    let passed_blocks = Decimal256::from_uint256(block_height - state.last_reward_updated);
+    let reward_accrued = passed_blocks * state.anc_emission_rate;
+    let borrow_amount = state.total_liabilities / state.global_interest_index;
+
+    if !reward_accrued.is_zero() && !borrow_amount.is_zero() {
+        state.global_reward_index += reward_accrued / borrow_amount;
+    }
+
+    state.last_reward_updated = block_height;
+}
+
+/// Compute reward amount a borrower received
+pub fn compute_borrower_reward(state: &MarketState, liability: &mut Liability) {
+    liability.pending_rewards += Decimal256::from_uint256(liability.loan_amount)
+        / state.global_interest_index
+        * (state.global_reward_index - liability.reward_index);
+    liability.reward_index = state.global_reward_index;
+}
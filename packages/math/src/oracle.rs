@@ -0,0 +1,72 @@
+use cosmwasm_bignumber::Decimal256;
+
+/// Median of the submitted prices, or the mean of the two middle ones
+/// for an even count; sorts `prices` in place
+pub fn compute_median_price(prices: &mut [Decimal256]) -> Option<Decimal256> {
+    if prices.is_empty() {
+        return None;
+    }
+
+    prices.sort_unstable();
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 1 {
+        Some(prices[mid])
+    } else {
+        Some((prices[mid - 1] + prices[mid]) / Decimal256::from_uint256(2u64))
+    }
+}
+
+/// Relative move from `prev_price` to `price`; any move away from
+/// a zero price is treated as unbounded
+pub fn compute_price_deviation(prev_price: Decimal256, price: Decimal256) -> Decimal256 {
+    if prev_price.is_zero() {
+        return if price.is_zero() {
+            Decimal256::zero()
+        } else {
+            Decimal256::MAX
+        };
+    }
+
+    if price > prev_price {
+        (price - prev_price) / prev_price
+    } else {
+        (prev_price - price) / prev_price
+    }
+}
+
+/// Confidence of a rate that multiplies or divides the given
+/// `(price, confidence)` legs, adding up their relative confidences;
+/// `None` when no leg has one
+pub fn compute_rate_confidence(
+    rate: Decimal256,
+    legs: &[(Decimal256, Option<Decimal256>)],
+) -> Option<Decimal256> {
+    let mut relative_confidence: Option<Decimal256> = None;
+    for (price, confidence) in legs.iter() {
+        if let Some(confidence) = confidence {
+            let relative = if price.is_zero() {
+                Decimal256::zero()
+            } else {
+                *confidence / *price
+            };
+            relative_confidence = Some(relative_confidence.unwrap_or_default() + relative);
+        }
+    }
+
+    relative_confidence.map(|relative| rate * relative)
+}
+
+/// `(price - confidence, price + confidence)`, floored at zero
+pub fn compute_confidence_band(
+    price: Decimal256,
+    confidence: Option<Decimal256>,
+) -> (Decimal256, Decimal256) {
+    let confidence = confidence.unwrap_or_default();
+    let lower = if confidence > price {
+        Decimal256::zero()
+    } else {
+        price - confidence
+    };
+
+    (lower, price + confidence)
+}
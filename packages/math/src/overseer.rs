@@ -0,0 +1,109 @@
+use core::cmp::min;
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+
+/// Deposit rate per block realized between two exchange rates;
+/// deposit_rate = (exchange_rate / prev_exchange_rate - 1) / blocks
+pub fn compute_deposit_rate(
+    prev_exchange_rate: Decimal256,
+    exchange_rate: Decimal256,
+    blocks: Uint256,
+) -> Decimal256 {
+    let effective_deposit_rate = exchange_rate / prev_exchange_rate;
+    (effective_deposit_rate - Decimal256::one()) / Decimal256::from_uint256(blocks)
+}
+
+/// Amount of the interest buffer to hand to depositors so the deposit rate
+/// reaches `threshold_deposit_rate`, capped by `distribution_buffer`
+pub fn compute_distributed_interest(
+    prev_aterra_supply: Uint256,
+    prev_exchange_rate: Decimal256,
+    blocks: Uint256,
+    deposit_rate: Decimal256,
+    threshold_deposit_rate: Decimal256,
+    distribution_buffer: Uint256,
+) -> Uint256 {
+    if deposit_rate >= threshold_deposit_rate {
+        return Uint256::zero();
+    }
+
+    // missing_deposit_rate(_per_block)
+    let missing_deposit_rate = threshold_deposit_rate - deposit_rate;
+    let prev_deposits = prev_aterra_supply * prev_exchange_rate;
+
+    // missing_deposits = prev_deposits * missing_deposit_rate(_per_block) * blocks
+    let missing_deposits = prev_deposits * blocks * missing_deposit_rate;
+
+    // When there was not enough deposits happens,
+    // distribute interest to market contract
+    min(missing_deposits, distribution_buffer)
+}
+
+/// Liquidation limit over loan amount; below one the loan can be
+/// liquidated. None without a loan
+pub fn compute_health_factor(
+    loan_amount: Uint256,
+    liquidation_limit: Uint256,
+) -> Option<Decimal256> {
+    if loan_amount.is_zero() {
+        return None;
+    }
+
+    Some(Decimal256::from_uint256(liquidation_limit) / Decimal256::from_uint256(loan_amount))
+}
+
+/// Collateral price below which the loan can be liquidated, with the other
+/// collaterals kept at their current value. `collateral_limit` is this
+/// collateral's share of `liquidation_limit`. None when the loan is covered
+/// by the other collaterals alone
+pub fn compute_liquidation_price(
+    loan_amount: Uint256,
+    liquidation_limit: Uint256,
+    collateral_limit: Uint256,
+    collateral_amount: Uint256,
+    liquidation_threshold: Decimal256,
+) -> Option<Decimal256> {
+    // limit provided by the other collaterals
+    let other_limit = liquidation_limit - collateral_limit;
+    if loan_amount <= other_limit || collateral_amount.is_zero() || liquidation_threshold.is_zero()
+    {
+        return None;
+    }
+
+    // loan_amount = other_limit + collateral_amount * price * liquidation_threshold
+    Some(
+        Decimal256::from_uint256(loan_amount - other_limit)
+            / (Decimal256::from_uint256(collateral_amount) * liquidation_threshold),
+    )
+}
+
+/// max_ltv of a deprecated collateral, ramped down linearly from
+/// `ramp_start` to zero after `ramp_blocks`
+pub fn compute_deprecated_ltv(
+    max_ltv: Decimal256,
+    ramp_start: u64,
+    ramp_blocks: u64,
+    block_height: u64,
+) -> Decimal256 {
+    let elapsed = block_height.saturating_sub(ramp_start);
+    if elapsed >= ramp_blocks {
+        return Decimal256::zero();
+    }
+
+    max_ltv * Decimal256::from_ratio(ramp_blocks - elapsed, ramp_blocks)
+}
+
+/// Share of the liquidation amount to sell so the repaid value stays
+/// within `close_factor` of the loan; zero close factor does not cap
+pub fn compute_close_factor_ratio(
+    loan_amount: Uint256,
+    close_factor: Decimal256,
+    liquidation_value: Uint256,
+) -> Decimal256 {
+    let max_repay_amount = loan_amount * close_factor;
+    if close_factor.is_zero() || liquidation_value <= max_repay_amount {
+        return Decimal256::one();
+    }
+
+    Decimal256::from_uint256(max_repay_amount) / Decimal256::from_uint256(liquidation_value)
+}
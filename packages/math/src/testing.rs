@@ -0,0 +1,567 @@
+use crate::interest_model::{
+    compute_borrow_rate, compute_kinked_borrow_rate, compute_pid_borrow_rate,
+};
+use crate::liquidation::{compute_fee_deductor, compute_liquidation_ratio};
+use crate::market::{
+    compute_borrower_interest, compute_borrower_reward, compute_exchange_rate, compute_interest,
+    compute_reward, Liability, MarketState,
+};
+use crate::oracle::{
+    compute_confidence_band, compute_median_price, compute_price_deviation, compute_rate_confidence,
+};
+use crate::overseer::{
+    compute_close_factor_ratio, compute_deposit_rate, compute_deprecated_ltv,
+    compute_distributed_interest, compute_health_factor, compute_liquidation_price,
+};
+use core::str::FromStr;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+
+fn mock_state() -> MarketState {
+    MarketState {
+        total_liabilities: Decimal256::from_uint256(1000000u64),
+        total_reserves: Decimal256::zero(),
+        last_interest_updated: 100,
+        last_reward_updated: 100,
+        global_interest_index: Decimal256::one(),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::from_uint256(10u64),
+        prev_aterra_supply: Uint256::from(2000000u64),
+        prev_exchange_rate: Decimal256::one(),
+    }
+}
+
+#[test]
+fn proper_compute_exchange_rate() {
+    let state = mock_state();
+    assert_eq!(
+        compute_exchange_rate(&state, Uint256::zero(), Uint256::from(1000000u64)),
+        Decimal256::one()
+    );
+
+    // (1000000 + 1000000 - 0) / 1600000
+    assert_eq!(
+        compute_exchange_rate(&state, Uint256::from(1600000u64), Uint256::from(1000000u64)),
+        Decimal256::from_str("1.25").unwrap()
+    );
+}
+
+#[test]
+fn proper_compute_interest() {
+    let mut state = mock_state();
+    compute_interest(
+        &mut state,
+        200,
+        Uint256::from(1000000u64),
+        Uint256::from(2000000u64),
+        Decimal256::from_str("0.0001").unwrap(),
+        Decimal256::MAX,
+    );
+
+    // 100 blocks * 0.0001 = 1% interest
+    assert_eq!(
+        state.global_interest_index,
+        Decimal256::from_str("1.01").unwrap()
+    );
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_uint256(1010000u64)
+    );
+    assert_eq!(state.total_reserves, Decimal256::zero());
+    assert_eq!(
+        state.prev_exchange_rate,
+        Decimal256::from_str("1.005").unwrap()
+    );
+    assert_eq!(state.last_interest_updated, 200);
+
+    // Same block is a no-op
+    let prev_state = state.clone();
+    compute_interest(
+        &mut state,
+        200,
+        Uint256::from(1000000u64),
+        Uint256::from(2000000u64),
+        Decimal256::from_str("0.0001").unwrap(),
+        Decimal256::MAX,
+    );
+    assert_eq!(state, prev_state);
+}
+
+#[test]
+fn compute_interest_moves_excess_yield_to_reserves() {
+    let mut state = mock_state();
+    compute_interest(
+        &mut state,
+        200,
+        Uint256::from(1000000u64),
+        Uint256::from(2000000u64),
+        Decimal256::from_str("0.0001").unwrap(),
+        Decimal256::from_str("0.00002").unwrap(),
+    );
+
+    // deposit_rate = 0.005 / 100 = 0.00005
+    // excess_yield = 2000000 * 100 * (0.00005 - 0.00002) = 6000
+    assert_eq!(state.total_reserves, Decimal256::from_uint256(6000u64));
+    assert_eq!(
+        state.prev_exchange_rate,
+        Decimal256::from_str("1.002").unwrap()
+    );
+}
+
+#[test]
+fn proper_compute_reward() {
+    let mut state = mock_state();
+    compute_reward(&mut state, 200);
+
+    // 100 blocks * 10 / 1000000
+    assert_eq!(
+        state.global_reward_index,
+        Decimal256::from_str("0.001").unwrap()
+    );
+    assert_eq!(state.last_reward_updated, 200);
+
+    let mut liability = Liability {
+        interest_index: Decimal256::one(),
+        reward_index: Decimal256::zero(),
+        loan_amount: Uint256::from(500000u64),
+        pending_rewards: Decimal256::zero(),
+    };
+    state.global_interest_index = Decimal256::from_str("1.01").unwrap();
+    compute_borrower_interest(&state, &mut liability);
+    assert_eq!(liability.loan_amount, Uint256::from(505000u64));
+    assert_eq!(liability.interest_index, state.global_interest_index);
+
+    compute_borrower_reward(&state, &mut liability);
+    assert_eq!(liability.pending_rewards, Decimal256::from_uint256(500u64));
+    assert_eq!(liability.reward_index, state.global_reward_index);
+}
+
+#[test]
+fn proper_compute_borrow_rate() {
+    // utilization_ratio = 1000000 / (4000000 + 1000000 - 0) = 0.2
+    assert_eq!(
+        compute_borrow_rate(
+            Decimal256::from_str("0.1").unwrap(),
+            Decimal256::from_str("0.5").unwrap(),
+            Uint256::from(4000000u64),
+            Decimal256::from_uint256(1000000u64),
+            Decimal256::zero(),
+        ),
+        Decimal256::from_str("0.2").unwrap()
+    );
+
+    // Empty market only pays the base rate
+    assert_eq!(
+        compute_borrow_rate(
+            Decimal256::from_str("0.1").unwrap(),
+            Decimal256::from_str("0.5").unwrap(),
+            Uint256::zero(),
+            Decimal256::zero(),
+            Decimal256::zero(),
+        ),
+        Decimal256::from_str("0.1").unwrap()
+    );
+}
+
+#[test]
+fn proper_compute_kinked_borrow_rate() {
+    let kinked_rate = |market_balance: u64, total_liabilities: u64| {
+        compute_kinked_borrow_rate(
+            Decimal256::from_str("0.1").unwrap(),
+            Decimal256::from_str("0.5").unwrap(),
+            Decimal256::from_str("0.8").unwrap(),
+            Decimal256::from_uint256(5u64),
+            Uint256::from(market_balance),
+            Decimal256::from_uint256(total_liabilities),
+            Decimal256::zero(),
+        )
+    };
+
+    // utilization_ratio = 0.2, below the kink the model is linear
+    assert_eq!(
+        kinked_rate(4000000, 1000000),
+        Decimal256::from_str("0.2").unwrap()
+    );
+
+    // utilization_ratio = 0.8, exactly at the kink
+    assert_eq!(
+        kinked_rate(1000000, 4000000),
+        Decimal256::from_str("0.5").unwrap()
+    );
+
+    // utilization_ratio = 0.9
+    // borrow_rate = 0.8 * 0.5 + (0.9 - 0.8) * 5 + 0.1
+    assert_eq!(
+        kinked_rate(1000000, 9000000),
+        Decimal256::from_str("1.0").unwrap()
+    );
+}
+
+#[test]
+fn proper_compute_pid_borrow_rate() {
+    let pid_rate = |integral: &str, market_balance: u64, total_liabilities: u64| {
+        compute_pid_borrow_rate(
+            Decimal256::from_str("0.8").unwrap(),
+            Decimal256::from_str("0.5").unwrap(),
+            Decimal256::from_str("0.1").unwrap(),
+            Decimal256::from_str("0.01").unwrap(),
+            Decimal256::from_str("0.2").unwrap(),
+            Decimal256::from_str(integral).unwrap(),
+            Uint256::from(market_balance),
+            Decimal256::from_uint256(total_liabilities),
+            Decimal256::zero(),
+        )
+    };
+
+    // utilization_ratio = 0.9, above the target
+    // integral = 0.1 + 0.1 * 0.1
+    // borrow_rate = 0.11 + 0.1 * 0.5
+    assert_eq!(
+        pid_rate("0.1", 1000000, 9000000),
+        (
+            Decimal256::from_str("0.16").unwrap(),
+            Decimal256::from_str("0.11").unwrap()
+        )
+    );
+
+    // utilization_ratio = 0.2, below the target
+    // integral = 0.1 - 0.6 * 0.1
+    // borrow_rate = 0.04 - 0.6 * 0.5, floored at min_rate
+    assert_eq!(
+        pid_rate("0.1", 4000000, 1000000),
+        (
+            Decimal256::from_str("0.01").unwrap(),
+            Decimal256::from_str("0.04").unwrap()
+        )
+    );
+
+    // utilization_ratio = 1, both terms capped at max_rate
+    assert_eq!(
+        pid_rate("0.19", 0, 1000000),
+        (
+            Decimal256::from_str("0.2").unwrap(),
+            Decimal256::from_str("0.2").unwrap()
+        )
+    );
+}
+
+#[test]
+fn proper_compute_deposit_rate() {
+    let deposit_rate = compute_deposit_rate(
+        Decimal256::one(),
+        Decimal256::from_str("1.01").unwrap(),
+        Uint256::from(100u64),
+    );
+    assert_eq!(deposit_rate, Decimal256::from_str("0.0001").unwrap());
+
+    // missing_deposits = 1000000 * 100 * (0.0003 - 0.0001) = 20000
+    assert_eq!(
+        compute_distributed_interest(
+            Uint256::from(1000000u64),
+            Decimal256::one(),
+            Uint256::from(100u64),
+            deposit_rate,
+            Decimal256::from_str("0.0003").unwrap(),
+            Uint256::from(50000u64),
+        ),
+        Uint256::from(20000u64)
+    );
+
+    // capped by the distribution buffer
+    assert_eq!(
+        compute_distributed_interest(
+            Uint256::from(1000000u64),
+            Decimal256::one(),
+            Uint256::from(100u64),
+            deposit_rate,
+            Decimal256::from_str("0.0003").unwrap(),
+            Uint256::from(5000u64),
+        ),
+        Uint256::from(5000u64)
+    );
+
+    // nothing to distribute above the threshold
+    assert_eq!(
+        compute_distributed_interest(
+            Uint256::from(1000000u64),
+            Decimal256::one(),
+            Uint256::from(100u64),
+            deposit_rate,
+            Decimal256::from_str("0.0001").unwrap(),
+            Uint256::from(50000u64),
+        ),
+        Uint256::zero()
+    );
+}
+
+#[test]
+fn proper_compute_deprecated_ltv() {
+    let max_ltv = Decimal256::percent(60);
+    assert_eq!(compute_deprecated_ltv(max_ltv, 100, 100, 100), max_ltv);
+    assert_eq!(
+        compute_deprecated_ltv(max_ltv, 100, 100, 125),
+        Decimal256::percent(45)
+    );
+    assert_eq!(
+        compute_deprecated_ltv(max_ltv, 100, 100, 200),
+        Decimal256::zero()
+    );
+    assert_eq!(
+        compute_deprecated_ltv(max_ltv, 100, 100, 1000),
+        Decimal256::zero()
+    );
+
+    // no ramp drops the ltv at once
+    assert_eq!(
+        compute_deprecated_ltv(max_ltv, 100, 0, 100),
+        Decimal256::zero()
+    );
+}
+
+#[test]
+fn proper_compute_health_factor() {
+    assert_eq!(
+        compute_health_factor(Uint256::from(800u64), Uint256::from(1200u64)),
+        Some(Decimal256::from_str("1.5").unwrap())
+    );
+    assert_eq!(
+        compute_health_factor(Uint256::from(1600u64), Uint256::from(1200u64)),
+        Some(Decimal256::percent(75))
+    );
+
+    // no loan, nothing to liquidate
+    assert_eq!(
+        compute_health_factor(Uint256::zero(), Uint256::from(1200u64)),
+        None
+    );
+}
+
+#[test]
+fn proper_compute_liquidation_price() {
+    // single collateral; 1000 * price * 0.6 = 480
+    assert_eq!(
+        compute_liquidation_price(
+            Uint256::from(480u64),
+            Uint256::from(1200u64),
+            Uint256::from(1200u64),
+            Uint256::from(1000u64),
+            Decimal256::percent(60),
+        ),
+        Some(Decimal256::percent(80))
+    );
+
+    // 600 of the limit comes from other collaterals;
+    // 600 + 1000 * price * 0.6 = 900
+    assert_eq!(
+        compute_liquidation_price(
+            Uint256::from(900u64),
+            Uint256::from(1800u64),
+            Uint256::from(1200u64),
+            Uint256::from(1000u64),
+            Decimal256::percent(60),
+        ),
+        Some(Decimal256::percent(50))
+    );
+
+    // the other collaterals cover the loan
+    assert_eq!(
+        compute_liquidation_price(
+            Uint256::from(600u64),
+            Uint256::from(1800u64),
+            Uint256::from(1200u64),
+            Uint256::from(1000u64),
+            Decimal256::percent(60),
+        ),
+        None
+    );
+}
+
+#[test]
+fn proper_compute_liquidation_ratio() {
+    let fee_deductor = compute_fee_deductor(
+        Decimal256::from_str("0.1").unwrap(),
+        Decimal256::from_str("0.5").unwrap(),
+        Decimal256::zero(),
+    );
+    assert_eq!(fee_deductor, Decimal256::from_str("0.45").unwrap());
+
+    // Safely collateralized
+    assert_eq!(
+        compute_liquidation_ratio(
+            Uint256::from(100u64),
+            Uint256::from(100u64),
+            Uint256::from(1000u64),
+            fee_deductor,
+            Decimal256::from_str("0.8").unwrap(),
+            Uint256::from(200u64),
+        ),
+        Decimal256::zero()
+    );
+
+    // Collaterals cannot cover the loan
+    assert_eq!(
+        compute_liquidation_ratio(
+            Uint256::from(500u64),
+            Uint256::from(100u64),
+            Uint256::from(1000u64),
+            fee_deductor,
+            Decimal256::from_str("0.8").unwrap(),
+            Uint256::from(200u64),
+        ),
+        Decimal256::one()
+    );
+
+    // (300 - 100 * 0.8) / (1000 * 0.45 - 100 * 0.8)
+    assert_eq!(
+        compute_liquidation_ratio(
+            Uint256::from(300u64),
+            Uint256::from(100u64),
+            Uint256::from(1000u64),
+            fee_deductor,
+            Decimal256::from_str("0.8").unwrap(),
+            Uint256::from(200u64),
+        ),
+        Decimal256::from_uint256(220u64) / Decimal256::from_uint256(370u64)
+    );
+
+    // Below the liquidation threshold; 300 / (1000 * 0.45)
+    assert_eq!(
+        compute_liquidation_ratio(
+            Uint256::from(300u64),
+            Uint256::from(100u64),
+            Uint256::from(1000u64),
+            fee_deductor,
+            Decimal256::from_str("0.8").unwrap(),
+            Uint256::from(2000u64),
+        ),
+        Decimal256::from_uint256(300u64) / Decimal256::from_uint256(450u64)
+    );
+}
+
+#[test]
+fn proper_compute_median_price() {
+    assert_eq!(compute_median_price(&mut []), None);
+
+    let mut prices = [
+        Decimal256::from_str("1.3").unwrap(),
+        Decimal256::from_str("1.1").unwrap(),
+        Decimal256::from_str("9.9").unwrap(),
+    ];
+    assert_eq!(
+        compute_median_price(&mut prices),
+        Some(Decimal256::from_str("1.3").unwrap())
+    );
+
+    // even number of prices takes the mean of the middle two
+    let mut prices = [
+        Decimal256::from_str("1.3").unwrap(),
+        Decimal256::from_str("1.1").unwrap(),
+        Decimal256::from_str("9.9").unwrap(),
+        Decimal256::zero(),
+    ];
+    assert_eq!(
+        compute_median_price(&mut prices),
+        Some(Decimal256::from_str("1.2").unwrap())
+    );
+}
+
+#[test]
+fn proper_compute_price_deviation() {
+    assert_eq!(
+        compute_price_deviation(
+            Decimal256::from_str("2.0").unwrap(),
+            Decimal256::from_str("2.5").unwrap()
+        ),
+        Decimal256::from_str("0.25").unwrap()
+    );
+    assert_eq!(
+        compute_price_deviation(
+            Decimal256::from_str("2.0").unwrap(),
+            Decimal256::from_str("1.5").unwrap()
+        ),
+        Decimal256::from_str("0.25").unwrap()
+    );
+
+    assert_eq!(
+        compute_price_deviation(Decimal256::zero(), Decimal256::zero()),
+        Decimal256::zero()
+    );
+    assert_eq!(
+        compute_price_deviation(Decimal256::zero(), Decimal256::one()),
+        Decimal256::MAX
+    );
+}
+
+#[test]
+fn proper_compute_rate_confidence() {
+    assert_eq!(
+        compute_rate_confidence(
+            Decimal256::from_str("2.0").unwrap(),
+            &[
+                (Decimal256::from_str("2.0").unwrap(), None),
+                (Decimal256::one(), None)
+            ]
+        ),
+        None
+    );
+
+    // 2.0 +- 0.1 divided by 1.0 +- 0.02 is off by 5% + 2%
+    assert_eq!(
+        compute_rate_confidence(
+            Decimal256::from_str("2.0").unwrap(),
+            &[
+                (
+                    Decimal256::from_str("2.0").unwrap(),
+                    Some(Decimal256::from_str("0.1").unwrap())
+                ),
+                (
+                    Decimal256::one(),
+                    Some(Decimal256::from_str("0.02").unwrap())
+                ),
+            ]
+        ),
+        Some(Decimal256::from_str("0.14").unwrap())
+    );
+
+    let (lower, upper) = compute_confidence_band(
+        Decimal256::from_str("2.0").unwrap(),
+        Some(Decimal256::from_str("0.14").unwrap()),
+    );
+    assert_eq!(lower, Decimal256::from_str("1.86").unwrap());
+    assert_eq!(upper, Decimal256::from_str("2.14").unwrap());
+
+    // the band does not go below zero
+    let (lower, _) =
+        compute_confidence_band(Decimal256::one(), Some(Decimal256::from_uint256(2u64)));
+    assert_eq!(lower, Decimal256::zero());
+}
+
+#[test]
+fn proper_compute_close_factor_ratio() {
+    // repay at most 1000 * 0.5 = 500 of the 2000 liquidation value
+    assert_eq!(
+        compute_close_factor_ratio(
+            Uint256::from(1000u64),
+            Decimal256::percent(50),
+            Uint256::from(2000u64)
+        ),
+        Decimal256::percent(25)
+    );
+    assert_eq!(
+        compute_close_factor_ratio(
+            Uint256::from(1000u64),
+            Decimal256::percent(50),
+            Uint256::from(400u64)
+        ),
+        Decimal256::one()
+    );
+
+    // zero close factor liquidates the whole amount
+    assert_eq!(
+        compute_close_factor_ratio(
+            Uint256::from(1000u64),
+            Decimal256::zero(),
+            Uint256::from(2000u64)
+        ),
+        Decimal256::one()
+    );
+}
@@ -0,0 +1,51 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::HumanAddr;
+
+pub use crate::interest_model::BorrowRateResponse;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitMsg {
+    pub owner: HumanAddr,
+    pub base_rate: Decimal256,
+    pub interest_multiplier: Decimal256,
+    pub optimal_utilization: Decimal256,
+    pub jump_multiplier: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleMsg {
+    UpdateConfig {
+        owner: Option<HumanAddr>,
+        base_rate: Option<Decimal256>,
+        interest_multiplier: Option<Decimal256>,
+        optimal_utilization: Option<Decimal256>,
+        jump_multiplier: Option<Decimal256>,
+    },
+}
+
+/// Same interface as `interest_model::QueryMsg`, so the market can use
+/// either model
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    BorrowRate {
+        market_balance: Uint256,
+        total_liabilities: Decimal256,
+        total_reserves: Decimal256,
+    },
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: HumanAddr,
+    pub base_rate: Decimal256,
+    pub interest_multiplier: Decimal256,
+    pub optimal_utilization: Decimal256,
+    pub jump_multiplier: Decimal256,
+}
@@ -1,10 +1,12 @@
 pub mod custody;
 pub mod distribution_model;
 pub mod interest_model;
+pub mod kinked_interest_model;
 pub mod liquidation;
 pub mod market;
 pub mod oracle;
 pub mod overseer;
+pub mod pid_interest_model;
 pub mod querier;
 pub mod tokens;
 
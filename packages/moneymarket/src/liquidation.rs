@@ -42,6 +42,7 @@ pub enum HandleMsg {
         max_premium_rate: Option<Decimal256>,
         liquidation_threshold: Option<Uint256>,
         price_timeframe: Option<u64>,
+        twap_window: Option<u64>,
     },
     SubmitBid {
         collateral_token: HumanAddr,
@@ -100,6 +101,7 @@ pub struct ConfigResponse {
     pub max_premium_rate: Decimal256,
     pub liquidation_threshold: Uint256,
     pub price_timeframe: u64,
+    pub twap_window: u64,
 }
 
 // We define a custom struct for each query response
@@ -6,7 +6,7 @@ use cosmwasm_std::{
 };
 use std::collections::HashMap;
 
-use crate::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
+use crate::oracle::{PriceResponse, PriceStatus, QueryMsg as OracleQueryMsg};
 
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -132,12 +132,16 @@ impl WasmMockQuerier {
                 contract_addr: _,
                 msg,
             }) => match from_binary(&msg).unwrap() {
-                OracleQueryMsg::Price { base, quote } => {
+                OracleQueryMsg::Price { base, quote, .. } => {
                     match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
                         Some(v) => Ok(to_binary(&PriceResponse {
                             rate: v.0,
                             last_updated_base: v.1,
                             last_updated_quote: v.2,
+                            feeders_base: 1,
+                            feeders_quote: 1,
+                            confidence: None,
+                            status: PriceStatus::Trading,
                         })),
                         None => Err(SystemError::InvalidRequest {
                             error: "No oracle price exists".to_string(),
@@ -8,6 +8,10 @@ use cosmwasm_std::HumanAddr;
 pub struct InitMsg {
     pub owner: HumanAddr,
     pub base_asset: String,
+    /// Number of fresh submissions required to update a price
+    pub min_feeders: u32,
+    /// Submissions older than this many seconds are left out of the median
+    pub feed_window: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -15,14 +19,38 @@ pub struct InitMsg {
 pub enum HandleMsg {
     UpdateConfig {
         owner: Option<HumanAddr>,
+        min_feeders: Option<u32>,
+        feed_window: Option<u64>,
     },
-    RegisterFeeder {
-        asset: String,
-        feeder: HumanAddr,
-    },
+    /// Adds a feeder to the set of feeders of the asset
+    RegisterFeeder { asset: String, feeder: HumanAddr },
+    /// Removes a feeder and its pending submission from the asset;
+    /// feeders are rotated by registering the new one first
+    DeregisterFeeder { asset: String, feeder: HumanAddr },
     FeedPrice {
         prices: Vec<(String, Decimal256)>, // (asset, price)
     },
+    /// Same as `FeedPrice` with a confidence band and market status for
+    /// each price; `FeedPrice` reports `Trading` without a band
+    FeedPriceWithConfidence { prices: Vec<PriceFeed> },
+    /// Replaces the circuit breaker limits of the asset;
+    /// a `None` limit is not checked
+    UpdateCircuitBreaker {
+        asset: String,
+        max_deviation_per_update: Option<Decimal256>,
+        floor: Option<Decimal256>,
+        ceiling: Option<Decimal256>,
+    },
+    /// Quotes the fed prices of `asset` in `quote`, which is resolved
+    /// through its own route down to the base asset; quoting in the
+    /// base asset removes the route
+    RegisterRoute { asset: String, quote: String },
+    /// Clears a tripped circuit breaker, applying the quarantined
+    /// price when `accept_quarantined` is set
+    ResetCircuitBreaker {
+        asset: String,
+        accept_quarantined: bool,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -32,14 +60,72 @@ pub enum QueryMsg {
     Feeder {
         asset: String,
     },
+    /// Feeders of every asset, paginated by asset
+    Feeders {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Latest price; with a `block_time`, each leg is rejected unless at
+    /// least `min_feeders` submissions are still within `feed_window`
     Price {
         base: String,
         quote: String,
+        block_time: Option<u64>,
+    },
+    /// Time weighted average price over the `window` seconds before
+    /// `block_time`, returned as a `PriceResponse`
+    TwapPrice {
+        base: String,
+        quote: String,
+        window: u64,
+        block_time: u64,
+    },
+    /// Price as of `time`, taken from the latest stored observation
+    /// at or before it, returned as a `PriceResponse`
+    PriceAt {
+        base: String,
+        quote: String,
+        time: u64,
     },
     Prices {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Prices last updated more than `max_age` seconds before
+    /// `block_time`, returned as a `PricesResponse`
+    StalePrices {
+        max_age: u64,
+        block_time: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    CircuitBreaker {
+        asset: String,
+    },
+    Route {
+        asset: String,
+    },
+}
+
+/// Market status reported along with a price,
+/// ordered from the most to the least reliable
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceStatus {
+    Trading,
+    Halted,
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceFeed {
+    pub asset: String,
+    pub price: Decimal256,
+    /// Half width of the band the price is expected to lie in
+    pub confidence: Option<Decimal256>,
+    pub status: PriceStatus,
 }
 
 // We define a custom struct for each query response
@@ -47,21 +133,62 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     pub owner: HumanAddr,
     pub base_asset: String,
+    pub min_feeders: u32,
+    pub feed_window: u64,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct FeederResponse {
     pub asset: String,
+    /// First of `feeders`, kept for clients of the single feeder API
     pub feeder: HumanAddr,
+    pub feeders: Vec<HumanAddr>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeedersResponse {
+    pub feeders: Vec<FeederResponse>,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PriceResponse {
     pub rate: Decimal256,
+    /// Update time of each asset; the oldest leg of a routed asset
     pub last_updated_base: u64,
     pub last_updated_quote: u64,
+    /// Number of feeders behind the median of each asset, the fewest
+    /// across the legs of a routed one; zero for the oracle base asset
+    pub feeders_base: u32,
+    pub feeders_quote: u32,
+    /// Half width of the band the rate is expected to lie in, combining
+    /// the relative confidence of every leg; `None` when no leg has one
+    pub confidence: Option<Decimal256>,
+    /// Least reliable status across the legs
+    pub status: PriceStatus,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CircuitBreakerResponse {
+    pub asset: String,
+    pub max_deviation_per_update: Option<Decimal256>,
+    pub floor: Option<Decimal256>,
+    pub ceiling: Option<Decimal256>,
+    /// While tripped, prices involving the asset cannot be queried
+    pub tripped: bool,
+    pub quarantined_price: Option<Decimal256>,
+    pub quarantined_time: Option<u64>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RouteResponse {
+    pub asset: String,
+    /// Intermediate assets the price goes through, ending with the base asset
+    pub route: Vec<String>,
 }
 
 // We define a custom struct for each query response
@@ -70,6 +197,8 @@ pub struct PricesResponseElem {
     pub asset: String,
     pub price: Decimal256,
     pub last_updated_time: u64,
+    pub confidence: Option<Decimal256>,
+    pub status: PriceStatus,
 }
 
 // We define a custom struct for each query response
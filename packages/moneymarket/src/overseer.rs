@@ -56,21 +56,63 @@ pub enum HandleMsg {
         anc_purchase_factor: Option<Decimal256>,
         epoch_period: Option<u64>,
         price_timeframe: Option<u64>,
+        twap_window: Option<u64>,
+        close_factor: Option<Decimal256>,
+        pid_interest_model: Option<HumanAddr>,
     },
 
     /// Create new custody contract for the given collateral token
     Whitelist {
-        name: String,                // bAsset name
-        symbol: String,              // bAsset symbol
-        collateral_token: HumanAddr, // bAsset token contract
-        custody_contract: HumanAddr, // bAsset custody contract
-        max_ltv: Decimal256,         // Loan To Value ratio
+        name: String,                       // bAsset name
+        symbol: String,                     // bAsset symbol
+        collateral_token: HumanAddr,        // bAsset token contract
+        custody_contract: HumanAddr,        // bAsset custody contract
+        max_ltv: Decimal256,                // Loan To Value ratio
+        liquidation_threshold: Decimal256,  // Liquidation LTV ratio
+        max_locked_amount: Option<Uint256>, // Cap on the total locked amount
+        isolated: bool,                     // Cannot be mixed with other collaterals
+        debt_ceiling: Option<Uint256>,      // Cap on the debt backed by isolated collateral
     },
     /// Update registered whitelist info
     UpdateWhitelist {
-        collateral_token: HumanAddr,         // bAsset token contract
-        custody_contract: Option<HumanAddr>, // bAsset custody contract
-        max_ltv: Option<Decimal256>,         // Loan To Value ratio
+        collateral_token: HumanAddr,               // bAsset token contract
+        custody_contract: Option<HumanAddr>,       // bAsset custody contract
+        max_ltv: Option<Decimal256>,               // Loan To Value ratio
+        liquidation_threshold: Option<Decimal256>, // Liquidation LTV ratio
+        max_locked_amount: Option<Uint256>,        // Cap on the total locked amount
+        remove_max_locked_amount: Option<bool>,    // Lift the cap on the total locked amount
+        debt_ceiling: Option<Uint256>,             // Cap on the debt backed by isolated collateral
+    },
+    /// Block new locks of the collateral and ramp its max_ltv
+    /// down to zero over `ramp_blocks`
+    DeprecateCollateral {
+        collateral_token: HumanAddr,
+        ramp_blocks: u64,
+    },
+    /// Remove a collateral which no borrower holds anymore
+    RemoveWhitelist {
+        collateral_token: HumanAddr,
+    },
+    /// Create a category of correlated collaterals with its own LTV ratios
+    CreateCategory {
+        category: String,
+        max_ltv: Decimal256,
+        liquidation_threshold: Decimal256,
+    },
+    /// Assign a whitelisted collateral to a category, or unassign it with None
+    AssignCategory {
+        collateral_token: HumanAddr,
+        category: Option<String>,
+    },
+
+    ////////////////////
+    /// Market operations
+    ////////////////////
+
+    /// Record the loan of a borrower holding isolated collateral after
+    /// a borrow or repay; the loan is read back from the market
+    UpdateBorrowerLoan {
+        borrower: HumanAddr,
     },
 
     /// Claims all staking rewards from the bAsset contracts
@@ -93,11 +135,31 @@ pub enum HandleMsg {
     UnlockCollateral {
         collaterals: TokensHuman, // <(Collateral Token, Amount)>
     },
+    /// Use the category LTV ratios for the borrower's collaterals,
+    /// which must all belong to the category
+    EnterCategory {
+        category: String,
+    },
+    LeaveCategory {},
 
     /////////////////////////////
     /// Permissionless operations
     /////////////////////////////
-    LiquidateCollateral { borrower: HumanAddr },
+    LiquidateCollateral {
+        borrower: HumanAddr,
+    },
+    /// Liquidate several borrowers in one transaction,
+    /// skipping the safely collateralized ones; a failing liquidation
+    /// reverts the whole batch
+    LiquidateCollaterals {
+        borrowers: Vec<HumanAddr>,
+    },
+    /// Liquidate a single borrower of LiquidateCollaterals on behalf of
+    /// the liquidator; only the overseer itself can send it
+    LiquidateBorrower {
+        borrower: HumanAddr,
+        liquidator: HumanAddr,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -117,8 +179,31 @@ pub enum QueryMsg {
         start_after: Option<HumanAddr>,
         limit: Option<u32>,
     },
+    /// Deprecated collaterals count with a fully ramped down
+    /// max_ltv when `block_height` is not given
     BorrowLimit {
         borrower: HumanAddr,
+        block_height: Option<u64>,
+        block_time: Option<u64>,
+    },
+    BorrowerHealth {
+        borrower: HumanAddr,
+        block_height: u64,
+        block_time: Option<u64>,
+    },
+    Category {
+        category: String,
+    },
+    /// Loan last recorded for a borrower holding isolated collateral
+    IsolatedLoan {
+        borrower: HumanAddr,
+    },
+    /// Borrowers among a page of `AllCollaterals` whose loan
+    /// exceeds the liquidation limit
+    LiquidatableBorrowers {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+        block_height: u64,
         block_time: Option<u64>,
     },
 }
@@ -138,6 +223,9 @@ pub struct ConfigResponse {
     pub stable_denom: String,
     pub epoch_period: u64,
     pub price_timeframe: u64,
+    pub twap_window: u64,
+    pub close_factor: Decimal256,
+    pub pid_interest_model: Option<HumanAddr>,
 }
 
 // We define a custom struct for each query response
@@ -146,8 +234,27 @@ pub struct WhitelistResponseElem {
     pub name: String,
     pub symbol: String,
     pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
     pub custody_contract: HumanAddr,
     pub collateral_token: HumanAddr,
+    pub deprecation: Option<CollateralDeprecation>,
+    /// None when the collateral is not capped
+    pub max_locked_amount: Option<Uint256>,
+    /// Amount locked by all borrowers; the utilisation of max_locked_amount
+    pub total_locked_amount: Uint256,
+    pub isolated: bool,
+    pub debt_ceiling: Option<Uint256>,
+    /// Debt of the borrowers holding the isolated collateral
+    pub isolated_debt: Uint256,
+    pub category: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralDeprecation {
+    /// Block height the max_ltv ramp starts from
+    pub start_height: u64,
+    /// # of blocks until max_ltv reaches zero
+    pub ramp_blocks: u64,
 }
 
 // We define a custom struct for each query response
@@ -173,6 +280,67 @@ pub struct AllCollateralsResponse {
 pub struct BorrowLimitResponse {
     pub borrower: HumanAddr,
     pub borrow_limit: Uint256,
+    pub liquidation_limit: Uint256,
+    /// Category whose LTV ratios were applied
+    pub category: Option<String>,
+    /// Whether the borrower holds isolated collateral,
+    /// whose loan changes must be reported with UpdateBorrowerLoan
+    pub isolated: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsolatedLoanResponse {
+    pub borrower: HumanAddr,
+    /// None when no loan is recorded for the borrower
+    pub collateral_token: Option<HumanAddr>,
+    pub loan_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidatableBorrowersResponseElem {
+    pub borrower: HumanAddr,
+    pub loan_amount: Uint256,
+    pub liquidation_limit: Uint256,
+    /// Loan amount above the liquidation limit
+    pub shortfall: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidatableBorrowersResponse {
+    pub borrowers: Vec<LiquidatableBorrowersResponseElem>,
+    /// Scanned borrowers whose health could not be computed
+    pub failed_borrowers: Vec<HumanAddr>,
+    /// `start_after` of the next page; None once all borrowers were scanned
+    pub last_scanned_borrower: Option<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CategoryResponse {
+    pub category: String,
+    pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerHealthResponseElem {
+    pub collateral_token: HumanAddr,
+    pub amount: Uint256,
+    pub price: Decimal256,
+    /// None when the other collaterals alone cover the loan
+    pub liquidation_price: Option<Decimal256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerHealthResponse {
+    pub borrower: HumanAddr,
+    pub loan_amount: Uint256,
+    pub borrow_limit: Uint256,
+    pub liquidation_limit: Uint256,
+    /// liquidation_limit / loan_amount; None without a loan
+    pub health_factor: Option<Decimal256>,
+    /// Amount that can still be borrowed before reaching the borrow limit
+    pub borrowable_amount: Uint256,
+    pub collaterals: Vec<BorrowerHealthResponseElem>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
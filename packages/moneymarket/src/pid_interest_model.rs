@@ -0,0 +1,85 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::HumanAddr;
+
+pub use crate::interest_model::BorrowRateResponse;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitMsg {
+    pub owner: HumanAddr,
+    /// Account allowed to trigger observations, usually the overseer
+    pub observer: HumanAddr,
+    /// Market contract whose utilization is observed
+    pub market: HumanAddr,
+    pub target_utilization: Decimal256,
+    pub proportional_gain: Decimal256,
+    pub integral_gain: Decimal256,
+    pub min_rate: Decimal256,
+    pub max_rate: Decimal256,
+    /// Borrow rate used until the first observation
+    pub initial_rate: Decimal256,
+    /// Minimum number of blocks between two observations
+    pub epoch_period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum HandleMsg {
+    UpdateConfig {
+        owner: Option<HumanAddr>,
+        observer: Option<HumanAddr>,
+        market: Option<HumanAddr>,
+        target_utilization: Option<Decimal256>,
+        proportional_gain: Option<Decimal256>,
+        integral_gain: Option<Decimal256>,
+        min_rate: Option<Decimal256>,
+        max_rate: Option<Decimal256>,
+        epoch_period: Option<u64>,
+    },
+
+    /// Reads the current market situation from the market contract and
+    /// feeds it to the controller; the borrow rate is adjusted at most
+    /// once per epoch
+    Observe {},
+}
+
+/// `Config` and `BorrowRate` follow `interest_model::QueryMsg`, so the market
+/// can use this model as well
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    State {},
+    /// Returns the rate computed by the last observation;
+    /// the market situation given here is not used
+    BorrowRate {
+        market_balance: Uint256,
+        total_liabilities: Decimal256,
+        total_reserves: Decimal256,
+    },
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: HumanAddr,
+    pub observer: HumanAddr,
+    pub market: HumanAddr,
+    pub target_utilization: Decimal256,
+    pub proportional_gain: Decimal256,
+    pub integral_gain: Decimal256,
+    pub min_rate: Decimal256,
+    pub max_rate: Decimal256,
+    pub epoch_period: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub borrow_rate: Decimal256,
+    pub integral: Decimal256,
+    pub last_observed_height: u64,
+}
@@ -121,9 +121,60 @@ pub fn query_price<S: Storage, A: Api, Q: Querier>(
     let oracle_price: PriceResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: HumanAddr::from(oracle_addr),
-            msg: to_binary(&OracleQueryMsg::Price { base, quote })?,
+            msg: to_binary(&OracleQueryMsg::Price {
+                base,
+                quote,
+                block_time: time_contraints.as_ref().map(|t| t.block_time),
+            })?,
         }))?;
 
+    assert_price_time(&oracle_price, time_contraints)?;
+    Ok(oracle_price)
+}
+
+/// Queries the oracle time weighted average price over the `window` seconds
+/// before the constrained block time; a zero window falls back to the spot
+/// `Price` query, and a non-zero window needs a block time to anchor at
+pub fn query_twap_price<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    oracle_addr: &HumanAddr,
+    base: String,
+    quote: String,
+    window: u64,
+    time_contraints: Option<TimeConstraints>,
+) -> StdResult<PriceResponse> {
+    if window == 0 {
+        return query_price(deps, oracle_addr, base, quote, time_contraints);
+    }
+
+    let block_time = match &time_contraints {
+        Some(time_contraints) => time_contraints.block_time,
+        None => {
+            return Err(StdError::generic_err(
+                "Block time is required for a time weighted average price",
+            ))
+        }
+    };
+
+    let oracle_price: PriceResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: HumanAddr::from(oracle_addr),
+            msg: to_binary(&OracleQueryMsg::TwapPrice {
+                base,
+                quote,
+                window,
+                block_time,
+            })?,
+        }))?;
+
+    assert_price_time(&oracle_price, time_contraints)?;
+    Ok(oracle_price)
+}
+
+fn assert_price_time(
+    oracle_price: &PriceResponse,
+    time_contraints: Option<TimeConstraints>,
+) -> StdResult<()> {
     if let Some(time_contraints) = time_contraints {
         let valid_update_time = time_contraints.block_time - time_contraints.valid_timeframe;
         if oracle_price.last_updated_base < valid_update_time
@@ -133,7 +184,7 @@ pub fn query_price<S: Storage, A: Api, Q: Querier>(
         }
     }
 
-    Ok(oracle_price)
+    Ok(())
 }
 
 #[inline]
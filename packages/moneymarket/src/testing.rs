@@ -1,6 +1,8 @@
 use crate::mock_querier::mock_dependencies;
-use crate::oracle::PriceResponse;
-use crate::querier::{compute_tax, deduct_tax, query_price, query_tax_rate, TimeConstraints};
+use crate::oracle::{PriceResponse, PriceStatus};
+use crate::querier::{
+    compute_tax, deduct_tax, query_price, query_tax_rate, query_twap_price, TimeConstraints,
+};
 use crate::tokens::{Tokens, TokensHuman, TokensMath, TokensToRaw};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -88,6 +90,10 @@ fn oracle_price_querier() {
             rate: Decimal256::from_ratio(131, 2),
             last_updated_base: 123,
             last_updated_quote: 321,
+            feeders_base: 1,
+            feeders_quote: 1,
+            confidence: None,
+            status: PriceStatus::Trading,
         }
     );
 
@@ -117,6 +123,44 @@ fn oracle_price_querier() {
     }
 }
 
+#[test]
+fn twap_price_requires_block_time() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    deps.querier.with_oracle_price(&[(
+        &("terra123123".to_string(), "uusd".to_string()),
+        &(Decimal256::from_ratio(131, 2), 123, 321),
+    )]);
+
+    // a zero window is the spot price
+    let oracle_price = query_twap_price(
+        &deps,
+        &HumanAddr::from("oracle"),
+        "terra123123".to_string(),
+        "uusd".to_string(),
+        0,
+        None,
+    )
+    .unwrap();
+    assert_eq!(oracle_price.rate, Decimal256::from_ratio(131, 2));
+
+    let res = query_twap_price(
+        &deps,
+        &HumanAddr::from("oracle"),
+        "terra123123".to_string(),
+        "uusd".to_string(),
+        3600,
+        None,
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Block time is required for a time weighted average price"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 #[test]
 fn tokens_math() {
     let deps = mock_dependencies(20, &[]);
@@ -0,0 +1,400 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{
+    to_binary, BankMsg, Binary, BlockInfo, Coin, ContractInfo, CosmosMsg, Decimal, Env, Extern,
+    HumanAddr, LogAttribute, MessageInfo, Querier, QueryRequest, StdError, StdResult, Uint128,
+    WasmMsg, WasmQuery,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::contract::{Contract, SimDeps, SimMsg};
+use crate::querier::SimQuerier;
+use crate::storage::ContractStorage;
+
+/// Canonical address length of the `MockApi` every contract runs with
+pub const CANONICAL_LENGTH: usize = 20;
+
+#[derive(Clone)]
+pub(crate) struct Instance {
+    code_id: u64,
+    storage: ContractStorage,
+}
+
+#[derive(Default)]
+pub(crate) struct TaxInfo {
+    pub rate: Decimal,
+    pub caps: HashMap<String, Uint128>,
+}
+
+type Bank = HashMap<HumanAddr, BTreeMap<String, u128>>;
+
+/// Chain state shared between the simulator and its querier
+#[derive(Default)]
+pub(crate) struct Router {
+    codes: RefCell<Vec<Rc<dyn Contract>>>,
+    contracts: RefCell<HashMap<HumanAddr, Instance>>,
+    bank: RefCell<Bank>,
+    pub tax: RefCell<TaxInfo>,
+}
+
+struct Snapshot {
+    contracts: HashMap<HumanAddr, BTreeMap<Vec<u8>, Vec<u8>>>,
+    bank: Bank,
+}
+
+impl Router {
+    pub fn instance(&self, address: &HumanAddr) -> Option<(Rc<dyn Contract>, ContractStorage)> {
+        let instance = self.contracts.borrow().get(address).cloned()?;
+        let code = self.codes.borrow()[instance.code_id as usize - 1].clone();
+        Some((code, instance.storage))
+    }
+
+    pub fn balance(&self, address: &HumanAddr, denom: &str) -> Uint128 {
+        let amount = self
+            .bank
+            .borrow()
+            .get(address)
+            .and_then(|balances| balances.get(denom).copied())
+            .unwrap_or_default();
+        Uint128(amount)
+    }
+
+    pub fn all_balances(&self, address: &HumanAddr) -> Vec<Coin> {
+        self.bank
+            .borrow()
+            .get(address)
+            .map(|balances| {
+                balances
+                    .iter()
+                    .filter(|(_, amount)| **amount > 0)
+                    .map(|(denom, amount)| Coin::new(*amount, denom))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn transfer(&self, from: &HumanAddr, to: &HumanAddr, coins: &[Coin]) -> StdResult<()> {
+        let mut bank = self.bank.borrow_mut();
+        for coin in coins.iter() {
+            let amount = coin.amount.u128();
+            let from_balance = bank
+                .entry(from.clone())
+                .or_default()
+                .entry(coin.denom.clone())
+                .or_default();
+            *from_balance = from_balance.checked_sub(amount).ok_or_else(|| {
+                StdError::generic_err(format!(
+                    "Insufficient {} balance of {}: {}",
+                    coin.denom, from, from_balance
+                ))
+            })?;
+
+            *bank
+                .entry(to.clone())
+                .or_default()
+                .entry(coin.denom.clone())
+                .or_default() += amount;
+        }
+
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            contracts: self
+                .contracts
+                .borrow()
+                .iter()
+                .map(|(address, instance)| (address.clone(), instance.storage.snapshot()))
+                .collect(),
+            bank: self.bank.borrow().clone(),
+        }
+    }
+
+    fn restore(&self, snapshot: Snapshot) {
+        let mut contracts = self.contracts.borrow_mut();
+        contracts.retain(|address, _| snapshot.contracts.contains_key(address));
+        for (address, data) in snapshot.contracts {
+            contracts[&address].storage.restore(data);
+        }
+
+        *self.bank.borrow_mut() = snapshot.bank;
+    }
+}
+
+/// Aggregated result of a message and every message it triggered
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SimResponse {
+    pub log: Vec<LogAttribute>,
+    pub data: Option<Binary>,
+}
+
+/// In-process chain running several contracts against each other
+///
+/// Wasm executes and instantiates returned by a contract are dispatched
+/// depth first with the contract as sender, like the wasm module does, and
+/// a failing message reverts the whole transaction it belongs to.
+pub struct Simulator {
+    router: Rc<Router>,
+    block: BlockInfo,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Simulator::new()
+    }
+}
+
+impl Simulator {
+    pub fn new() -> Self {
+        Simulator {
+            router: Rc::new(Router::default()),
+            block: BlockInfo {
+                height: 12_345,
+                time: 1_571_797_419,
+                chain_id: "cosmos-testnet-14002".to_string(),
+            },
+        }
+    }
+
+    pub fn block_info(&self) -> BlockInfo {
+        self.block.clone()
+    }
+
+    pub fn update_block<F: FnOnce(&mut BlockInfo)>(&mut self, action: F) {
+        action(&mut self.block);
+    }
+
+    /// Sets the tax rate and per denom caps returned to `TerraQuerier`
+    pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
+        let mut tax = self.router.tax.borrow_mut();
+        tax.rate = rate;
+        tax.caps = caps
+            .iter()
+            .map(|(denom, cap)| (denom.to_string(), **cap))
+            .collect();
+    }
+
+    /// Overwrites the native balances of an account
+    pub fn init_balance(&mut self, address: &HumanAddr, coins: Vec<Coin>) {
+        self.router.bank.borrow_mut().insert(
+            address.clone(),
+            coins
+                .into_iter()
+                .map(|coin| (coin.denom, coin.amount.u128()))
+                .collect(),
+        );
+    }
+
+    pub fn balance(&self, address: &HumanAddr, denom: &str) -> Uint128 {
+        self.router.balance(address, denom)
+    }
+
+    pub fn all_balances(&self, address: &HumanAddr) -> Vec<Coin> {
+        self.router.all_balances(address)
+    }
+
+    /// Registers contract code and returns its code id
+    pub fn store_code(&mut self, code: Box<dyn Contract>) -> u64 {
+        let mut codes = self.router.codes.borrow_mut();
+        codes.push(Rc::from(code));
+        codes.len() as u64
+    }
+
+    pub fn instantiate<T: Serialize>(
+        &mut self,
+        code_id: u64,
+        sender: &HumanAddr,
+        msg: &T,
+        send: &[Coin],
+    ) -> StdResult<HumanAddr> {
+        let msg = to_binary(msg)?;
+        self.transact(|| {
+            self.instantiate_wasm(sender, code_id, msg, send.to_vec())
+                .map(|(address, _)| address)
+        })
+    }
+
+    pub fn execute<T: Serialize>(
+        &mut self,
+        sender: &HumanAddr,
+        contract_addr: &HumanAddr,
+        msg: &T,
+        send: &[Coin],
+    ) -> StdResult<SimResponse> {
+        let msg = to_binary(msg)?;
+        self.transact(|| self.execute_wasm(sender, contract_addr, msg, send.to_vec()))
+    }
+
+    /// Native transfer signed by `sender`
+    pub fn send_tokens(
+        &mut self,
+        sender: &HumanAddr,
+        recipient: &HumanAddr,
+        amount: &[Coin],
+    ) -> StdResult<()> {
+        self.transact(|| self.router.transfer(sender, recipient, amount))
+    }
+
+    pub fn query<T: Serialize, U: DeserializeOwned>(
+        &self,
+        contract_addr: &HumanAddr,
+        msg: &T,
+    ) -> StdResult<U> {
+        self.querier().query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: contract_addr.clone(),
+            msg: to_binary(msg)?,
+        }))
+    }
+
+    pub fn querier(&self) -> SimQuerier {
+        SimQuerier::new(self.router.clone())
+    }
+
+    fn transact<T, F: FnOnce() -> StdResult<T>>(&self, action: F) -> StdResult<T> {
+        let snapshot = self.router.snapshot();
+        let res = action();
+        if res.is_err() {
+            self.router.restore(snapshot);
+        }
+
+        res
+    }
+
+    fn deps(&self, storage: ContractStorage) -> SimDeps {
+        Extern {
+            storage,
+            api: MockApi::new(CANONICAL_LENGTH),
+            querier: self.querier(),
+        }
+    }
+
+    fn env(&self, sender: &HumanAddr, contract_addr: &HumanAddr, send: Vec<Coin>) -> Env {
+        Env {
+            block: self.block.clone(),
+            message: MessageInfo {
+                sender: sender.clone(),
+                sent_funds: send,
+            },
+            contract: ContractInfo {
+                address: contract_addr.clone(),
+            },
+        }
+    }
+
+    fn instantiate_wasm(
+        &self,
+        sender: &HumanAddr,
+        code_id: u64,
+        msg: Binary,
+        send: Vec<Coin>,
+    ) -> StdResult<(HumanAddr, SimResponse)> {
+        if code_id == 0 || code_id as usize > self.router.codes.borrow().len() {
+            return Err(StdError::generic_err(format!(
+                "Unknown code id: {}",
+                code_id
+            )));
+        }
+
+        let storage = ContractStorage::new();
+        let contract_addr = {
+            let mut contracts = self.router.contracts.borrow_mut();
+            let contract_addr = HumanAddr(format!("contract{}", contracts.len()));
+            contracts.insert(
+                contract_addr.clone(),
+                Instance {
+                    code_id,
+                    storage: storage.clone(),
+                },
+            );
+            contract_addr
+        };
+        let (code, _) = self.router.instance(&contract_addr).unwrap();
+
+        self.router.transfer(sender, &contract_addr, &send)?;
+        let res = code.init(
+            &mut self.deps(storage),
+            self.env(sender, &contract_addr, send),
+            msg.as_slice(),
+        )?;
+
+        let log = self.dispatch_all(&contract_addr, res.messages, res.log)?;
+        Ok((contract_addr, SimResponse { log, data: None }))
+    }
+
+    fn execute_wasm(
+        &self,
+        sender: &HumanAddr,
+        contract_addr: &HumanAddr,
+        msg: Binary,
+        send: Vec<Coin>,
+    ) -> StdResult<SimResponse> {
+        let (code, storage) = self
+            .router
+            .instance(contract_addr)
+            .ok_or_else(|| StdError::generic_err(format!("Unknown contract: {}", contract_addr)))?;
+
+        self.router.transfer(sender, contract_addr, &send)?;
+        let res = code.handle(
+            &mut self.deps(storage),
+            self.env(sender, contract_addr, send),
+            msg.as_slice(),
+        )?;
+
+        let log = self.dispatch_all(contract_addr, res.messages, res.log)?;
+        Ok(SimResponse {
+            log,
+            data: res.data,
+        })
+    }
+
+    fn dispatch_all(
+        &self,
+        sender: &HumanAddr,
+        messages: Vec<SimMsg>,
+        mut log: Vec<LogAttribute>,
+    ) -> StdResult<Vec<LogAttribute>> {
+        for msg in messages {
+            log.extend(self.dispatch(sender, msg)?.log);
+        }
+
+        Ok(log)
+    }
+
+    fn dispatch(&self, sender: &HumanAddr, msg: SimMsg) -> StdResult<SimResponse> {
+        match msg {
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address,
+                to_address,
+                amount,
+            }) => {
+                if from_address != *sender {
+                    return Err(StdError::unauthorized());
+                }
+
+                self.router.transfer(&from_address, &to_address, &amount)?;
+                Ok(SimResponse::default())
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                send,
+            }) => self.execute_wasm(sender, &contract_addr, msg, send),
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                code_id, msg, send, ..
+            }) => self
+                .instantiate_wasm(sender, code_id, msg, send)
+                .map(|(_, res)| res),
+            CosmosMsg::Custom(_) => Err(StdError::generic_err(
+                "Terra custom messages are not supported by the simulator",
+            )),
+            CosmosMsg::Staking(_) => Err(StdError::generic_err(
+                "Staking messages are not supported by the simulator",
+            )),
+        }
+    }
+}
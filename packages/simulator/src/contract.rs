@@ -0,0 +1,142 @@
+use std::fmt;
+
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{
+    from_slice, Binary, CosmosMsg, Empty, Env, Extern, HandleResponse, InitResponse, StdError,
+    StdResult,
+};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use terra_cosmwasm::TerraMsgWrapper;
+
+use crate::querier::SimQuerier;
+use crate::storage::ContractStorage;
+
+/// Dependencies every simulated contract runs with
+pub type SimDeps = Extern<ContractStorage, MockApi, SimQuerier>;
+
+/// Message type the simulator dispatches; every contract response is
+/// converted into it
+pub type SimMsg = CosmosMsg<TerraMsgWrapper>;
+
+/// Type erased contract code, fed with json encoded messages
+pub trait Contract {
+    fn init(
+        &self,
+        deps: &mut SimDeps,
+        env: Env,
+        msg: &[u8],
+    ) -> StdResult<InitResponse<TerraMsgWrapper>>;
+
+    fn handle(
+        &self,
+        deps: &mut SimDeps,
+        env: Env,
+        msg: &[u8],
+    ) -> StdResult<HandleResponse<TerraMsgWrapper>>;
+
+    fn query(&self, deps: &SimDeps, msg: &[u8]) -> StdResult<Binary>;
+}
+
+/// Custom message types contracts may return
+pub trait CustomMsg: Clone + fmt::Debug + PartialEq + JsonSchema {
+    fn into_terra(self) -> StdResult<TerraMsgWrapper>;
+}
+
+impl CustomMsg for Empty {
+    fn into_terra(self) -> StdResult<TerraMsgWrapper> {
+        Err(StdError::generic_err(
+            "Empty custom message cannot be dispatched",
+        ))
+    }
+}
+
+impl CustomMsg for TerraMsgWrapper {
+    fn into_terra(self) -> StdResult<TerraMsgWrapper> {
+        Ok(self)
+    }
+}
+
+type InitFn<T, C> = fn(&mut SimDeps, Env, T) -> StdResult<InitResponse<C>>;
+type HandleFn<T, C> = fn(&mut SimDeps, Env, T) -> StdResult<HandleResponse<C>>;
+type QueryFn<T> = fn(&SimDeps, T) -> StdResult<Binary>;
+
+/// Wraps the typed `init`, `handle` and `query` entry points of a contract;
+/// `IC` and `HC` are the custom messages returned by `init` and `handle`
+pub struct ContractWrapper<I, H, Q, IC, HC>
+where
+    IC: CustomMsg,
+    HC: CustomMsg,
+{
+    init_fn: InitFn<I, IC>,
+    handle_fn: HandleFn<H, HC>,
+    query_fn: QueryFn<Q>,
+}
+
+impl<I, H, Q, IC, HC> ContractWrapper<I, H, Q, IC, HC>
+where
+    IC: CustomMsg,
+    HC: CustomMsg,
+{
+    pub fn new(init_fn: InitFn<I, IC>, handle_fn: HandleFn<H, HC>, query_fn: QueryFn<Q>) -> Self {
+        ContractWrapper {
+            init_fn,
+            handle_fn,
+            query_fn,
+        }
+    }
+}
+
+impl<I, H, Q, IC, HC> Contract for ContractWrapper<I, H, Q, IC, HC>
+where
+    I: DeserializeOwned,
+    H: DeserializeOwned,
+    Q: DeserializeOwned,
+    IC: CustomMsg,
+    HC: CustomMsg,
+{
+    fn init(
+        &self,
+        deps: &mut SimDeps,
+        env: Env,
+        msg: &[u8],
+    ) -> StdResult<InitResponse<TerraMsgWrapper>> {
+        let res = (self.init_fn)(deps, env, from_slice(msg)?)?;
+        Ok(InitResponse {
+            messages: convert_messages(res.messages)?,
+            log: res.log,
+        })
+    }
+
+    fn handle(
+        &self,
+        deps: &mut SimDeps,
+        env: Env,
+        msg: &[u8],
+    ) -> StdResult<HandleResponse<TerraMsgWrapper>> {
+        let res = (self.handle_fn)(deps, env, from_slice(msg)?)?;
+        Ok(HandleResponse {
+            messages: convert_messages(res.messages)?,
+            log: res.log,
+            data: res.data,
+        })
+    }
+
+    fn query(&self, deps: &SimDeps, msg: &[u8]) -> StdResult<Binary> {
+        (self.query_fn)(deps, from_slice(msg)?)
+    }
+}
+
+fn convert_messages<C: CustomMsg>(messages: Vec<CosmosMsg<C>>) -> StdResult<Vec<SimMsg>> {
+    messages
+        .into_iter()
+        .map(|msg| {
+            Ok(match msg {
+                CosmosMsg::Bank(msg) => CosmosMsg::Bank(msg),
+                CosmosMsg::Custom(msg) => CosmosMsg::Custom(msg.into_terra()?),
+                CosmosMsg::Staking(msg) => CosmosMsg::Staking(msg),
+                CosmosMsg::Wasm(msg) => CosmosMsg::Wasm(msg),
+            })
+        })
+        .collect()
+}
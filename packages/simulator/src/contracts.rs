@@ -0,0 +1,76 @@
+use crate::contract::{Contract, ContractWrapper};
+
+pub use crate::reward::reward_contract;
+pub use crate::token::token_contract;
+
+pub fn market_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        moneymarket_market::contract::init,
+        moneymarket_market::contract::handle,
+        moneymarket_market::contract::query,
+    ))
+}
+
+pub fn overseer_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        moneymarket_overseer::contract::init,
+        moneymarket_overseer::contract::handle,
+        moneymarket_overseer::contract::query,
+    ))
+}
+
+pub fn custody_bluna_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        moneymarket_custody_bluna::contract::init,
+        moneymarket_custody_bluna::contract::handle,
+        moneymarket_custody_bluna::contract::query,
+    ))
+}
+
+pub fn liquidation_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        moneymarket_liquidation::contract::init,
+        moneymarket_liquidation::contract::handle,
+        moneymarket_liquidation::contract::query,
+    ))
+}
+
+pub fn oracle_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        moneymarket_oracle::contract::init,
+        moneymarket_oracle::contract::handle,
+        moneymarket_oracle::contract::query,
+    ))
+}
+
+pub fn interest_model_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        moneymarket_interest_model::contract::init,
+        moneymarket_interest_model::contract::handle,
+        moneymarket_interest_model::contract::query,
+    ))
+}
+
+pub fn kinked_interest_model_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        moneymarket_kinked_interest_model::contract::init,
+        moneymarket_kinked_interest_model::contract::handle,
+        moneymarket_kinked_interest_model::contract::query,
+    ))
+}
+
+pub fn pid_interest_model_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        moneymarket_pid_interest_model::contract::init,
+        moneymarket_pid_interest_model::contract::handle,
+        moneymarket_pid_interest_model::contract::query,
+    ))
+}
+
+pub fn distribution_model_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        moneymarket_distribution_model::contract::init,
+        moneymarket_distribution_model::contract::handle,
+        moneymarket_distribution_model::contract::query,
+    ))
+}
@@ -0,0 +1,254 @@
+use std::str::FromStr;
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Coin, HumanAddr, StdResult};
+use cw20::MinterResponse;
+
+use moneymarket::custody::{BAssetInfo, InitMsg as CustodyInitMsg};
+use moneymarket::distribution_model::InitMsg as DistributionModelInitMsg;
+use moneymarket::interest_model::InitMsg as InterestModelInitMsg;
+use moneymarket::liquidation::InitMsg as LiquidationInitMsg;
+use moneymarket::market::{
+    ConfigResponse as MarketConfigResponse, HandleMsg as MarketHandleMsg, InitMsg as MarketInitMsg,
+    QueryMsg as MarketQueryMsg,
+};
+use moneymarket::oracle::{HandleMsg as OracleHandleMsg, InitMsg as OracleInitMsg};
+use moneymarket::overseer::{HandleMsg as OverseerHandleMsg, InitMsg as OverseerInitMsg};
+use terraswap::token::InitMsg as TokenInitMsg;
+
+use crate::app::Simulator;
+use crate::contracts::{
+    custody_bluna_contract, distribution_model_contract, interest_model_contract,
+    liquidation_contract, market_contract, oracle_contract, overseer_contract, reward_contract,
+    token_contract,
+};
+use crate::reward::InitMsg as RewardInitMsg;
+
+pub use moneymarket_market::contract::INITIAL_DEPOSIT_AMOUNT;
+
+/// Addresses of a money market deployed by [`MoneyMarket::deploy`]
+///
+/// The owner instantiates every contract, feeds the bLuna price and is the
+/// bLuna minter. Collector and distributor contracts are plain accounts;
+/// the reward contract is a stub that never pays rewards.
+pub struct MoneyMarket {
+    pub owner: HumanAddr,
+    pub stable_denom: String,
+    pub market: HumanAddr,
+    pub aterra: HumanAddr,
+    pub overseer: HumanAddr,
+    pub custody: HumanAddr,
+    pub bluna: HumanAddr,
+    pub liquidation: HumanAddr,
+    pub oracle: HumanAddr,
+    pub interest_model: HumanAddr,
+    pub distribution_model: HumanAddr,
+    pub collector: HumanAddr,
+    pub distributor: HumanAddr,
+    pub reward: HumanAddr,
+}
+
+impl MoneyMarket {
+    /// Instantiates and wires every money market contract, whitelisting
+    /// bLuna with a 50% max ltv; the owner needs `INITIAL_DEPOSIT_AMOUNT`
+    /// of the stable denom to seed the market
+    pub fn deploy(sim: &mut Simulator, owner: &HumanAddr, stable_denom: &str) -> StdResult<Self> {
+        let stable_denom = stable_denom.to_string();
+        let collector = HumanAddr::from("collector");
+        let distributor = HumanAddr::from("distributor");
+
+        let token_code_id = sim.store_code(token_contract());
+        let market_code_id = sim.store_code(market_contract());
+        let overseer_code_id = sim.store_code(overseer_contract());
+        let custody_code_id = sim.store_code(custody_bluna_contract());
+        let liquidation_code_id = sim.store_code(liquidation_contract());
+        let oracle_code_id = sim.store_code(oracle_contract());
+        let interest_model_code_id = sim.store_code(interest_model_contract());
+        let distribution_model_code_id = sim.store_code(distribution_model_contract());
+
+        let reward_code_id = sim.store_code(reward_contract());
+        let reward = sim.instantiate(reward_code_id, owner, &RewardInitMsg {}, &[])?;
+
+        let oracle = sim.instantiate(
+            oracle_code_id,
+            owner,
+            &OracleInitMsg {
+                owner: owner.clone(),
+                base_asset: stable_denom.clone(),
+                min_feeders: 1,
+                feed_window: 60,
+            },
+            &[],
+        )?;
+
+        let interest_model = sim.instantiate(
+            interest_model_code_id,
+            owner,
+            &InterestModelInitMsg {
+                owner: owner.clone(),
+                base_rate: Decimal256::from_str("0.000000004076272770")?,
+                interest_multiplier: Decimal256::from_str("0.000000085601728176")?,
+            },
+            &[],
+        )?;
+
+        let distribution_model = sim.instantiate(
+            distribution_model_code_id,
+            owner,
+            &DistributionModelInitMsg {
+                owner: owner.clone(),
+                emission_cap: Decimal256::from_uint256(100u64),
+                emission_floor: Decimal256::from_uint256(10u64),
+                increment_multiplier: Decimal256::from_str("1.007")?,
+                decrement_multiplier: Decimal256::from_str("0.997")?,
+            },
+            &[],
+        )?;
+
+        let liquidation = sim.instantiate(
+            liquidation_code_id,
+            owner,
+            &LiquidationInitMsg {
+                owner: owner.clone(),
+                oracle_contract: oracle.clone(),
+                stable_denom: stable_denom.clone(),
+                safe_ratio: Decimal256::percent(80),
+                bid_fee: Decimal256::percent(1),
+                max_premium_rate: Decimal256::percent(30),
+                liquidation_threshold: Uint256::from(200u64),
+                price_timeframe: 60,
+            },
+            &[],
+        )?;
+
+        let market = sim.instantiate(
+            market_code_id,
+            owner,
+            &MarketInitMsg {
+                owner_addr: owner.clone(),
+                stable_denom: stable_denom.clone(),
+                aterra_code_id: token_code_id,
+                anc_emission_rate: Decimal256::one(),
+                max_borrow_factor: Decimal256::percent(95),
+            },
+            &[Coin::new(INITIAL_DEPOSIT_AMOUNT, stable_denom.as_str())],
+        )?;
+
+        let overseer = sim.instantiate(
+            overseer_code_id,
+            owner,
+            &OverseerInitMsg {
+                owner_addr: owner.clone(),
+                oracle_contract: oracle.clone(),
+                market_contract: market.clone(),
+                liquidation_contract: liquidation.clone(),
+                collector_contract: collector.clone(),
+                stable_denom: stable_denom.clone(),
+                epoch_period: 86400,
+                threshold_deposit_rate: Decimal256::from_str("0.000000030572045778")?,
+                target_deposit_rate: Decimal256::from_str("0.000000040762727704")?,
+                buffer_distribution_factor: Decimal256::percent(20),
+                anc_purchase_factor: Decimal256::percent(10),
+                price_timeframe: 60,
+            },
+            &[],
+        )?;
+
+        let bluna = sim.instantiate(
+            token_code_id,
+            owner,
+            &TokenInitMsg {
+                name: "bluna".to_string(),
+                symbol: "BLUNA".to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: owner.clone(),
+                    cap: None,
+                }),
+                init_hook: None,
+            },
+            &[],
+        )?;
+
+        let custody = sim.instantiate(
+            custody_code_id,
+            owner,
+            &CustodyInitMsg {
+                owner: owner.clone(),
+                collateral_token: bluna.clone(),
+                overseer_contract: overseer.clone(),
+                market_contract: market.clone(),
+                reward_contract: reward.clone(),
+                liquidation_contract: liquidation.clone(),
+                stable_denom: stable_denom.clone(),
+                basset_info: BAssetInfo {
+                    name: "bluna".to_string(),
+                    symbol: "BLUNA".to_string(),
+                    decimals: 6,
+                },
+            },
+            &[],
+        )?;
+
+        sim.execute(
+            owner,
+            &market,
+            &MarketHandleMsg::RegisterContracts {
+                overseer_contract: overseer.clone(),
+                interest_model: interest_model.clone(),
+                distribution_model: distribution_model.clone(),
+                collector_contract: collector.clone(),
+                distributor_contract: distributor.clone(),
+            },
+            &[],
+        )?;
+
+        // the market config only resolves once every contract is registered
+        let market_config: MarketConfigResponse = sim.query(&market, &MarketQueryMsg::Config {})?;
+
+        sim.execute(
+            owner,
+            &overseer,
+            &OverseerHandleMsg::Whitelist {
+                name: "bluna".to_string(),
+                symbol: "BLUNA".to_string(),
+                collateral_token: bluna.clone(),
+                custody_contract: custody.clone(),
+                max_ltv: Decimal256::percent(50),
+                liquidation_threshold: Decimal256::percent(60),
+                max_locked_amount: None,
+                isolated: false,
+                debt_ceiling: None,
+            },
+            &[],
+        )?;
+
+        sim.execute(
+            owner,
+            &oracle,
+            &OracleHandleMsg::RegisterFeeder {
+                asset: bluna.to_string(),
+                feeder: owner.clone(),
+            },
+            &[],
+        )?;
+
+        Ok(MoneyMarket {
+            owner: owner.clone(),
+            stable_denom,
+            market,
+            aterra: market_config.aterra_contract,
+            overseer,
+            custody,
+            bluna,
+            liquidation,
+            oracle,
+            interest_model,
+            distribution_model,
+            collector,
+            distributor,
+            reward,
+        })
+    }
+}
@@ -0,0 +1,15 @@
+pub mod app;
+pub mod contract;
+pub mod contracts;
+pub mod deploy;
+pub mod querier;
+pub mod reward;
+pub mod storage;
+pub mod token;
+
+#[cfg(test)]
+mod testing;
+
+pub use crate::app::{SimResponse, Simulator};
+pub use crate::contract::{Contract, ContractWrapper, SimDeps};
+pub use crate::deploy::MoneyMarket;
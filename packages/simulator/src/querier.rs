@@ -0,0 +1,112 @@
+use std::rc::Rc;
+
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{
+    from_slice, to_binary, AllBalanceResponse, BalanceResponse, BankQuery, Binary, Coin, Extern,
+    Querier, QuerierResult, QueryRequest, ReadonlyStorage, StdError, SystemError, WasmQuery,
+};
+use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper};
+
+use crate::app::{Router, CANONICAL_LENGTH};
+
+/// Querier handed to every simulated contract
+///
+/// Smart and raw wasm queries are answered by the instantiated contracts,
+/// bank queries by the simulator bank and Terra treasury queries by the
+/// configured tax rate and caps.
+#[derive(Clone)]
+pub struct SimQuerier {
+    router: Rc<Router>,
+}
+
+impl SimQuerier {
+    pub(crate) fn new(router: Rc<Router>) -> Self {
+        SimQuerier { router }
+    }
+
+    fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
+        match request {
+            QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
+                Ok(to_binary(&BalanceResponse {
+                    amount: Coin {
+                        denom: denom.to_string(),
+                        amount: self.router.balance(address, denom),
+                    },
+                }))
+            }
+            QueryRequest::Bank(BankQuery::AllBalances { address }) => {
+                Ok(to_binary(&AllBalanceResponse {
+                    amount: self.router.all_balances(address),
+                }))
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                let (code, storage) = match self.router.instance(contract_addr) {
+                    Some(instance) => instance,
+                    None => {
+                        return Err(SystemError::NoSuchContract {
+                            addr: contract_addr.clone(),
+                        })
+                    }
+                };
+
+                let deps = Extern {
+                    storage,
+                    api: MockApi::new(CANONICAL_LENGTH),
+                    querier: self.clone(),
+                };
+                Ok(code.query(&deps, msg.as_slice()))
+            }
+            QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
+                let (_, storage) = match self.router.instance(contract_addr) {
+                    Some(instance) => instance,
+                    None => {
+                        return Err(SystemError::NoSuchContract {
+                            addr: contract_addr.clone(),
+                        })
+                    }
+                };
+
+                Ok(match storage.get(key.as_slice()) {
+                    Some(value) => to_binary(&Binary(value)),
+                    None => Err(StdError::not_found("raw key")),
+                })
+            }
+            QueryRequest::Custom(TerraQueryWrapper { query_data, .. }) => match query_data {
+                TerraQuery::TaxRate {} => Ok(to_binary(&TaxRateResponse {
+                    rate: self.router.tax.borrow().rate,
+                })),
+                TerraQuery::TaxCap { denom } => Ok(to_binary(&TaxCapResponse {
+                    cap: self
+                        .router
+                        .tax
+                        .borrow()
+                        .caps
+                        .get(denom)
+                        .copied()
+                        .unwrap_or_default(),
+                })),
+                _ => Err(SystemError::UnsupportedRequest {
+                    kind: "terra".to_string(),
+                }),
+            },
+            QueryRequest::Staking(_) => Err(SystemError::UnsupportedRequest {
+                kind: "staking".to_string(),
+            }),
+        }
+    }
+}
+
+impl Querier for SimQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
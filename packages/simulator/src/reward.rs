@@ -0,0 +1,41 @@
+use cosmwasm_std::{
+    Binary, Env, HandleResponse, HandleResult, HumanAddr, InitResponse, InitResult, StdError,
+    StdResult,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::{Contract, ContractWrapper, SimDeps};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleMsg {
+    ClaimRewards { recipient: Option<HumanAddr> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {}
+
+/// Minimal bAsset reward contract; claiming rewards pays nothing,
+/// so custody reward distribution runs without any swap
+pub fn reward_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(init, handle, query))
+}
+
+pub fn init(_deps: &mut SimDeps, _env: Env, _msg: InitMsg) -> InitResult {
+    Ok(InitResponse::default())
+}
+
+pub fn handle(_deps: &mut SimDeps, _env: Env, msg: HandleMsg) -> HandleResult {
+    match msg {
+        HandleMsg::ClaimRewards { .. } => Ok(HandleResponse::default()),
+    }
+}
+
+pub fn query(_deps: &SimDeps, _msg: QueryMsg) -> StdResult<Binary> {
+    Err(StdError::generic_err("Reward contract has no queries"))
+}
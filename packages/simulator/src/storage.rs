@@ -0,0 +1,73 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::rc::Rc;
+
+use cosmwasm_std::{Order, ReadonlyStorage, Storage, KV};
+
+/// Key value store of a single contract instance
+///
+/// Clones share the same underlying map, which lets the querier read a
+/// contract's storage while another contract is being executed.
+#[derive(Clone, Default)]
+pub struct ContractStorage(Rc<RefCell<BTreeMap<Vec<u8>, Vec<u8>>>>);
+
+impl ContractStorage {
+    pub fn new() -> Self {
+        ContractStorage::default()
+    }
+
+    /// Copy of the current contents, used to roll back a failed execution
+    pub fn snapshot(&self) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        self.0.borrow().clone()
+    }
+
+    pub fn restore(&self, snapshot: BTreeMap<Vec<u8>, Vec<u8>>) {
+        *self.0.borrow_mut() = snapshot;
+    }
+}
+
+impl ReadonlyStorage for ContractStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.borrow().get(key).cloned()
+    }
+
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = KV> + 'a> {
+        let start = start.map_or(Bound::Unbounded, |s| Bound::Included(s.to_vec()));
+        let end = end.map_or(Bound::Unbounded, |e| Bound::Excluded(e.to_vec()));
+
+        // an inverted range is empty rather than a panic in BTreeMap::range
+        if let (Bound::Included(s), Bound::Excluded(e)) = (&start, &end) {
+            if s >= e {
+                return Box::new(std::iter::empty());
+            }
+        }
+
+        let mut items: Vec<KV> = self
+            .0
+            .borrow()
+            .range((start, end))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        if let Order::Descending = order {
+            items.reverse();
+        }
+
+        Box::new(items.into_iter())
+    }
+}
+
+impl Storage for ContractStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.0.borrow_mut().insert(key.to_vec(), value.to_vec());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.0.borrow_mut().remove(key);
+    }
+}
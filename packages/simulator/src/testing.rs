@@ -0,0 +1,884 @@
+use std::str::FromStr;
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{to_binary, Coin, Extern, HumanAddr, StdError, Uint128};
+use cw20::{BalanceResponse, Cw20HandleMsg, Cw20QueryMsg};
+
+use moneymarket::custody::{
+    BorrowerResponse, Cw20HookMsg as CustodyCw20HookMsg, QueryMsg as CustodyQueryMsg,
+};
+use moneymarket::kinked_interest_model::InitMsg as KinkedInterestModelInitMsg;
+use moneymarket::liquidation::HandleMsg as LiquidationHandleMsg;
+use moneymarket::market::{
+    BorrowerInfoResponse, HandleMsg as MarketHandleMsg, QueryMsg as MarketQueryMsg,
+};
+use moneymarket::oracle::{HandleMsg as OracleHandleMsg, PriceFeed, PriceStatus};
+use moneymarket::overseer::{
+    BorrowLimitResponse, CollateralsResponse, HandleMsg as OverseerHandleMsg,
+    QueryMsg as OverseerQueryMsg,
+};
+use moneymarket::pid_interest_model::{
+    HandleMsg as PidInterestModelHandleMsg, InitMsg as PidInterestModelInitMsg,
+    QueryMsg as PidInterestModelQueryMsg, StateResponse as PidInterestModelStateResponse,
+};
+use moneymarket::querier::{query_supply, query_token_balance};
+
+use crate::app::{Simulator, CANONICAL_LENGTH};
+use crate::contracts::{kinked_interest_model_contract, pid_interest_model_contract};
+use crate::deploy::{MoneyMarket, INITIAL_DEPOSIT_AMOUNT};
+use crate::storage::ContractStorage;
+
+const STABLE_DENOM: &str = "uusd";
+
+fn setup() -> (Simulator, MoneyMarket) {
+    let mut sim = Simulator::new();
+    let owner = HumanAddr::from("owner");
+    sim.init_balance(
+        &owner,
+        vec![Coin::new(INITIAL_DEPOSIT_AMOUNT, STABLE_DENOM)],
+    );
+    sim.init_balance(
+        &HumanAddr::from("depositor"),
+        vec![Coin::new(10_000_000u128, STABLE_DENOM)],
+    );
+    sim.init_balance(
+        &HumanAddr::from("liquidator"),
+        vec![Coin::new(10_000_000u128, STABLE_DENOM)],
+    );
+
+    let mm = MoneyMarket::deploy(&mut sim, &owner, STABLE_DENOM).unwrap();
+    (sim, mm)
+}
+
+fn feed_bluna_price(sim: &mut Simulator, mm: &MoneyMarket, price: Decimal256) {
+    sim.execute(
+        &mm.owner,
+        &mm.oracle,
+        &OracleHandleMsg::FeedPrice {
+            prices: vec![(mm.bluna.to_string(), price)],
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn token_balance(sim: &Simulator, token: &HumanAddr, address: &HumanAddr) -> Uint128 {
+    let res: BalanceResponse = sim
+        .query(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.clone(),
+            },
+        )
+        .unwrap();
+    res.balance
+}
+
+fn loan_amount(sim: &Simulator, mm: &MoneyMarket, borrower: &HumanAddr) -> Uint256 {
+    let res: BorrowerInfoResponse = sim
+        .query(
+            &mm.market,
+            &MarketQueryMsg::BorrowerInfo {
+                borrower: borrower.clone(),
+                block_height: Some(sim.block_info().height),
+            },
+        )
+        .unwrap();
+    res.loan_amount
+}
+
+/// Mints bLuna to the borrower, deposits it into custody and locks it
+fn deposit_and_lock(sim: &mut Simulator, mm: &MoneyMarket, borrower: &HumanAddr, amount: u128) {
+    sim.execute(
+        &mm.owner,
+        &mm.bluna,
+        &Cw20HandleMsg::Mint {
+            recipient: borrower.clone(),
+            amount: Uint128(amount),
+        },
+        &[],
+    )
+    .unwrap();
+
+    sim.execute(
+        borrower,
+        &mm.bluna,
+        &Cw20HandleMsg::Send {
+            contract: mm.custody.clone(),
+            amount: Uint128(amount),
+            msg: Some(to_binary(&CustodyCw20HookMsg::DepositCollateral {}).unwrap()),
+        },
+        &[],
+    )
+    .unwrap();
+
+    sim.execute(
+        borrower,
+        &mm.overseer,
+        &OverseerHandleMsg::LockCollateral {
+            collaterals: vec![(mm.bluna.clone(), Uint256::from(amount))],
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn deposit_lock_borrow_and_liquidate() {
+    let (mut sim, mm) = setup();
+    let depositor = HumanAddr::from("depositor");
+    let borrower = HumanAddr::from("borrower");
+    let liquidator = HumanAddr::from("liquidator");
+
+    // depositing mints aTerra through the real token contract
+    sim.execute(
+        &depositor,
+        &mm.market,
+        &MarketHandleMsg::DepositStable {},
+        &[Coin::new(5_000_000u128, STABLE_DENOM)],
+    )
+    .unwrap();
+    assert_eq!(
+        token_balance(&sim, &mm.aterra, &depositor),
+        Uint128(5_000_000)
+    );
+    assert_eq!(
+        sim.balance(&mm.market, STABLE_DENOM),
+        Uint128(INITIAL_DEPOSIT_AMOUNT + 5_000_000)
+    );
+
+    feed_bluna_price(&mut sim, &mm, Decimal256::one());
+    deposit_and_lock(&mut sim, &mm, &borrower, 2_000_000);
+
+    let custody_res: BorrowerResponse = sim
+        .query(
+            &mm.custody,
+            &CustodyQueryMsg::Borrower {
+                address: borrower.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(custody_res.balance, Uint256::from(2_000_000u64));
+    assert_eq!(custody_res.spendable, Uint256::zero());
+
+    // borrow limit is 2_000_000 * 1 * 0.5
+    sim.execute(
+        &borrower,
+        &mm.market,
+        &MarketHandleMsg::BorrowStable {
+            borrow_amount: Uint256::from(800_000u64),
+            to: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(sim.balance(&borrower, STABLE_DENOM), Uint128(800_000));
+    assert_eq!(loan_amount(&sim, &mm, &borrower), Uint256::from(800_000u64));
+
+    sim.execute(
+        &liquidator,
+        &mm.liquidation,
+        &LiquidationHandleMsg::SubmitBid {
+            collateral_token: mm.bluna.clone(),
+            premium_rate: Decimal256::percent(5),
+        },
+        &[Coin::new(5_000_000u128, STABLE_DENOM)],
+    )
+    .unwrap();
+
+    // safely collateralized loans cannot be liquidated
+    let res = sim.execute(
+        &liquidator,
+        &mm.overseer,
+        &OverseerHandleMsg::LiquidateCollateral {
+            borrower: borrower.clone(),
+        },
+        &[],
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot liquidate safely collateralized loan")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // price drop lowers the liquidation limit to 720_000
+    sim.update_block(|block| {
+        block.height += 100;
+        block.time += 30;
+    });
+    feed_bluna_price(&mut sim, &mm, Decimal256::percent(60));
+
+    let loan_before = loan_amount(&sim, &mm, &borrower);
+    let market_balance_before = sim.balance(&mm.market, STABLE_DENOM);
+    sim.execute(
+        &liquidator,
+        &mm.overseer,
+        &OverseerHandleMsg::LiquidateCollateral {
+            borrower: borrower.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // custody, overseer and the liquidator agree on the liquidated amount
+    let liquidated = token_balance(&sim, &mm.bluna, &liquidator);
+    assert!(!liquidated.is_zero());
+    let custody_res: BorrowerResponse = sim
+        .query(
+            &mm.custody,
+            &CustodyQueryMsg::Borrower {
+                address: borrower.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        custody_res.balance,
+        Uint256::from(2_000_000u64) - Uint256::from(liquidated)
+    );
+    let collaterals: CollateralsResponse = sim
+        .query(
+            &mm.overseer,
+            &OverseerQueryMsg::Collaterals {
+                borrower: borrower.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        collaterals.collaterals,
+        vec![(mm.bluna.clone(), custody_res.balance)]
+    );
+
+    // the bid repaid the loan through the market and paid the overseer a fee
+    let repaid =
+        Uint256::from(sim.balance(&mm.market, STABLE_DENOM)) - Uint256::from(market_balance_before);
+    assert!(!repaid.is_zero());
+    assert_eq!(loan_amount(&sim, &mm, &borrower), loan_before - repaid);
+    assert!(!sim.balance(&mm.overseer, STABLE_DENOM).is_zero());
+}
+
+#[test]
+fn failed_execution_is_reverted() {
+    let (mut sim, mm) = setup();
+    let borrower = HumanAddr::from("borrower");
+    let liquidator = HumanAddr::from("liquidator");
+
+    feed_bluna_price(&mut sim, &mm, Decimal256::one());
+    deposit_and_lock(&mut sim, &mm, &borrower, 2_000_000);
+    sim.execute(
+        &borrower,
+        &mm.market,
+        &MarketHandleMsg::BorrowStable {
+            borrow_amount: Uint256::from(800_000u64),
+            to: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the bid cannot cover the liquidated collateral
+    sim.execute(
+        &liquidator,
+        &mm.liquidation,
+        &LiquidationHandleMsg::SubmitBid {
+            collateral_token: mm.bluna.clone(),
+            premium_rate: Decimal256::percent(5),
+        },
+        &[Coin::new(10u128, STABLE_DENOM)],
+    )
+    .unwrap();
+    feed_bluna_price(&mut sim, &mm, Decimal256::percent(60));
+
+    // overseer and custody update their state before the bid fails,
+    // which has to be rolled back with the rest of the transaction
+    let res = sim.execute(
+        &liquidator,
+        &mm.overseer,
+        &OverseerHandleMsg::LiquidateCollateral {
+            borrower: borrower.clone(),
+        },
+        &[],
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert!(msg.starts_with("Insufficient bid balance"))
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let custody_res: BorrowerResponse = sim
+        .query(
+            &mm.custody,
+            &CustodyQueryMsg::Borrower {
+                address: borrower.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(custody_res.balance, Uint256::from(2_000_000u64));
+    let collaterals: CollateralsResponse = sim
+        .query(
+            &mm.overseer,
+            &OverseerQueryMsg::Collaterals {
+                borrower: borrower.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        collaterals.collaterals,
+        vec![(mm.bluna.clone(), Uint256::from(2_000_000u64))]
+    );
+    assert_eq!(
+        token_balance(&sim, &mm.bluna, &mm.custody),
+        Uint128(2_000_000)
+    );
+    assert_eq!(
+        token_balance(&sim, &mm.bluna, &mm.liquidation),
+        Uint128::zero()
+    );
+    assert_eq!(sim.balance(&mm.liquidation, STABLE_DENOM), Uint128(10));
+}
+
+#[test]
+fn raw_queries_read_token_storage() {
+    let (mut sim, mm) = setup();
+    let depositor = HumanAddr::from("depositor");
+
+    sim.execute(
+        &depositor,
+        &mm.market,
+        &MarketHandleMsg::DepositStable {},
+        &[Coin::new(3_000_000u128, STABLE_DENOM)],
+    )
+    .unwrap();
+
+    let deps = Extern {
+        storage: ContractStorage::new(),
+        api: MockApi::new(CANONICAL_LENGTH),
+        querier: sim.querier(),
+    };
+    // the market holds the aTerra minted for its initial deposit
+    assert_eq!(
+        query_supply(&deps, &mm.aterra).unwrap(),
+        Uint256::from(INITIAL_DEPOSIT_AMOUNT + 3_000_000)
+    );
+    assert_eq!(
+        query_token_balance(&deps, &mm.aterra, &depositor).unwrap(),
+        Uint256::from(3_000_000u64)
+    );
+    assert_eq!(
+        query_token_balance(&deps, &mm.aterra, &HumanAddr::from("nobody")).unwrap(),
+        Uint256::zero()
+    );
+}
+
+#[test]
+fn market_accrues_interest_with_kinked_model() {
+    let (mut sim, mm) = setup();
+    let depositor = HumanAddr::from("depositor");
+    let borrower = HumanAddr::from("borrower");
+
+    let code_id = sim.store_code(kinked_interest_model_contract());
+    let kinked_interest_model = sim
+        .instantiate(
+            code_id,
+            &mm.owner,
+            &KinkedInterestModelInitMsg {
+                owner: mm.owner.clone(),
+                base_rate: Decimal256::from_str("0.0001").unwrap(),
+                interest_multiplier: Decimal256::zero(),
+                optimal_utilization: Decimal256::percent(10),
+                jump_multiplier: Decimal256::from_str("0.0003").unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // the market only needs the new address, the query interface is shared
+    sim.execute(
+        &mm.owner,
+        &mm.market,
+        &MarketHandleMsg::UpdateConfig {
+            owner_addr: None,
+            max_borrow_factor: None,
+            interest_model: Some(kinked_interest_model.clone()),
+            distribution_model: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    sim.execute(
+        &depositor,
+        &mm.market,
+        &MarketHandleMsg::DepositStable {},
+        &[Coin::new(5_000_000u128, STABLE_DENOM)],
+    )
+    .unwrap();
+    feed_bluna_price(&mut sim, &mm, Decimal256::one());
+    deposit_and_lock(&mut sim, &mm, &borrower, 2_000_000);
+    sim.execute(
+        &borrower,
+        &mm.market,
+        &MarketHandleMsg::BorrowStable {
+            borrow_amount: Uint256::from(800_000u64),
+            to: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // utilization_ratio = 800_000 / 6_000_000, above the kink
+    // borrow_rate = 0.0001 + (0.13333 - 0.1) * 0.0003 = 0.000109999.. per block
+    // loan_amount = 800_000 * (1 + 100 * 0.000109999..), truncated
+    sim.update_block(|block| block.height += 100);
+    assert_eq!(loan_amount(&sim, &mm, &borrower), Uint256::from(808_799u64));
+}
+
+#[test]
+fn market_uses_rate_observed_by_pid_model() {
+    let (mut sim, mm) = setup();
+    let borrower = HumanAddr::from("borrower");
+
+    let code_id = sim.store_code(pid_interest_model_contract());
+    let pid_interest_model = sim
+        .instantiate(
+            code_id,
+            &mm.owner,
+            &PidInterestModelInitMsg {
+                owner: mm.owner.clone(),
+                observer: mm.overseer.clone(),
+                market: mm.market.clone(),
+                target_utilization: Decimal256::percent(80),
+                proportional_gain: Decimal256::zero(),
+                integral_gain: Decimal256::from_str("0.001").unwrap(),
+                min_rate: Decimal256::from_str("0.0001").unwrap(),
+                max_rate: Decimal256::from_str("0.001").unwrap(),
+                initial_rate: Decimal256::from_str("0.0001").unwrap(),
+                epoch_period: 10,
+            },
+            &[],
+        )
+        .unwrap();
+    sim.execute(
+        &mm.owner,
+        &mm.market,
+        &MarketHandleMsg::UpdateConfig {
+            owner_addr: None,
+            max_borrow_factor: None,
+            interest_model: Some(pid_interest_model.clone()),
+            distribution_model: None,
+        },
+        &[],
+    )
+    .unwrap();
+    sim.execute(
+        &mm.owner,
+        &mm.overseer,
+        &OverseerHandleMsg::UpdateConfig {
+            owner_addr: None,
+            oracle_contract: None,
+            liquidation_contract: None,
+            threshold_deposit_rate: None,
+            target_deposit_rate: None,
+            buffer_distribution_factor: None,
+            anc_purchase_factor: None,
+            epoch_period: Some(10),
+            price_timeframe: None,
+            twap_window: None,
+            close_factor: None,
+            pid_interest_model: Some(pid_interest_model.clone()),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the market has no deposits besides the initial one,
+    // so borrowing 900_000 leaves it 90% utilized
+    feed_bluna_price(&mut sim, &mm, Decimal256::one());
+    deposit_and_lock(&mut sim, &mm, &borrower, 2_000_000);
+    sim.execute(
+        &borrower,
+        &mm.market,
+        &MarketHandleMsg::BorrowStable {
+            borrow_amount: Uint256::from(900_000u64),
+            to: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the overseer epoch operations make the model observe the market
+    // integral = 0.0001 + (0.9 - 0.8) * 0.001
+    sim.update_block(|block| block.height += 10);
+    sim.execute(
+        &borrower,
+        &mm.overseer,
+        &OverseerHandleMsg::ExecuteEpochOperations {},
+        &[],
+    )
+    .unwrap();
+    let state: PidInterestModelStateResponse = sim
+        .query(&pid_interest_model, &PidInterestModelQueryMsg::State {})
+        .unwrap();
+    assert_eq!(state.borrow_rate, Decimal256::from_str("0.0002").unwrap());
+    assert_eq!(state.last_observed_height, sim.block_info().height);
+
+    // loan_amount = 900_000 * (1 + 10 * 0.0002) * (1 + 100 * 0.0002)
+    sim.update_block(|block| block.height += 100);
+    assert_eq!(loan_amount(&sim, &mm, &borrower), Uint256::from(919_836u64));
+
+    // the model's own epoch has not passed yet, which the
+    // overseer skips instead of failing its epoch operations
+    sim.execute(
+        &mm.owner,
+        &pid_interest_model,
+        &PidInterestModelHandleMsg::UpdateConfig {
+            owner: None,
+            observer: None,
+            market: None,
+            target_utilization: None,
+            proportional_gain: None,
+            integral_gain: None,
+            min_rate: None,
+            max_rate: None,
+            epoch_period: Some(1000),
+        },
+        &[],
+    )
+    .unwrap();
+    sim.execute(
+        &borrower,
+        &mm.overseer,
+        &OverseerHandleMsg::ExecuteEpochOperations {},
+        &[],
+    )
+    .unwrap();
+    let res: PidInterestModelStateResponse = sim
+        .query(&pid_interest_model, &PidInterestModelQueryMsg::State {})
+        .unwrap();
+    assert_eq!(res, state);
+}
+
+fn borrow_limit(sim: &Simulator, mm: &MoneyMarket, borrower: &HumanAddr) -> Uint256 {
+    let res: BorrowLimitResponse = sim
+        .query(
+            &mm.overseer,
+            &OverseerQueryMsg::BorrowLimit {
+                borrower: borrower.clone(),
+                block_height: Some(sim.block_info().height),
+                block_time: Some(sim.block_info().time),
+            },
+        )
+        .unwrap();
+    res.borrow_limit
+}
+
+fn update_overseer_twap_window(sim: &mut Simulator, mm: &MoneyMarket, twap_window: u64) {
+    sim.execute(
+        &mm.owner,
+        &mm.overseer,
+        &OverseerHandleMsg::UpdateConfig {
+            owner_addr: None,
+            oracle_contract: None,
+            liquidation_contract: None,
+            threshold_deposit_rate: None,
+            target_deposit_rate: None,
+            buffer_distribution_factor: None,
+            anc_purchase_factor: None,
+            epoch_period: None,
+            price_timeframe: None,
+            twap_window: Some(twap_window),
+            close_factor: None,
+            pid_interest_model: None,
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn twap_pricing_ignores_a_single_bad_feed() {
+    let (mut sim, mm) = setup();
+    let depositor = HumanAddr::from("depositor");
+    let borrower = HumanAddr::from("borrower");
+
+    sim.execute(
+        &depositor,
+        &mm.market,
+        &MarketHandleMsg::DepositStable {},
+        &[Coin::new(5_000_000u128, STABLE_DENOM)],
+    )
+    .unwrap();
+    feed_bluna_price(&mut sim, &mm, Decimal256::one());
+    deposit_and_lock(&mut sim, &mm, &borrower, 2_000_000);
+    sim.execute(
+        &borrower,
+        &mm.market,
+        &MarketHandleMsg::BorrowStable {
+            borrow_amount: Uint256::from(800_000u64),
+            to: None,
+        },
+        &[],
+    )
+    .unwrap();
+    update_overseer_twap_window(&mut sim, &mm, 600);
+
+    // the price was 1 for the whole window before the bad feed
+    sim.update_block(|block| {
+        block.height += 100;
+        block.time += 600;
+    });
+    feed_bluna_price(&mut sim, &mm, Decimal256::percent(50));
+    assert_eq!(
+        borrow_limit(&sim, &mm, &borrower),
+        Uint256::from(1_000_000u64)
+    );
+
+    let res = sim.execute(
+        &HumanAddr::from("liquidator"),
+        &mm.overseer,
+        &OverseerHandleMsg::LiquidateCollateral {
+            borrower: borrower.clone(),
+        },
+        &[],
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot liquidate safely collateralized loan")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the bad price is weighted in as time passes
+    // (1 * 300 + 0.5 * 300) / 600
+    sim.update_block(|block| {
+        block.height += 50;
+        block.time += 300;
+    });
+    feed_bluna_price(&mut sim, &mm, Decimal256::percent(50));
+    assert_eq!(
+        borrow_limit(&sim, &mm, &borrower),
+        Uint256::from(750_000u64)
+    );
+
+    // spot pricing reacts immediately
+    update_overseer_twap_window(&mut sim, &mm, 0);
+    assert_eq!(
+        borrow_limit(&sim, &mm, &borrower),
+        Uint256::from(500_000u64)
+    );
+}
+
+#[test]
+fn tripped_circuit_breaker_blocks_unlock_and_liquidation() {
+    let (mut sim, mm) = setup();
+    let depositor = HumanAddr::from("depositor");
+    let borrower = HumanAddr::from("borrower");
+
+    sim.execute(
+        &depositor,
+        &mm.market,
+        &MarketHandleMsg::DepositStable {},
+        &[Coin::new(5_000_000u128, STABLE_DENOM)],
+    )
+    .unwrap();
+    feed_bluna_price(&mut sim, &mm, Decimal256::one());
+    deposit_and_lock(&mut sim, &mm, &borrower, 2_000_000);
+    sim.execute(
+        &borrower,
+        &mm.market,
+        &MarketHandleMsg::BorrowStable {
+            borrow_amount: Uint256::from(800_000u64),
+            to: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    sim.execute(
+        &mm.owner,
+        &mm.oracle,
+        &OracleHandleMsg::UpdateCircuitBreaker {
+            asset: mm.bluna.to_string(),
+            max_deviation_per_update: Some(Decimal256::percent(20)),
+            floor: None,
+            ceiling: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // a 60% drop would make the loan liquidatable, but it is quarantined
+    sim.update_block(|block| {
+        block.height += 1;
+        block.time += 5;
+    });
+    feed_bluna_price(&mut sim, &mm, Decimal256::percent(40));
+
+    let tripped_err = format!(
+        "Price circuit breaker is tripped for the asset: {}",
+        mm.bluna
+    );
+    let res = sim.execute(
+        &HumanAddr::from("liquidator"),
+        &mm.overseer,
+        &OverseerHandleMsg::LiquidateCollateral {
+            borrower: borrower.clone(),
+        },
+        &[],
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, tripped_err),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = sim.execute(
+        &borrower,
+        &mm.overseer,
+        &OverseerHandleMsg::UnlockCollateral {
+            collaterals: vec![(mm.bluna.clone(), Uint256::from(100_000u64))],
+        },
+        &[],
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, tripped_err),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // once the owner accepts the price, the overseer uses it again
+    sim.execute(
+        &mm.owner,
+        &mm.oracle,
+        &OracleHandleMsg::ResetCircuitBreaker {
+            asset: mm.bluna.to_string(),
+            accept_quarantined: true,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        borrow_limit(&sim, &mm, &borrower),
+        Uint256::from(400_000u64)
+    );
+}
+
+#[test]
+fn borrow_limit_values_collateral_at_the_bottom_of_the_confidence_band() {
+    let (mut sim, mm) = setup();
+    let borrower = HumanAddr::from("borrower");
+
+    feed_bluna_price(&mut sim, &mm, Decimal256::one());
+    deposit_and_lock(&mut sim, &mm, &borrower, 2_000_000);
+    assert_eq!(
+        borrow_limit(&sim, &mm, &borrower),
+        Uint256::from(1_000_000u64)
+    );
+
+    // 2_000_000 * (1 - 0.1) * 0.5
+    sim.execute(
+        &mm.owner,
+        &mm.oracle,
+        &OracleHandleMsg::FeedPriceWithConfidence {
+            prices: vec![PriceFeed {
+                asset: mm.bluna.to_string(),
+                price: Decimal256::one(),
+                confidence: Some(Decimal256::percent(10)),
+                status: PriceStatus::Trading,
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        borrow_limit(&sim, &mm, &borrower),
+        Uint256::from(900_000u64)
+    );
+}
+
+#[test]
+fn batch_liquidation_repays_each_borrower_with_its_own_proceeds() {
+    let (mut sim, mm) = setup();
+    let depositor = HumanAddr::from("depositor");
+    let keeper = HumanAddr::from("liquidator");
+    let unhealthy = [HumanAddr::from("borrower0"), HumanAddr::from("borrower1")];
+    let healthy = HumanAddr::from("borrower2");
+
+    sim.execute(
+        &depositor,
+        &mm.market,
+        &MarketHandleMsg::DepositStable {},
+        &[Coin::new(5_000_000u128, STABLE_DENOM)],
+    )
+    .unwrap();
+    feed_bluna_price(&mut sim, &mm, Decimal256::one());
+    for (borrower, borrow_amount) in &[
+        (&unhealthy[0], 800_000u64),
+        (&unhealthy[1], 750_000u64),
+        (&healthy, 500_000u64),
+    ] {
+        deposit_and_lock(&mut sim, &mm, borrower, 2_000_000);
+        sim.execute(
+            borrower,
+            &mm.market,
+            &MarketHandleMsg::BorrowStable {
+                borrow_amount: Uint256::from(*borrow_amount),
+                to: None,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    sim.execute(
+        &keeper,
+        &mm.liquidation,
+        &LiquidationHandleMsg::SubmitBid {
+            collateral_token: mm.bluna.clone(),
+            premium_rate: Decimal256::percent(5),
+        },
+        &[Coin::new(5_000_000u128, STABLE_DENOM)],
+    )
+    .unwrap();
+
+    // price drop lowers every liquidation limit to 720_000
+    sim.update_block(|block| {
+        block.height += 100;
+        block.time += 30;
+    });
+    feed_bluna_price(&mut sim, &mm, Decimal256::percent(60));
+
+    let borrowers = vec![unhealthy[0].clone(), healthy.clone(), unhealthy[1].clone()];
+    let loans_before: Vec<Uint256> = borrowers
+        .iter()
+        .map(|borrower| loan_amount(&sim, &mm, borrower))
+        .collect();
+    let market_balance_before = sim.balance(&mm.market, STABLE_DENOM);
+    sim.execute(
+        &keeper,
+        &mm.overseer,
+        &OverseerHandleMsg::LiquidateCollaterals {
+            borrowers: borrowers.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the healthy borrower is skipped and the keeper receives the collaterals
+    assert_eq!(loan_amount(&sim, &mm, &healthy), loans_before[1]);
+    assert!(!token_balance(&sim, &mm.bluna, &keeper).is_zero());
+
+    // together the repaid loans add up to what the market received
+    let mut repaid = Uint256::zero();
+    for (borrower, loan_before) in borrowers.iter().zip(loans_before.iter()) {
+        let loan_after = loan_amount(&sim, &mm, borrower);
+        if borrower != &healthy {
+            assert!(loan_after < *loan_before);
+        }
+        repaid += *loan_before - loan_after;
+    }
+    assert_eq!(
+        Uint256::from(sim.balance(&mm.market, STABLE_DENOM)) - Uint256::from(market_balance_before),
+        repaid
+    );
+}
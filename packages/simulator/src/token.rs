@@ -0,0 +1,207 @@
+use cosmwasm_std::{
+    log, to_binary, Api, Binary, CanonicalAddr, CosmosMsg, Env, HandleResponse, HandleResult,
+    InitResponse, InitResult, StdError, StdResult, Storage, Uint128, WasmMsg,
+};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use cw20::{
+    BalanceResponse, Cw20HandleMsg, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse, TokenInfoResponse,
+};
+use terraswap::token::InitMsg;
+
+use crate::contract::{Contract, ContractWrapper, SimDeps};
+
+// Same keys as the terraswap token, so raw balance and supply queries
+// from `moneymarket::querier` read them the way they do on chain
+static PREFIX_BALANCE: &[u8] = b"balance";
+static KEY_TOKEN_INFO: &[u8] = b"token_info";
+static KEY_MINTER: &[u8] = b"minter";
+
+/// Minimal cw20 token used for aTerra and bAsset collaterals;
+/// supports transfer, send, mint and burn
+pub fn token_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(init, handle, query))
+}
+
+pub fn init(deps: &mut SimDeps, _env: Env, msg: InitMsg) -> InitResult {
+    msg.validate()?;
+
+    let mut total_supply: u128 = 0;
+    for coin in msg.initial_balances.iter() {
+        let address_raw = deps.api.canonical_address(&coin.address)?;
+        add_balance(&mut deps.storage, &address_raw, coin.amount)?;
+        total_supply += coin.amount.u128();
+    }
+
+    singleton(&mut deps.storage, KEY_TOKEN_INFO).save(&TokenInfoResponse {
+        name: msg.name,
+        symbol: msg.symbol,
+        decimals: msg.decimals,
+        total_supply: Uint128(total_supply),
+    })?;
+    singleton(&mut deps.storage, KEY_MINTER).save(&msg.mint)?;
+
+    let messages = match msg.init_hook {
+        Some(hook) => vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hook.contract_addr,
+            msg: hook.msg,
+            send: vec![],
+        })],
+        None => vec![],
+    };
+
+    Ok(InitResponse {
+        messages,
+        log: vec![],
+    })
+}
+
+pub fn handle(deps: &mut SimDeps, env: Env, msg: Cw20HandleMsg) -> HandleResult {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    match msg {
+        Cw20HandleMsg::Transfer { recipient, amount } => {
+            sub_balance(&mut deps.storage, &sender_raw, amount)?;
+            add_balance(
+                &mut deps.storage,
+                &deps.api.canonical_address(&recipient)?,
+                amount,
+            )?;
+
+            Ok(HandleResponse {
+                messages: vec![],
+                log: vec![
+                    log("action", "transfer"),
+                    log("from", env.message.sender),
+                    log("to", recipient),
+                    log("amount", amount),
+                ],
+                data: None,
+            })
+        }
+        Cw20HandleMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => {
+            sub_balance(&mut deps.storage, &sender_raw, amount)?;
+            add_balance(
+                &mut deps.storage,
+                &deps.api.canonical_address(&contract)?,
+                amount,
+            )?;
+
+            Ok(HandleResponse {
+                messages: vec![Cw20ReceiveMsg {
+                    sender: env.message.sender.clone(),
+                    amount,
+                    msg,
+                }
+                .into_cosmos_msg(contract.clone())?],
+                log: vec![
+                    log("action", "send"),
+                    log("from", env.message.sender),
+                    log("to", contract),
+                    log("amount", amount),
+                ],
+                data: None,
+            })
+        }
+        Cw20HandleMsg::Mint { recipient, amount } => {
+            let minter: Option<MinterResponse> =
+                singleton_read(&deps.storage, KEY_MINTER).load()?;
+            if minter.map(|m| m.minter) != Some(env.message.sender) {
+                return Err(StdError::unauthorized());
+            }
+
+            add_balance(
+                &mut deps.storage,
+                &deps.api.canonical_address(&recipient)?,
+                amount,
+            )?;
+            singleton(&mut deps.storage, KEY_TOKEN_INFO).update(
+                |mut info: TokenInfoResponse| {
+                    info.total_supply += amount;
+                    Ok(info)
+                },
+            )?;
+
+            Ok(HandleResponse {
+                messages: vec![],
+                log: vec![
+                    log("action", "mint"),
+                    log("to", recipient),
+                    log("amount", amount),
+                ],
+                data: None,
+            })
+        }
+        Cw20HandleMsg::Burn { amount } => {
+            sub_balance(&mut deps.storage, &sender_raw, amount)?;
+            singleton(&mut deps.storage, KEY_TOKEN_INFO).update(
+                |mut info: TokenInfoResponse| {
+                    info.total_supply = (info.total_supply - amount)?;
+                    Ok(info)
+                },
+            )?;
+
+            Ok(HandleResponse {
+                messages: vec![],
+                log: vec![
+                    log("action", "burn"),
+                    log("from", env.message.sender),
+                    log("amount", amount),
+                ],
+                data: None,
+            })
+        }
+        _ => Err(StdError::generic_err(
+            "Allowances are not supported by the simulated token",
+        )),
+    }
+}
+
+pub fn query(deps: &SimDeps, msg: Cw20QueryMsg) -> StdResult<Binary> {
+    match msg {
+        Cw20QueryMsg::Balance { address } => to_binary(&BalanceResponse {
+            balance: read_balance(&deps.storage, &deps.api.canonical_address(&address)?)?,
+        }),
+        Cw20QueryMsg::TokenInfo {} => {
+            let info: TokenInfoResponse = singleton_read(&deps.storage, KEY_TOKEN_INFO).load()?;
+            to_binary(&info)
+        }
+        Cw20QueryMsg::Minter {} => {
+            let minter: Option<MinterResponse> =
+                singleton_read(&deps.storage, KEY_MINTER).load()?;
+            to_binary(&minter)
+        }
+        _ => Err(StdError::generic_err(
+            "Allowances are not supported by the simulated token",
+        )),
+    }
+}
+
+fn read_balance<S: Storage>(storage: &S, address: &CanonicalAddr) -> StdResult<Uint128> {
+    let balance_bucket: ReadonlyBucket<S, Uint128> = ReadonlyBucket::new(PREFIX_BALANCE, storage);
+    Ok(balance_bucket
+        .may_load(address.as_slice())?
+        .unwrap_or_else(Uint128::zero))
+}
+
+fn add_balance<S: Storage>(
+    storage: &mut S,
+    address: &CanonicalAddr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let balance = read_balance(storage, address)? + amount;
+    let mut balance_bucket: Bucket<S, Uint128> = Bucket::new(PREFIX_BALANCE, storage);
+    balance_bucket.save(address.as_slice(), &balance)
+}
+
+fn sub_balance<S: Storage>(
+    storage: &mut S,
+    address: &CanonicalAddr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let balance = (read_balance(storage, address)? - amount)?;
+    let mut balance_bucket: Bucket<S, Uint128> = Bucket::new(PREFIX_BALANCE, storage);
+    balance_bucket.save(address.as_slice(), &balance)
+}
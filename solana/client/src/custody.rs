@@ -0,0 +1,172 @@
+use solana_money_market::pda::find_program_data;
+use solana_money_market_custody::instruction::CustodyInstruction;
+use solana_money_market_custody::pda::{
+    find_borrower_info, find_collateral_vault, find_custody_authority, find_custody_config,
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::instruction;
+
+/// Accounts of the custody of a collateral mint for the market of a stable
+/// mint
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CustodyAddresses {
+    pub program_id: Pubkey,
+    pub stable_mint: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub config: Pubkey,
+}
+
+impl CustodyAddresses {
+    pub fn new(program_id: Pubkey, stable_mint: Pubkey, collateral_mint: Pubkey) -> Self {
+        CustodyAddresses {
+            program_id,
+            stable_mint,
+            collateral_mint,
+            authority: find_custody_authority(&program_id, &stable_mint, &collateral_mint).0,
+            vault: find_collateral_vault(&program_id, &stable_mint, &collateral_mint).0,
+            config: find_custody_config(&program_id, &stable_mint, &collateral_mint).0,
+        }
+    }
+
+    pub fn borrower_info(&self, borrower: &Pubkey) -> Pubkey {
+        find_borrower_info(
+            &self.program_id,
+            &self.stable_mint,
+            &self.collateral_mint,
+            borrower,
+        )
+        .0
+    }
+}
+
+/// The payer must be the upgrade authority of the custody program.
+pub fn initialize_custody(
+    custody: &CustodyAddresses,
+    payer: &Pubkey,
+    overseer: Pubkey,
+) -> Instruction {
+    instruction(
+        custody.program_id,
+        &CustodyInstruction::InitializeCustody { overseer },
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(find_program_data(&custody.program_id).0, false),
+            AccountMeta::new_readonly(custody.stable_mint, false),
+            AccountMeta::new_readonly(custody.collateral_mint, false),
+            AccountMeta::new_readonly(custody.authority, false),
+            AccountMeta::new(custody.vault, false),
+            AccountMeta::new(custody.config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn deposit_collateral(
+    custody: &CustodyAddresses,
+    borrower: &Pubkey,
+    source: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        custody.program_id,
+        &CustodyInstruction::DepositCollateral { amount },
+        vec![
+            AccountMeta::new(*borrower, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(custody.borrower_info(borrower), false),
+            AccountMeta::new(custody.vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+/// Withdraws all spendable collateral when no amount is given.
+pub fn withdraw_collateral(
+    custody: &CustodyAddresses,
+    borrower: &Pubkey,
+    destination: &Pubkey,
+    amount: Option<u64>,
+) -> Instruction {
+    instruction(
+        custody.program_id,
+        &CustodyInstruction::WithdrawCollateral { amount },
+        vec![
+            AccountMeta::new_readonly(*borrower, true),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(custody.borrower_info(borrower), false),
+            AccountMeta::new(custody.vault, false),
+            AccountMeta::new_readonly(custody.authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Lock, unlock and liquidate are only signed by the overseer program through
+// a CPI; borrowers go through the overseer instructions instead.
+
+pub fn lock_collateral(
+    custody: &CustodyAddresses,
+    overseer_authority: &Pubkey,
+    borrower: Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        custody.program_id,
+        &CustodyInstruction::LockCollateral { borrower, amount },
+        vec![
+            AccountMeta::new_readonly(*overseer_authority, true),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(custody.borrower_info(&borrower), false),
+        ],
+    )
+}
+
+pub fn unlock_collateral(
+    custody: &CustodyAddresses,
+    overseer_authority: &Pubkey,
+    borrower: Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        custody.program_id,
+        &CustodyInstruction::UnlockCollateral { borrower, amount },
+        vec![
+            AccountMeta::new_readonly(*overseer_authority, true),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(custody.borrower_info(&borrower), false),
+        ],
+    )
+}
+
+pub fn liquidate_collateral(
+    custody: &CustodyAddresses,
+    overseer_authority: &Pubkey,
+    destination: &Pubkey,
+    borrower: Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        custody.program_id,
+        &CustodyInstruction::LiquidateCollateral { borrower, amount },
+        vec![
+            AccountMeta::new_readonly(*overseer_authority, true),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(custody.borrower_info(&borrower), false),
+            AccountMeta::new(custody.vault, false),
+            AccountMeta::new_readonly(custody.authority, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
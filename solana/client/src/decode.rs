@@ -0,0 +1,48 @@
+//! Decoders for the data of the program state accounts
+
+use solana_money_market::state::{BorrowLimit, BorrowerInfo, Config, InterestModel, State};
+use solana_money_market_custody::state::{
+    BorrowerInfo as CustodyBorrowerInfo, Config as CustodyConfig,
+};
+use solana_money_market_overseer::state::{Collaterals, Config as OverseerConfig, WhitelistElem};
+use solana_program::{program_error::ProgramError, program_pack::Pack};
+
+pub fn market_config(data: &[u8]) -> Result<Config, ProgramError> {
+    Config::unpack(data)
+}
+
+pub fn market_state(data: &[u8]) -> Result<State, ProgramError> {
+    State::unpack(data)
+}
+
+pub fn interest_model(data: &[u8]) -> Result<InterestModel, ProgramError> {
+    InterestModel::unpack(data)
+}
+
+pub fn liability(data: &[u8]) -> Result<BorrowerInfo, ProgramError> {
+    BorrowerInfo::unpack(data)
+}
+
+pub fn borrow_limit(data: &[u8]) -> Result<BorrowLimit, ProgramError> {
+    BorrowLimit::unpack(data)
+}
+
+pub fn custody_config(data: &[u8]) -> Result<CustodyConfig, ProgramError> {
+    CustodyConfig::unpack(data)
+}
+
+pub fn custody_borrower_info(data: &[u8]) -> Result<CustodyBorrowerInfo, ProgramError> {
+    CustodyBorrowerInfo::unpack(data)
+}
+
+pub fn overseer_config(data: &[u8]) -> Result<OverseerConfig, ProgramError> {
+    OverseerConfig::unpack(data)
+}
+
+pub fn whitelist_elem(data: &[u8]) -> Result<WhitelistElem, ProgramError> {
+    WhitelistElem::unpack(data)
+}
+
+pub fn collaterals(data: &[u8]) -> Result<Collaterals, ProgramError> {
+    Collaterals::unpack(data)
+}
@@ -0,0 +1,21 @@
+//! Off-chain helpers for the Solana money market programs: PDA derivation,
+//! instruction builders with their accounts in program order, and decoders
+//! for the state accounts.
+
+pub mod custody;
+pub mod decode;
+pub mod market;
+pub mod overseer;
+
+use borsh::BorshSerialize;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+
+fn instruction<T: BorshSerialize>(
+    program_id: Pubkey,
+    data: &T,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    // Serializing instruction enums into a vector cannot fail
+    Instruction::new_with_bytes(program_id, &data.try_to_vec().unwrap(), accounts)
+}
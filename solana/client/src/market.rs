@@ -0,0 +1,207 @@
+use solana_money_market::instruction::MarketInstruction;
+use solana_money_market::pda::{
+    find_atoken_mint, find_config, find_interest_model, find_liability, find_market_authority,
+    find_program_data, find_stable_vault, find_state,
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::instruction;
+
+/// Accounts of the market of a stable mint
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MarketAddresses {
+    pub program_id: Pubkey,
+    pub stable_mint: Pubkey,
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub atoken_mint: Pubkey,
+    pub config: Pubkey,
+    pub state: Pubkey,
+    pub interest_model: Pubkey,
+}
+
+impl MarketAddresses {
+    pub fn new(program_id: Pubkey, stable_mint: Pubkey) -> Self {
+        MarketAddresses {
+            program_id,
+            stable_mint,
+            authority: find_market_authority(&program_id, &stable_mint).0,
+            vault: find_stable_vault(&program_id, &stable_mint).0,
+            atoken_mint: find_atoken_mint(&program_id, &stable_mint).0,
+            config: find_config(&program_id, &stable_mint).0,
+            state: find_state(&program_id, &stable_mint).0,
+            interest_model: find_interest_model(&program_id, &stable_mint).0,
+        }
+    }
+
+    pub fn liability(&self, borrower: &Pubkey) -> Pubkey {
+        find_liability(&self.program_id, &self.stable_mint, borrower).0
+    }
+}
+
+/// The payer must be the upgrade authority of the market program. Decimal
+/// arguments are raw values with 18 fractional digits.
+pub fn initialize_market(
+    market: &MarketAddresses,
+    payer: &Pubkey,
+    overseer: Pubkey,
+    base_rate: u128,
+    interest_multiplier: u128,
+    max_borrow_factor: u128,
+    target_deposit_rate: u128,
+) -> Instruction {
+    instruction(
+        market.program_id,
+        &MarketInstruction::InitializeMarket {
+            overseer,
+            base_rate,
+            interest_multiplier,
+            max_borrow_factor,
+            target_deposit_rate,
+        },
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(find_program_data(&market.program_id).0, false),
+            AccountMeta::new_readonly(market.stable_mint, false),
+            AccountMeta::new_readonly(market.authority, false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new(market.atoken_mint, false),
+            AccountMeta::new(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new(market.interest_model, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn deposit_stable(
+    market: &MarketAddresses,
+    depositor: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        market.program_id,
+        &MarketInstruction::DepositStable { amount },
+        vec![
+            AccountMeta::new_readonly(*depositor, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new(market.atoken_mint, false),
+            AccountMeta::new_readonly(market.authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn redeem_stable(
+    market: &MarketAddresses,
+    redeemer: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    burn_amount: u64,
+) -> Instruction {
+    instruction(
+        market.program_id,
+        &MarketInstruction::RedeemStable { burn_amount },
+        vec![
+            AccountMeta::new_readonly(*redeemer, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new(market.atoken_mint, false),
+            AccountMeta::new_readonly(market.authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+/// The borrow limit account has to be updated by the overseer in the same
+/// slot, see `overseer::update_borrow_limit`.
+pub fn borrow_stable(
+    market: &MarketAddresses,
+    borrower: &Pubkey,
+    destination: &Pubkey,
+    borrow_limit: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        market.program_id,
+        &MarketInstruction::BorrowStable { amount },
+        vec![
+            AccountMeta::new(*borrower, true),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(market.liability(borrower), false),
+            AccountMeta::new_readonly(*borrow_limit, false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new_readonly(market.atoken_mint, false),
+            AccountMeta::new_readonly(market.authority, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn repay_stable(
+    market: &MarketAddresses,
+    borrower: &Pubkey,
+    source: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        market.program_id,
+        &MarketInstruction::RepayStable { amount },
+        vec![
+            AccountMeta::new_readonly(*borrower, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(market.liability(borrower), false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new_readonly(market.atoken_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+/// Only the overseer program can sign this one, through a CPI.
+pub fn repay_stable_from_liquidation(
+    market: &MarketAddresses,
+    overseer_authority: &Pubkey,
+    source: &Pubkey,
+    borrower: Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        market.program_id,
+        &MarketInstruction::RepayStableFromLiquidation { borrower, amount },
+        vec![
+            AccountMeta::new_readonly(*overseer_authority, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(market.liability(&borrower), false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new_readonly(market.atoken_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
@@ -0,0 +1,285 @@
+use cosmwasm_bignumber::Uint256;
+use solana_money_market::pda::find_program_data;
+use solana_money_market_overseer::instruction::OverseerInstruction;
+use solana_money_market_overseer::pda::{
+    find_authority, find_borrow_limit, find_collaterals, find_config, find_proceeds,
+    find_whitelist_elem,
+};
+use solana_money_market_overseer::state::Collaterals;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::custody::CustodyAddresses;
+use crate::instruction;
+use crate::market::MarketAddresses;
+
+/// Accounts of the overseer of a market, along with the market and custody
+/// program it calls into
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OverseerAddresses {
+    pub program_id: Pubkey,
+    pub stable_mint: Pubkey,
+    pub authority: Pubkey,
+    pub proceeds: Pubkey,
+    pub config: Pubkey,
+    pub market: MarketAddresses,
+    pub custody_program: Pubkey,
+}
+
+impl OverseerAddresses {
+    pub fn new(program_id: Pubkey, market: MarketAddresses, custody_program: Pubkey) -> Self {
+        let stable_mint = market.stable_mint;
+        OverseerAddresses {
+            program_id,
+            stable_mint,
+            authority: find_authority(&program_id, &stable_mint).0,
+            proceeds: find_proceeds(&program_id, &stable_mint).0,
+            config: find_config(&program_id, &stable_mint).0,
+            market,
+            custody_program,
+        }
+    }
+
+    pub fn custody(&self, collateral_mint: &Pubkey) -> CustodyAddresses {
+        CustodyAddresses::new(self.custody_program, self.stable_mint, *collateral_mint)
+    }
+
+    pub fn whitelist_elem(&self, collateral_mint: &Pubkey) -> Pubkey {
+        find_whitelist_elem(&self.program_id, &self.stable_mint, collateral_mint).0
+    }
+
+    pub fn collaterals(&self, borrower: &Pubkey) -> Pubkey {
+        find_collaterals(&self.program_id, &self.stable_mint, borrower).0
+    }
+
+    pub fn borrow_limit(&self, borrower: &Pubkey) -> Pubkey {
+        find_borrow_limit(&self.program_id, &self.stable_mint, borrower).0
+    }
+
+    /// Accounts the overseer reads the loan of a borrower from
+    fn loan_accounts(&self, borrower: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.market.config, false),
+            AccountMeta::new_readonly(self.market.state, false),
+            AccountMeta::new_readonly(self.market.interest_model, false),
+            AccountMeta::new_readonly(self.market.liability(borrower), false),
+            AccountMeta::new_readonly(self.market.vault, false),
+            AccountMeta::new_readonly(self.market.atoken_mint, false),
+        ]
+    }
+
+    fn whitelist_elems<'a, I: IntoIterator<Item = &'a Pubkey>>(
+        &self,
+        collateral_mints: I,
+    ) -> Vec<AccountMeta> {
+        collateral_mints
+            .into_iter()
+            .map(|mint| AccountMeta::new_readonly(self.whitelist_elem(mint), false))
+            .collect()
+    }
+}
+
+/// The payer must be the upgrade authority of the overseer program. Decimal
+/// arguments are raw values with 18 fractional digits.
+pub fn initialize_overseer(
+    overseer: &OverseerAddresses,
+    payer: &Pubkey,
+    oracle_feeder: Pubkey,
+    price_timeframe: u64,
+    max_premium_rate: u128,
+    safe_ratio: u128,
+    liquidation_threshold: u64,
+) -> Instruction {
+    instruction(
+        overseer.program_id,
+        &OverseerInstruction::InitializeOverseer {
+            market: overseer.market.program_id,
+            custody: overseer.custody_program,
+            oracle_feeder,
+            price_timeframe,
+            max_premium_rate,
+            safe_ratio,
+            liquidation_threshold,
+        },
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(find_program_data(&overseer.program_id).0, false),
+            AccountMeta::new_readonly(overseer.stable_mint, false),
+            AccountMeta::new_readonly(overseer.authority, false),
+            AccountMeta::new(overseer.proceeds, false),
+            AccountMeta::new(overseer.config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+pub fn whitelist(
+    overseer: &OverseerAddresses,
+    owner: &Pubkey,
+    collateral_mint: &Pubkey,
+    max_ltv: u128,
+) -> Instruction {
+    instruction(
+        overseer.program_id,
+        &OverseerInstruction::Whitelist { max_ltv },
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(overseer.config, false),
+            AccountMeta::new_readonly(*collateral_mint, false),
+            AccountMeta::new(overseer.whitelist_elem(collateral_mint), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn feed_price(
+    overseer: &OverseerAddresses,
+    oracle_feeder: &Pubkey,
+    collateral_mint: &Pubkey,
+    price: u128,
+) -> Instruction {
+    instruction(
+        overseer.program_id,
+        &OverseerInstruction::FeedPrice { price },
+        vec![
+            AccountMeta::new_readonly(*oracle_feeder, true),
+            AccountMeta::new_readonly(overseer.config, false),
+            AccountMeta::new(overseer.whitelist_elem(collateral_mint), false),
+        ],
+    )
+}
+
+pub fn lock_collateral(
+    overseer: &OverseerAddresses,
+    borrower: &Pubkey,
+    collateral_mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let custody = overseer.custody(collateral_mint);
+    instruction(
+        overseer.program_id,
+        &OverseerInstruction::LockCollateral { amount },
+        vec![
+            AccountMeta::new(*borrower, true),
+            AccountMeta::new_readonly(overseer.config, false),
+            AccountMeta::new_readonly(overseer.whitelist_elem(collateral_mint), false),
+            AccountMeta::new(overseer.collaterals(borrower), false),
+            AccountMeta::new_readonly(overseer.authority, false),
+            AccountMeta::new_readonly(custody.program_id, false),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(custody.borrower_info(borrower), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `collaterals` is the current state of the borrower collaterals account;
+/// the borrow limit is checked against what is left after the unlock.
+pub fn unlock_collateral(
+    overseer: &OverseerAddresses,
+    collaterals: &Collaterals,
+    collateral_mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let borrower = collaterals.borrower;
+    let custody = overseer.custody(collateral_mint);
+
+    let mut left = collaterals.clone();
+    if left.sub(*collateral_mint, Uint256::from(amount)).is_err() {
+        // The program rejects the unlock anyway
+        left = collaterals.clone();
+    }
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(borrower, true),
+        AccountMeta::new_readonly(overseer.config, false),
+        AccountMeta::new(overseer.collaterals(&borrower), false),
+        AccountMeta::new_readonly(overseer.authority, false),
+        AccountMeta::new_readonly(custody.program_id, false),
+        AccountMeta::new_readonly(custody.config, false),
+        AccountMeta::new(custody.borrower_info(&borrower), false),
+    ];
+    accounts.extend(overseer.loan_accounts(&borrower));
+    accounts.extend(overseer.whitelist_elems(left.collaterals.iter().map(|c| &c.0)));
+
+    instruction(
+        overseer.program_id,
+        &OverseerInstruction::UnlockCollateral { amount },
+        accounts,
+    )
+}
+
+/// `collaterals` is the current state of the borrower collaterals account,
+/// or `Collaterals::new` when nothing was locked yet.
+pub fn update_borrow_limit(
+    overseer: &OverseerAddresses,
+    payer: &Pubkey,
+    collaterals: &Collaterals,
+) -> Instruction {
+    let borrower = collaterals.borrower;
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(overseer.config, false),
+        AccountMeta::new_readonly(overseer.collaterals(&borrower), false),
+        AccountMeta::new(overseer.borrow_limit(&borrower), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(overseer.whitelist_elems(collaterals.collaterals.iter().map(|c| &c.0)));
+
+    instruction(
+        overseer.program_id,
+        &OverseerInstruction::UpdateBorrowLimit { borrower },
+        accounts,
+    )
+}
+
+/// `destinations` are the liquidator token accounts receiving each of the
+/// borrower collaterals, in the order of `collaterals`.
+pub fn liquidate_collateral(
+    overseer: &OverseerAddresses,
+    liquidator: &Pubkey,
+    source: &Pubkey,
+    collaterals: &Collaterals,
+    destinations: &[Pubkey],
+) -> Instruction {
+    let borrower = collaterals.borrower;
+    let market = &overseer.market;
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*liquidator, true),
+        AccountMeta::new(*source, false),
+        AccountMeta::new_readonly(overseer.config, false),
+        AccountMeta::new(overseer.collaterals(&borrower), false),
+        AccountMeta::new_readonly(overseer.authority, false),
+        AccountMeta::new(overseer.proceeds, false),
+        AccountMeta::new_readonly(overseer.custody_program, false),
+        AccountMeta::new_readonly(market.program_id, false),
+        AccountMeta::new_readonly(market.config, false),
+        AccountMeta::new(market.state, false),
+        AccountMeta::new_readonly(market.interest_model, false),
+        AccountMeta::new(market.liability(&borrower), false),
+        AccountMeta::new(market.vault, false),
+        AccountMeta::new_readonly(market.atoken_mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    for ((mint, _), destination) in collaterals.collaterals.iter().zip(destinations) {
+        let custody = overseer.custody(mint);
+        accounts.extend(vec![
+            AccountMeta::new_readonly(overseer.whitelist_elem(mint), false),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(custody.borrower_info(&borrower), false),
+            AccountMeta::new(custody.vault, false),
+            AccountMeta::new_readonly(custody.authority, false),
+            AccountMeta::new(*destination, false),
+        ]);
+    }
+
+    instruction(
+        overseer.program_id,
+        &OverseerInstruction::LiquidateCollateral { borrower },
+        accounts,
+    )
+}
@@ -0,0 +1,243 @@
+mod common;
+
+use common::*;
+use cosmwasm_bignumber::Decimal256;
+use solana_money_market_client::{custody, decode, market, overseer};
+use solana_money_market_overseer::state::Collaterals;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+// 1 collateral is worth 100 stable and lends half of it
+async fn setup() -> (ProgramTestContext, Protocol, User) {
+    let (mut context, programs) = start().await;
+    let protocol = create_protocol(
+        &mut context,
+        programs,
+        DECIMAL_FRACTIONAL / 2,
+        DECIMAL_FRACTIONAL * 100,
+    )
+    .await;
+
+    let lender = create_user(&mut context, &protocol, 1_000_000, 0).await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[market::deposit_stable(
+            protocol.market(),
+            &lender.keypair.pubkey(),
+            &lender.stable,
+            &lender.atoken,
+            1_000_000,
+        )],
+        &[&lender.keypair],
+    )
+    .await
+    .unwrap();
+
+    let borrower = create_user(&mut context, &protocol, 0, 1_000).await;
+    let owner = borrower.keypair.pubkey();
+    let collateral_mint = protocol.collateral_mint.pubkey();
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[
+            custody::deposit_collateral(&protocol.custody, &owner, &borrower.collateral, 1_000),
+            overseer::lock_collateral(&protocol.overseer, &owner, &collateral_mint, 1_000),
+        ],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    (context, protocol, borrower)
+}
+
+async fn collaterals(
+    context: &mut ProgramTestContext,
+    protocol: &Protocol,
+    user: &User,
+) -> Collaterals {
+    let key = protocol.overseer.collaterals(&user.keypair.pubkey());
+    decode::collaterals(&account_data(&mut context.banks_client, &key).await).unwrap()
+}
+
+async fn borrow(context: &mut ProgramTestContext, protocol: &Protocol, user: &User, amount: u64) {
+    let owner = user.keypair.pubkey();
+    let locked = collaterals(context, protocol, user).await;
+    let payer = context.payer.pubkey();
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[
+            overseer::update_borrow_limit(&protocol.overseer, &payer, &locked),
+            market::borrow_stable(
+                protocol.market(),
+                &owner,
+                &user.stable,
+                &protocol.overseer.borrow_limit(&owner),
+                amount,
+            ),
+        ],
+        &[&user.keypair],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn decode_initialized_accounts() {
+    let (mut context, programs) = start().await;
+    let protocol = create_protocol(
+        &mut context,
+        programs,
+        DECIMAL_FRACTIONAL / 2,
+        DECIMAL_FRACTIONAL * 100,
+    )
+    .await;
+    let banks_client = &mut context.banks_client;
+    let stable_mint = protocol.stable_mint.pubkey();
+    let collateral_mint = protocol.collateral_mint.pubkey();
+
+    let config =
+        decode::market_config(&account_data(banks_client, &protocol.market().config).await)
+            .unwrap();
+    assert_eq!(config.stable_mint, stable_mint);
+    assert_eq!(config.atoken_mint, protocol.market().atoken_mint);
+    assert_eq!(config.interest_model, protocol.market().interest_model);
+    assert_eq!(config.overseer, protocol.overseer.program_id);
+
+    let state =
+        decode::market_state(&account_data(banks_client, &protocol.market().state).await).unwrap();
+    assert!(state.total_liabilities.is_zero());
+    let interest_model = decode::interest_model(
+        &account_data(banks_client, &protocol.market().interest_model).await,
+    )
+    .unwrap();
+    assert!(interest_model.base_rate.is_zero());
+
+    let config =
+        decode::custody_config(&account_data(banks_client, &protocol.custody.config).await)
+            .unwrap();
+    assert_eq!(config.collateral_mint, collateral_mint);
+    assert_eq!(config.overseer, protocol.overseer.program_id);
+
+    let config =
+        decode::overseer_config(&account_data(banks_client, &protocol.overseer.config).await)
+            .unwrap();
+    assert_eq!(config.market, protocol.market().program_id);
+    assert_eq!(config.custody, protocol.custody.program_id);
+    assert_eq!(config.oracle_feeder, protocol.oracle_feeder.pubkey());
+
+    let elem = decode::whitelist_elem(
+        &account_data(
+            banks_client,
+            &protocol.overseer.whitelist_elem(&collateral_mint),
+        )
+        .await,
+    )
+    .unwrap();
+    assert_eq!(elem.collateral_mint, collateral_mint);
+    assert_eq!(elem.price, Decimal256::from_uint256(100u64));
+}
+
+#[tokio::test]
+async fn borrow_repay_and_withdraw() {
+    let (mut context, protocol, borrower) = setup().await;
+    let owner = borrower.keypair.pubkey();
+    let collateral_mint = protocol.collateral_mint.pubkey();
+
+    borrow(&mut context, &protocol, &borrower, 50_000).await;
+    let banks_client = &mut context.banks_client;
+    let limit = decode::borrow_limit(
+        &account_data(banks_client, &protocol.overseer.borrow_limit(&owner)).await,
+    )
+    .unwrap();
+    assert_eq!(limit.borrow_limit, 50_000u64.into());
+    let liability =
+        decode::liability(&account_data(banks_client, &protocol.market().liability(&owner)).await)
+            .unwrap();
+    assert_eq!(liability.loan_amount, 50_000u64.into());
+    let info = decode::custody_borrower_info(
+        &account_data(banks_client, &protocol.custody.borrower_info(&owner)).await,
+    )
+    .unwrap();
+    assert_eq!(info.balance, 1_000u64.into());
+    assert!(info.spendable.is_zero());
+
+    process(
+        banks_client,
+        &context.payer,
+        &[market::repay_stable(
+            protocol.market(),
+            &owner,
+            &borrower.stable,
+            50_000,
+        )],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+    let locked = collaterals(&mut context, &protocol, &borrower).await;
+    // Unlocking everything leaves no whitelist account to pass along
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[
+            overseer::unlock_collateral(&protocol.overseer, &locked, &collateral_mint, 1_000),
+            custody::withdraw_collateral(&protocol.custody, &owner, &borrower.collateral, None),
+        ],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&mut context.banks_client, &borrower.collateral).await,
+        1_000
+    );
+    assert!(collaterals(&mut context, &protocol, &borrower)
+        .await
+        .collaterals
+        .is_empty());
+}
+
+#[tokio::test]
+async fn liquidate_collateral() {
+    let (mut context, protocol, borrower) = setup().await;
+    let collateral_mint = protocol.collateral_mint.pubkey();
+    borrow(&mut context, &protocol, &borrower, 40_000).await;
+    let liquidator = create_user(&mut context, &protocol, 100_000, 0).await;
+
+    let locked = collaterals(&mut context, &protocol, &borrower).await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[
+            overseer::feed_price(
+                &protocol.overseer,
+                &protocol.oracle_feeder.pubkey(),
+                &collateral_mint,
+                DECIMAL_FRACTIONAL * 50,
+            ),
+            overseer::liquidate_collateral(
+                &protocol.overseer,
+                &liquidator.keypair.pubkey(),
+                &liquidator.stable,
+                &locked,
+                &[liquidator.collateral],
+            ),
+        ],
+        &[&protocol.oracle_feeder, &liquidator.keypair],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&mut context.banks_client, &liquidator.collateral).await,
+        800
+    );
+    assert_eq!(
+        token_balance(&mut context.banks_client, &liquidator.stable).await,
+        64_000
+    );
+}
@@ -0,0 +1,339 @@
+#![allow(dead_code)]
+
+use solana_money_market::pda::find_program_data;
+use solana_money_market_client::{custody, market, overseer};
+use solana_money_market_client::{
+    custody::CustodyAddresses, market::MarketAddresses, overseer::OverseerAddresses,
+};
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{keypair_from_seed, Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+pub const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
+
+pub struct Protocol {
+    pub stable_mint: Keypair,
+    pub collateral_mint: Keypair,
+    pub oracle_feeder: Keypair,
+    pub overseer: OverseerAddresses,
+    pub custody: CustodyAddresses,
+}
+
+impl Protocol {
+    pub fn market(&self) -> &MarketAddresses {
+        &self.overseer.market
+    }
+}
+
+pub struct User {
+    pub keypair: Keypair,
+    pub stable: Pubkey,
+    pub atoken: Pubkey,
+    pub collateral: Pubkey,
+}
+
+/// Starts a bank with the market, custody and overseer programs loaded
+pub async fn start() -> (ProgramTestContext, [Pubkey; 3]) {
+    let market_program = Pubkey::new_unique();
+    let custody_program = Pubkey::new_unique();
+    let overseer_program = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "solana_money_market",
+        market_program,
+        processor!(solana_money_market::processor::process_instruction),
+    );
+    program_test.add_program(
+        "solana_money_market_custody",
+        custody_program,
+        processor!(solana_money_market_custody::processor::process_instruction),
+    );
+    program_test.add_program(
+        "solana_money_market_overseer",
+        overseer_program,
+        processor!(solana_money_market_overseer::processor::process_instruction),
+    );
+    for program_id in &[market_program, custody_program, overseer_program] {
+        add_program_data(&mut program_test, program_id);
+    }
+    program_test.prefer_bpf(false);
+
+    (
+        program_test.start_with_context().await,
+        [market_program, custody_program, overseer_program],
+    )
+}
+
+/// Key the programs are deployed with; only it can initialize them
+pub fn upgrade_authority() -> Keypair {
+    keypair_from_seed(&[1; 32]).unwrap()
+}
+
+/// Records the upgrade authority of a program the way the upgradeable loader
+/// does, and funds the authority to pay for the accounts it initializes
+pub fn add_program_data(program_test: &mut ProgramTest, program_id: &Pubkey) {
+    let authority = upgrade_authority().pubkey();
+    program_test.add_account(
+        find_program_data(program_id).0,
+        Account::new_data(
+            1_000_000_000,
+            &UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(authority),
+            },
+            &bpf_loader_upgradeable::id(),
+        )
+        .unwrap(),
+    );
+    program_test.add_account(
+        authority,
+        Account::new(1_000_000_000_000, 0, &system_program::id()),
+    );
+}
+
+pub async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+pub async fn account_data(banks_client: &mut BanksClient, account: &Pubkey) -> Vec<u8> {
+    banks_client
+        .get_account(*account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
+
+pub async fn token_balance(banks_client: &mut BanksClient, account: &Pubkey) -> u64 {
+    TokenAccount::unpack(&account_data(banks_client, account).await)
+        .unwrap()
+        .amount
+}
+
+async fn create_mint(banks_client: &mut BanksClient, payer: &Keypair, mint: &Keypair) {
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                6,
+            )
+            .unwrap(),
+        ],
+        &[mint],
+    )
+    .await
+    .unwrap();
+}
+
+async fn create_token_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let account = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::id(),
+                &account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        &[&account],
+    )
+    .await
+    .unwrap();
+
+    // Only the stable and collateral mints are minted by the payer
+    if amount != 0 {
+        process(
+            banks_client,
+            payer,
+            &[spl_token::instruction::mint_to(
+                &spl_token::id(),
+                mint,
+                &account.pubkey(),
+                &payer.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap()],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    account.pubkey()
+}
+
+/// Initializes an interest free market with one collateral through the
+/// client instruction builders
+pub async fn create_protocol(
+    context: &mut ProgramTestContext,
+    [market_program, custody_program, overseer_program]: [Pubkey; 3],
+    max_ltv: u128,
+    price: u128,
+) -> Protocol {
+    let stable_mint = Keypair::new();
+    let collateral_mint = Keypair::new();
+    let oracle_feeder = Keypair::new();
+    create_mint(&mut context.banks_client, &context.payer, &stable_mint).await;
+    create_mint(&mut context.banks_client, &context.payer, &collateral_mint).await;
+
+    let market = MarketAddresses::new(market_program, stable_mint.pubkey());
+    let overseer = OverseerAddresses::new(overseer_program, market, custody_program);
+    let custody = overseer.custody(&collateral_mint.pubkey());
+    let upgrade_authority = upgrade_authority();
+    let payer = upgrade_authority.pubkey();
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[
+            market::initialize_market(
+                &market,
+                &payer,
+                overseer_program,
+                0,
+                0,
+                DECIMAL_FRACTIONAL * 95 / 100,
+                DECIMAL_FRACTIONAL / 100_000,
+            ),
+            custody::initialize_custody(&custody, &payer, overseer_program),
+            overseer::initialize_overseer(
+                &overseer,
+                &payer,
+                oracle_feeder.pubkey(),
+                60,
+                DECIMAL_FRACTIONAL / 10,
+                DECIMAL_FRACTIONAL * 8 / 10,
+                0,
+            ),
+            overseer::whitelist(&overseer, &payer, &collateral_mint.pubkey(), max_ltv),
+            overseer::feed_price(
+                &overseer,
+                &oracle_feeder.pubkey(),
+                &collateral_mint.pubkey(),
+                price,
+            ),
+        ],
+        &[&upgrade_authority, &oracle_feeder],
+    )
+    .await
+    .unwrap();
+
+    Protocol {
+        stable_mint,
+        collateral_mint,
+        oracle_feeder,
+        overseer,
+        custody,
+    }
+}
+
+pub async fn create_user(
+    context: &mut ProgramTestContext,
+    protocol: &Protocol,
+    stable_amount: u64,
+    collateral_amount: u64,
+) -> User {
+    let keypair = Keypair::new();
+    let owner = keypair.pubkey();
+    let banks_client = &mut context.banks_client;
+    let payer = &context.payer;
+    process(
+        banks_client,
+        payer,
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &owner,
+            1_000_000_000,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    User {
+        stable: create_token_account(
+            banks_client,
+            payer,
+            &protocol.stable_mint.pubkey(),
+            &owner,
+            stable_amount,
+        )
+        .await,
+        atoken: create_token_account(
+            banks_client,
+            payer,
+            &protocol.market().atoken_mint,
+            &owner,
+            0,
+        )
+        .await,
+        collateral: create_token_account(
+            banks_client,
+            payer,
+            &protocol.collateral_mint.pubkey(),
+            &owner,
+            collateral_amount,
+        )
+        .await,
+        keypair,
+    }
+}
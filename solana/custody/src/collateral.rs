@@ -0,0 +1,296 @@
+use cosmwasm_bignumber::Uint256;
+use solana_money_market::deposit::to_u64;
+use solana_money_market::processor::{
+    assert_token_program, create_pda_account, unpack_token_account,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::error::CustodyError;
+use crate::pda::{find_borrower_info, find_collateral_vault, BORROWER_SEED};
+use crate::processor::{
+    assert_custody_vault, assert_overseer, custody_authority_seeds, load_borrower_info, load_config,
+};
+use crate::state::BorrowerInfo;
+
+/// Deposit new collateral
+/// Executor: borrower
+pub fn process_deposit_collateral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let borrower_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let balance_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if !borrower_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *system_program_info.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    assert_token_program(token_program_info)?;
+
+    let config = load_config(program_id, config_info)?;
+    let (expected_vault, _) =
+        find_collateral_vault(program_id, &config.stable_mint, &config.collateral_mint);
+    if *vault_info.key != expected_vault {
+        return Err(CustodyError::InvalidSeeds.into());
+    }
+
+    let source = unpack_token_account(source_info)?;
+    if source.mint != config.collateral_mint {
+        return Err(CustodyError::MintMismatch.into());
+    }
+
+    // Borrower info accounts are created on the first deposit
+    let (expected_balance, balance_bump) = find_borrower_info(
+        program_id,
+        &config.stable_mint,
+        &config.collateral_mint,
+        borrower_info.key,
+    );
+    if *balance_info.key != expected_balance {
+        return Err(CustodyError::InvalidSeeds.into());
+    }
+    let mut info = if balance_info.data_is_empty() {
+        create_pda_account(
+            borrower_info,
+            balance_info,
+            system_program_info,
+            program_id,
+            BorrowerInfo::LEN,
+            &[
+                BORROWER_SEED,
+                config.stable_mint.as_ref(),
+                config.collateral_mint.as_ref(),
+                borrower_info.key.as_ref(),
+                &[balance_bump],
+            ],
+        )?;
+        BorrowerInfo::new(*borrower_info.key)
+    } else {
+        load_borrower_info(program_id, &config, balance_info, borrower_info.key)?
+    };
+
+    info.balance += Uint256::from(amount);
+    info.spendable += Uint256::from(amount);
+    BorrowerInfo::pack(info, &mut balance_info.data.borrow_mut())?;
+
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program_info.key,
+            source_info.key,
+            vault_info.key,
+            borrower_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            source_info.clone(),
+            vault_info.clone(),
+            borrower_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    msg!("borrower: {}, amount: {}", borrower_info.key, amount);
+
+    Ok(())
+}
+
+/// Withdraw spendable collateral or a specified amount of collateral
+/// Executor: borrower
+pub fn process_withdraw_collateral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: Option<u64>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let borrower_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let balance_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if !borrower_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    assert_token_program(token_program_info)?;
+
+    let config = load_config(program_id, config_info)?;
+    let authority_bump = assert_custody_vault(program_id, &config, vault_info, authority_info)?;
+    let mut info = load_borrower_info(program_id, &config, balance_info, borrower_info.key)?;
+
+    // Check spendable balance
+    let amount = amount.map_or(info.spendable, Uint256::from);
+    if info.spendable < amount {
+        return Err(CustodyError::WithdrawAmountExceedsSpendable.into());
+    }
+
+    info.balance = info.balance - amount;
+    info.spendable = info.spendable - amount;
+    BorrowerInfo::pack(info, &mut balance_info.data.borrow_mut())?;
+
+    let amount = to_u64(amount)?;
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program_info.key,
+            vault_info.key,
+            destination_info.key,
+            authority_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            vault_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&custody_authority_seeds(&config, &[authority_bump])],
+    )?;
+
+    msg!("borrower: {}, amount: {}", borrower_info.key, amount);
+
+    Ok(())
+}
+
+/// Decrease spendable collateral to lock
+/// specified amount of collateral token
+/// Executor: overseer
+pub fn process_lock_collateral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    borrower: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let overseer_authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let balance_info = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_info)?;
+    assert_overseer(&config, overseer_authority_info)?;
+
+    let mut info = load_borrower_info(program_id, &config, balance_info, &borrower)?;
+    let amount = Uint256::from(amount);
+    if amount > info.spendable {
+        return Err(CustodyError::LockAmountExceedsSpendable.into());
+    }
+
+    info.spendable = info.spendable - amount;
+    BorrowerInfo::pack(info, &mut balance_info.data.borrow_mut())?;
+
+    msg!("borrower: {}, amount: {}", borrower, amount);
+
+    Ok(())
+}
+
+/// Increase spendable collateral to unlock
+/// specified amount of collateral token
+/// Executor: overseer
+pub fn process_unlock_collateral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    borrower: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let overseer_authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let balance_info = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_info)?;
+    assert_overseer(&config, overseer_authority_info)?;
+
+    let mut info = load_borrower_info(program_id, &config, balance_info, &borrower)?;
+    let amount = Uint256::from(amount);
+    let borrowed_amt = info.balance - info.spendable;
+    if amount > borrowed_amt {
+        return Err(CustodyError::UnlockAmountExceedsLocked.into());
+    }
+
+    info.spendable += amount;
+    BorrowerInfo::pack(info, &mut balance_info.data.borrow_mut())?;
+
+    msg!("borrower: {}, amount: {}", borrower, amount);
+
+    Ok(())
+}
+
+/// Send locked collateral to the liquidator
+/// Executor: overseer
+pub fn process_liquidate_collateral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    borrower: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let overseer_authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let balance_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let liquidator_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    assert_token_program(token_program_info)?;
+
+    let config = load_config(program_id, config_info)?;
+    assert_overseer(&config, overseer_authority_info)?;
+    let authority_bump = assert_custody_vault(program_id, &config, vault_info, authority_info)?;
+
+    let mut info = load_borrower_info(program_id, &config, balance_info, &borrower)?;
+    let borrowed_amt = info.balance - info.spendable;
+    if Uint256::from(amount) > borrowed_amt {
+        return Err(CustodyError::LiquidationAmountExceedsLocked.into());
+    }
+
+    info.balance = info.balance - Uint256::from(amount);
+    BorrowerInfo::pack(info, &mut balance_info.data.borrow_mut())?;
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program_info.key,
+            vault_info.key,
+            liquidator_info.key,
+            authority_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            vault_info.clone(),
+            liquidator_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&custody_authority_seeds(&config, &[authority_bump])],
+    )?;
+
+    msg!(
+        "liquidator: {}, borrower: {}, amount: {}",
+        liquidator_info.key,
+        borrower,
+        amount
+    );
+
+    Ok(())
+}
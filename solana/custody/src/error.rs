@@ -0,0 +1,28 @@
+use solana_program::program_error::ProgramError;
+use thiserror::Error;
+
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum CustodyError {
+    #[error("Invalid instruction")]
+    InvalidInstruction,
+    #[error("Account does not match the expected program derived address")]
+    InvalidSeeds,
+    #[error("Token account mint does not match the custody collateral mint")]
+    MintMismatch,
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("Withdraw amount cannot exceed the user's spendable amount")]
+    WithdrawAmountExceedsSpendable,
+    #[error("Lock amount cannot exceed the user's spendable amount")]
+    LockAmountExceedsSpendable,
+    #[error("Unlock amount cannot exceed locked amount")]
+    UnlockAmountExceedsLocked,
+    #[error("Liquidation amount cannot exceed locked amount")]
+    LiquidationAmountExceedsLocked,
+}
+
+impl From<CustodyError> for ProgramError {
+    fn from(e: CustodyError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,83 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum CustodyInstruction {
+    /// Creates the collateral vault and config accounts of the custody for a
+    /// collateral mint of a market. The payer must be the upgrade authority of
+    /// the program and becomes the custody owner.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[writable, signer]` Payer, the upgrade authority of the program
+    ///   1. `[]` Program data account of the program
+    ///   2. `[]` Stable mint of the market
+    ///   3. `[]` Collateral mint
+    ///   4. `[]` Custody authority PDA
+    ///   5. `[writable]` Collateral vault PDA
+    ///   6. `[writable]` Config PDA
+    ///   7. `[]` System program
+    ///   8. `[]` Token program
+    InitializeCustody { overseer: Pubkey },
+
+    /// Deposits collateral tokens into the vault as spendable balance. The
+    /// borrower info account is created on the first deposit.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[writable, signer]` Borrower
+    ///   1. `[writable]` Borrower collateral token account
+    ///   2. `[]` Config PDA
+    ///   3. `[writable]` Borrower info PDA
+    ///   4. `[writable]` Collateral vault PDA
+    ///   5. `[]` System program
+    ///   6. `[]` Token program
+    DepositCollateral { amount: u64 },
+
+    /// Withdraws spendable collateral, or all of it when no amount is given.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Borrower
+    ///   1. `[writable]` Borrower collateral token account
+    ///   2. `[]` Config PDA
+    ///   3. `[writable]` Borrower info PDA
+    ///   4. `[writable]` Collateral vault PDA
+    ///   5. `[]` Custody authority PDA
+    ///   6. `[]` Token program
+    WithdrawCollateral { amount: Option<u64> },
+
+    /// Moves spendable collateral to locked. Only the overseer authority PDA
+    /// can sign it.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Overseer authority PDA
+    ///   1. `[]` Config PDA
+    ///   2. `[writable]` Borrower info PDA
+    LockCollateral { borrower: Pubkey, amount: u64 },
+
+    /// Moves locked collateral back to spendable. Only the overseer authority
+    /// PDA can sign it.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Overseer authority PDA
+    ///   1. `[]` Config PDA
+    ///   2. `[writable]` Borrower info PDA
+    UnlockCollateral { borrower: Pubkey, amount: u64 },
+
+    /// Sends locked collateral to the liquidator. Only the overseer authority
+    /// PDA can sign it.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Overseer authority PDA
+    ///   1. `[]` Config PDA
+    ///   2. `[writable]` Borrower info PDA
+    ///   3. `[writable]` Collateral vault PDA
+    ///   4. `[]` Custody authority PDA
+    ///   5. `[writable]` Liquidator collateral token account
+    ///   6. `[]` Token program
+    LiquidateCollateral { borrower: Pubkey, amount: u64 },
+}
@@ -0,0 +1,7 @@
+pub mod collateral;
+mod entrypoint;
+pub mod error;
+pub mod instruction;
+pub mod pda;
+pub mod processor;
+pub mod state;
@@ -0,0 +1,62 @@
+use solana_program::pubkey::Pubkey;
+
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const BORROWER_SEED: &[u8] = b"borrower";
+
+// Every custody is keyed by the stable mint of its market and the collateral
+// mint it holds; the authority PDA owns the collateral vault.
+pub fn find_custody_authority(
+    program_id: &Pubkey,
+    stable_mint: &Pubkey,
+    collateral_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            AUTHORITY_SEED,
+            stable_mint.as_ref(),
+            collateral_mint.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+pub fn find_collateral_vault(
+    program_id: &Pubkey,
+    stable_mint: &Pubkey,
+    collateral_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_SEED, stable_mint.as_ref(), collateral_mint.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_custody_config(
+    program_id: &Pubkey,
+    stable_mint: &Pubkey,
+    collateral_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CONFIG_SEED, stable_mint.as_ref(), collateral_mint.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_borrower_info(
+    program_id: &Pubkey,
+    stable_mint: &Pubkey,
+    collateral_mint: &Pubkey,
+    borrower: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            BORROWER_SEED,
+            stable_mint.as_ref(),
+            collateral_mint.as_ref(),
+            borrower.as_ref(),
+        ],
+        program_id,
+    )
+}
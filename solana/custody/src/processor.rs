@@ -0,0 +1,234 @@
+use borsh::BorshDeserialize;
+use solana_money_market::pda::find_overseer_authority;
+use solana_money_market::processor::{
+    assert_token_program, assert_upgrade_authority, create_pda_account, unpack_mint,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_token::state::Account as TokenAccount;
+
+use crate::collateral::{
+    process_deposit_collateral, process_liquidate_collateral, process_lock_collateral,
+    process_unlock_collateral, process_withdraw_collateral,
+};
+use crate::error::CustodyError;
+use crate::instruction::CustodyInstruction;
+use crate::pda::{
+    find_borrower_info, find_collateral_vault, find_custody_authority, find_custody_config,
+    AUTHORITY_SEED, CONFIG_SEED, VAULT_SEED,
+};
+use crate::state::{BorrowerInfo, Config, STATE_VERSION};
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = CustodyInstruction::try_from_slice(instruction_data)
+        .map_err(|_| CustodyError::InvalidInstruction)?;
+
+    match instruction {
+        CustodyInstruction::InitializeCustody { overseer } => {
+            msg!("Instruction: InitializeCustody");
+            process_initialize_custody(program_id, accounts, overseer)
+        }
+        CustodyInstruction::DepositCollateral { amount } => {
+            msg!("Instruction: DepositCollateral");
+            process_deposit_collateral(program_id, accounts, amount)
+        }
+        CustodyInstruction::WithdrawCollateral { amount } => {
+            msg!("Instruction: WithdrawCollateral");
+            process_withdraw_collateral(program_id, accounts, amount)
+        }
+        CustodyInstruction::LockCollateral { borrower, amount } => {
+            msg!("Instruction: LockCollateral");
+            process_lock_collateral(program_id, accounts, borrower, amount)
+        }
+        CustodyInstruction::UnlockCollateral { borrower, amount } => {
+            msg!("Instruction: UnlockCollateral");
+            process_unlock_collateral(program_id, accounts, borrower, amount)
+        }
+        CustodyInstruction::LiquidateCollateral { borrower, amount } => {
+            msg!("Instruction: LiquidateCollateral");
+            process_liquidate_collateral(program_id, accounts, borrower, amount)
+        }
+    }
+}
+
+fn process_initialize_custody(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    overseer: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let program_data_info = next_account_info(account_info_iter)?;
+    let stable_mint_info = next_account_info(account_info_iter)?;
+    let collateral_mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    assert_upgrade_authority(program_id, program_data_info, payer_info)?;
+    if *system_program_info.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    assert_token_program(token_program_info)?;
+
+    let stable_mint = stable_mint_info.key;
+    let collateral_mint = collateral_mint_info.key;
+    unpack_mint(stable_mint_info)?;
+    unpack_mint(collateral_mint_info)?;
+    let (authority, _) = find_custody_authority(program_id, stable_mint, collateral_mint);
+    let (vault, vault_bump) = find_collateral_vault(program_id, stable_mint, collateral_mint);
+    let (config, config_bump) = find_custody_config(program_id, stable_mint, collateral_mint);
+    if *authority_info.key != authority || *vault_info.key != vault || *config_info.key != config {
+        return Err(CustodyError::InvalidSeeds.into());
+    }
+
+    create_pda_account(
+        payer_info,
+        vault_info,
+        system_program_info,
+        token_program_info.key,
+        TokenAccount::LEN,
+        &[
+            VAULT_SEED,
+            stable_mint.as_ref(),
+            collateral_mint.as_ref(),
+            &[vault_bump],
+        ],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_account3(
+            token_program_info.key,
+            vault_info.key,
+            collateral_mint,
+            authority_info.key,
+        )?,
+        &[
+            vault_info.clone(),
+            collateral_mint_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    create_pda_account(
+        payer_info,
+        config_info,
+        system_program_info,
+        program_id,
+        Config::LEN,
+        &[
+            CONFIG_SEED,
+            stable_mint.as_ref(),
+            collateral_mint.as_ref(),
+            &[config_bump],
+        ],
+    )?;
+    Config::pack(
+        Config {
+            version: STATE_VERSION,
+            is_initialized: true,
+            owner: *payer_info.key,
+            overseer,
+            stable_mint: *stable_mint,
+            collateral_mint: *collateral_mint,
+        },
+        &mut config_info.data.borrow_mut(),
+    )?;
+
+    Ok(())
+}
+
+/// Checks the config is the custody PDA it claims to be and loads it.
+pub(crate) fn load_config(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+) -> Result<Config, ProgramError> {
+    if config_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let config = Config::unpack(&config_info.data.borrow())?;
+    let (expected_config, _) =
+        find_custody_config(program_id, &config.stable_mint, &config.collateral_mint);
+    if *config_info.key != expected_config {
+        return Err(CustodyError::InvalidSeeds.into());
+    }
+
+    Ok(config)
+}
+
+/// Checks the signer is the overseer authority PDA of the custody market.
+pub(crate) fn assert_overseer(
+    config: &Config,
+    overseer_authority_info: &AccountInfo,
+) -> ProgramResult {
+    let (expected_authority, _) = find_overseer_authority(&config.overseer, &config.stable_mint);
+    if !overseer_authority_info.is_signer || *overseer_authority_info.key != expected_authority {
+        return Err(CustodyError::Unauthorized.into());
+    }
+
+    Ok(())
+}
+
+/// Checks the vault and authority derive from the custody mints and returns
+/// the authority bump seed.
+pub(crate) fn assert_custody_vault(
+    program_id: &Pubkey,
+    config: &Config,
+    vault_info: &AccountInfo,
+    authority_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (expected_vault, _) =
+        find_collateral_vault(program_id, &config.stable_mint, &config.collateral_mint);
+    let (expected_authority, bump) =
+        find_custody_authority(program_id, &config.stable_mint, &config.collateral_mint);
+    if *vault_info.key != expected_vault || *authority_info.key != expected_authority {
+        return Err(CustodyError::InvalidSeeds.into());
+    }
+
+    Ok(bump)
+}
+
+pub(crate) fn custody_authority_seeds<'a>(config: &'a Config, bump: &'a [u8; 1]) -> [&'a [u8]; 4] {
+    [
+        AUTHORITY_SEED,
+        config.stable_mint.as_ref(),
+        config.collateral_mint.as_ref(),
+        bump,
+    ]
+}
+
+pub(crate) fn load_borrower_info(
+    program_id: &Pubkey,
+    config: &Config,
+    borrower_info: &AccountInfo,
+    borrower: &Pubkey,
+) -> Result<BorrowerInfo, ProgramError> {
+    let (expected_borrower_info, _) = find_borrower_info(
+        program_id,
+        &config.stable_mint,
+        &config.collateral_mint,
+        borrower,
+    );
+    if *borrower_info.key != expected_borrower_info {
+        return Err(CustodyError::InvalidSeeds.into());
+    }
+    if borrower_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    BorrowerInfo::unpack(&borrower_info.data.borrow())
+}
@@ -0,0 +1,140 @@
+use borsh::{
+    maybestd::io::{Read, Result as IoResult, Write},
+    BorshDeserialize, BorshSerialize,
+};
+use cosmwasm_bignumber::Uint256;
+use solana_money_market::state::{
+    assert_version, deserialize_u256, pack_borsh, serialize_u256, U256_LEN,
+};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+pub use solana_money_market::state::STATE_VERSION;
+
+// Mirrors `moneymarket_custody_bluna::state::Config`; the overseer is the
+// overseer program, whose authority PDA for the stable mint locks, unlocks
+// and liquidates collaterals.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+    pub overseer: Pubkey,
+    pub stable_mint: Pubkey,
+    pub collateral_mint: Pubkey,
+}
+
+// Mirrors `moneymarket_custody_bluna::state::BorrowerInfo`; the borrower is
+// stored in the account since there is no bucket key to recover it from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BorrowerInfo {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub borrower: Pubkey,
+    pub balance: Uint256,
+    pub spendable: Uint256,
+}
+
+impl BorrowerInfo {
+    pub fn new(borrower: Pubkey) -> Self {
+        BorrowerInfo {
+            version: STATE_VERSION,
+            is_initialized: true,
+            borrower,
+            balance: Uint256::zero(),
+            spendable: Uint256::zero(),
+        }
+    }
+}
+
+impl BorshSerialize for Config {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        self.owner.serialize(writer)?;
+        self.overseer.serialize(writer)?;
+        self.stable_mint.serialize(writer)?;
+        self.collateral_mint.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Config {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        Ok(Config {
+            version: u8::deserialize_reader(reader)?,
+            is_initialized: bool::deserialize_reader(reader)?,
+            owner: Pubkey::deserialize_reader(reader)?,
+            overseer: Pubkey::deserialize_reader(reader)?,
+            stable_mint: Pubkey::deserialize_reader(reader)?,
+            collateral_mint: Pubkey::deserialize_reader(reader)?,
+        })
+    }
+}
+
+impl BorshSerialize for BorrowerInfo {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        self.borrower.serialize(writer)?;
+        serialize_u256(&self.balance.0, writer)?;
+        serialize_u256(&self.spendable.0, writer)
+    }
+}
+
+impl BorshDeserialize for BorrowerInfo {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        Ok(BorrowerInfo {
+            version: u8::deserialize_reader(reader)?,
+            is_initialized: bool::deserialize_reader(reader)?,
+            borrower: Pubkey::deserialize_reader(reader)?,
+            balance: Uint256(deserialize_u256(reader)?),
+            spendable: Uint256(deserialize_u256(reader)?),
+        })
+    }
+}
+
+impl Sealed for Config {}
+impl Sealed for BorrowerInfo {}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for BorrowerInfo {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Config {
+    const LEN: usize = 2 + 32 * 4;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let config = Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(config.version, config.is_initialized)?;
+        Ok(config)
+    }
+}
+
+impl Pack for BorrowerInfo {
+    const LEN: usize = 2 + 32 + U256_LEN * 2;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let info = Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(info.version, info.is_initialized)?;
+        Ok(info)
+    }
+}
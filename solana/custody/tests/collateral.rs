@@ -0,0 +1,240 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_money_market::pda::{find_overseer_authority, AUTHORITY_SEED};
+use solana_money_market_custody::{
+    error::CustodyError, instruction::CustodyInstruction, pda::find_borrower_info,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program::invoke_signed,
+    pubkey::Pubkey,
+};
+use solana_program_test::{processor, ProgramTestContext};
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+use std::convert::TryInto;
+
+// Stands in for the overseer program: forwards a custody instruction, signing
+// it with the overseer authority PDA of the stable mint in the first 32 bytes.
+fn mock_overseer(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let stable_mint = Pubkey::new_from_array(data[..32].try_into().unwrap());
+    let (authority, bump) = find_overseer_authority(program_id, &stable_mint);
+    let (custody_program_info, custody_accounts) = accounts.split_first().unwrap();
+    let instruction = Instruction {
+        program_id: *custody_program_info.key,
+        accounts: custody_accounts
+            .iter()
+            .map(|a| AccountMeta {
+                pubkey: *a.key,
+                is_signer: a.is_signer || *a.key == authority,
+                is_writable: a.is_writable,
+            })
+            .collect(),
+        data: data[32..].to_vec(),
+    };
+
+    invoke_signed(
+        &instruction,
+        custody_accounts,
+        &[&[AUTHORITY_SEED, stable_mint.as_ref(), &[bump]]],
+    )
+}
+
+async fn setup() -> (ProgramTestContext, Custody, User) {
+    let program_id = Pubkey::new_unique();
+    let overseer = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    program_test.add_program("mock_overseer", overseer, processor!(mock_overseer));
+    let mut context = program_test.start_with_context().await;
+
+    let custody = create_custody(
+        &mut context.banks_client,
+        &context.payer,
+        program_id,
+        overseer,
+    )
+    .await;
+    let borrower = create_user(&mut context.banks_client, &context.payer, &custody, 1_000).await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[deposit_collateral(&custody, &borrower, 1_000)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    (context, custody, borrower)
+}
+
+fn lock_collateral(custody: &Custody, user: &User, amount: u64) -> Instruction {
+    let (overseer_authority, _) = find_overseer_authority(&custody.overseer, &custody.stable_mint);
+    let (borrower_info, _) = find_borrower_info(
+        &custody.program_id,
+        &custody.stable_mint,
+        &custody.collateral_mint.pubkey(),
+        &user.keypair.pubkey(),
+    );
+    let mut data = custody.stable_mint.to_bytes().to_vec();
+    data.extend(
+        CustodyInstruction::LockCollateral {
+            borrower: user.keypair.pubkey(),
+            amount,
+        }
+        .try_to_vec()
+        .unwrap(),
+    );
+    Instruction {
+        program_id: custody.overseer,
+        accounts: vec![
+            AccountMeta::new_readonly(custody.program_id, false),
+            AccountMeta::new_readonly(overseer_authority, false),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(borrower_info, false),
+        ],
+        data,
+    }
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw_collateral() {
+    let (mut context, custody, borrower) = setup().await;
+
+    assert_eq!(
+        token_balance(&mut context.banks_client, &borrower.collateral).await,
+        0
+    );
+    assert_eq!(
+        token_balance(&mut context.banks_client, &custody.vault).await,
+        1_000
+    );
+    let info = borrower_info(
+        &mut context.banks_client,
+        &custody,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert_eq!(info.balance, 1_000u64.into());
+    assert_eq!(info.spendable, 1_000u64.into());
+
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[withdraw_collateral(&custody, &borrower, Some(400))],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_balance(&mut context.banks_client, &borrower.collateral).await,
+        400
+    );
+
+    // Withdrawing without an amount takes all spendable collateral
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[withdraw_collateral(&custody, &borrower, None)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_balance(&mut context.banks_client, &borrower.collateral).await,
+        1_000
+    );
+    let info = borrower_info(
+        &mut context.banks_client,
+        &custody,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert!(info.balance.is_zero());
+    assert!(info.spendable.is_zero());
+}
+
+#[tokio::test]
+async fn locked_collateral_is_not_spendable() {
+    let (mut context, custody, borrower) = setup().await;
+
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[lock_collateral(&custody, &borrower, 700)],
+        &[],
+    )
+    .await
+    .unwrap();
+    let info = borrower_info(
+        &mut context.banks_client,
+        &custody,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert_eq!(info.balance, 1_000u64.into());
+    assert_eq!(info.spendable, 300u64.into());
+
+    let err = process(
+        &mut context.banks_client,
+        &context.payer,
+        &[withdraw_collateral(&custody, &borrower, Some(301))],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(CustodyError::WithdrawAmountExceedsSpendable as u32)
+        )
+    );
+
+    let err = process(
+        &mut context.banks_client,
+        &context.payer,
+        &[lock_collateral(&custody, &borrower, 301)],
+        &[],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(CustodyError::LockAmountExceedsSpendable as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn lock_by_other_signer_fails() {
+    let (mut context, custody, borrower) = setup().await;
+
+    // The borrower signing in place of the overseer authority is rejected
+    let mut instruction = lock_collateral(&custody, &borrower, 100);
+    let mut accounts = instruction.accounts[1..].to_vec();
+    accounts[0] = AccountMeta::new_readonly(borrower.keypair.pubkey(), true);
+    instruction.program_id = custody.program_id;
+    instruction.accounts = accounts;
+    instruction.data = instruction.data[32..].to_vec();
+
+    let err = process(
+        &mut context.banks_client,
+        &context.payer,
+        &[instruction],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(CustodyError::Unauthorized as u32)
+        )
+    );
+}
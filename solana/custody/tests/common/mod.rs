@@ -0,0 +1,335 @@
+#![allow(dead_code)]
+
+use borsh::BorshSerialize;
+use solana_money_market::pda::find_program_data;
+use solana_money_market_custody::{
+    instruction::CustodyInstruction,
+    pda::{find_borrower_info, find_collateral_vault, find_custody_authority, find_custody_config},
+    processor::process_instruction,
+    state::BorrowerInfo,
+};
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{keypair_from_seed, Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+pub struct Custody {
+    pub program_id: Pubkey,
+    pub overseer: Pubkey,
+    pub stable_mint: Pubkey,
+    pub collateral_mint: Keypair,
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub config: Pubkey,
+}
+
+pub struct User {
+    pub keypair: Keypair,
+    pub collateral: Pubkey,
+}
+
+pub fn program_test(program_id: Pubkey) -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "solana_money_market_custody",
+        program_id,
+        processor!(process_instruction),
+    );
+    add_program_data(&mut program_test, &program_id);
+    program_test.prefer_bpf(false);
+    program_test
+}
+
+/// Key the programs are deployed with; only it can initialize them
+pub fn upgrade_authority() -> Keypair {
+    keypair_from_seed(&[1; 32]).unwrap()
+}
+
+/// Records the upgrade authority of a program the way the upgradeable loader
+/// does, and funds the authority to pay for the accounts it initializes
+pub fn add_program_data(program_test: &mut ProgramTest, program_id: &Pubkey) {
+    let authority = upgrade_authority().pubkey();
+    program_test.add_account(
+        find_program_data(program_id).0,
+        Account::new_data(
+            1_000_000_000,
+            &UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(authority),
+            },
+            &bpf_loader_upgradeable::id(),
+        )
+        .unwrap(),
+    );
+    program_test.add_account(
+        authority,
+        Account::new(1_000_000_000_000, 0, &system_program::id()),
+    );
+}
+
+pub async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+pub async fn create_mint(banks_client: &mut BanksClient, payer: &Keypair, mint: &Keypair) {
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                6,
+            )
+            .unwrap(),
+        ],
+        &[mint],
+    )
+    .await
+    .unwrap();
+}
+
+pub async fn create_token_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let account = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::id(),
+                &account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        &[&account],
+    )
+    .await
+    .unwrap();
+
+    account.pubkey()
+}
+
+pub async fn token_balance(banks_client: &mut BanksClient, account: &Pubkey) -> u64 {
+    let account = banks_client.get_account(*account).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+pub async fn borrower_info(
+    banks_client: &mut BanksClient,
+    custody: &Custody,
+    borrower: &Pubkey,
+) -> BorrowerInfo {
+    let (borrower_info, _) = find_borrower_info(
+        &custody.program_id,
+        &custody.stable_mint,
+        &custody.collateral_mint.pubkey(),
+        borrower,
+    );
+    let account = banks_client
+        .get_account(borrower_info)
+        .await
+        .unwrap()
+        .unwrap();
+    BorrowerInfo::unpack(&account.data).unwrap()
+}
+
+pub async fn create_custody(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    program_id: Pubkey,
+    overseer: Pubkey,
+) -> Custody {
+    let stable_mint = Keypair::new();
+    let collateral_mint = Keypair::new();
+    create_mint(banks_client, payer, &stable_mint).await;
+    create_mint(banks_client, payer, &collateral_mint).await;
+
+    let stable_mint = stable_mint.pubkey();
+    let (authority, _) =
+        find_custody_authority(&program_id, &stable_mint, &collateral_mint.pubkey());
+    let (vault, _) = find_collateral_vault(&program_id, &stable_mint, &collateral_mint.pubkey());
+    let (config, _) = find_custody_config(&program_id, &stable_mint, &collateral_mint.pubkey());
+    let upgrade_authority = upgrade_authority();
+    process(
+        banks_client,
+        payer,
+        &[Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(upgrade_authority.pubkey(), true),
+                AccountMeta::new_readonly(find_program_data(&program_id).0, false),
+                AccountMeta::new_readonly(stable_mint, false),
+                AccountMeta::new_readonly(collateral_mint.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+            data: CustodyInstruction::InitializeCustody { overseer }
+                .try_to_vec()
+                .unwrap(),
+        }],
+        &[&upgrade_authority],
+    )
+    .await
+    .unwrap();
+
+    Custody {
+        program_id,
+        overseer,
+        stable_mint,
+        collateral_mint,
+        authority,
+        vault,
+        config,
+    }
+}
+
+pub async fn create_user(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    custody: &Custody,
+    collateral_amount: u64,
+) -> User {
+    let keypair = Keypair::new();
+    process(
+        banks_client,
+        payer,
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &keypair.pubkey(),
+            1_000_000_000,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let collateral = create_token_account(
+        banks_client,
+        payer,
+        &custody.collateral_mint.pubkey(),
+        &keypair.pubkey(),
+    )
+    .await;
+    process(
+        banks_client,
+        payer,
+        &[spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &custody.collateral_mint.pubkey(),
+            &collateral,
+            &payer.pubkey(),
+            &[],
+            collateral_amount,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    User {
+        keypair,
+        collateral,
+    }
+}
+
+pub fn deposit_collateral(custody: &Custody, user: &User, amount: u64) -> Instruction {
+    let (borrower_info, _) = find_borrower_info(
+        &custody.program_id,
+        &custody.stable_mint,
+        &custody.collateral_mint.pubkey(),
+        &user.keypair.pubkey(),
+    );
+    Instruction {
+        program_id: custody.program_id,
+        accounts: vec![
+            AccountMeta::new(user.keypair.pubkey(), true),
+            AccountMeta::new(user.collateral, false),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(borrower_info, false),
+            AccountMeta::new(custody.vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: CustodyInstruction::DepositCollateral { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn withdraw_collateral(custody: &Custody, user: &User, amount: Option<u64>) -> Instruction {
+    let (borrower_info, _) = find_borrower_info(
+        &custody.program_id,
+        &custody.stable_mint,
+        &custody.collateral_mint.pubkey(),
+        &user.keypair.pubkey(),
+    );
+    Instruction {
+        program_id: custody.program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(user.keypair.pubkey(), true),
+            AccountMeta::new(user.collateral, false),
+            AccountMeta::new_readonly(custody.config, false),
+            AccountMeta::new(borrower_info, false),
+            AccountMeta::new(custody.vault, false),
+            AccountMeta::new_readonly(custody.authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: CustodyInstruction::WithdrawCollateral { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
@@ -0,0 +1,621 @@
+use borsh::BorshSerialize;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use moneymarket_math::liquidation::{compute_fee_deductor, compute_liquidation_ratio};
+use solana_money_market::borrow::{compute_loan_amount, load_liability};
+use solana_money_market::deposit::to_u64;
+use solana_money_market::instruction::MarketInstruction;
+use solana_money_market::pda::find_liability;
+use solana_money_market::processor::{
+    assert_token_program, authority_seeds, create_pda_account, load_market,
+};
+use solana_money_market::state::BorrowLimit;
+use solana_money_market_custody::instruction::CustodyInstruction;
+use solana_money_market_custody::state::Config as CustodyConfig;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::Sysvar,
+};
+
+use crate::error::OverseerError;
+use crate::pda::{
+    find_borrow_limit, find_collaterals, find_proceeds, BORROW_LIMIT_SEED, COLLATERALS_SEED,
+};
+use crate::processor::{assert_authority, load_collaterals, load_config, load_whitelist_elem};
+use crate::state::{Collaterals, Config, STATE_VERSION};
+
+/// Accounts a liquidation needs for every locked collateral
+const LIQUIDATION_ACCOUNTS_PER_COLLATERAL: usize = 6;
+
+pub fn process_lock_collateral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let borrower_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let whitelist_info = next_account_info(account_info_iter)?;
+    let collaterals_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let custody_program_info = next_account_info(account_info_iter)?;
+    let custody_config_info = next_account_info(account_info_iter)?;
+    let custody_borrower_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if !borrower_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *system_program_info.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let config = load_config(program_id, config_info)?;
+    let authority_bump = assert_authority(program_id, &config, authority_info)?;
+    let elem = load_whitelist_elem(program_id, &config, whitelist_info)?;
+    let custody_config = load_custody_config(&config, custody_program_info, custody_config_info)?;
+    if custody_config.collateral_mint != elem.collateral_mint {
+        return Err(OverseerError::MintMismatch.into());
+    }
+
+    // Collaterals accounts are created on the first lock
+    let mut collaterals =
+        load_collaterals(program_id, &config, collaterals_info, borrower_info.key)?;
+    if collaterals_info.data_is_empty() {
+        let (_, bump) = find_collaterals(program_id, &config.stable_mint, borrower_info.key);
+        create_pda_account(
+            borrower_info,
+            collaterals_info,
+            system_program_info,
+            program_id,
+            Collaterals::LEN,
+            &[
+                COLLATERALS_SEED,
+                config.stable_mint.as_ref(),
+                borrower_info.key.as_ref(),
+                &[bump],
+            ],
+        )?;
+    }
+
+    collaterals.add(elem.collateral_mint, Uint256::from(amount))?;
+    Collaterals::pack(collaterals, &mut collaterals_info.data.borrow_mut())?;
+
+    invoke_signed(
+        &custody_instruction(
+            &config,
+            vec![
+                AccountMeta::new_readonly(*authority_info.key, true),
+                AccountMeta::new_readonly(*custody_config_info.key, false),
+                AccountMeta::new(*custody_borrower_info.key, false),
+            ],
+            CustodyInstruction::LockCollateral {
+                borrower: *borrower_info.key,
+                amount,
+            },
+        )?,
+        &[
+            authority_info.clone(),
+            custody_config_info.clone(),
+            custody_borrower_info.clone(),
+            custody_program_info.clone(),
+        ],
+        &[&authority_seeds(&config.stable_mint, &[authority_bump])],
+    )?;
+
+    msg!(
+        "borrower: {}, collateral: {}{}",
+        borrower_info.key,
+        amount,
+        elem.collateral_mint
+    );
+
+    Ok(())
+}
+
+pub fn process_unlock_collateral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let borrower_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let collaterals_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let custody_program_info = next_account_info(account_info_iter)?;
+    let custody_config_info = next_account_info(account_info_iter)?;
+    let custody_borrower_info = next_account_info(account_info_iter)?;
+    let market_accounts = next_market_accounts(account_info_iter)?;
+    let whitelist_infos = account_info_iter.as_slice();
+
+    if !borrower_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_info)?;
+    let authority_bump = assert_authority(program_id, &config, authority_info)?;
+    let custody_config = load_custody_config(&config, custody_program_info, custody_config_info)?;
+    let mut collaterals =
+        load_collaterals(program_id, &config, collaterals_info, borrower_info.key)?;
+
+    // Underflow check is done in sub
+    collaterals.sub(custody_config.collateral_mint, Uint256::from(amount))?;
+
+    // Compute borrow limit with collaterals except unlock target collaterals
+    let clock = Clock::get()?;
+    let (borrow_limit, _) = compute_borrow_limit(
+        program_id,
+        &config,
+        &collaterals,
+        whitelist_infos,
+        clock.unix_timestamp,
+    )?;
+    let loan_amount = load_loan_amount(
+        program_id,
+        &config,
+        &market_accounts,
+        borrower_info.key,
+        clock.slot,
+    )?;
+    if borrow_limit < loan_amount {
+        return Err(OverseerError::UnlockTooHigh.into());
+    }
+
+    Collaterals::pack(collaterals, &mut collaterals_info.data.borrow_mut())?;
+
+    invoke_signed(
+        &custody_instruction(
+            &config,
+            vec![
+                AccountMeta::new_readonly(*authority_info.key, true),
+                AccountMeta::new_readonly(*custody_config_info.key, false),
+                AccountMeta::new(*custody_borrower_info.key, false),
+            ],
+            CustodyInstruction::UnlockCollateral {
+                borrower: *borrower_info.key,
+                amount,
+            },
+        )?,
+        &[
+            authority_info.clone(),
+            custody_config_info.clone(),
+            custody_borrower_info.clone(),
+            custody_program_info.clone(),
+        ],
+        &[&authority_seeds(&config.stable_mint, &[authority_bump])],
+    )?;
+
+    msg!(
+        "borrower: {}, collateral: {}{}",
+        borrower_info.key,
+        amount,
+        custody_config.collateral_mint
+    );
+
+    Ok(())
+}
+
+pub fn process_update_borrow_limit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    borrower: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let collaterals_info = next_account_info(account_info_iter)?;
+    let borrow_limit_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let whitelist_infos = account_info_iter.as_slice();
+
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *system_program_info.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let config = load_config(program_id, config_info)?;
+    let collaterals = load_collaterals(program_id, &config, collaterals_info, &borrower)?;
+
+    let clock = Clock::get()?;
+    let (borrow_limit, _) = compute_borrow_limit(
+        program_id,
+        &config,
+        &collaterals,
+        whitelist_infos,
+        clock.unix_timestamp,
+    )?;
+
+    // Borrow limit accounts are created on the first update
+    let (expected_borrow_limit, bump) =
+        find_borrow_limit(program_id, &config.stable_mint, &borrower);
+    if *borrow_limit_info.key != expected_borrow_limit {
+        return Err(OverseerError::InvalidSeeds.into());
+    }
+    if borrow_limit_info.data_is_empty() {
+        create_pda_account(
+            payer_info,
+            borrow_limit_info,
+            system_program_info,
+            program_id,
+            BorrowLimit::LEN,
+            &[
+                BORROW_LIMIT_SEED,
+                config.stable_mint.as_ref(),
+                borrower.as_ref(),
+                &[bump],
+            ],
+        )?;
+    } else if borrow_limit_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    BorrowLimit::pack(
+        BorrowLimit {
+            version: STATE_VERSION,
+            is_initialized: true,
+            borrower,
+            stable_mint: config.stable_mint,
+            borrow_limit,
+            last_updated_slot: clock.slot,
+        },
+        &mut borrow_limit_info.data.borrow_mut(),
+    )?;
+
+    msg!("borrower: {}, borrow_limit: {}", borrower, borrow_limit);
+
+    Ok(())
+}
+
+pub fn process_liquidate_collateral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    borrower: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let liquidator_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let collaterals_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let proceeds_info = next_account_info(account_info_iter)?;
+    let custody_program_info = next_account_info(account_info_iter)?;
+    let market_program_info = next_account_info(account_info_iter)?;
+    let market_accounts = next_market_accounts(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let collateral_infos = account_info_iter.as_slice();
+
+    if !liquidator_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    assert_token_program(token_program_info)?;
+
+    let config = load_config(program_id, config_info)?;
+    let authority_bump = assert_authority(program_id, &config, authority_info)?;
+    let (expected_proceeds, _) = find_proceeds(program_id, &config.stable_mint);
+    if *proceeds_info.key != expected_proceeds {
+        return Err(OverseerError::InvalidSeeds.into());
+    }
+    if *market_program_info.key != config.market || *custody_program_info.key != config.custody {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut collaterals = load_collaterals(program_id, &config, collaterals_info, &borrower)?;
+    if collateral_infos.len() < collaterals.collaterals.len() * LIQUIDATION_ACCOUNTS_PER_COLLATERAL
+    {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let collateral_infos: Vec<&[AccountInfo]> = collateral_infos
+        .chunks(LIQUIDATION_ACCOUNTS_PER_COLLATERAL)
+        .take(collaterals.collaterals.len())
+        .collect();
+    let whitelist_infos: Vec<AccountInfo> = collateral_infos.iter().map(|c| c[0].clone()).collect();
+
+    // Compute borrow limit with all locked collaterals
+    let clock = Clock::get()?;
+    let (borrow_limit, collateral_prices) = compute_borrow_limit(
+        program_id,
+        &config,
+        &collaterals,
+        &whitelist_infos,
+        clock.unix_timestamp,
+    )?;
+    let borrow_amount =
+        load_loan_amount(program_id, &config, &market_accounts, &borrower, clock.slot)?;
+
+    // borrow limit is equal or bigger than loan amount
+    // cannot liquidation collaterals
+    if borrow_limit >= borrow_amount {
+        return Err(OverseerError::CannotLiquidateSafeLoan.into());
+    }
+
+    let mut collaterals_value = Uint256::zero();
+    for (collateral, price) in collaterals.collaterals.iter().zip(collateral_prices.iter()) {
+        collaterals_value += collateral.1 * *price;
+    }
+
+    // Liquidators pay the collateral value less the max premium rate, with
+    // no bid fee or tax on Solana
+    let fee_deductor = compute_fee_deductor(
+        config.max_premium_rate,
+        Decimal256::zero(),
+        Decimal256::zero(),
+    );
+    let liquidation_ratio = compute_liquidation_ratio(
+        borrow_amount,
+        borrow_limit,
+        collaterals_value,
+        fee_deductor,
+        config.safe_ratio,
+        config.liquidation_threshold,
+    );
+
+    let mut repay_amount = Uint256::zero();
+    let locked = collaterals.collaterals.clone();
+    for ((collateral, price), infos) in locked
+        .iter()
+        .zip(collateral_prices.iter())
+        .zip(collateral_infos.iter())
+    {
+        // Liquidate no more than what is needed to repay the rest of the
+        // loan, so the liquidator never keeps collateral it did not pay for
+        let unit_value = *price * fee_deductor;
+        let remaining_loan = borrow_amount - repay_amount;
+        let mut liquidation_amount = collateral.1 * liquidation_ratio;
+        if liquidation_amount * unit_value > remaining_loan {
+            liquidation_amount = remaining_loan / unit_value;
+        }
+        if liquidation_amount.is_zero() {
+            continue;
+        }
+
+        let custody_config = load_custody_config(&config, custody_program_info, &infos[1])?;
+        if custody_config.collateral_mint != collateral.0 {
+            return Err(OverseerError::MintMismatch.into());
+        }
+
+        collaterals.sub(collateral.0, liquidation_amount)?;
+        repay_amount += liquidation_amount * unit_value;
+
+        invoke_signed(
+            &custody_instruction(
+                &config,
+                vec![
+                    AccountMeta::new_readonly(*authority_info.key, true),
+                    AccountMeta::new_readonly(*infos[1].key, false),
+                    AccountMeta::new(*infos[2].key, false),
+                    AccountMeta::new(*infos[3].key, false),
+                    AccountMeta::new_readonly(*infos[4].key, false),
+                    AccountMeta::new(*infos[5].key, false),
+                    AccountMeta::new_readonly(*token_program_info.key, false),
+                ],
+                CustodyInstruction::LiquidateCollateral {
+                    borrower,
+                    amount: to_u64(liquidation_amount)?,
+                },
+            )?,
+            &[
+                authority_info.clone(),
+                infos[1].clone(),
+                infos[2].clone(),
+                infos[3].clone(),
+                infos[4].clone(),
+                infos[5].clone(),
+                token_program_info.clone(),
+                custody_program_info.clone(),
+            ],
+            &[&authority_seeds(&config.stable_mint, &[authority_bump])],
+        )?;
+    }
+
+    // Store left collaterals
+    Collaterals::pack(collaterals, &mut collaterals_info.data.borrow_mut())?;
+
+    let repay_amount = to_u64(repay_amount)?;
+    if repay_amount != 0 {
+        invoke(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                source_info.key,
+                proceeds_info.key,
+                liquidator_info.key,
+                &[],
+                repay_amount,
+            )?,
+            &[
+                source_info.clone(),
+                proceeds_info.clone(),
+                liquidator_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+
+        let MarketAccounts {
+            config: market_config_info,
+            state: market_state_info,
+            interest_model: interest_model_info,
+            liability: liability_info,
+            vault: vault_info,
+            atoken_mint: atoken_mint_info,
+        } = market_accounts;
+        invoke_signed(
+            &Instruction {
+                program_id: config.market,
+                accounts: vec![
+                    AccountMeta::new_readonly(*authority_info.key, true),
+                    AccountMeta::new(*proceeds_info.key, false),
+                    AccountMeta::new_readonly(*market_config_info.key, false),
+                    AccountMeta::new(*market_state_info.key, false),
+                    AccountMeta::new_readonly(*interest_model_info.key, false),
+                    AccountMeta::new(*liability_info.key, false),
+                    AccountMeta::new(*vault_info.key, false),
+                    AccountMeta::new_readonly(*atoken_mint_info.key, false),
+                    AccountMeta::new_readonly(*token_program_info.key, false),
+                ],
+                data: MarketInstruction::RepayStableFromLiquidation {
+                    borrower,
+                    amount: repay_amount,
+                }
+                .try_to_vec()
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            &[
+                authority_info.clone(),
+                proceeds_info.clone(),
+                market_config_info.clone(),
+                market_state_info.clone(),
+                interest_model_info.clone(),
+                liability_info.clone(),
+                vault_info.clone(),
+                atoken_mint_info.clone(),
+                token_program_info.clone(),
+                market_program_info.clone(),
+            ],
+            &[&authority_seeds(&config.stable_mint, &[authority_bump])],
+        )?;
+    }
+
+    msg!(
+        "liquidator: {}, borrower: {}, repay_amount: {}",
+        liquidator_info.key,
+        borrower,
+        repay_amount
+    );
+
+    Ok(())
+}
+
+/// Market accounts needed to read the loan of a borrower
+struct MarketAccounts<'a, 'b> {
+    config: &'a AccountInfo<'b>,
+    state: &'a AccountInfo<'b>,
+    interest_model: &'a AccountInfo<'b>,
+    liability: &'a AccountInfo<'b>,
+    vault: &'a AccountInfo<'b>,
+    atoken_mint: &'a AccountInfo<'b>,
+}
+
+fn next_market_accounts<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    iter: &mut I,
+) -> Result<MarketAccounts<'a, 'b>, ProgramError> {
+    Ok(MarketAccounts {
+        config: next_account_info(iter)?,
+        state: next_account_info(iter)?,
+        interest_model: next_account_info(iter)?,
+        liability: next_account_info(iter)?,
+        vault: next_account_info(iter)?,
+        atoken_mint: next_account_info(iter)?,
+    })
+}
+
+/// Loan amount of the borrower with interest accrued up to the slot; zero
+/// before the first borrow.
+fn load_loan_amount(
+    program_id: &Pubkey,
+    config: &Config,
+    market_accounts: &MarketAccounts,
+    borrower: &Pubkey,
+    slot: u64,
+) -> Result<Uint256, ProgramError> {
+    let market = load_market(
+        &config.market,
+        market_accounts.config,
+        market_accounts.state,
+        market_accounts.interest_model,
+        market_accounts.vault,
+        market_accounts.atoken_mint,
+    )?;
+    if market.config.stable_mint != config.stable_mint {
+        return Err(OverseerError::MintMismatch.into());
+    }
+    if market.config.overseer != *program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (expected_liability, _) = find_liability(&config.market, &config.stable_mint, borrower);
+    if *market_accounts.liability.key != expected_liability {
+        return Err(OverseerError::InvalidSeeds.into());
+    }
+    if market_accounts.liability.data_is_empty() {
+        return Ok(Uint256::zero());
+    }
+
+    let liability = load_liability(&config.market, market_accounts.liability, borrower)?;
+    Ok(compute_loan_amount(&market, &liability, slot))
+}
+
+/// Computes the borrow limit from the locked collaterals with the prices fed
+/// within the price timeframe, and returns the prices along with it.
+pub(crate) fn compute_borrow_limit(
+    program_id: &Pubkey,
+    config: &Config,
+    collaterals: &Collaterals,
+    whitelist_infos: &[AccountInfo],
+    now: i64,
+) -> Result<(Uint256, Vec<Decimal256>), ProgramError> {
+    if whitelist_infos.len() < collaterals.collaterals.len() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut borrow_limit: Uint256 = Uint256::zero();
+    let mut collateral_prices: Vec<Decimal256> = vec![];
+    for (collateral, whitelist_info) in collaterals.collaterals.iter().zip(whitelist_infos.iter()) {
+        let elem = load_whitelist_elem(program_id, config, whitelist_info)?;
+        if elem.collateral_mint != collateral.0 {
+            return Err(OverseerError::MintMismatch.into());
+        }
+        if elem.last_price_updated < now - config.price_timeframe as i64 {
+            return Err(OverseerError::PriceTooOld.into());
+        }
+
+        let collateral_value = collateral.1 * elem.price;
+        borrow_limit += collateral_value * elem.max_ltv;
+        collateral_prices.push(elem.price);
+    }
+
+    // returns borrow_limit with collaterals value in stable denom
+    Ok((borrow_limit, collateral_prices))
+}
+
+/// Checks the custody config belongs to the configured custody program and
+/// the same market, and loads it.
+fn load_custody_config(
+    config: &Config,
+    custody_program_info: &AccountInfo,
+    custody_config_info: &AccountInfo,
+) -> Result<CustodyConfig, ProgramError> {
+    if *custody_program_info.key != config.custody || *custody_config_info.owner != config.custody {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let custody_config = CustodyConfig::unpack(&custody_config_info.data.borrow())?;
+    if custody_config.stable_mint != config.stable_mint {
+        return Err(OverseerError::MintMismatch.into());
+    }
+
+    Ok(custody_config)
+}
+
+fn custody_instruction(
+    config: &Config,
+    accounts: Vec<AccountMeta>,
+    instruction: CustodyInstruction,
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: config.custody,
+        accounts,
+        data: instruction
+            .try_to_vec()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    })
+}
@@ -0,0 +1,32 @@
+use solana_program::program_error::ProgramError;
+use thiserror::Error;
+
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum OverseerError {
+    #[error("Invalid instruction")]
+    InvalidInstruction,
+    #[error("Account does not match the expected program derived address")]
+    InvalidSeeds,
+    #[error("Token account mint does not match the expected mint")]
+    MintMismatch,
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("Collateral is not whitelisted")]
+    NotWhitelisted,
+    #[error("Price is too old")]
+    PriceTooOld,
+    #[error("Cannot lock more collateral kinds")]
+    TooManyCollaterals,
+    #[error("Unlock amount cannot exceed locked amount")]
+    UnlockAmountExceedsLocked,
+    #[error("Unlock amount too high; Loan liability becomes greater than borrow limit")]
+    UnlockTooHigh,
+    #[error("Cannot liquidate safely collateralized loan")]
+    CannotLiquidateSafeLoan,
+}
+
+impl From<OverseerError> for ProgramError {
+    fn from(e: OverseerError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,131 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+// Decimal arguments are raw values with 18 fractional digits, see
+// `solana_money_market::state::decimal_from_atomics`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum OverseerInstruction {
+    /// Creates the config and liquidation proceeds accounts of the overseer
+    /// for the market of a stable mint. The payer must be the upgrade
+    /// authority of the program and becomes the owner.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[writable, signer]` Payer, the upgrade authority of the program
+    ///   1. `[]` Program data account of the program
+    ///   2. `[]` Stable mint
+    ///   3. `[]` Overseer authority PDA
+    ///   4. `[writable]` Proceeds PDA
+    ///   5. `[writable]` Config PDA
+    ///   6. `[]` System program
+    ///   7. `[]` Token program
+    InitializeOverseer {
+        market: Pubkey,
+        custody: Pubkey,
+        oracle_feeder: Pubkey,
+        price_timeframe: u64,
+        max_premium_rate: u128,
+        safe_ratio: u128,
+        liquidation_threshold: u64,
+    },
+
+    /// Whitelists a collateral mint, or updates its max ltv.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[writable, signer]` Owner
+    ///   1. `[]` Config PDA
+    ///   2. `[]` Collateral mint
+    ///   3. `[writable]` Whitelist PDA of the collateral
+    ///   4. `[]` System program
+    Whitelist { max_ltv: u128 },
+
+    /// Records the collateral price in the stable.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Oracle feeder
+    ///   1. `[]` Config PDA
+    ///   2. `[writable]` Whitelist PDA of the collateral
+    FeedPrice { price: u128 },
+
+    /// Locks spendable collateral held by the custody program.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[writable, signer]` Borrower
+    ///   1. `[]` Config PDA
+    ///   2. `[]` Whitelist PDA of the collateral
+    ///   3. `[writable]` Collaterals PDA of the borrower
+    ///   4. `[]` Overseer authority PDA
+    ///   5. `[]` Custody program
+    ///   6. `[]` Custody config PDA of the collateral
+    ///   7. `[writable]` Custody borrower info PDA
+    ///   8. `[]` System program
+    LockCollateral { amount: u64 },
+
+    /// Unlocks collateral as long as the loan stays within the borrow limit
+    /// of what is left locked.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Borrower
+    ///   1. `[]` Config PDA
+    ///   2. `[writable]` Collaterals PDA of the borrower
+    ///   3. `[]` Overseer authority PDA
+    ///   4. `[]` Custody program
+    ///   5. `[]` Custody config PDA of the collateral
+    ///   6. `[writable]` Custody borrower info PDA
+    ///   7. `[]` Market config PDA
+    ///   8. `[]` Market state PDA
+    ///   9. `[]` Market interest model PDA
+    ///   10. `[]` Market liability PDA of the borrower
+    ///   11. `[]` Market stable vault PDA
+    ///   12. `[]` Market aToken mint PDA
+    ///   13. `[]` Whitelist PDA of every locked collateral, one each in mint order
+    UnlockCollateral { amount: u64 },
+
+    /// Records the borrow limit of a borrower for the current slot, which the
+    /// market requires for borrowing in the same transaction.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[writable, signer]` Payer
+    ///   1. `[]` Config PDA
+    ///   2. `[]` Collaterals PDA of the borrower
+    ///   3. `[writable]` Borrow limit PDA of the borrower
+    ///   4. `[]` System program
+    ///   5. `[]` Whitelist PDA of every locked collateral, one each in mint order
+    UpdateBorrowLimit { borrower: Pubkey },
+
+    /// Liquidates the collaterals of a borrower whose loan exceeds the borrow
+    /// limit. The liquidator receives the collaterals and pays their value
+    /// less the max premium rate, which repays the loan. No more collateral
+    /// is liquidated than the loan can absorb.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Liquidator
+    ///   1. `[writable]` Liquidator stable token account
+    ///   2. `[]` Config PDA
+    ///   3. `[writable]` Collaterals PDA of the borrower
+    ///   4. `[]` Overseer authority PDA
+    ///   5. `[writable]` Proceeds PDA
+    ///   6. `[]` Custody program
+    ///   7. `[]` Market program
+    ///   8. `[]` Market config PDA
+    ///   9. `[writable]` Market state PDA
+    ///   10. `[]` Market interest model PDA
+    ///   11. `[writable]` Market liability PDA of the borrower
+    ///   12. `[writable]` Market stable vault PDA
+    ///   13. `[]` Market aToken mint PDA
+    ///   14. `[]` Token program
+    ///   15. For every locked collateral, in mint order:
+    ///       - `[]` Whitelist PDA
+    ///       - `[]` Custody config PDA
+    ///       - `[writable]` Custody borrower info PDA
+    ///       - `[writable]` Custody collateral vault PDA
+    ///       - `[]` Custody authority PDA
+    ///       - `[writable]` Liquidator collateral token account
+    LiquidateCollateral { borrower: Pubkey },
+}
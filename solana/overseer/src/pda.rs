@@ -0,0 +1,63 @@
+use solana_program::pubkey::Pubkey;
+
+pub use solana_money_market::pda::AUTHORITY_SEED;
+
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const PROCEEDS_SEED: &[u8] = b"proceeds";
+pub const WHITELIST_SEED: &[u8] = b"whitelist";
+pub const COLLATERALS_SEED: &[u8] = b"collaterals";
+pub const BORROW_LIMIT_SEED: &[u8] = b"borrow_limit";
+
+// Every overseer is keyed by the stable mint of its market. The authority
+// PDA is the one the market and custody programs expect to sign
+// liquidations, see `solana_money_market::pda::find_overseer_authority`.
+pub fn find_authority(program_id: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_SEED, stable_mint.as_ref()], program_id)
+}
+
+pub fn find_config(program_id: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED, stable_mint.as_ref()], program_id)
+}
+
+// Stable token account owned by the authority; liquidators pay into it and
+// the market pulls the repayment from it.
+pub fn find_proceeds(program_id: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROCEEDS_SEED, stable_mint.as_ref()], program_id)
+}
+
+pub fn find_whitelist_elem(
+    program_id: &Pubkey,
+    stable_mint: &Pubkey,
+    collateral_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            WHITELIST_SEED,
+            stable_mint.as_ref(),
+            collateral_mint.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+pub fn find_collaterals(
+    program_id: &Pubkey,
+    stable_mint: &Pubkey,
+    borrower: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[COLLATERALS_SEED, stable_mint.as_ref(), borrower.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_borrow_limit(
+    program_id: &Pubkey,
+    stable_mint: &Pubkey,
+    borrower: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BORROW_LIMIT_SEED, stable_mint.as_ref(), borrower.as_ref()],
+        program_id,
+    )
+}
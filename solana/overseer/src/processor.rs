@@ -0,0 +1,321 @@
+use borsh::BorshDeserialize;
+use cosmwasm_bignumber::Uint256;
+use solana_money_market::processor::{
+    assert_token_program, assert_upgrade_authority, create_pda_account, unpack_mint,
+};
+use solana_money_market::state::decimal_from_atomics;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+use crate::collateral::{
+    process_liquidate_collateral, process_lock_collateral, process_unlock_collateral,
+    process_update_borrow_limit,
+};
+use crate::error::OverseerError;
+use crate::instruction::OverseerInstruction;
+use crate::pda::{
+    find_authority, find_collaterals, find_config, find_proceeds, find_whitelist_elem, CONFIG_SEED,
+    PROCEEDS_SEED, WHITELIST_SEED,
+};
+use crate::state::{Collaterals, Config, WhitelistElem, STATE_VERSION};
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = OverseerInstruction::try_from_slice(instruction_data)
+        .map_err(|_| OverseerError::InvalidInstruction)?;
+
+    match instruction {
+        OverseerInstruction::InitializeOverseer {
+            market,
+            custody,
+            oracle_feeder,
+            price_timeframe,
+            max_premium_rate,
+            safe_ratio,
+            liquidation_threshold,
+        } => {
+            msg!("Instruction: InitializeOverseer");
+            process_initialize_overseer(
+                program_id,
+                accounts,
+                Config {
+                    version: STATE_VERSION,
+                    is_initialized: true,
+                    owner: Pubkey::default(),
+                    market,
+                    custody,
+                    stable_mint: Pubkey::default(),
+                    oracle_feeder,
+                    price_timeframe,
+                    max_premium_rate: decimal_from_atomics(max_premium_rate),
+                    safe_ratio: decimal_from_atomics(safe_ratio),
+                    liquidation_threshold: Uint256::from(liquidation_threshold),
+                },
+            )
+        }
+        OverseerInstruction::Whitelist { max_ltv } => {
+            msg!("Instruction: Whitelist");
+            process_whitelist(program_id, accounts, max_ltv)
+        }
+        OverseerInstruction::FeedPrice { price } => {
+            msg!("Instruction: FeedPrice");
+            process_feed_price(program_id, accounts, price)
+        }
+        OverseerInstruction::LockCollateral { amount } => {
+            msg!("Instruction: LockCollateral");
+            process_lock_collateral(program_id, accounts, amount)
+        }
+        OverseerInstruction::UnlockCollateral { amount } => {
+            msg!("Instruction: UnlockCollateral");
+            process_unlock_collateral(program_id, accounts, amount)
+        }
+        OverseerInstruction::UpdateBorrowLimit { borrower } => {
+            msg!("Instruction: UpdateBorrowLimit");
+            process_update_borrow_limit(program_id, accounts, borrower)
+        }
+        OverseerInstruction::LiquidateCollateral { borrower } => {
+            msg!("Instruction: LiquidateCollateral");
+            process_liquidate_collateral(program_id, accounts, borrower)
+        }
+    }
+}
+
+fn process_initialize_overseer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mut config: Config,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let program_data_info = next_account_info(account_info_iter)?;
+    let stable_mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let proceeds_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    assert_upgrade_authority(program_id, program_data_info, payer_info)?;
+    if *system_program_info.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    assert_token_program(token_program_info)?;
+
+    let stable_mint = stable_mint_info.key;
+    unpack_mint(stable_mint_info)?;
+    let (authority, _) = find_authority(program_id, stable_mint);
+    let (proceeds, proceeds_bump) = find_proceeds(program_id, stable_mint);
+    let (expected_config, config_bump) = find_config(program_id, stable_mint);
+    if *authority_info.key != authority
+        || *proceeds_info.key != proceeds
+        || *config_info.key != expected_config
+    {
+        return Err(OverseerError::InvalidSeeds.into());
+    }
+
+    create_pda_account(
+        payer_info,
+        proceeds_info,
+        system_program_info,
+        token_program_info.key,
+        TokenAccount::LEN,
+        &[PROCEEDS_SEED, stable_mint.as_ref(), &[proceeds_bump]],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_account3(
+            token_program_info.key,
+            proceeds_info.key,
+            stable_mint,
+            authority_info.key,
+        )?,
+        &[
+            proceeds_info.clone(),
+            stable_mint_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    create_pda_account(
+        payer_info,
+        config_info,
+        system_program_info,
+        program_id,
+        Config::LEN,
+        &[CONFIG_SEED, stable_mint.as_ref(), &[config_bump]],
+    )?;
+    config.owner = *payer_info.key;
+    config.stable_mint = *stable_mint;
+    Config::pack(config, &mut config_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_whitelist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_ltv: u128,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let collateral_mint_info = next_account_info(account_info_iter)?;
+    let whitelist_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_info)?;
+    if !owner_info.is_signer || *owner_info.key != config.owner {
+        return Err(OverseerError::Unauthorized.into());
+    }
+    if *system_program_info.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let collateral_mint = collateral_mint_info.key;
+    unpack_mint(collateral_mint_info)?;
+    let (expected_whitelist, whitelist_bump) =
+        find_whitelist_elem(program_id, &config.stable_mint, collateral_mint);
+    if *whitelist_info.key != expected_whitelist {
+        return Err(OverseerError::InvalidSeeds.into());
+    }
+
+    let max_ltv = decimal_from_atomics(max_ltv);
+    let elem = if whitelist_info.data_is_empty() {
+        create_pda_account(
+            owner_info,
+            whitelist_info,
+            system_program_info,
+            program_id,
+            WhitelistElem::LEN,
+            &[
+                WHITELIST_SEED,
+                config.stable_mint.as_ref(),
+                collateral_mint.as_ref(),
+                &[whitelist_bump],
+            ],
+        )?;
+        WhitelistElem {
+            version: STATE_VERSION,
+            is_initialized: true,
+            collateral_mint: *collateral_mint,
+            max_ltv,
+            ..WhitelistElem::default()
+        }
+    } else {
+        WhitelistElem {
+            max_ltv,
+            ..load_whitelist_elem(program_id, &config, whitelist_info)?
+        }
+    };
+    WhitelistElem::pack(elem, &mut whitelist_info.data.borrow_mut())?;
+
+    msg!("collateral_mint: {}, max_ltv: {}", collateral_mint, max_ltv);
+
+    Ok(())
+}
+
+fn process_feed_price(program_id: &Pubkey, accounts: &[AccountInfo], price: u128) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let feeder_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let whitelist_info = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_info)?;
+    if !feeder_info.is_signer || *feeder_info.key != config.oracle_feeder {
+        return Err(OverseerError::Unauthorized.into());
+    }
+
+    let mut elem = load_whitelist_elem(program_id, &config, whitelist_info)?;
+    elem.price = decimal_from_atomics(price);
+    elem.last_price_updated = Clock::get()?.unix_timestamp;
+    WhitelistElem::pack(elem, &mut whitelist_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Checks the config is the overseer PDA it claims to be and loads it.
+pub(crate) fn load_config(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+) -> Result<Config, ProgramError> {
+    if config_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let config = Config::unpack(&config_info.data.borrow())?;
+    let (expected_config, _) = find_config(program_id, &config.stable_mint);
+    if *config_info.key != expected_config {
+        return Err(OverseerError::InvalidSeeds.into());
+    }
+
+    Ok(config)
+}
+
+/// Checks the authority derives from the stable mint and returns its bump
+/// seed.
+pub(crate) fn assert_authority(
+    program_id: &Pubkey,
+    config: &Config,
+    authority_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (expected_authority, bump) = find_authority(program_id, &config.stable_mint);
+    if *authority_info.key != expected_authority {
+        return Err(OverseerError::InvalidSeeds.into());
+    }
+
+    Ok(bump)
+}
+
+pub(crate) fn load_whitelist_elem(
+    program_id: &Pubkey,
+    config: &Config,
+    whitelist_info: &AccountInfo,
+) -> Result<WhitelistElem, ProgramError> {
+    if whitelist_info.owner != program_id {
+        return Err(OverseerError::NotWhitelisted.into());
+    }
+
+    let elem = WhitelistElem::unpack(&whitelist_info.data.borrow())?;
+    let (expected_whitelist, _) =
+        find_whitelist_elem(program_id, &config.stable_mint, &elem.collateral_mint);
+    if *whitelist_info.key != expected_whitelist {
+        return Err(OverseerError::InvalidSeeds.into());
+    }
+
+    Ok(elem)
+}
+
+/// Loads the collaterals a borrower locked; nothing is locked before the
+/// account is created.
+pub(crate) fn load_collaterals(
+    program_id: &Pubkey,
+    config: &Config,
+    collaterals_info: &AccountInfo,
+    borrower: &Pubkey,
+) -> Result<Collaterals, ProgramError> {
+    let (expected_collaterals, _) = find_collaterals(program_id, &config.stable_mint, borrower);
+    if *collaterals_info.key != expected_collaterals {
+        return Err(OverseerError::InvalidSeeds.into());
+    }
+    if collaterals_info.data_is_empty() {
+        return Ok(Collaterals::new(*borrower));
+    }
+    if collaterals_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Collaterals::unpack(&collaterals_info.data.borrow())
+}
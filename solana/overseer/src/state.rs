@@ -0,0 +1,273 @@
+use borsh::{
+    maybestd::io::{Error as IoError, ErrorKind, Read, Result as IoResult, Write},
+    BorshDeserialize, BorshSerialize,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use solana_money_market::state::{
+    assert_version, deserialize_u256, pack_borsh, serialize_u256, U256_LEN,
+};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::error::OverseerError;
+
+pub use solana_money_market::state::STATE_VERSION;
+
+/// Collateral kinds a borrower can lock at once; bounds the collaterals
+/// account size.
+pub const MAX_COLLATERALS: usize = 8;
+
+// Mirrors `moneymarket_overseer::state::Config`. There is no liquidation
+// contract on Solana; liquidators buy collateral straight from custody, so
+// the liquidation settings live here too.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub custody: Pubkey,
+    pub stable_mint: Pubkey,
+    pub oracle_feeder: Pubkey,
+    pub price_timeframe: u64,
+    pub max_premium_rate: Decimal256,
+    pub safe_ratio: Decimal256,
+    pub liquidation_threshold: Uint256,
+}
+
+// Mirrors `moneymarket_overseer::state::WhitelistElem`. The collateral price
+// in the stable is fed here by the oracle feeder, standing in for the oracle
+// contract `Price` query.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WhitelistElem {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub collateral_mint: Pubkey,
+    pub max_ltv: Decimal256,
+    pub price: Decimal256,
+    pub last_price_updated: i64,
+}
+
+// Collaterals locked by a borrower, sorted by mint like the `Tokens` the
+// overseer contract stores.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Collaterals {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub borrower: Pubkey,
+    pub collaterals: Vec<(Pubkey, Uint256)>,
+}
+
+impl Collaterals {
+    pub fn new(borrower: Pubkey) -> Self {
+        Collaterals {
+            version: STATE_VERSION,
+            is_initialized: true,
+            borrower,
+            collaterals: vec![],
+        }
+    }
+
+    pub fn add(&mut self, mint: Pubkey, amount: Uint256) -> Result<(), ProgramError> {
+        match self.collaterals.binary_search_by(|c| c.0.cmp(&mint)) {
+            Ok(i) => self.collaterals[i].1 += amount,
+            Err(i) => {
+                if self.collaterals.len() >= MAX_COLLATERALS {
+                    return Err(OverseerError::TooManyCollaterals.into());
+                }
+                self.collaterals.insert(i, (mint, amount));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn sub(&mut self, mint: Pubkey, amount: Uint256) -> Result<(), ProgramError> {
+        let i = self
+            .collaterals
+            .binary_search_by(|c| c.0.cmp(&mint))
+            .map_err(|_| OverseerError::UnlockAmountExceedsLocked)?;
+        if self.collaterals[i].1 < amount {
+            return Err(OverseerError::UnlockAmountExceedsLocked.into());
+        }
+
+        self.collaterals[i].1 = self.collaterals[i].1 - amount;
+        if self.collaterals[i].1.is_zero() {
+            self.collaterals.remove(i);
+        }
+
+        Ok(())
+    }
+}
+
+impl BorshSerialize for Config {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        self.owner.serialize(writer)?;
+        self.market.serialize(writer)?;
+        self.custody.serialize(writer)?;
+        self.stable_mint.serialize(writer)?;
+        self.oracle_feeder.serialize(writer)?;
+        self.price_timeframe.serialize(writer)?;
+        serialize_u256(&self.max_premium_rate.0, writer)?;
+        serialize_u256(&self.safe_ratio.0, writer)?;
+        serialize_u256(&self.liquidation_threshold.0, writer)
+    }
+}
+
+impl BorshDeserialize for Config {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        Ok(Config {
+            version: u8::deserialize_reader(reader)?,
+            is_initialized: bool::deserialize_reader(reader)?,
+            owner: Pubkey::deserialize_reader(reader)?,
+            market: Pubkey::deserialize_reader(reader)?,
+            custody: Pubkey::deserialize_reader(reader)?,
+            stable_mint: Pubkey::deserialize_reader(reader)?,
+            oracle_feeder: Pubkey::deserialize_reader(reader)?,
+            price_timeframe: u64::deserialize_reader(reader)?,
+            max_premium_rate: Decimal256(deserialize_u256(reader)?),
+            safe_ratio: Decimal256(deserialize_u256(reader)?),
+            liquidation_threshold: Uint256(deserialize_u256(reader)?),
+        })
+    }
+}
+
+impl BorshSerialize for WhitelistElem {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        self.collateral_mint.serialize(writer)?;
+        serialize_u256(&self.max_ltv.0, writer)?;
+        serialize_u256(&self.price.0, writer)?;
+        self.last_price_updated.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for WhitelistElem {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        Ok(WhitelistElem {
+            version: u8::deserialize_reader(reader)?,
+            is_initialized: bool::deserialize_reader(reader)?,
+            collateral_mint: Pubkey::deserialize_reader(reader)?,
+            max_ltv: Decimal256(deserialize_u256(reader)?),
+            price: Decimal256(deserialize_u256(reader)?),
+            last_price_updated: i64::deserialize_reader(reader)?,
+        })
+    }
+}
+
+impl BorshSerialize for Collaterals {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        self.borrower.serialize(writer)?;
+        (self.collaterals.len() as u32).serialize(writer)?;
+        for (mint, amount) in self.collaterals.iter() {
+            mint.serialize(writer)?;
+            serialize_u256(&amount.0, writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for Collaterals {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        let version = u8::deserialize_reader(reader)?;
+        let is_initialized = bool::deserialize_reader(reader)?;
+        let borrower = Pubkey::deserialize_reader(reader)?;
+        let len = u32::deserialize_reader(reader)? as usize;
+        if len > MAX_COLLATERALS {
+            return Err(IoError::new(
+                ErrorKind::InvalidData,
+                "collaterals length exceeds MAX_COLLATERALS",
+            ));
+        }
+        let mut collaterals = Vec::with_capacity(len);
+        for _ in 0..len {
+            collaterals.push((
+                Pubkey::deserialize_reader(reader)?,
+                Uint256(deserialize_u256(reader)?),
+            ));
+        }
+
+        Ok(Collaterals {
+            version,
+            is_initialized,
+            borrower,
+            collaterals,
+        })
+    }
+}
+
+impl Sealed for Config {}
+impl Sealed for WhitelistElem {}
+impl Sealed for Collaterals {}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for WhitelistElem {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Collaterals {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Config {
+    const LEN: usize = 2 + 32 * 5 + 8 + U256_LEN * 3;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let config = Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(config.version, config.is_initialized)?;
+        Ok(config)
+    }
+}
+
+impl Pack for WhitelistElem {
+    const LEN: usize = 2 + 32 + U256_LEN * 2 + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let elem = Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(elem.version, elem.is_initialized)?;
+        Ok(elem)
+    }
+}
+
+impl Pack for Collaterals {
+    const LEN: usize = 2 + 32 + 4 + (32 + U256_LEN) * MAX_COLLATERALS;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account is sized for MAX_COLLATERALS, so trailing bytes are
+        // expected after the entries in use
+        let collaterals =
+            Self::deserialize(&mut &src[..]).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(collaterals.version, collaterals.is_initialized)?;
+        Ok(collaterals)
+    }
+}
@@ -0,0 +1,268 @@
+mod common;
+
+use common::*;
+use solana_money_market_overseer::{
+    error::OverseerError,
+    state::{Collaterals, MAX_COLLATERALS, STATE_VERSION},
+};
+use solana_program::{
+    instruction::InstructionError, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+// 1 collateral is worth 100 stable and lends half of it
+async fn setup() -> (ProgramTestContext, Protocol, User) {
+    let market_program = Pubkey::new_unique();
+    let custody_program = Pubkey::new_unique();
+    let overseer_program = Pubkey::new_unique();
+    let mut context = program_test(market_program, custody_program, overseer_program)
+        .start_with_context()
+        .await;
+
+    let protocol = create_protocol(
+        &mut context.banks_client,
+        &context.payer,
+        market_program,
+        custody_program,
+        overseer_program,
+        DECIMAL_FRACTIONAL / 2,
+        DECIMAL_FRACTIONAL * 100,
+    )
+    .await;
+
+    let lender = create_user(
+        &mut context.banks_client,
+        &context.payer,
+        &protocol,
+        1_000_000,
+        0,
+    )
+    .await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[deposit_stable(&protocol, &lender, 1_000_000)],
+        &[&lender.keypair],
+    )
+    .await
+    .unwrap();
+
+    let borrower = create_user(
+        &mut context.banks_client,
+        &context.payer,
+        &protocol,
+        0,
+        1_000,
+    )
+    .await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[
+            deposit_collateral(&protocol, &borrower, 1_000),
+            lock_collateral(&protocol, &borrower, 1_000),
+        ],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    (context, protocol, borrower)
+}
+
+async fn borrow(
+    context: &mut ProgramTestContext,
+    protocol: &Protocol,
+    borrower: &User,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    let payer = context.payer.pubkey();
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[
+            update_borrow_limit(protocol, &payer, &borrower.keypair.pubkey()),
+            borrow_stable(protocol, borrower, amount),
+        ],
+        &[&borrower.keypair],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn borrow_against_locked_collateral() {
+    let (mut context, protocol, borrower) = setup().await;
+
+    let locked = collaterals(
+        &mut context.banks_client,
+        &protocol,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert_eq!(
+        locked.collaterals,
+        vec![(protocol.collateral_mint.pubkey(), 1_000u64.into())]
+    );
+
+    // 1_000 * 100 * 0.5
+    borrow(&mut context, &protocol, &borrower, 50_000)
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut context.banks_client, &borrower.stable).await,
+        50_000
+    );
+
+    let err = borrow(&mut context, &protocol, &borrower, 1)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                solana_money_market::error::MarketError::BorrowLimitExceeded as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn unlock_collateral_within_borrow_limit() {
+    let (mut context, protocol, borrower) = setup().await;
+    borrow(&mut context, &protocol, &borrower, 40_000)
+        .await
+        .unwrap();
+
+    // 700 * 100 * 0.5 < 40_000
+    let err = process(
+        &mut context.banks_client,
+        &context.payer,
+        &[unlock_collateral(&protocol, &borrower, 300)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(OverseerError::UnlockTooHigh as u32)
+        )
+    );
+
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[unlock_collateral(&protocol, &borrower, 200)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+    let locked = collaterals(
+        &mut context.banks_client,
+        &protocol,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert_eq!(
+        locked.collaterals,
+        vec![(protocol.collateral_mint.pubkey(), 800u64.into())]
+    );
+}
+
+#[tokio::test]
+async fn liquidate_collateral_after_price_drop() {
+    let (mut context, protocol, borrower) = setup().await;
+    borrow(&mut context, &protocol, &borrower, 40_000)
+        .await
+        .unwrap();
+    let liquidator = create_user(
+        &mut context.banks_client,
+        &context.payer,
+        &protocol,
+        100_000,
+        0,
+    )
+    .await;
+
+    let err = process(
+        &mut context.banks_client,
+        &context.payer,
+        &[liquidate_collateral(
+            &protocol,
+            &liquidator,
+            &borrower.keypair.pubkey(),
+        )],
+        &[&liquidator.keypair],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(OverseerError::CannotLiquidateSafeLoan as u32)
+        )
+    );
+
+    // borrow limit 1_000 * 50 * 0.5 = 25_000, collaterals value 50_000
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[
+            feed_price(&protocol, DECIMAL_FRACTIONAL * 50),
+            liquidate_collateral(&protocol, &liquidator, &borrower.keypair.pubkey()),
+        ],
+        &[&protocol.oracle_feeder, &liquidator.keypair],
+    )
+    .await
+    .unwrap();
+
+    // ratio (40_000 - 20_000) / (45_000 - 20_000) = 0.8 liquidates 800,
+    // repaying 800 * 50 * 0.9 = 36_000
+    assert_eq!(
+        token_balance(&mut context.banks_client, &liquidator.collateral).await,
+        800
+    );
+    assert_eq!(
+        token_balance(&mut context.banks_client, &liquidator.stable).await,
+        64_000
+    );
+    assert_eq!(
+        token_balance(&mut context.banks_client, &protocol.proceeds).await,
+        0
+    );
+    let locked = collaterals(
+        &mut context.banks_client,
+        &protocol,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert_eq!(
+        locked.collaterals,
+        vec![(protocol.collateral_mint.pubkey(), 200u64.into())]
+    );
+    let info = liability(
+        &mut context.banks_client,
+        &protocol,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert_eq!(info.loan_amount, 4_000u64.into());
+}
+
+#[test]
+fn unpack_rejects_oversized_collaterals() {
+    let mut data = vec![0u8; Collaterals::LEN];
+    data[0] = STATE_VERSION;
+    data[1] = 1;
+    data[2..34].copy_from_slice(Pubkey::new_unique().as_ref());
+    data[34..38].copy_from_slice(&(MAX_COLLATERALS as u32 + 1).to_le_bytes());
+
+    assert_eq!(
+        Collaterals::unpack(&data).unwrap_err(),
+        ProgramError::InvalidAccountData
+    );
+}
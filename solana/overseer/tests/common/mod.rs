@@ -0,0 +1,702 @@
+#![allow(dead_code)]
+
+use borsh::BorshSerialize;
+use solana_money_market::{
+    instruction::MarketInstruction,
+    pda::{
+        find_atoken_mint, find_config as find_market_config, find_interest_model, find_liability,
+        find_market_authority, find_program_data, find_stable_vault, find_state,
+    },
+    state::BorrowerInfo,
+};
+use solana_money_market_custody::{
+    instruction::CustodyInstruction,
+    pda::{find_borrower_info, find_collateral_vault, find_custody_authority, find_custody_config},
+};
+use solana_money_market_overseer::{
+    instruction::OverseerInstruction,
+    pda::{
+        find_authority, find_borrow_limit, find_collaterals, find_config, find_proceeds,
+        find_whitelist_elem,
+    },
+    processor::process_instruction,
+    state::Collaterals,
+};
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{keypair_from_seed, Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+pub const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
+
+/// Market, custody and overseer of a stable mint with a single whitelisted
+/// collateral
+pub struct Protocol {
+    pub market_program: Pubkey,
+    pub custody_program: Pubkey,
+    pub overseer_program: Pubkey,
+    pub stable_mint: Keypair,
+    pub collateral_mint: Keypair,
+    pub oracle_feeder: Keypair,
+    pub market_authority: Pubkey,
+    pub market_vault: Pubkey,
+    pub atoken_mint: Pubkey,
+    pub market_config: Pubkey,
+    pub market_state: Pubkey,
+    pub interest_model: Pubkey,
+    pub custody_authority: Pubkey,
+    pub custody_vault: Pubkey,
+    pub custody_config: Pubkey,
+    pub authority: Pubkey,
+    pub proceeds: Pubkey,
+    pub config: Pubkey,
+    pub whitelist: Pubkey,
+}
+
+pub struct User {
+    pub keypair: Keypair,
+    pub stable: Pubkey,
+    pub atoken: Pubkey,
+    pub collateral: Pubkey,
+}
+
+pub fn program_test(
+    market_program: Pubkey,
+    custody_program: Pubkey,
+    overseer_program: Pubkey,
+) -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "solana_money_market_overseer",
+        overseer_program,
+        processor!(process_instruction),
+    );
+    program_test.add_program(
+        "solana_money_market",
+        market_program,
+        processor!(solana_money_market::processor::process_instruction),
+    );
+    program_test.add_program(
+        "solana_money_market_custody",
+        custody_program,
+        processor!(solana_money_market_custody::processor::process_instruction),
+    );
+    for program_id in &[market_program, custody_program, overseer_program] {
+        add_program_data(&mut program_test, program_id);
+    }
+    program_test.prefer_bpf(false);
+    program_test
+}
+
+/// Key the programs are deployed with; only it can initialize them
+pub fn upgrade_authority() -> Keypair {
+    keypair_from_seed(&[1; 32]).unwrap()
+}
+
+/// Records the upgrade authority of a program the way the upgradeable loader
+/// does, and funds the authority to pay for the accounts it initializes
+pub fn add_program_data(program_test: &mut ProgramTest, program_id: &Pubkey) {
+    let authority = upgrade_authority().pubkey();
+    program_test.add_account(
+        find_program_data(program_id).0,
+        Account::new_data(
+            1_000_000_000,
+            &UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(authority),
+            },
+            &bpf_loader_upgradeable::id(),
+        )
+        .unwrap(),
+    );
+    program_test.add_account(
+        authority,
+        Account::new(1_000_000_000_000, 0, &system_program::id()),
+    );
+}
+
+pub async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+pub async fn create_mint(banks_client: &mut BanksClient, payer: &Keypair, mint: &Keypair) {
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                6,
+            )
+            .unwrap(),
+        ],
+        &[mint],
+    )
+    .await
+    .unwrap();
+}
+
+pub async fn create_token_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let account = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::id(),
+                &account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        &[&account],
+    )
+    .await
+    .unwrap();
+
+    account.pubkey()
+}
+
+pub async fn mint_to(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) {
+    process(
+        banks_client,
+        payer,
+        &[spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            destination,
+            &payer.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+pub async fn token_balance(banks_client: &mut BanksClient, account: &Pubkey) -> u64 {
+    let account = banks_client.get_account(*account).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+pub async fn collaterals(
+    banks_client: &mut BanksClient,
+    protocol: &Protocol,
+    borrower: &Pubkey,
+) -> Collaterals {
+    let (collaterals, _) = find_collaterals(
+        &protocol.overseer_program,
+        &protocol.stable_mint.pubkey(),
+        borrower,
+    );
+    let account = banks_client
+        .get_account(collaterals)
+        .await
+        .unwrap()
+        .unwrap();
+    Collaterals::unpack(&account.data).unwrap()
+}
+
+pub async fn liability(
+    banks_client: &mut BanksClient,
+    protocol: &Protocol,
+    borrower: &Pubkey,
+) -> BorrowerInfo {
+    let (liability, _) = find_liability(
+        &protocol.market_program,
+        &protocol.stable_mint.pubkey(),
+        borrower,
+    );
+    let account = banks_client.get_account(liability).await.unwrap().unwrap();
+    BorrowerInfo::unpack(&account.data).unwrap()
+}
+
+/// Sets up an interest free market whose only collateral is whitelisted at
+/// the given max ltv and priced by the oracle feeder.
+pub async fn create_protocol(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    market_program: Pubkey,
+    custody_program: Pubkey,
+    overseer_program: Pubkey,
+    max_ltv: u128,
+    price: u128,
+) -> Protocol {
+    let stable_mint = Keypair::new();
+    let collateral_mint = Keypair::new();
+    create_mint(banks_client, payer, &stable_mint).await;
+    create_mint(banks_client, payer, &collateral_mint).await;
+    let stable = stable_mint.pubkey();
+    let collateral = collateral_mint.pubkey();
+
+    let upgrade_authority = upgrade_authority();
+    let protocol = Protocol {
+        market_program,
+        custody_program,
+        overseer_program,
+        oracle_feeder: Keypair::new(),
+        market_authority: find_market_authority(&market_program, &stable).0,
+        market_vault: find_stable_vault(&market_program, &stable).0,
+        atoken_mint: find_atoken_mint(&market_program, &stable).0,
+        market_config: find_market_config(&market_program, &stable).0,
+        market_state: find_state(&market_program, &stable).0,
+        interest_model: find_interest_model(&market_program, &stable).0,
+        custody_authority: find_custody_authority(&custody_program, &stable, &collateral).0,
+        custody_vault: find_collateral_vault(&custody_program, &stable, &collateral).0,
+        custody_config: find_custody_config(&custody_program, &stable, &collateral).0,
+        authority: find_authority(&overseer_program, &stable).0,
+        proceeds: find_proceeds(&overseer_program, &stable).0,
+        config: find_config(&overseer_program, &stable).0,
+        whitelist: find_whitelist_elem(&overseer_program, &stable, &collateral).0,
+        stable_mint,
+        collateral_mint,
+    };
+
+    process(
+        banks_client,
+        payer,
+        &[
+            Instruction {
+                program_id: market_program,
+                accounts: vec![
+                    AccountMeta::new(upgrade_authority.pubkey(), true),
+                    AccountMeta::new_readonly(find_program_data(&market_program).0, false),
+                    AccountMeta::new_readonly(stable, false),
+                    AccountMeta::new_readonly(protocol.market_authority, false),
+                    AccountMeta::new(protocol.market_vault, false),
+                    AccountMeta::new(protocol.atoken_mint, false),
+                    AccountMeta::new(protocol.market_config, false),
+                    AccountMeta::new(protocol.market_state, false),
+                    AccountMeta::new(protocol.interest_model, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+                data: MarketInstruction::InitializeMarket {
+                    overseer: overseer_program,
+                    base_rate: 0,
+                    interest_multiplier: 0,
+                    max_borrow_factor: DECIMAL_FRACTIONAL * 95 / 100,
+                    target_deposit_rate: DECIMAL_FRACTIONAL / 100_000,
+                }
+                .try_to_vec()
+                .unwrap(),
+            },
+            Instruction {
+                program_id: custody_program,
+                accounts: vec![
+                    AccountMeta::new(upgrade_authority.pubkey(), true),
+                    AccountMeta::new_readonly(find_program_data(&custody_program).0, false),
+                    AccountMeta::new_readonly(stable, false),
+                    AccountMeta::new_readonly(collateral, false),
+                    AccountMeta::new_readonly(protocol.custody_authority, false),
+                    AccountMeta::new(protocol.custody_vault, false),
+                    AccountMeta::new(protocol.custody_config, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+                data: CustodyInstruction::InitializeCustody {
+                    overseer: overseer_program,
+                }
+                .try_to_vec()
+                .unwrap(),
+            },
+            Instruction {
+                program_id: overseer_program,
+                accounts: vec![
+                    AccountMeta::new(upgrade_authority.pubkey(), true),
+                    AccountMeta::new_readonly(find_program_data(&overseer_program).0, false),
+                    AccountMeta::new_readonly(stable, false),
+                    AccountMeta::new_readonly(protocol.authority, false),
+                    AccountMeta::new(protocol.proceeds, false),
+                    AccountMeta::new(protocol.config, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+                data: OverseerInstruction::InitializeOverseer {
+                    market: market_program,
+                    custody: custody_program,
+                    oracle_feeder: protocol.oracle_feeder.pubkey(),
+                    price_timeframe: 60,
+                    max_premium_rate: DECIMAL_FRACTIONAL / 10,
+                    safe_ratio: DECIMAL_FRACTIONAL * 8 / 10,
+                    liquidation_threshold: 0,
+                }
+                .try_to_vec()
+                .unwrap(),
+            },
+            Instruction {
+                program_id: overseer_program,
+                accounts: vec![
+                    AccountMeta::new(upgrade_authority.pubkey(), true),
+                    AccountMeta::new_readonly(protocol.config, false),
+                    AccountMeta::new_readonly(collateral, false),
+                    AccountMeta::new(protocol.whitelist, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+                data: OverseerInstruction::Whitelist { max_ltv }
+                    .try_to_vec()
+                    .unwrap(),
+            },
+            feed_price(&protocol, price),
+        ],
+        &[&upgrade_authority, &protocol.oracle_feeder],
+    )
+    .await
+    .unwrap();
+
+    protocol
+}
+
+pub async fn create_user(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    protocol: &Protocol,
+    stable_amount: u64,
+    collateral_amount: u64,
+) -> User {
+    let keypair = Keypair::new();
+    process(
+        banks_client,
+        payer,
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &keypair.pubkey(),
+            1_000_000_000,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let owner = keypair.pubkey();
+    let stable =
+        create_token_account(banks_client, payer, &protocol.stable_mint.pubkey(), &owner).await;
+    let atoken = create_token_account(banks_client, payer, &protocol.atoken_mint, &owner).await;
+    let collateral = create_token_account(
+        banks_client,
+        payer,
+        &protocol.collateral_mint.pubkey(),
+        &owner,
+    )
+    .await;
+    mint_to(
+        banks_client,
+        payer,
+        &protocol.stable_mint.pubkey(),
+        &stable,
+        stable_amount,
+    )
+    .await;
+    mint_to(
+        banks_client,
+        payer,
+        &protocol.collateral_mint.pubkey(),
+        &collateral,
+        collateral_amount,
+    )
+    .await;
+
+    User {
+        keypair,
+        stable,
+        atoken,
+        collateral,
+    }
+}
+
+fn custody_borrower_info(protocol: &Protocol, borrower: &Pubkey) -> Pubkey {
+    find_borrower_info(
+        &protocol.custody_program,
+        &protocol.stable_mint.pubkey(),
+        &protocol.collateral_mint.pubkey(),
+        borrower,
+    )
+    .0
+}
+
+fn market_accounts(protocol: &Protocol, borrower: &Pubkey) -> Vec<AccountMeta> {
+    let (liability, _) = find_liability(
+        &protocol.market_program,
+        &protocol.stable_mint.pubkey(),
+        borrower,
+    );
+    vec![
+        AccountMeta::new_readonly(protocol.market_config, false),
+        AccountMeta::new_readonly(protocol.market_state, false),
+        AccountMeta::new_readonly(protocol.interest_model, false),
+        AccountMeta::new_readonly(liability, false),
+        AccountMeta::new_readonly(protocol.market_vault, false),
+        AccountMeta::new_readonly(protocol.atoken_mint, false),
+    ]
+}
+
+pub fn feed_price(protocol: &Protocol, price: u128) -> Instruction {
+    Instruction {
+        program_id: protocol.overseer_program,
+        accounts: vec![
+            AccountMeta::new_readonly(protocol.oracle_feeder.pubkey(), true),
+            AccountMeta::new_readonly(protocol.config, false),
+            AccountMeta::new(protocol.whitelist, false),
+        ],
+        data: OverseerInstruction::FeedPrice { price }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn deposit_stable(protocol: &Protocol, user: &User, amount: u64) -> Instruction {
+    Instruction {
+        program_id: protocol.market_program,
+        accounts: vec![
+            AccountMeta::new_readonly(user.keypair.pubkey(), true),
+            AccountMeta::new(user.stable, false),
+            AccountMeta::new(user.atoken, false),
+            AccountMeta::new_readonly(protocol.market_config, false),
+            AccountMeta::new(protocol.market_state, false),
+            AccountMeta::new_readonly(protocol.interest_model, false),
+            AccountMeta::new(protocol.market_vault, false),
+            AccountMeta::new(protocol.atoken_mint, false),
+            AccountMeta::new_readonly(protocol.market_authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MarketInstruction::DepositStable { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn borrow_stable(protocol: &Protocol, user: &User, amount: u64) -> Instruction {
+    let borrower = user.keypair.pubkey();
+    let (liability, _) = find_liability(
+        &protocol.market_program,
+        &protocol.stable_mint.pubkey(),
+        &borrower,
+    );
+    let (borrow_limit, _) = find_borrow_limit(
+        &protocol.overseer_program,
+        &protocol.stable_mint.pubkey(),
+        &borrower,
+    );
+    Instruction {
+        program_id: protocol.market_program,
+        accounts: vec![
+            AccountMeta::new(borrower, true),
+            AccountMeta::new(user.stable, false),
+            AccountMeta::new_readonly(protocol.market_config, false),
+            AccountMeta::new(protocol.market_state, false),
+            AccountMeta::new_readonly(protocol.interest_model, false),
+            AccountMeta::new(liability, false),
+            AccountMeta::new_readonly(borrow_limit, false),
+            AccountMeta::new(protocol.market_vault, false),
+            AccountMeta::new_readonly(protocol.atoken_mint, false),
+            AccountMeta::new_readonly(protocol.market_authority, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MarketInstruction::BorrowStable { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn deposit_collateral(protocol: &Protocol, user: &User, amount: u64) -> Instruction {
+    let borrower = user.keypair.pubkey();
+    Instruction {
+        program_id: protocol.custody_program,
+        accounts: vec![
+            AccountMeta::new(borrower, true),
+            AccountMeta::new(user.collateral, false),
+            AccountMeta::new_readonly(protocol.custody_config, false),
+            AccountMeta::new(custody_borrower_info(protocol, &borrower), false),
+            AccountMeta::new(protocol.custody_vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: CustodyInstruction::DepositCollateral { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn lock_collateral(protocol: &Protocol, user: &User, amount: u64) -> Instruction {
+    let borrower = user.keypair.pubkey();
+    let (collaterals, _) = find_collaterals(
+        &protocol.overseer_program,
+        &protocol.stable_mint.pubkey(),
+        &borrower,
+    );
+    Instruction {
+        program_id: protocol.overseer_program,
+        accounts: vec![
+            AccountMeta::new(borrower, true),
+            AccountMeta::new_readonly(protocol.config, false),
+            AccountMeta::new_readonly(protocol.whitelist, false),
+            AccountMeta::new(collaterals, false),
+            AccountMeta::new_readonly(protocol.authority, false),
+            AccountMeta::new_readonly(protocol.custody_program, false),
+            AccountMeta::new_readonly(protocol.custody_config, false),
+            AccountMeta::new(custody_borrower_info(protocol, &borrower), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: OverseerInstruction::LockCollateral { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn unlock_collateral(protocol: &Protocol, user: &User, amount: u64) -> Instruction {
+    let borrower = user.keypair.pubkey();
+    let (collaterals, _) = find_collaterals(
+        &protocol.overseer_program,
+        &protocol.stable_mint.pubkey(),
+        &borrower,
+    );
+    let mut accounts = vec![
+        AccountMeta::new_readonly(borrower, true),
+        AccountMeta::new_readonly(protocol.config, false),
+        AccountMeta::new(collaterals, false),
+        AccountMeta::new_readonly(protocol.authority, false),
+        AccountMeta::new_readonly(protocol.custody_program, false),
+        AccountMeta::new_readonly(protocol.custody_config, false),
+        AccountMeta::new(custody_borrower_info(protocol, &borrower), false),
+    ];
+    accounts.extend(market_accounts(protocol, &borrower));
+    accounts.push(AccountMeta::new_readonly(protocol.whitelist, false));
+    Instruction {
+        program_id: protocol.overseer_program,
+        accounts,
+        data: OverseerInstruction::UnlockCollateral { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn update_borrow_limit(protocol: &Protocol, payer: &Pubkey, borrower: &Pubkey) -> Instruction {
+    let stable = protocol.stable_mint.pubkey();
+    let (collaterals, _) = find_collaterals(&protocol.overseer_program, &stable, borrower);
+    let (borrow_limit, _) = find_borrow_limit(&protocol.overseer_program, &stable, borrower);
+    Instruction {
+        program_id: protocol.overseer_program,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(protocol.config, false),
+            AccountMeta::new_readonly(collaterals, false),
+            AccountMeta::new(borrow_limit, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(protocol.whitelist, false),
+        ],
+        data: OverseerInstruction::UpdateBorrowLimit {
+            borrower: *borrower,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+pub fn liquidate_collateral(
+    protocol: &Protocol,
+    liquidator: &User,
+    borrower: &Pubkey,
+) -> Instruction {
+    let (collaterals, _) = find_collaterals(
+        &protocol.overseer_program,
+        &protocol.stable_mint.pubkey(),
+        borrower,
+    );
+    let mut accounts = vec![
+        AccountMeta::new_readonly(liquidator.keypair.pubkey(), true),
+        AccountMeta::new(liquidator.stable, false),
+        AccountMeta::new_readonly(protocol.config, false),
+        AccountMeta::new(collaterals, false),
+        AccountMeta::new_readonly(protocol.authority, false),
+        AccountMeta::new(protocol.proceeds, false),
+        AccountMeta::new_readonly(protocol.custody_program, false),
+        AccountMeta::new_readonly(protocol.market_program, false),
+    ];
+    accounts.extend(
+        market_accounts(protocol, borrower)
+            .into_iter()
+            .map(|mut meta| {
+                meta.is_writable = meta.pubkey != protocol.market_config
+                    && meta.pubkey != protocol.interest_model
+                    && meta.pubkey != protocol.atoken_mint;
+                meta
+            }),
+    );
+    accounts.extend(vec![
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(protocol.whitelist, false),
+        AccountMeta::new_readonly(protocol.custody_config, false),
+        AccountMeta::new(custody_borrower_info(protocol, borrower), false),
+        AccountMeta::new(protocol.custody_vault, false),
+        AccountMeta::new_readonly(protocol.custody_authority, false),
+        AccountMeta::new(liquidator.collateral, false),
+    ]);
+    Instruction {
+        program_id: protocol.overseer_program,
+        accounts,
+        data: OverseerInstruction::LiquidateCollateral {
+            borrower: *borrower,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
@@ -0,0 +1,416 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::Sysvar,
+};
+
+use moneymarket_math::market::{self as math, Liability, MarketState};
+
+use crate::deposit::to_u64;
+use crate::error::MarketError;
+use crate::pda::{find_liability, find_overseer_authority, LIABILITY_SEED};
+use crate::processor::{
+    assert_market_authority, assert_token_program, authority_seeds, create_pda_account,
+    load_market, unpack_token_account, Market,
+};
+use crate::state::{BorrowLimit, BorrowerInfo, Config, InterestModel, State};
+
+pub fn process_borrow_stable(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let borrower_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let state_info = next_account_info(account_info_iter)?;
+    let interest_model_info = next_account_info(account_info_iter)?;
+    let liability_info = next_account_info(account_info_iter)?;
+    let borrow_limit_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let atoken_mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if !borrower_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *system_program_info.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    assert_token_program(token_program_info)?;
+
+    let Market {
+        config,
+        mut state,
+        interest_model,
+        vault_balance,
+        atoken_supply,
+    } = load_market(
+        program_id,
+        config_info,
+        state_info,
+        interest_model_info,
+        vault_info,
+        atoken_mint_info,
+    )?;
+    let authority_bump = assert_market_authority(program_id, &config, authority_info)?;
+
+    let destination = unpack_token_account(destination_info)?;
+    if destination.mint != config.stable_mint {
+        return Err(MarketError::MintMismatch.into());
+    }
+
+    // Liability accounts are created on the first borrow
+    let (liability_key, liability_bump) =
+        find_liability(program_id, &config.stable_mint, borrower_info.key);
+    if *liability_info.key != liability_key {
+        return Err(MarketError::InvalidSeeds.into());
+    }
+    let mut liability = if liability_info.data_is_empty() {
+        create_pda_account(
+            borrower_info,
+            liability_info,
+            system_program_info,
+            program_id,
+            BorrowerInfo::LEN,
+            &[
+                LIABILITY_SEED,
+                config.stable_mint.as_ref(),
+                borrower_info.key.as_ref(),
+                &[liability_bump],
+            ],
+        )?;
+        BorrowerInfo::new(*borrower_info.key)
+    } else {
+        load_liability(program_id, liability_info, borrower_info.key)?
+    };
+
+    let slot = Clock::get()?.slot;
+
+    // Compute interest
+    compute_interest(
+        &mut state,
+        &interest_model,
+        slot,
+        Uint256::from(vault_balance),
+        Uint256::from(atoken_supply),
+        config.target_deposit_rate,
+    );
+    compute_borrower_interest(&state, &mut liability);
+
+    // Compute ANC reward
+    compute_reward(&mut state, slot);
+    compute_borrower_reward(&state, &mut liability);
+
+    let borrow_amount = Uint256::from(amount);
+    let borrow_limit = read_borrow_limit(&config, borrow_limit_info, borrower_info.key, slot)?;
+    if borrow_limit < borrow_amount + liability.loan_amount {
+        return Err(MarketError::BorrowLimitExceeded.into());
+    }
+
+    // Assert borrow amount
+    assert_max_borrow_factor(&config, &state, Uint256::from(vault_balance), borrow_amount)?;
+
+    liability.loan_amount += borrow_amount;
+    state.total_liabilities += Decimal256::from_uint256(borrow_amount);
+    State::pack(state, &mut state_info.data.borrow_mut())?;
+    BorrowerInfo::pack(liability, &mut liability_info.data.borrow_mut())?;
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program_info.key,
+            vault_info.key,
+            destination_info.key,
+            authority_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            vault_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&authority_seeds(&config.stable_mint, &[authority_bump])],
+    )?;
+
+    msg!("borrower: {}, borrow_amount: {}", borrower_info.key, amount);
+
+    Ok(())
+}
+
+pub fn process_repay_stable(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let borrower_info = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if !borrower_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    repay_stable(program_id, accounts, *borrower_info.key, amount)
+}
+
+pub fn process_repay_stable_from_liquidation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    borrower: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let overseer_authority_info = next_account_info(account_info_iter)?;
+    let _source_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let config = Config::unpack(&config_info.data.borrow())?;
+    let (expected_authority, _) = find_overseer_authority(&config.overseer, &config.stable_mint);
+    if !overseer_authority_info.is_signer || *overseer_authority_info.key != expected_authority {
+        return Err(MarketError::Unauthorized.into());
+    }
+
+    repay_stable(program_id, accounts, borrower, amount)
+}
+
+fn repay_stable(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    borrower: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let state_info = next_account_info(account_info_iter)?;
+    let interest_model_info = next_account_info(account_info_iter)?;
+    let liability_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let atoken_mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    assert_token_program(token_program_info)?;
+
+    // Cannot repay zero amount
+    if amount == 0 {
+        return Err(MarketError::ZeroRepay.into());
+    }
+
+    let Market {
+        config,
+        mut state,
+        interest_model,
+        vault_balance,
+        atoken_supply,
+    } = load_market(
+        program_id,
+        config_info,
+        state_info,
+        interest_model_info,
+        vault_info,
+        atoken_mint_info,
+    )?;
+
+    let (liability_key, _) = find_liability(program_id, &config.stable_mint, &borrower);
+    if *liability_info.key != liability_key {
+        return Err(MarketError::InvalidSeeds.into());
+    }
+    let mut liability = load_liability(program_id, liability_info, &borrower)?;
+
+    let slot = Clock::get()?.slot;
+
+    // Compute interest
+    compute_interest(
+        &mut state,
+        &interest_model,
+        slot,
+        Uint256::from(vault_balance),
+        Uint256::from(atoken_supply),
+        config.target_deposit_rate,
+    );
+    compute_borrower_interest(&state, &mut liability);
+
+    // Compute ANC reward
+    compute_reward(&mut state, slot);
+    compute_borrower_reward(&state, &mut liability);
+
+    // Only the outstanding loan is pulled from the payer, so there is no
+    // change to send back
+    let repay_amount = if liability.loan_amount < Uint256::from(amount) {
+        liability.loan_amount
+    } else {
+        Uint256::from(amount)
+    };
+    liability.loan_amount = liability.loan_amount - repay_amount;
+    state.total_liabilities = state.total_liabilities - Decimal256::from_uint256(repay_amount);
+
+    State::pack(state, &mut state_info.data.borrow_mut())?;
+    BorrowerInfo::pack(liability, &mut liability_info.data.borrow_mut())?;
+
+    let repay_amount = to_u64(repay_amount)?;
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program_info.key,
+            source_info.key,
+            vault_info.key,
+            payer_info.key,
+            &[],
+            repay_amount,
+        )?,
+        &[
+            source_info.clone(),
+            vault_info.clone(),
+            payer_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    msg!("borrower: {}, repay_amount: {}", borrower, repay_amount);
+
+    Ok(())
+}
+
+pub fn load_liability(
+    program_id: &Pubkey,
+    liability_info: &AccountInfo,
+    borrower: &Pubkey,
+) -> Result<BorrowerInfo, ProgramError> {
+    if liability_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let liability = BorrowerInfo::unpack(&liability_info.data.borrow())?;
+    if liability.borrower != *borrower {
+        return Err(MarketError::InvalidSeeds.into());
+    }
+
+    Ok(liability)
+}
+
+/// Reads the borrow limit the overseer program recorded for the borrower.
+/// Only a limit written in the current slot is trusted, so the overseer
+/// instruction refreshing it must run in the same transaction.
+pub fn read_borrow_limit(
+    config: &Config,
+    borrow_limit_info: &AccountInfo,
+    borrower: &Pubkey,
+    slot: u64,
+) -> Result<Uint256, ProgramError> {
+    if *borrow_limit_info.owner != config.overseer {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let borrow_limit = BorrowLimit::unpack(&borrow_limit_info.data.borrow())?;
+    if borrow_limit.borrower != *borrower || borrow_limit.stable_mint != config.stable_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if borrow_limit.last_updated_slot != slot {
+        return Err(MarketError::StaleBorrowLimit.into());
+    }
+
+    Ok(borrow_limit.borrow_limit)
+}
+
+/// Compute interest and update state
+/// total liabilities and total reserves
+pub(crate) fn compute_interest(
+    state: &mut State,
+    interest_model: &InterestModel,
+    slot: u64,
+    balance: Uint256,
+    aterra_supply: Uint256,
+    target_deposit_rate: Decimal256,
+) {
+    if state.last_interest_updated >= slot {
+        return;
+    }
+
+    let borrow_rate =
+        interest_model.borrow_rate(balance, state.total_liabilities, state.total_reserves);
+
+    let mut market_state = MarketState::from(&*state);
+    math::compute_interest(
+        &mut market_state,
+        slot,
+        balance,
+        aterra_supply,
+        borrow_rate,
+        target_deposit_rate,
+    );
+    state.apply(market_state);
+}
+
+pub(crate) fn compute_reward(state: &mut State, slot: u64) {
+    let mut market_state = MarketState::from(&*state);
+    math::compute_reward(&mut market_state, slot);
+    state.apply(market_state);
+}
+
+fn compute_borrower_interest(state: &State, liability: &mut BorrowerInfo) {
+    let mut market_liability = Liability::from(&*liability);
+    math::compute_borrower_interest(&MarketState::from(state), &mut market_liability);
+    liability.apply(market_liability);
+}
+
+fn compute_borrower_reward(state: &State, liability: &mut BorrowerInfo) {
+    let mut market_liability = Liability::from(&*liability);
+    math::compute_borrower_reward(&MarketState::from(state), &mut market_liability);
+    liability.apply(market_liability);
+}
+
+/// Loan amount of a liability with interest accrued up to the slot, as the
+/// overseer program checks it against the borrow limit.
+pub fn compute_loan_amount(market: &Market, liability: &BorrowerInfo, slot: u64) -> Uint256 {
+    let mut state = market.state.clone();
+    let mut liability = liability.clone();
+    compute_interest(
+        &mut state,
+        &market.interest_model,
+        slot,
+        Uint256::from(market.vault_balance),
+        Uint256::from(market.atoken_supply),
+        market.config.target_deposit_rate,
+    );
+    compute_borrower_interest(&state, &mut liability);
+
+    liability.loan_amount
+}
+
+fn assert_max_borrow_factor(
+    config: &Config,
+    state: &State,
+    current_balance: Uint256,
+    borrow_amount: Uint256,
+) -> ProgramResult {
+    let current_balance = Decimal256::from_uint256(current_balance);
+    let borrow_amount = Decimal256::from_uint256(borrow_amount);
+
+    // Assert max borrow factor
+    if state.total_liabilities + borrow_amount
+        > (current_balance + state.total_liabilities - state.total_reserves)
+            * config.max_borrow_factor
+    {
+        return Err(MarketError::MaxBorrowFactorExceeded.into());
+    }
+
+    // Assert available balance
+    if borrow_amount + state.total_reserves > current_balance {
+        return Err(MarketError::InsufficientLiquidity.into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use moneymarket_math::market::{compute_exchange_rate, MarketState};
+
+use crate::borrow::{compute_interest, compute_reward};
+use crate::error::MarketError;
+use crate::processor::{
+    assert_market_authority, assert_token_program, authority_seeds, load_market,
+    unpack_token_account, Market,
+};
+use crate::state::State;
+
+pub fn process_deposit_stable(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let depositor_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let state_info = next_account_info(account_info_iter)?;
+    let interest_model_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let atoken_mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if !depositor_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    assert_token_program(token_program_info)?;
+
+    // Cannot deposit zero amount
+    if amount == 0 {
+        return Err(MarketError::ZeroDeposit.into());
+    }
+
+    let Market {
+        config,
+        mut state,
+        interest_model,
+        vault_balance,
+        atoken_supply,
+    } = load_market(
+        program_id,
+        config_info,
+        state_info,
+        interest_model_info,
+        vault_info,
+        atoken_mint_info,
+    )?;
+    let authority_bump = assert_market_authority(program_id, &config, authority_info)?;
+
+    let source = unpack_token_account(source_info)?;
+    if source.mint != config.stable_mint {
+        return Err(MarketError::MintMismatch.into());
+    }
+
+    // Update interest related state; the deposit has not reached the vault
+    // yet, so the vault balance is used as is
+    let slot = Clock::get()?.slot;
+    compute_interest(
+        &mut state,
+        &interest_model,
+        slot,
+        Uint256::from(vault_balance),
+        Uint256::from(atoken_supply),
+        config.target_deposit_rate,
+    );
+    compute_reward(&mut state, slot);
+
+    // Load anchor token exchange rate with updated state
+    let exchange_rate = compute_exchange_rate(
+        &MarketState::from(&state),
+        Uint256::from(atoken_supply),
+        Uint256::from(vault_balance),
+    );
+    let mint_amount = Uint256::from(amount) / exchange_rate;
+
+    state.prev_aterra_supply += mint_amount;
+    State::pack(state, &mut state_info.data.borrow_mut())?;
+
+    let mint_amount = to_u64(mint_amount)?;
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program_info.key,
+            source_info.key,
+            vault_info.key,
+            depositor_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            source_info.clone(),
+            vault_info.clone(),
+            depositor_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program_info.key,
+            atoken_mint_info.key,
+            destination_info.key,
+            authority_info.key,
+            &[],
+            mint_amount,
+        )?,
+        &[
+            atoken_mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&authority_seeds(&config.stable_mint, &[authority_bump])],
+    )?;
+
+    msg!(
+        "depositor: {}, mint_amount: {}, deposit_amount: {}",
+        depositor_info.key,
+        mint_amount,
+        amount
+    );
+
+    Ok(())
+}
+
+pub fn process_redeem_stable(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    burn_amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let redeemer_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let state_info = next_account_info(account_info_iter)?;
+    let interest_model_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let atoken_mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if !redeemer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    assert_token_program(token_program_info)?;
+
+    if burn_amount == 0 {
+        return Err(MarketError::ZeroRedeem.into());
+    }
+
+    let Market {
+        config,
+        mut state,
+        interest_model,
+        vault_balance,
+        atoken_supply,
+    } = load_market(
+        program_id,
+        config_info,
+        state_info,
+        interest_model_info,
+        vault_info,
+        atoken_mint_info,
+    )?;
+    let authority_bump = assert_market_authority(program_id, &config, authority_info)?;
+
+    let destination = unpack_token_account(destination_info)?;
+    if destination.mint != config.stable_mint {
+        return Err(MarketError::MintMismatch.into());
+    }
+
+    // Update interest related state
+    let slot = Clock::get()?.slot;
+    compute_interest(
+        &mut state,
+        &interest_model,
+        slot,
+        Uint256::from(vault_balance),
+        Uint256::from(atoken_supply),
+        config.target_deposit_rate,
+    );
+    compute_reward(&mut state, slot);
+
+    // Load anchor token exchange rate with updated state
+    let exchange_rate = compute_exchange_rate(
+        &MarketState::from(&state),
+        Uint256::from(atoken_supply),
+        Uint256::from(vault_balance),
+    );
+    let redeem_amount = Uint256::from(burn_amount) * exchange_rate;
+
+    // Assert redeem amount
+    if Decimal256::from_uint256(redeem_amount) + state.total_reserves
+        > Decimal256::from_uint256(vault_balance)
+    {
+        return Err(MarketError::InsufficientLiquidity.into());
+    }
+
+    state.prev_aterra_supply = state.prev_aterra_supply - Uint256::from(burn_amount);
+    State::pack(state, &mut state_info.data.borrow_mut())?;
+
+    let redeem_amount = to_u64(redeem_amount)?;
+    invoke(
+        &spl_token::instruction::burn(
+            token_program_info.key,
+            source_info.key,
+            atoken_mint_info.key,
+            redeemer_info.key,
+            &[],
+            burn_amount,
+        )?,
+        &[
+            source_info.clone(),
+            atoken_mint_info.clone(),
+            redeemer_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program_info.key,
+            vault_info.key,
+            destination_info.key,
+            authority_info.key,
+            &[],
+            redeem_amount,
+        )?,
+        &[
+            vault_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&authority_seeds(&config.stable_mint, &[authority_bump])],
+    )?;
+
+    msg!(
+        "burn_amount: {}, redeem_amount: {}",
+        burn_amount,
+        redeem_amount
+    );
+
+    Ok(())
+}
+
+pub fn to_u64(amount: Uint256) -> Result<u64, ProgramError> {
+    if amount > Uint256::from(u64::MAX) {
+        return Err(MarketError::MathOverflow.into());
+    }
+
+    let amount: u128 = amount.into();
+    Ok(amount as u64)
+}
@@ -0,0 +1,36 @@
+use solana_program::program_error::ProgramError;
+use thiserror::Error;
+
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum MarketError {
+    #[error("Invalid instruction")]
+    InvalidInstruction,
+    #[error("Deposit amount must be greater than 0")]
+    ZeroDeposit,
+    #[error("Redeem amount must be greater than 0")]
+    ZeroRedeem,
+    #[error("Not enough stable available; borrow demand too high")]
+    InsufficientLiquidity,
+    #[error("Account does not match the expected program derived address")]
+    InvalidSeeds,
+    #[error("Token account mint does not match the market stable mint")]
+    MintMismatch,
+    #[error("Math operation overflow")]
+    MathOverflow,
+    #[error("Repay amount must be greater than 0")]
+    ZeroRepay,
+    #[error("Borrow amount too high; Loan liability becomes greater than borrow limit")]
+    BorrowLimitExceeded,
+    #[error("Exceeds max borrow factor; borrow demand too high")]
+    MaxBorrowFactorExceeded,
+    #[error("Borrow limit was not refreshed in the current slot")]
+    StaleBorrowLimit,
+    #[error("Unauthorized")]
+    Unauthorized,
+}
+
+impl From<MarketError> for ProgramError {
+    fn from(e: MarketError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
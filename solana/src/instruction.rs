@@ -0,0 +1,118 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+// Decimal arguments are raw values with 18 fractional digits, see
+// `state::decimal_from_atomics`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum MarketInstruction {
+    /// Creates the stable vault, aToken mint, config, state and interest
+    /// model accounts of the market for a stable mint. The payer must be the
+    /// upgrade authority of the program and becomes the market owner. Deposit
+    /// yield above `target_deposit_rate` per slot is moved into reserves.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[writable, signer]` Payer, the upgrade authority of the program
+    ///   1. `[]` Program data account of the program
+    ///   2. `[]` Stable mint
+    ///   3. `[]` Market authority PDA
+    ///   4. `[writable]` Stable vault PDA
+    ///   5. `[writable]` aToken mint PDA
+    ///   6. `[writable]` Config PDA
+    ///   7. `[writable]` State PDA
+    ///   8. `[writable]` Interest model PDA
+    ///   9. `[]` System program
+    ///   10. `[]` Token program
+    InitializeMarket {
+        overseer: Pubkey,
+        base_rate: u128,
+        interest_multiplier: u128,
+        max_borrow_factor: u128,
+        target_deposit_rate: u128,
+    },
+
+    /// Deposits stable tokens into the vault and mints aTokens at the current
+    /// exchange rate.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Depositor
+    ///   1. `[writable]` Depositor stable token account
+    ///   2. `[writable]` Depositor aToken account
+    ///   3. `[]` Config PDA
+    ///   4. `[writable]` State PDA
+    ///   5. `[]` Interest model PDA
+    ///   6. `[writable]` Stable vault PDA
+    ///   7. `[writable]` aToken mint PDA
+    ///   8. `[]` Market authority PDA
+    ///   9. `[]` Token program
+    DepositStable { amount: u64 },
+
+    /// Burns aTokens and sends back the stable tokens they are worth at the
+    /// current exchange rate.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Redeemer
+    ///   1. `[writable]` Redeemer aToken account
+    ///   2. `[writable]` Redeemer stable token account
+    ///   3. `[]` Config PDA
+    ///   4. `[writable]` State PDA
+    ///   5. `[]` Interest model PDA
+    ///   6. `[writable]` Stable vault PDA
+    ///   7. `[writable]` aToken mint PDA
+    ///   8. `[]` Market authority PDA
+    ///   9. `[]` Token program
+    RedeemStable { burn_amount: u64 },
+
+    /// Borrows stable tokens from the vault against the borrow limit the
+    /// overseer recorded for the borrower in the current slot. The liability
+    /// account is created on the first borrow.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[writable, signer]` Borrower
+    ///   1. `[writable]` Borrower stable token account
+    ///   2. `[]` Config PDA
+    ///   3. `[writable]` State PDA
+    ///   4. `[]` Interest model PDA
+    ///   5. `[writable]` Liability PDA of the borrower
+    ///   6. `[]` Borrow limit account owned by the overseer program
+    ///   7. `[writable]` Stable vault PDA
+    ///   8. `[]` aToken mint PDA
+    ///   9. `[]` Market authority PDA
+    ///   10. `[]` System program
+    ///   11. `[]` Token program
+    BorrowStable { amount: u64 },
+
+    /// Repays a loan; anything above the loan amount is left with the payer.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Borrower
+    ///   1. `[writable]` Borrower stable token account
+    ///   2. `[]` Config PDA
+    ///   3. `[writable]` State PDA
+    ///   4. `[]` Interest model PDA
+    ///   5. `[writable]` Liability PDA of the borrower
+    ///   6. `[writable]` Stable vault PDA
+    ///   7. `[]` aToken mint PDA
+    ///   8. `[]` Token program
+    RepayStable { amount: u64 },
+
+    /// Repays a loan on behalf of the borrower with liquidation proceeds.
+    /// Only the overseer authority PDA can sign it.
+    ///
+    /// Accounts expected:
+    ///
+    ///   0. `[signer]` Overseer authority PDA
+    ///   1. `[writable]` Overseer authority stable token account
+    ///   2. `[]` Config PDA
+    ///   3. `[writable]` State PDA
+    ///   4. `[]` Interest model PDA
+    ///   5. `[writable]` Liability PDA of the borrower
+    ///   6. `[writable]` Stable vault PDA
+    ///   7. `[]` aToken mint PDA
+    ///   8. `[]` Token program
+    RepayStableFromLiquidation { borrower: Pubkey, amount: u64 },
+}
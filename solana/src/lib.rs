@@ -1,2 +1,8 @@
+pub mod borrow;
+pub mod deposit;
 mod entrypoint;
+pub mod error;
+pub mod instruction;
+pub mod pda;
 pub mod processor;
+pub mod state;
@@ -0,0 +1,58 @@
+use solana_program::{bpf_loader_upgradeable, pubkey::Pubkey};
+
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const ATOKEN_SEED: &[u8] = b"atoken";
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const STATE_SEED: &[u8] = b"state";
+pub const INTEREST_MODEL_SEED: &[u8] = b"interest_model";
+pub const LIABILITY_SEED: &[u8] = b"liability";
+
+// Every market is keyed by its stable mint; the authority PDA owns the
+// stable vault and is the mint authority of the aToken mint.
+pub fn find_market_authority(program_id: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_SEED, stable_mint.as_ref()], program_id)
+}
+
+pub fn find_stable_vault(program_id: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, stable_mint.as_ref()], program_id)
+}
+
+pub fn find_atoken_mint(program_id: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ATOKEN_SEED, stable_mint.as_ref()], program_id)
+}
+
+pub fn find_config(program_id: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED, stable_mint.as_ref()], program_id)
+}
+
+pub fn find_state(program_id: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATE_SEED, stable_mint.as_ref()], program_id)
+}
+
+pub fn find_interest_model(program_id: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INTEREST_MODEL_SEED, stable_mint.as_ref()], program_id)
+}
+
+pub fn find_liability(
+    program_id: &Pubkey,
+    stable_mint: &Pubkey,
+    borrower: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LIABILITY_SEED, stable_mint.as_ref(), borrower.as_ref()],
+        program_id,
+    )
+}
+
+// The overseer program signs liquidation repayments with its own authority,
+// derived with the same seeds under the overseer program id.
+pub fn find_overseer_authority(overseer: &Pubkey, stable_mint: &Pubkey) -> (Pubkey, u8) {
+    find_market_authority(overseer, stable_mint)
+}
+
+// Program data account of an upgradeable program, which records its upgrade
+// authority. Every program of the market is initialized by that authority.
+pub fn find_program_data(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id())
+}
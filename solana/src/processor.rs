@@ -1,8 +1,392 @@
-use solana_program::{account_info::{AccountInfo, next_account_info}, entrypoint::ProgramResult, pubkey::Pubkey};
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+    sysvar::{rent::Rent, Sysvar},
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+use crate::borrow::{
+    process_borrow_stable, process_repay_stable, process_repay_stable_from_liquidation,
+};
+use crate::deposit::{process_deposit_stable, process_redeem_stable};
+use crate::error::MarketError;
+use crate::instruction::MarketInstruction;
+use crate::pda::{
+    find_atoken_mint, find_config, find_interest_model, find_market_authority, find_program_data,
+    find_stable_vault, find_state, ATOKEN_SEED, AUTHORITY_SEED, CONFIG_SEED, INTEREST_MODEL_SEED,
+    STATE_SEED, VAULT_SEED,
+};
+use crate::state::{decimal_from_atomics, Config, InterestModel, State, STATE_VERSION};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+
 pub fn process_instruction(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
-    _instruction_data: &[u8],
+    instruction_data: &[u8],
 ) -> ProgramResult {
-    
+    let instruction = MarketInstruction::try_from_slice(instruction_data)
+        .map_err(|_| MarketError::InvalidInstruction)?;
+
+    match instruction {
+        MarketInstruction::InitializeMarket {
+            overseer,
+            base_rate,
+            interest_multiplier,
+            max_borrow_factor,
+            target_deposit_rate,
+        } => {
+            msg!("Instruction: InitializeMarket");
+            process_initialize_market(
+                program_id,
+                accounts,
+                overseer,
+                decimal_from_atomics(base_rate),
+                decimal_from_atomics(interest_multiplier),
+                decimal_from_atomics(max_borrow_factor),
+                decimal_from_atomics(target_deposit_rate),
+            )
+        }
+        MarketInstruction::DepositStable { amount } => {
+            msg!("Instruction: DepositStable");
+            process_deposit_stable(program_id, accounts, amount)
+        }
+        MarketInstruction::RedeemStable { burn_amount } => {
+            msg!("Instruction: RedeemStable");
+            process_redeem_stable(program_id, accounts, burn_amount)
+        }
+        MarketInstruction::BorrowStable { amount } => {
+            msg!("Instruction: BorrowStable");
+            process_borrow_stable(program_id, accounts, amount)
+        }
+        MarketInstruction::RepayStable { amount } => {
+            msg!("Instruction: RepayStable");
+            process_repay_stable(program_id, accounts, amount)
+        }
+        MarketInstruction::RepayStableFromLiquidation { borrower, amount } => {
+            msg!("Instruction: RepayStableFromLiquidation");
+            process_repay_stable_from_liquidation(program_id, accounts, borrower, amount)
+        }
+    }
+}
+
+fn process_initialize_market(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    overseer: Pubkey,
+    base_rate: Decimal256,
+    interest_multiplier: Decimal256,
+    max_borrow_factor: Decimal256,
+    target_deposit_rate: Decimal256,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let program_data_info = next_account_info(account_info_iter)?;
+    let stable_mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let atoken_mint_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let state_info = next_account_info(account_info_iter)?;
+    let interest_model_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    assert_upgrade_authority(program_id, program_data_info, payer_info)?;
+    if *system_program_info.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    assert_token_program(token_program_info)?;
+
+    let stable_mint_key = stable_mint_info.key;
+    let stable_mint = unpack_mint(stable_mint_info)?;
+    let (authority, _) = find_market_authority(program_id, stable_mint_key);
+    let (vault, vault_bump) = find_stable_vault(program_id, stable_mint_key);
+    let (atoken_mint, atoken_bump) = find_atoken_mint(program_id, stable_mint_key);
+    let (config, config_bump) = find_config(program_id, stable_mint_key);
+    let (state, state_bump) = find_state(program_id, stable_mint_key);
+    let (interest_model, interest_model_bump) = find_interest_model(program_id, stable_mint_key);
+    if *authority_info.key != authority
+        || *vault_info.key != vault
+        || *atoken_mint_info.key != atoken_mint
+        || *config_info.key != config
+        || *state_info.key != state
+        || *interest_model_info.key != interest_model
+    {
+        return Err(MarketError::InvalidSeeds.into());
+    }
+
+    // aToken shares the decimals of the stable it is redeemable for
+    create_pda_account(
+        payer_info,
+        atoken_mint_info,
+        system_program_info,
+        token_program_info.key,
+        Mint::LEN,
+        &[ATOKEN_SEED, stable_mint_key.as_ref(), &[atoken_bump]],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_mint2(
+            token_program_info.key,
+            atoken_mint_info.key,
+            authority_info.key,
+            None,
+            stable_mint.decimals,
+        )?,
+        &[atoken_mint_info.clone(), token_program_info.clone()],
+    )?;
+
+    create_pda_account(
+        payer_info,
+        vault_info,
+        system_program_info,
+        token_program_info.key,
+        TokenAccount::LEN,
+        &[VAULT_SEED, stable_mint_key.as_ref(), &[vault_bump]],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_account3(
+            token_program_info.key,
+            vault_info.key,
+            stable_mint_key,
+            authority_info.key,
+        )?,
+        &[
+            vault_info.clone(),
+            stable_mint_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    create_pda_account(
+        payer_info,
+        config_info,
+        system_program_info,
+        program_id,
+        Config::LEN,
+        &[CONFIG_SEED, stable_mint_key.as_ref(), &[config_bump]],
+    )?;
+    Config::pack(
+        Config {
+            version: STATE_VERSION,
+            is_initialized: true,
+            owner: *payer_info.key,
+            stable_mint: *stable_mint_key,
+            atoken_mint,
+            interest_model,
+            distribution_model: Pubkey::default(),
+            overseer,
+            collector: Pubkey::default(),
+            distributor: Pubkey::default(),
+            max_borrow_factor,
+            target_deposit_rate,
+        },
+        &mut config_info.data.borrow_mut(),
+    )?;
+
+    create_pda_account(
+        payer_info,
+        state_info,
+        system_program_info,
+        program_id,
+        State::LEN,
+        &[STATE_SEED, stable_mint_key.as_ref(), &[state_bump]],
+    )?;
+    let slot = Clock::get()?.slot;
+    State::pack(
+        State {
+            version: STATE_VERSION,
+            is_initialized: true,
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: slot,
+            last_reward_updated: slot,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::zero(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+        &mut state_info.data.borrow_mut(),
+    )?;
+
+    create_pda_account(
+        payer_info,
+        interest_model_info,
+        system_program_info,
+        program_id,
+        InterestModel::LEN,
+        &[
+            INTEREST_MODEL_SEED,
+            stable_mint_key.as_ref(),
+            &[interest_model_bump],
+        ],
+    )?;
+    InterestModel::pack(
+        InterestModel {
+            version: STATE_VERSION,
+            is_initialized: true,
+            owner: *payer_info.key,
+            base_rate,
+            interest_multiplier,
+        },
+        &mut interest_model_info.data.borrow_mut(),
+    )?;
+
+    Ok(())
+}
+
+/// Market accounts every deposit, redeem, borrow and repay has to load.
+pub struct Market {
+    pub config: Config,
+    pub state: State,
+    pub interest_model: InterestModel,
+    pub vault_balance: u64,
+    pub atoken_supply: u64,
+}
+
+/// Checks the config, state, interest model, vault and aToken mint all
+/// belong to the same market and loads them.
+pub fn load_market(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+    state_info: &AccountInfo,
+    interest_model_info: &AccountInfo,
+    vault_info: &AccountInfo,
+    atoken_mint_info: &AccountInfo,
+) -> Result<Market, ProgramError> {
+    if config_info.owner != program_id
+        || state_info.owner != program_id
+        || interest_model_info.owner != program_id
+    {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let config = Config::unpack(&config_info.data.borrow())?;
+    let (expected_config, _) = find_config(program_id, &config.stable_mint);
+    let (expected_state, _) = find_state(program_id, &config.stable_mint);
+    let (expected_vault, _) = find_stable_vault(program_id, &config.stable_mint);
+    if *config_info.key != expected_config
+        || *state_info.key != expected_state
+        || *interest_model_info.key != config.interest_model
+        || *vault_info.key != expected_vault
+        || *atoken_mint_info.key != config.atoken_mint
+    {
+        return Err(MarketError::InvalidSeeds.into());
+    }
+
+    Ok(Market {
+        state: State::unpack(&state_info.data.borrow())?,
+        interest_model: InterestModel::unpack(&interest_model_info.data.borrow())?,
+        vault_balance: unpack_token_account(vault_info)?.amount,
+        atoken_supply: unpack_mint(atoken_mint_info)?.supply,
+        config,
+    })
+}
+
+/// Checks the authority derives from the market stable mint and returns its
+/// bump seed.
+pub fn assert_market_authority(
+    program_id: &Pubkey,
+    config: &Config,
+    authority_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (expected_authority, bump) = find_market_authority(program_id, &config.stable_mint);
+    if *authority_info.key != expected_authority {
+        return Err(MarketError::InvalidSeeds.into());
+    }
+
+    Ok(bump)
+}
+
+pub fn authority_seeds<'a>(stable_mint: &'a Pubkey, bump: &'a [u8; 1]) -> [&'a [u8]; 3] {
+    [AUTHORITY_SEED, stable_mint.as_ref(), bump]
+}
+
+/// Checks that the authority signed and is the upgrade authority recorded in
+/// the program data account, so that nobody else can initialize a market
+/// with their own owner and trusted programs.
+pub fn assert_upgrade_authority(
+    program_id: &Pubkey,
+    program_data_info: &AccountInfo,
+    authority_info: &AccountInfo,
+) -> ProgramResult {
+    let (program_data, _) = find_program_data(program_id);
+    if *program_data_info.key != program_data
+        || *program_data_info.owner != bpf_loader_upgradeable::id()
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let state: UpgradeableLoaderState = limited_deserialize(
+        &program_data_info.data.borrow(),
+        UpgradeableLoaderState::size_of_programdata_metadata() as u64,
+    )
+    .map_err(|_| ProgramError::InvalidAccountData)?;
+    match state {
+        UpgradeableLoaderState::ProgramData {
+            upgrade_authority_address: Some(authority),
+            ..
+        } if authority == *authority_info.key && authority_info.is_signer => Ok(()),
+        _ => Err(ProgramError::MissingRequiredSignature),
+    }
+}
+
+pub fn assert_token_program(token_program_info: &AccountInfo) -> ProgramResult {
+    if *token_program_info.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Ok(())
+}
+
+pub fn unpack_mint(mint_info: &AccountInfo) -> Result<Mint, ProgramError> {
+    if *mint_info.owner != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Mint::unpack(&mint_info.data.borrow())
+}
+
+pub fn unpack_token_account(account_info: &AccountInfo) -> Result<TokenAccount, ProgramError> {
+    if *account_info.owner != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    TokenAccount::unpack(&account_info.data.borrow())
+}
+
+pub fn create_pda_account<'a>(
+    payer_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    owner: &Pubkey,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_info.key,
+            account_info.key,
+            rent.minimum_balance(space),
+            space as u64,
+            owner,
+        ),
+        &[
+            payer_info.clone(),
+            account_info.clone(),
+            system_program_info.clone(),
+        ],
+        &[seeds],
+    )
 }
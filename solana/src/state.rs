@@ -0,0 +1,449 @@
+use bigint::U256;
+use borsh::{
+    maybestd::io::{Read, Result as IoResult, Write},
+    BorshDeserialize, BorshSerialize,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use moneymarket_math::interest_model::compute_borrow_rate;
+use moneymarket_math::market::{Liability, MarketState};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+/// Layout version written as the first byte of every market account.
+pub const STATE_VERSION: u8 = 1;
+
+// Mirrors `moneymarket_market::state::Config`; contract addresses become
+// pubkeys and `stable_denom` becomes the stable mint.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+    pub stable_mint: Pubkey,
+    pub atoken_mint: Pubkey,
+    pub interest_model: Pubkey,
+    pub distribution_model: Pubkey,
+    pub overseer: Pubkey,
+    pub collector: Pubkey,
+    pub distributor: Pubkey,
+    pub max_borrow_factor: Decimal256,
+    /// Deposit rate above which excess yield is moved into reserves; the
+    /// CosmWasm market reads it from the overseer config instead
+    pub target_deposit_rate: Decimal256,
+}
+
+// Mirrors `moneymarket_market::state::State`; heights are slots.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct State {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub total_liabilities: Decimal256,
+    pub total_reserves: Decimal256,
+    pub last_interest_updated: u64,
+    pub last_reward_updated: u64,
+    pub global_interest_index: Decimal256,
+    pub global_reward_index: Decimal256,
+    pub anc_emission_rate: Decimal256,
+    pub prev_aterra_supply: Uint256,
+    pub prev_exchange_rate: Decimal256,
+}
+
+impl State {
+    /// Writes back the interest and reward fields updated by the shared math
+    pub fn apply(&mut self, state: MarketState) {
+        self.total_liabilities = state.total_liabilities;
+        self.total_reserves = state.total_reserves;
+        self.last_interest_updated = state.last_interest_updated;
+        self.last_reward_updated = state.last_reward_updated;
+        self.global_interest_index = state.global_interest_index;
+        self.global_reward_index = state.global_reward_index;
+        self.anc_emission_rate = state.anc_emission_rate;
+        self.prev_aterra_supply = state.prev_aterra_supply;
+        self.prev_exchange_rate = state.prev_exchange_rate;
+    }
+}
+
+impl From<&State> for MarketState {
+    fn from(state: &State) -> Self {
+        MarketState {
+            total_liabilities: state.total_liabilities,
+            total_reserves: state.total_reserves,
+            last_interest_updated: state.last_interest_updated,
+            last_reward_updated: state.last_reward_updated,
+            global_interest_index: state.global_interest_index,
+            global_reward_index: state.global_reward_index,
+            anc_emission_rate: state.anc_emission_rate,
+            prev_aterra_supply: state.prev_aterra_supply,
+            prev_exchange_rate: state.prev_exchange_rate,
+        }
+    }
+}
+
+// Mirrors `moneymarket_market::state::BorrowerInfo`; the borrower is stored
+// in the account since there is no bucket key to recover it from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BorrowerInfo {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub borrower: Pubkey,
+    pub interest_index: Decimal256,
+    pub reward_index: Decimal256,
+    pub loan_amount: Uint256,
+    pub pending_rewards: Decimal256,
+}
+
+impl BorrowerInfo {
+    pub fn new(borrower: Pubkey) -> Self {
+        BorrowerInfo {
+            version: STATE_VERSION,
+            is_initialized: true,
+            borrower,
+            interest_index: Decimal256::one(),
+            reward_index: Decimal256::zero(),
+            loan_amount: Uint256::zero(),
+            pending_rewards: Decimal256::zero(),
+        }
+    }
+
+    /// Writes back the loan fields updated by the shared math
+    pub fn apply(&mut self, liability: Liability) {
+        self.interest_index = liability.interest_index;
+        self.reward_index = liability.reward_index;
+        self.loan_amount = liability.loan_amount;
+        self.pending_rewards = liability.pending_rewards;
+    }
+}
+
+impl From<&BorrowerInfo> for Liability {
+    fn from(info: &BorrowerInfo) -> Self {
+        Liability {
+            interest_index: info.interest_index,
+            reward_index: info.reward_index,
+            loan_amount: info.loan_amount,
+            pending_rewards: info.pending_rewards,
+        }
+    }
+}
+
+// Mirrors `moneymarket_interest_model::state::Config`; one per market.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterestModel {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+    pub base_rate: Decimal256,
+    pub interest_multiplier: Decimal256,
+}
+
+impl InterestModel {
+    pub fn borrow_rate(
+        &self,
+        market_balance: Uint256,
+        total_liabilities: Decimal256,
+        total_reserves: Decimal256,
+    ) -> Decimal256 {
+        compute_borrow_rate(
+            self.base_rate,
+            self.interest_multiplier,
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        )
+    }
+}
+
+// Written by the overseer program for a borrower; stands in for the
+// `BorrowLimit` smart query the CosmWasm market sends to the overseer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BorrowLimit {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub borrower: Pubkey,
+    pub stable_mint: Pubkey,
+    pub borrow_limit: Uint256,
+    pub last_updated_slot: u64,
+}
+
+impl BorshSerialize for Config {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        self.owner.serialize(writer)?;
+        self.stable_mint.serialize(writer)?;
+        self.atoken_mint.serialize(writer)?;
+        self.interest_model.serialize(writer)?;
+        self.distribution_model.serialize(writer)?;
+        self.overseer.serialize(writer)?;
+        self.collector.serialize(writer)?;
+        self.distributor.serialize(writer)?;
+        serialize_u256(&self.max_borrow_factor.0, writer)?;
+        serialize_u256(&self.target_deposit_rate.0, writer)
+    }
+}
+
+impl BorshDeserialize for Config {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        Ok(Config {
+            version: u8::deserialize_reader(reader)?,
+            is_initialized: bool::deserialize_reader(reader)?,
+            owner: Pubkey::deserialize_reader(reader)?,
+            stable_mint: Pubkey::deserialize_reader(reader)?,
+            atoken_mint: Pubkey::deserialize_reader(reader)?,
+            interest_model: Pubkey::deserialize_reader(reader)?,
+            distribution_model: Pubkey::deserialize_reader(reader)?,
+            overseer: Pubkey::deserialize_reader(reader)?,
+            collector: Pubkey::deserialize_reader(reader)?,
+            distributor: Pubkey::deserialize_reader(reader)?,
+            max_borrow_factor: Decimal256(deserialize_u256(reader)?),
+            target_deposit_rate: Decimal256(deserialize_u256(reader)?),
+        })
+    }
+}
+
+impl BorshSerialize for State {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        serialize_u256(&self.total_liabilities.0, writer)?;
+        serialize_u256(&self.total_reserves.0, writer)?;
+        self.last_interest_updated.serialize(writer)?;
+        self.last_reward_updated.serialize(writer)?;
+        serialize_u256(&self.global_interest_index.0, writer)?;
+        serialize_u256(&self.global_reward_index.0, writer)?;
+        serialize_u256(&self.anc_emission_rate.0, writer)?;
+        serialize_u256(&self.prev_aterra_supply.0, writer)?;
+        serialize_u256(&self.prev_exchange_rate.0, writer)
+    }
+}
+
+impl BorshDeserialize for State {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        Ok(State {
+            version: u8::deserialize_reader(reader)?,
+            is_initialized: bool::deserialize_reader(reader)?,
+            total_liabilities: Decimal256(deserialize_u256(reader)?),
+            total_reserves: Decimal256(deserialize_u256(reader)?),
+            last_interest_updated: u64::deserialize_reader(reader)?,
+            last_reward_updated: u64::deserialize_reader(reader)?,
+            global_interest_index: Decimal256(deserialize_u256(reader)?),
+            global_reward_index: Decimal256(deserialize_u256(reader)?),
+            anc_emission_rate: Decimal256(deserialize_u256(reader)?),
+            prev_aterra_supply: Uint256(deserialize_u256(reader)?),
+            prev_exchange_rate: Decimal256(deserialize_u256(reader)?),
+        })
+    }
+}
+
+impl BorshSerialize for BorrowerInfo {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        self.borrower.serialize(writer)?;
+        serialize_u256(&self.interest_index.0, writer)?;
+        serialize_u256(&self.reward_index.0, writer)?;
+        serialize_u256(&self.loan_amount.0, writer)?;
+        serialize_u256(&self.pending_rewards.0, writer)
+    }
+}
+
+impl BorshDeserialize for BorrowerInfo {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        Ok(BorrowerInfo {
+            version: u8::deserialize_reader(reader)?,
+            is_initialized: bool::deserialize_reader(reader)?,
+            borrower: Pubkey::deserialize_reader(reader)?,
+            interest_index: Decimal256(deserialize_u256(reader)?),
+            reward_index: Decimal256(deserialize_u256(reader)?),
+            loan_amount: Uint256(deserialize_u256(reader)?),
+            pending_rewards: Decimal256(deserialize_u256(reader)?),
+        })
+    }
+}
+
+impl BorshSerialize for InterestModel {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        self.owner.serialize(writer)?;
+        serialize_u256(&self.base_rate.0, writer)?;
+        serialize_u256(&self.interest_multiplier.0, writer)
+    }
+}
+
+impl BorshDeserialize for InterestModel {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        Ok(InterestModel {
+            version: u8::deserialize_reader(reader)?,
+            is_initialized: bool::deserialize_reader(reader)?,
+            owner: Pubkey::deserialize_reader(reader)?,
+            base_rate: Decimal256(deserialize_u256(reader)?),
+            interest_multiplier: Decimal256(deserialize_u256(reader)?),
+        })
+    }
+}
+
+impl BorshSerialize for BorrowLimit {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        self.version.serialize(writer)?;
+        self.is_initialized.serialize(writer)?;
+        self.borrower.serialize(writer)?;
+        self.stable_mint.serialize(writer)?;
+        serialize_u256(&self.borrow_limit.0, writer)?;
+        self.last_updated_slot.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for BorrowLimit {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        Ok(BorrowLimit {
+            version: u8::deserialize_reader(reader)?,
+            is_initialized: bool::deserialize_reader(reader)?,
+            borrower: Pubkey::deserialize_reader(reader)?,
+            stable_mint: Pubkey::deserialize_reader(reader)?,
+            borrow_limit: Uint256(deserialize_u256(reader)?),
+            last_updated_slot: u64::deserialize_reader(reader)?,
+        })
+    }
+}
+
+impl Sealed for Config {}
+impl Sealed for State {}
+impl Sealed for BorrowerInfo {}
+impl Sealed for InterestModel {}
+impl Sealed for BorrowLimit {}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for State {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for BorrowerInfo {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for InterestModel {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for BorrowLimit {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Config {
+    const LEN: usize = 2 + 32 * 8 + U256_LEN * 2;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let config = Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(config.version, config.is_initialized)?;
+        Ok(config)
+    }
+}
+
+impl Pack for State {
+    const LEN: usize = 2 + 8 * 2 + U256_LEN * 7;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let state = Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(state.version, state.is_initialized)?;
+        Ok(state)
+    }
+}
+
+impl Pack for BorrowerInfo {
+    const LEN: usize = 2 + 32 + U256_LEN * 4;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let info = Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(info.version, info.is_initialized)?;
+        Ok(info)
+    }
+}
+
+impl Pack for InterestModel {
+    const LEN: usize = 2 + 32 + U256_LEN * 2;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let model = Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(model.version, model.is_initialized)?;
+        Ok(model)
+    }
+}
+
+impl Pack for BorrowLimit {
+    const LEN: usize = 2 + 32 * 2 + U256_LEN + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_borsh(self, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let limit = Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_version(limit.version, limit.is_initialized)?;
+        Ok(limit)
+    }
+}
+
+/// Instruction arguments carry decimals as their raw value with 18
+/// fractional digits, i.e. `1_000_000_000_000_000_000` is 1.0.
+pub fn decimal_from_atomics(atomics: u128) -> Decimal256 {
+    Decimal256(Uint256::from(atomics).0)
+}
+
+// Decimal256 and Uint256 are both stored as their raw little-endian 256-bit
+// words, so a Decimal256 keeps its 18 fractional digits without rounding.
+pub const U256_LEN: usize = 32;
+
+pub fn serialize_u256<W: Write>(value: &U256, writer: &mut W) -> IoResult<()> {
+    value.0.serialize(writer)
+}
+
+pub fn deserialize_u256<R: Read>(reader: &mut R) -> IoResult<U256> {
+    Ok(U256(<[u64; 4]>::deserialize_reader(reader)?))
+}
+
+pub fn pack_borsh<T: BorshSerialize>(value: &T, dst: &mut [u8]) {
+    let data = value.try_to_vec().unwrap();
+    dst[..data.len()].copy_from_slice(&data);
+}
+
+// A zeroed account is a valid uninitialized layout; anything initialized must
+// carry the current version.
+pub fn assert_version(version: u8, is_initialized: bool) -> Result<(), ProgramError> {
+    if is_initialized && version != STATE_VERSION {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
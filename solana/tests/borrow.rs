@@ -0,0 +1,474 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use cosmwasm_bignumber::Decimal256;
+use solana_money_market::{
+    error::MarketError,
+    instruction::MarketInstruction,
+    pda::{find_liability, find_overseer_authority, AUTHORITY_SEED},
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program::invoke_signed,
+    pubkey::Pubkey,
+};
+use solana_program_test::{processor, ProgramTestContext};
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use std::convert::TryInto;
+
+// Stands in for the overseer program: forwards a market instruction, signing
+// it with the overseer authority PDA of the stable mint in the first 32 bytes.
+fn mock_overseer(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let stable_mint = Pubkey::new_from_array(data[..32].try_into().unwrap());
+    let (authority, bump) = find_overseer_authority(program_id, &stable_mint);
+    let (market_program_info, market_accounts) = accounts.split_first().unwrap();
+    let instruction = Instruction {
+        program_id: *market_program_info.key,
+        accounts: market_accounts
+            .iter()
+            .map(|a| AccountMeta {
+                pubkey: *a.key,
+                is_signer: a.is_signer || *a.key == authority,
+                is_writable: a.is_writable,
+            })
+            .collect(),
+        data: data[32..].to_vec(),
+    };
+
+    invoke_signed(
+        &instruction,
+        market_accounts,
+        &[&[AUTHORITY_SEED, stable_mint.as_ref(), &[bump]]],
+    )
+}
+
+async fn setup() -> (ProgramTestContext, Market) {
+    let program_id = Pubkey::new_unique();
+    let overseer = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    program_test.add_program("mock_overseer", overseer, processor!(mock_overseer));
+    let mut context = program_test.start_with_context().await;
+
+    // 0.0001 per slot regardless of utilization, 0.000005 target deposit rate
+    let market = create_market(
+        &mut context.banks_client,
+        &context.payer,
+        program_id,
+        overseer,
+        DECIMAL_FRACTIONAL / 10_000,
+        0,
+        DECIMAL_FRACTIONAL / 200_000,
+    )
+    .await;
+
+    let lender = create_user(
+        &mut context.banks_client,
+        &context.payer,
+        &market,
+        1_000_000,
+    )
+    .await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[deposit_stable(&market, &lender, 1_000_000)],
+        &[&lender.keypair],
+    )
+    .await
+    .unwrap();
+
+    (context, market)
+}
+
+async fn current_slot(context: &mut ProgramTestContext) -> u64 {
+    context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .slot
+}
+
+#[tokio::test]
+async fn borrow_and_repay_stable() {
+    let (mut context, market) = setup().await;
+    let borrower = create_user(&mut context.banks_client, &context.payer, &market, 0).await;
+    let borrow_limit = Pubkey::new_unique();
+    set_borrow_limit(
+        &mut context,
+        &market,
+        &borrow_limit,
+        &borrower.keypair.pubkey(),
+        500_000,
+    )
+    .await;
+
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[borrow_stable(&market, &borrower, &borrow_limit, 400_000)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&mut context.banks_client, &borrower.stable).await,
+        400_000
+    );
+    assert_eq!(
+        token_balance(&mut context.banks_client, &market.vault).await,
+        600_000
+    );
+    let info = liability(
+        &mut context.banks_client,
+        &market,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert_eq!(info.loan_amount, 400_000u64.into());
+    assert_eq!(
+        market_state(&mut context.banks_client, &market)
+            .await
+            .total_liabilities,
+        Decimal256::from_uint256(400_000u64)
+    );
+
+    // Repaying more than the loan only takes the loan amount
+    mint_stable(
+        &mut context.banks_client,
+        &context.payer,
+        &market,
+        &borrower.stable,
+        100_000,
+    )
+    .await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[repay_stable(&market, &borrower, 500_000)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&mut context.banks_client, &borrower.stable).await,
+        100_000
+    );
+    let info = liability(
+        &mut context.banks_client,
+        &market,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert!(info.loan_amount.is_zero());
+}
+
+#[tokio::test]
+async fn borrow_over_limit_fails() {
+    let (mut context, market) = setup().await;
+    let borrower = create_user(&mut context.banks_client, &context.payer, &market, 0).await;
+    let borrow_limit = Pubkey::new_unique();
+    set_borrow_limit(
+        &mut context,
+        &market,
+        &borrow_limit,
+        &borrower.keypair.pubkey(),
+        300_000,
+    )
+    .await;
+
+    let err = process(
+        &mut context.banks_client,
+        &context.payer,
+        &[borrow_stable(&market, &borrower, &borrow_limit, 400_000)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MarketError::BorrowLimitExceeded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn stale_borrow_limit_fails() {
+    let (mut context, market) = setup().await;
+    let borrower = create_user(&mut context.banks_client, &context.payer, &market, 0).await;
+    let borrow_limit = Pubkey::new_unique();
+    set_borrow_limit(
+        &mut context,
+        &market,
+        &borrow_limit,
+        &borrower.keypair.pubkey(),
+        500_000,
+    )
+    .await;
+
+    let slot = current_slot(&mut context).await;
+    context.warp_to_slot(slot + 10).unwrap();
+
+    let err = process(
+        &mut context.banks_client,
+        &context.payer,
+        &[borrow_stable(&market, &borrower, &borrow_limit, 400_000)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MarketError::StaleBorrowLimit as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn borrow_limit_from_other_program_fails() {
+    let (mut context, mut market) = setup().await;
+    let borrower = create_user(&mut context.banks_client, &context.payer, &market, 0).await;
+    let borrow_limit = Pubkey::new_unique();
+
+    // written by a program that is not the configured overseer
+    let overseer = market.overseer;
+    market.overseer = Pubkey::new_unique();
+    set_borrow_limit(
+        &mut context,
+        &market,
+        &borrow_limit,
+        &borrower.keypair.pubkey(),
+        500_000,
+    )
+    .await;
+    market.overseer = overseer;
+
+    let err = process(
+        &mut context.banks_client,
+        &context.payer,
+        &[borrow_stable(&market, &borrower, &borrow_limit, 400_000)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+}
+
+#[tokio::test]
+async fn loan_accrues_interest() {
+    let (mut context, market) = setup().await;
+    let borrower = create_user(&mut context.banks_client, &context.payer, &market, 0).await;
+    let borrow_limit = Pubkey::new_unique();
+    set_borrow_limit(
+        &mut context,
+        &market,
+        &borrow_limit,
+        &borrower.keypair.pubkey(),
+        500_000,
+    )
+    .await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[borrow_stable(&market, &borrower, &borrow_limit, 100_000)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    // 100 slots * 0.0001 = 1% interest
+    let slot = current_slot(&mut context).await;
+    context.warp_to_slot(slot + 100).unwrap();
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[repay_stable(&market, &borrower, 1)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    let info = liability(
+        &mut context.banks_client,
+        &market,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert_eq!(info.loan_amount, 100_999u64.into());
+}
+
+#[tokio::test]
+async fn excess_yield_moves_to_reserves() {
+    let (mut context, market) = setup().await;
+    let borrower = create_user(&mut context.banks_client, &context.payer, &market, 0).await;
+    let borrow_limit = Pubkey::new_unique();
+    set_borrow_limit(
+        &mut context,
+        &market,
+        &borrow_limit,
+        &borrower.keypair.pubkey(),
+        500_000,
+    )
+    .await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[borrow_stable(&market, &borrower, &borrow_limit, 100_000)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    // 1,000 interest over 100 slots is a 0.00001 deposit rate, twice the
+    // target, so half of it goes to reserves
+    let slot = current_slot(&mut context).await;
+    context.warp_to_slot(slot + 100).unwrap();
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[repay_stable(&market, &borrower, 1)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    let state = market_state(&mut context.banks_client, &market).await;
+    assert_eq!(state.total_reserves, Decimal256::from_uint256(500u64));
+}
+
+#[tokio::test]
+async fn repay_stable_from_liquidation() {
+    let (mut context, market) = setup().await;
+    let borrower = create_user(&mut context.banks_client, &context.payer, &market, 0).await;
+    let borrow_limit = Pubkey::new_unique();
+    set_borrow_limit(
+        &mut context,
+        &market,
+        &borrow_limit,
+        &borrower.keypair.pubkey(),
+        500_000,
+    )
+    .await;
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[borrow_stable(&market, &borrower, &borrow_limit, 400_000)],
+        &[&borrower.keypair],
+    )
+    .await
+    .unwrap();
+
+    let (overseer_authority, _) =
+        find_overseer_authority(&market.overseer, &market.stable_mint.pubkey());
+    let proceeds = create_token_account(
+        &mut context.banks_client,
+        &context.payer,
+        &market.stable_mint.pubkey(),
+        &overseer_authority,
+    )
+    .await;
+    mint_stable(
+        &mut context.banks_client,
+        &context.payer,
+        &market,
+        &proceeds,
+        150_000,
+    )
+    .await;
+
+    let (liability_key, _) = find_liability(
+        &market.program_id,
+        &market.stable_mint.pubkey(),
+        &borrower.keypair.pubkey(),
+    );
+    let mut data = market.stable_mint.pubkey().to_bytes().to_vec();
+    data.extend(
+        MarketInstruction::RepayStableFromLiquidation {
+            borrower: borrower.keypair.pubkey(),
+            amount: 150_000,
+        }
+        .try_to_vec()
+        .unwrap(),
+    );
+    let repay_accounts = |authority_is_signer: bool| {
+        vec![
+            AccountMeta::new_readonly(market.program_id, false),
+            AccountMeta::new_readonly(overseer_authority, authority_is_signer),
+            AccountMeta::new(proceeds, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(liability_key, false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new_readonly(market.atoken_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ]
+    };
+
+    process(
+        &mut context.banks_client,
+        &context.payer,
+        &[Instruction {
+            program_id: market.overseer,
+            accounts: repay_accounts(false),
+            data,
+        }],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let info = liability(
+        &mut context.banks_client,
+        &market,
+        &borrower.keypair.pubkey(),
+    )
+    .await;
+    assert_eq!(info.loan_amount, 250_000u64.into());
+    assert_eq!(token_balance(&mut context.banks_client, &proceeds).await, 0);
+
+    // Anyone else signing in place of the overseer authority is rejected
+    let impostor = Keypair::new();
+    let mut accounts = repay_accounts(false)[1..].to_vec();
+    accounts[0] = AccountMeta::new_readonly(impostor.pubkey(), true);
+    let err = process(
+        &mut context.banks_client,
+        &context.payer,
+        &[Instruction {
+            program_id: market.program_id,
+            accounts,
+            data: MarketInstruction::RepayStableFromLiquidation {
+                borrower: borrower.keypair.pubkey(),
+                amount: 1,
+            }
+            .try_to_vec()
+            .unwrap(),
+        }],
+        &[&impostor],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MarketError::Unauthorized as u32)
+        )
+    );
+}
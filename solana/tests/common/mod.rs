@@ -0,0 +1,495 @@
+#![allow(dead_code)]
+
+use borsh::BorshSerialize;
+use solana_money_market::{
+    instruction::MarketInstruction,
+    pda::{
+        find_atoken_mint, find_config, find_interest_model, find_liability, find_market_authority,
+        find_program_data, find_stable_vault, find_state,
+    },
+    processor::process_instruction,
+    state::{BorrowLimit, BorrowerInfo, State, STATE_VERSION},
+};
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{keypair_from_seed, Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+pub const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
+
+pub struct Market {
+    pub program_id: Pubkey,
+    pub overseer: Pubkey,
+    pub stable_mint: Keypair,
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub atoken_mint: Pubkey,
+    pub config: Pubkey,
+    pub state: Pubkey,
+    pub interest_model: Pubkey,
+}
+
+pub struct User {
+    pub keypair: Keypair,
+    pub stable: Pubkey,
+    pub atoken: Pubkey,
+}
+
+pub fn program_test(program_id: Pubkey) -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "solana_money_market",
+        program_id,
+        processor!(process_instruction),
+    );
+    add_program_data(&mut program_test, &program_id);
+    program_test.prefer_bpf(false);
+    program_test
+}
+
+/// Key the programs are deployed with; only it can initialize them
+pub fn upgrade_authority() -> Keypair {
+    keypair_from_seed(&[1; 32]).unwrap()
+}
+
+/// Records the upgrade authority of a program the way the upgradeable loader
+/// does, and funds the authority to pay for the accounts it initializes
+pub fn add_program_data(program_test: &mut ProgramTest, program_id: &Pubkey) {
+    let authority = upgrade_authority().pubkey();
+    program_test.add_account(
+        find_program_data(program_id).0,
+        Account::new_data(
+            1_000_000_000,
+            &UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(authority),
+            },
+            &bpf_loader_upgradeable::id(),
+        )
+        .unwrap(),
+    );
+    program_test.add_account(
+        authority,
+        Account::new(1_000_000_000_000, 0, &system_program::id()),
+    );
+}
+
+pub async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+pub async fn create_token_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let account = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::id(),
+                &account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        &[&account],
+    )
+    .await
+    .unwrap();
+
+    account.pubkey()
+}
+
+pub async fn mint_stable(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    market: &Market,
+    destination: &Pubkey,
+    amount: u64,
+) {
+    process(
+        banks_client,
+        payer,
+        &[spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &market.stable_mint.pubkey(),
+            destination,
+            &payer.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+pub async fn token_balance(banks_client: &mut BanksClient, account: &Pubkey) -> u64 {
+    let account = banks_client.get_account(*account).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+pub async fn market_state(banks_client: &mut BanksClient, market: &Market) -> State {
+    let account = banks_client
+        .get_account(market.state)
+        .await
+        .unwrap()
+        .unwrap();
+    State::unpack(&account.data).unwrap()
+}
+
+pub async fn liability(
+    banks_client: &mut BanksClient,
+    market: &Market,
+    borrower: &Pubkey,
+) -> BorrowerInfo {
+    let (liability, _) = find_liability(&market.program_id, &market.stable_mint.pubkey(), borrower);
+    let account = banks_client.get_account(liability).await.unwrap().unwrap();
+    BorrowerInfo::unpack(&account.data).unwrap()
+}
+
+pub async fn create_market(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    program_id: Pubkey,
+    overseer: Pubkey,
+    base_rate: u128,
+    interest_multiplier: u128,
+    target_deposit_rate: u128,
+) -> Market {
+    let stable_mint = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &stable_mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                &stable_mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                6,
+            )
+            .unwrap(),
+        ],
+        &[&stable_mint],
+    )
+    .await
+    .unwrap();
+
+    let upgrade_authority = upgrade_authority();
+    process(
+        banks_client,
+        payer,
+        &[initialize_market(
+            program_id,
+            &stable_mint.pubkey(),
+            &upgrade_authority.pubkey(),
+            overseer,
+            base_rate,
+            interest_multiplier,
+            target_deposit_rate,
+        )],
+        &[&upgrade_authority],
+    )
+    .await
+    .unwrap();
+
+    let (authority, _) = find_market_authority(&program_id, &stable_mint.pubkey());
+    let (vault, _) = find_stable_vault(&program_id, &stable_mint.pubkey());
+    let (atoken_mint, _) = find_atoken_mint(&program_id, &stable_mint.pubkey());
+    let (config, _) = find_config(&program_id, &stable_mint.pubkey());
+    let (state, _) = find_state(&program_id, &stable_mint.pubkey());
+    let (interest_model, _) = find_interest_model(&program_id, &stable_mint.pubkey());
+    Market {
+        program_id,
+        overseer,
+        stable_mint,
+        authority,
+        vault,
+        atoken_mint,
+        config,
+        state,
+        interest_model,
+    }
+}
+
+pub fn initialize_market(
+    program_id: Pubkey,
+    stable_mint: &Pubkey,
+    payer: &Pubkey,
+    overseer: Pubkey,
+    base_rate: u128,
+    interest_multiplier: u128,
+    target_deposit_rate: u128,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(find_program_data(&program_id).0, false),
+            AccountMeta::new_readonly(*stable_mint, false),
+            AccountMeta::new_readonly(find_market_authority(&program_id, stable_mint).0, false),
+            AccountMeta::new(find_stable_vault(&program_id, stable_mint).0, false),
+            AccountMeta::new(find_atoken_mint(&program_id, stable_mint).0, false),
+            AccountMeta::new(find_config(&program_id, stable_mint).0, false),
+            AccountMeta::new(find_state(&program_id, stable_mint).0, false),
+            AccountMeta::new(find_interest_model(&program_id, stable_mint).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MarketInstruction::InitializeMarket {
+            overseer,
+            base_rate,
+            interest_multiplier,
+            max_borrow_factor: DECIMAL_FRACTIONAL * 95 / 100,
+            target_deposit_rate,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+pub async fn create_user(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    market: &Market,
+    stable_amount: u64,
+) -> User {
+    let keypair = Keypair::new();
+    process(
+        banks_client,
+        payer,
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &keypair.pubkey(),
+            1_000_000_000,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let stable = create_token_account(
+        banks_client,
+        payer,
+        &market.stable_mint.pubkey(),
+        &keypair.pubkey(),
+    )
+    .await;
+    let atoken =
+        create_token_account(banks_client, payer, &market.atoken_mint, &keypair.pubkey()).await;
+    mint_stable(banks_client, payer, market, &stable, stable_amount).await;
+
+    User {
+        keypair,
+        stable,
+        atoken,
+    }
+}
+
+/// Writes the borrow limit the overseer program would record for a borrower
+pub async fn set_borrow_limit(
+    context: &mut ProgramTestContext,
+    market: &Market,
+    borrow_limit_account: &Pubkey,
+    borrower: &Pubkey,
+    borrow_limit: u64,
+) {
+    let slot = context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .slot;
+    let limit = BorrowLimit {
+        version: STATE_VERSION,
+        is_initialized: true,
+        borrower: *borrower,
+        stable_mint: market.stable_mint.pubkey(),
+        borrow_limit: borrow_limit.into(),
+        last_updated_slot: slot,
+    };
+    let mut data = vec![0u8; BorrowLimit::LEN];
+    BorrowLimit::pack(limit, &mut data).unwrap();
+
+    // Lamports are moved from the payer so the bank capitalization still
+    // adds up when the test warps to a later slot
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let lamports = rent.minimum_balance(BorrowLimit::LEN);
+    if context
+        .banks_client
+        .get_account(*borrow_limit_account)
+        .await
+        .unwrap()
+        .is_none()
+    {
+        let payer = context.payer.pubkey();
+        let mut payer_account = context
+            .banks_client
+            .get_account(payer)
+            .await
+            .unwrap()
+            .unwrap();
+        payer_account.lamports -= lamports;
+        context.set_account(&payer, &payer_account.into());
+    }
+    context.set_account(
+        borrow_limit_account,
+        &Account {
+            lamports,
+            data,
+            owner: market.overseer,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+}
+
+pub fn deposit_stable(market: &Market, user: &User, amount: u64) -> Instruction {
+    Instruction {
+        program_id: market.program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(user.keypair.pubkey(), true),
+            AccountMeta::new(user.stable, false),
+            AccountMeta::new(user.atoken, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new(market.atoken_mint, false),
+            AccountMeta::new_readonly(market.authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MarketInstruction::DepositStable { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn redeem_stable(market: &Market, user: &User, burn_amount: u64) -> Instruction {
+    Instruction {
+        program_id: market.program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(user.keypair.pubkey(), true),
+            AccountMeta::new(user.atoken, false),
+            AccountMeta::new(user.stable, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new(market.atoken_mint, false),
+            AccountMeta::new_readonly(market.authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MarketInstruction::RedeemStable { burn_amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn borrow_stable(
+    market: &Market,
+    user: &User,
+    borrow_limit_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (liability, _) = find_liability(
+        &market.program_id,
+        &market.stable_mint.pubkey(),
+        &user.keypair.pubkey(),
+    );
+    Instruction {
+        program_id: market.program_id,
+        accounts: vec![
+            AccountMeta::new(user.keypair.pubkey(), true),
+            AccountMeta::new(user.stable, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(liability, false),
+            AccountMeta::new_readonly(*borrow_limit_account, false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new_readonly(market.atoken_mint, false),
+            AccountMeta::new_readonly(market.authority, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MarketInstruction::BorrowStable { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn repay_stable(market: &Market, user: &User, amount: u64) -> Instruction {
+    let (liability, _) = find_liability(
+        &market.program_id,
+        &market.stable_mint.pubkey(),
+        &user.keypair.pubkey(),
+    );
+    Instruction {
+        program_id: market.program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(user.keypair.pubkey(), true),
+            AccountMeta::new(user.stable, false),
+            AccountMeta::new_readonly(market.config, false),
+            AccountMeta::new(market.state, false),
+            AccountMeta::new_readonly(market.interest_model, false),
+            AccountMeta::new(liability, false),
+            AccountMeta::new(market.vault, false),
+            AccountMeta::new_readonly(market.atoken_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MarketInstruction::RepayStable { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
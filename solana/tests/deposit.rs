@@ -0,0 +1,194 @@
+mod common;
+
+use common::*;
+use solana_money_market::error::MarketError;
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+use solana_sdk::transaction::TransactionError;
+
+#[tokio::test]
+async fn deposit_and_redeem_stable() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = program_test(program_id).start().await;
+    let market = create_market(
+        &mut banks_client,
+        &payer,
+        program_id,
+        Pubkey::new_unique(),
+        0,
+        0,
+        0,
+    )
+    .await;
+    let user = create_user(&mut banks_client, &payer, &market, 1_000_000).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[deposit_stable(&market, &user, 1_000_000)],
+        &[&user.keypair],
+    )
+    .await
+    .unwrap();
+
+    // First deposit is minted one to one
+    assert_eq!(
+        token_balance(&mut banks_client, &user.atoken).await,
+        1_000_000
+    );
+    assert_eq!(token_balance(&mut banks_client, &user.stable).await, 0);
+    assert_eq!(
+        token_balance(&mut banks_client, &market.vault).await,
+        1_000_000
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[redeem_stable(&market, &user, 400_000)],
+        &[&user.keypair],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&mut banks_client, &user.atoken).await,
+        600_000
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, &user.stable).await,
+        400_000
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, &market.vault).await,
+        600_000
+    );
+}
+
+#[tokio::test]
+async fn deposit_uses_exchange_rate() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = program_test(program_id).start().await;
+    let market = create_market(
+        &mut banks_client,
+        &payer,
+        program_id,
+        Pubkey::new_unique(),
+        0,
+        0,
+        0,
+    )
+    .await;
+    let alice = create_user(&mut banks_client, &payer, &market, 1_000_000).await;
+    let bob = create_user(&mut banks_client, &payer, &market, 1_000_000).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[deposit_stable(&market, &alice, 1_000_000)],
+        &[&alice.keypair],
+    )
+    .await
+    .unwrap();
+
+    // vault grows without minting, exchange_rate = 2_000_000 / 1_000_000 = 2
+    mint_stable(&mut banks_client, &payer, &market, &market.vault, 1_000_000).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[deposit_stable(&market, &bob, 1_000_000)],
+        &[&bob.keypair],
+    )
+    .await
+    .unwrap();
+    assert_eq!(token_balance(&mut banks_client, &bob.atoken).await, 500_000);
+
+    // exchange_rate = 3_000_000 / 1_500_000 = 2
+    process(
+        &mut banks_client,
+        &payer,
+        &[redeem_stable(&market, &alice, 1_000_000)],
+        &[&alice.keypair],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_balance(&mut banks_client, &alice.stable).await,
+        2_000_000
+    );
+}
+
+#[tokio::test]
+async fn zero_deposit_fails() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = program_test(program_id).start().await;
+    let market = create_market(
+        &mut banks_client,
+        &payer,
+        program_id,
+        Pubkey::new_unique(),
+        0,
+        0,
+        0,
+    )
+    .await;
+    let user = create_user(&mut banks_client, &payer, &market, 1_000_000).await;
+
+    let err = process(
+        &mut banks_client,
+        &payer,
+        &[deposit_stable(&market, &user, 0)],
+        &[&user.keypair],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MarketError::ZeroDeposit as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn redeem_more_than_vault_fails() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = program_test(program_id).start().await;
+    let market = create_market(
+        &mut banks_client,
+        &payer,
+        program_id,
+        Pubkey::new_unique(),
+        0,
+        0,
+        0,
+    )
+    .await;
+    let user = create_user(&mut banks_client, &payer, &market, 1_000_000).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[deposit_stable(&market, &user, 1_000_000)],
+        &[&user.keypair],
+    )
+    .await
+    .unwrap();
+
+    let err = process(
+        &mut banks_client,
+        &payer,
+        &[redeem_stable(&market, &user, 1_000_001)],
+        &[&user.keypair],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MarketError::InsufficientLiquidity as u32)
+        )
+    );
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::*;
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+#[tokio::test]
+async fn initialize_market_requires_upgrade_authority() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = program_test(program_id).start().await;
+
+    let err = process(
+        &mut banks_client,
+        &payer,
+        &[initialize_market(
+            program_id,
+            &Pubkey::new_unique(),
+            &payer.pubkey(),
+            Pubkey::new_unique(),
+            0,
+            0,
+            0,
+        )],
+        &[],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
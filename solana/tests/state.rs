@@ -0,0 +1,94 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use solana_money_market::state::{BorrowerInfo, Config, State, STATE_VERSION};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+};
+use std::str::FromStr;
+
+#[test]
+fn pack_config() {
+    let config = Config {
+        version: STATE_VERSION,
+        is_initialized: true,
+        owner: Pubkey::new_unique(),
+        stable_mint: Pubkey::new_unique(),
+        atoken_mint: Pubkey::new_unique(),
+        interest_model: Pubkey::new_unique(),
+        distribution_model: Pubkey::new_unique(),
+        overseer: Pubkey::new_unique(),
+        collector: Pubkey::new_unique(),
+        distributor: Pubkey::new_unique(),
+        max_borrow_factor: Decimal256::from_str("0.95").unwrap(),
+        target_deposit_rate: Decimal256::from_str("0.000000041717").unwrap(),
+    };
+
+    let mut data = vec![0u8; Config::LEN];
+    Config::pack(config.clone(), &mut data).unwrap();
+    assert_eq!(Config::unpack(&data).unwrap(), config);
+}
+
+#[test]
+fn pack_state_keeps_fixed_point_precision() {
+    let state = State {
+        version: STATE_VERSION,
+        is_initialized: true,
+        total_liabilities: Decimal256::from_str("1000000.000000000000000001").unwrap(),
+        total_reserves: Decimal256::from_str("12.5").unwrap(),
+        last_interest_updated: 100,
+        last_reward_updated: 101,
+        global_interest_index: Decimal256::from_str("1.020304050607080901").unwrap(),
+        global_reward_index: Decimal256::from_str("0.000000000000000123").unwrap(),
+        anc_emission_rate: Decimal256::from_str("100.1").unwrap(),
+        prev_aterra_supply: Uint256::from_str("340282366920938463463374607431768211455000")
+            .unwrap(),
+        prev_exchange_rate: Decimal256::one(),
+    };
+
+    let mut data = vec![0u8; State::LEN];
+    State::pack(state.clone(), &mut data).unwrap();
+    assert_eq!(State::unpack(&data).unwrap(), state);
+}
+
+#[test]
+fn pack_borrower_info() {
+    let borrower = Pubkey::new_unique();
+    let mut info = BorrowerInfo::new(borrower);
+    info.loan_amount = Uint256::from(1_000_000u64);
+    info.pending_rewards = Decimal256::percent(3);
+
+    let mut data = vec![0u8; BorrowerInfo::LEN];
+    BorrowerInfo::pack(info.clone(), &mut data).unwrap();
+    let unpacked = BorrowerInfo::unpack(&data).unwrap();
+    assert_eq!(unpacked, info);
+    assert_eq!(unpacked.interest_index, Decimal256::one());
+}
+
+#[test]
+fn uninitialized_account() {
+    let data = vec![0u8; State::LEN];
+    assert_eq!(
+        State::unpack(&data).unwrap_err(),
+        ProgramError::UninitializedAccount
+    );
+    assert!(!State::unpack_unchecked(&data).unwrap().is_initialized());
+
+    // unpack_unchecked still reports initialized accounts
+    let mut data = vec![0u8; BorrowerInfo::LEN];
+    BorrowerInfo::pack(BorrowerInfo::new(Pubkey::new_unique()), &mut data).unwrap();
+    assert!(BorrowerInfo::unpack_unchecked(&data)
+        .unwrap()
+        .is_initialized());
+}
+
+#[test]
+fn unknown_version() {
+    let mut data = vec![0u8; BorrowerInfo::LEN];
+    BorrowerInfo::pack(BorrowerInfo::new(Pubkey::new_unique()), &mut data).unwrap();
+    data[0] = STATE_VERSION + 1;
+    assert_eq!(
+        BorrowerInfo::unpack(&data).unwrap_err(),
+        ProgramError::InvalidAccountData
+    );
+}